@@ -0,0 +1,139 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#ifndef MSITRACE_H
+#define MSITRACE_H
+
+// Generated by cbindgen. Do not edit; run `cbindgen --config capi/cbindgen.toml --output capi/include/msitrace.h capi/src/lib.rs` instead.
+
+#include <stdbool.h>
+#include <stdint.h>
+
+// The operation completed successfully.
+#define MSITRACE_SUCCESS 0
+
+// An argument was null, not valid UTF-8, or otherwise invalid.
+#define MSITRACE_ERROR_INVALID_PARAMETER 87
+
+// The install failed or was refused before it started, e.g. failed verification.
+#define MSITRACE_ERROR_INSTALL_FAILURE 1603
+
+// msitrace panicked. The panic was caught so it did not unwind into the caller.
+#define MSITRACE_ERROR_INTERNAL 1359
+
+// A fatal error ended the install.
+#define MSITRACE_MESSAGE_FATAL_EXIT 0x00000000
+
+// An error, usually with an error code from the `Error` table.
+#define MSITRACE_MESSAGE_ERROR 0x01000000
+
+// A warning that does not end the install.
+#define MSITRACE_MESSAGE_WARNING 0x02000000
+
+// A request for a response from the user.
+#define MSITRACE_MESSAGE_USER 0x03000000
+
+// Information written to the log.
+#define MSITRACE_MESSAGE_INFO 0x04000000
+
+// Applications are holding files the install needs to replace.
+#define MSITRACE_MESSAGE_FILES_IN_USE 0x05000000
+
+// Windows Installer is resolving the source of a product.
+#define MSITRACE_MESSAGE_RESOLVE_SOURCE 0x06000000
+
+// There is not enough disk space to install.
+#define MSITRACE_MESSAGE_OUT_OF_DISK_SPACE 0x07000000
+
+// An action started.
+#define MSITRACE_MESSAGE_ACTION_START 0x08000000
+
+// Data about the current action, e.g. the file being copied.
+#define MSITRACE_MESSAGE_ACTION_DATA 0x09000000
+
+// An update to the progress bar.
+#define MSITRACE_MESSAGE_PROGRESS 0x0A000000
+
+// Information shared with any user interface, like the language.
+#define MSITRACE_MESSAGE_COMMON_DATA 0x0B000000
+
+// The user interface was initialized.
+#define MSITRACE_MESSAGE_INITIALIZE 0x0C000000
+
+// The user interface was terminated.
+#define MSITRACE_MESSAGE_TERMINATE 0x0D000000
+
+// A dialog is to be shown.
+#define MSITRACE_MESSAGE_SHOW_DIALOG 0x0E000000
+
+// Performance information, only sent when logging with the `+` mode.
+#define MSITRACE_MESSAGE_PERFORMANCE 0x0F000000
+
+// The Restart Manager found applications holding files the install needs to replace.
+#define MSITRACE_MESSAGE_RM_FILES_IN_USE 0x19000000
+
+// A product started installing.
+#define MSITRACE_MESSAGE_INSTALL_START 0x1A000000
+
+// A product finished installing.
+#define MSITRACE_MESSAGE_INSTALL_END 0x1B000000
+
+// Options for [`msitrace_install`] and [`msitrace_uninstall`].
+typedef struct MsitraceOptions MsitraceOptions;
+
+// Receives the message type and redacted text of each message traced during an install.
+//
+// The message type is one of the `MSITRACE_MESSAGE_*` constants, or another `INSTALLMESSAGE`
+// value newer versions of Windows Installer send. The text is only valid for the duration
+// of the call.
+typedef void (*MsitraceMessageCallback)(void *context, uint32_t message_type, const char *text);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates options to pass to [`msitrace_install`]. Free with [`msitrace_options_free`].
+struct MsitraceOptions *msitrace_options_new(void);
+
+// Frees options created with [`msitrace_options_new`].
+void msitrace_options_free(struct MsitraceOptions *options);
+
+// Sets the path of a verbose log file to write, or clears it if `path` is null.
+uint32_t msitrace_options_set_log(struct MsitraceOptions *options, const char *path);
+
+// Sets the user interface level: 1 (default), 2 (none), 3 (basic), 4 (reduced), or 5 (full).
+uint32_t msitrace_options_set_ui(struct MsitraceOptions *options, uint32_t level);
+
+// Sets a public property to pass to the install. Hidden properties are redacted from traces.
+uint32_t msitrace_options_set_property(struct MsitraceOptions *options,
+                                       const char *name,
+                                       const char *value,
+                                       bool hidden);
+
+// Registers a callback to receive each traced message, or clears it if `callback` is null.
+//
+// The callback is called on the thread that called [`msitrace_install`] and `context`
+// is passed back unchanged.
+uint32_t msitrace_options_set_callback(struct MsitraceOptions *options,
+                                       MsitraceMessageCallback callback,
+                                       void *context);
+
+// Installs a package and traces its actions. `options` may be null to use defaults.
+//
+// Returns 0 on success or the Windows Installer error code, e.g. 3010 if a reboot is required.
+uint32_t msitrace_install(const char *path, const struct MsitraceOptions *options);
+
+// Uninstalls a product by its product code and traces its actions.
+// `options` may be null to use defaults.
+uint32_t msitrace_uninstall(const char *product_code, const struct MsitraceOptions *options);
+
+// Gets a description of the last error on the calling thread, or null if there was none.
+//
+// The string is valid until the next call into this library on the same thread.
+const char *msitrace_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MSITRACE_H */
@@ -0,0 +1,359 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! C API to trace Windows Installer installations.
+//!
+//! All strings are null-terminated UTF-8. Functions return 0 on success or a Windows error
+//! code; call [`msitrace_last_error`] for a description of the last error on the calling thread.
+//! Panics are caught and returned as [`MSITRACE_ERROR_INTERNAL`], since unwinding into the
+//! caller is undefined behavior.
+
+#![cfg(windows)]
+// Pointers are checked for null, but otherwise must be valid as documented in the header.
+#![allow(clippy::missing_safety_doc)]
+
+use msitrace::{CommandLine, InstallOptions, MessageHandler, UILevel};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::AssertUnwindSafe;
+
+/// The operation completed successfully.
+pub const MSITRACE_SUCCESS: u32 = 0;
+
+/// An argument was null, not valid UTF-8, or otherwise invalid.
+pub const MSITRACE_ERROR_INVALID_PARAMETER: u32 = 87;
+
+/// The install failed or was refused before it started, e.g. failed verification.
+pub const MSITRACE_ERROR_INSTALL_FAILURE: u32 = 1603;
+
+/// msitrace panicked. The panic was caught so it did not unwind into the caller.
+pub const MSITRACE_ERROR_INTERNAL: u32 = 1359;
+
+/// A fatal error ended the install.
+pub const MSITRACE_MESSAGE_FATAL_EXIT: u32 = 0x0000_0000;
+
+/// An error, usually with an error code from the `Error` table.
+pub const MSITRACE_MESSAGE_ERROR: u32 = 0x0100_0000;
+
+/// A warning that does not end the install.
+pub const MSITRACE_MESSAGE_WARNING: u32 = 0x0200_0000;
+
+/// A request for a response from the user.
+pub const MSITRACE_MESSAGE_USER: u32 = 0x0300_0000;
+
+/// Information written to the log.
+pub const MSITRACE_MESSAGE_INFO: u32 = 0x0400_0000;
+
+/// Applications are holding files the install needs to replace.
+pub const MSITRACE_MESSAGE_FILES_IN_USE: u32 = 0x0500_0000;
+
+/// Windows Installer is resolving the source of a product.
+pub const MSITRACE_MESSAGE_RESOLVE_SOURCE: u32 = 0x0600_0000;
+
+/// There is not enough disk space to install.
+pub const MSITRACE_MESSAGE_OUT_OF_DISK_SPACE: u32 = 0x0700_0000;
+
+/// An action started.
+pub const MSITRACE_MESSAGE_ACTION_START: u32 = 0x0800_0000;
+
+/// Data about the current action, e.g. the file being copied.
+pub const MSITRACE_MESSAGE_ACTION_DATA: u32 = 0x0900_0000;
+
+/// An update to the progress bar.
+pub const MSITRACE_MESSAGE_PROGRESS: u32 = 0x0A00_0000;
+
+/// Information shared with any user interface, like the language.
+pub const MSITRACE_MESSAGE_COMMON_DATA: u32 = 0x0B00_0000;
+
+/// The user interface was initialized.
+pub const MSITRACE_MESSAGE_INITIALIZE: u32 = 0x0C00_0000;
+
+/// The user interface was terminated.
+pub const MSITRACE_MESSAGE_TERMINATE: u32 = 0x0D00_0000;
+
+/// A dialog is to be shown.
+pub const MSITRACE_MESSAGE_SHOW_DIALOG: u32 = 0x0E00_0000;
+
+/// Performance information, only sent when logging with the `+` mode.
+pub const MSITRACE_MESSAGE_PERFORMANCE: u32 = 0x0F00_0000;
+
+/// The Restart Manager found applications holding files the install needs to replace.
+pub const MSITRACE_MESSAGE_RM_FILES_IN_USE: u32 = 0x1900_0000;
+
+/// A product started installing.
+pub const MSITRACE_MESSAGE_INSTALL_START: u32 = 0x1A00_0000;
+
+/// A product finished installing.
+pub const MSITRACE_MESSAGE_INSTALL_END: u32 = 0x1B00_0000;
+
+/// Options for [`msitrace_install`] and [`msitrace_uninstall`].
+pub struct MsitraceOptions {
+    options: InstallOptions,
+}
+
+/// Receives the message type and redacted text of each message traced during an install.
+///
+/// The message type is one of the `MSITRACE_MESSAGE_*` constants, or another `INSTALLMESSAGE`
+/// value newer versions of Windows Installer send. The text is only valid for the duration
+/// of the call.
+pub type MsitraceMessageCallback =
+    Option<unsafe extern "C" fn(context: *mut c_void, message_type: u32, text: *const c_char)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Creates options to pass to [`msitrace_install`]. Free with [`msitrace_options_free`].
+#[no_mangle]
+pub extern "C" fn msitrace_options_new() -> *mut MsitraceOptions {
+    catch_panic(std::ptr::null_mut(), || {
+        Box::into_raw(Box::new(MsitraceOptions {
+            options: InstallOptions::default(),
+        }))
+    })
+}
+
+/// Frees options created with [`msitrace_options_new`].
+#[no_mangle]
+pub unsafe extern "C" fn msitrace_options_free(options: *mut MsitraceOptions) {
+    catch_panic((), || {
+        if !options.is_null() {
+            drop(Box::from_raw(options));
+        }
+    })
+}
+
+/// Sets the path of a verbose log file to write, or clears it if `path` is null.
+#[no_mangle]
+pub unsafe extern "C" fn msitrace_options_set_log(
+    options: *mut MsitraceOptions,
+    path: *const c_char,
+) -> u32 {
+    catch_panic(MSITRACE_ERROR_INTERNAL, || {
+        let Some(options) = options.as_mut() else {
+            return invalid_parameter("options");
+        };
+
+        options.options.log = match path.is_null() {
+            true => None,
+            false => match to_str(path, "path") {
+                Ok(path) => Some(path.to_owned()),
+                Err(err) => return err,
+            },
+        };
+
+        MSITRACE_SUCCESS
+    })
+}
+
+/// Sets the user interface level: 1 (default), 2 (none), 3 (basic), 4 (reduced), or 5 (full).
+#[no_mangle]
+pub unsafe extern "C" fn msitrace_options_set_ui(options: *mut MsitraceOptions, level: u32) -> u32 {
+    catch_panic(MSITRACE_ERROR_INTERNAL, || {
+        let Some(options) = options.as_mut() else {
+            return invalid_parameter("options");
+        };
+
+        options.options.ui = match level {
+            1 => UILevel::Default,
+            2 => UILevel::None,
+            3 => UILevel::Basic,
+            4 => UILevel::Reduced,
+            5 => UILevel::Full,
+            _ => return invalid_parameter("level"),
+        };
+
+        MSITRACE_SUCCESS
+    })
+}
+
+/// Sets a public property to pass to the install. Hidden properties are redacted from traces.
+#[no_mangle]
+pub unsafe extern "C" fn msitrace_options_set_property(
+    options: *mut MsitraceOptions,
+    name: *const c_char,
+    value: *const c_char,
+    hidden: bool,
+) -> u32 {
+    catch_panic(MSITRACE_ERROR_INTERNAL, || {
+        let Some(options) = options.as_mut() else {
+            return invalid_parameter("options");
+        };
+        let name = match to_str(name, "name") {
+            Ok(name) => name,
+            Err(err) => return err,
+        };
+        let value = match to_str(value, "value") {
+            Ok(value) => value,
+            Err(err) => return err,
+        };
+
+        let properties: &mut CommandLine = &mut options.options.properties;
+        let result = properties
+            .set(name, value)
+            .and_then(|properties| match hidden {
+                true => properties.hide(name),
+                false => Ok(properties),
+            });
+        match result {
+            Ok(_) => MSITRACE_SUCCESS,
+            Err(err) => set_error(MSITRACE_ERROR_INVALID_PARAMETER, &err),
+        }
+    })
+}
+
+/// Registers a callback to receive each traced message, or clears it if `callback` is null.
+///
+/// The callback is called on the thread that called [`msitrace_install`] and `context`
+/// is passed back unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn msitrace_options_set_callback(
+    options: *mut MsitraceOptions,
+    callback: MsitraceMessageCallback,
+    context: *mut c_void,
+) -> u32 {
+    catch_panic(MSITRACE_ERROR_INTERNAL, || {
+        let Some(options) = options.as_mut() else {
+            return invalid_parameter("options");
+        };
+
+        /// The caller owns the context and is responsible for its thread safety.
+        struct Context(*mut c_void);
+        unsafe impl Send for Context {}
+        unsafe impl Sync for Context {}
+
+        impl Context {
+            // Closures would otherwise capture only the raw pointer field.
+            fn get(&self) -> *mut c_void {
+                self.0
+            }
+        }
+
+        options.options.handler = callback.map(|callback| {
+            let context = Context(context);
+            MessageHandler::new(move |message, text| {
+                // Interior nulls cannot be represented, so truncate at the first.
+                let text = text.split('\0').next().unwrap_or_default();
+                let text = CString::new(text).unwrap_or_default();
+                callback(context.get(), message.value(), text.as_ptr());
+            })
+        });
+
+        MSITRACE_SUCCESS
+    })
+}
+
+/// Installs a package and traces its actions. `options` may be null to use defaults.
+///
+/// Returns 0 on success or the Windows Installer error code, e.g. 3010 if a reboot is required.
+#[no_mangle]
+pub unsafe extern "C" fn msitrace_install(
+    path: *const c_char,
+    options: *const MsitraceOptions,
+) -> u32 {
+    catch_panic(MSITRACE_ERROR_INTERNAL, || {
+        let path = match to_str(path, "path") {
+            Ok(path) => path,
+            Err(err) => return err,
+        };
+
+        run(options, |options| msitrace::install(path, options))
+    })
+}
+
+/// Uninstalls a product by its product code and traces its actions.
+/// `options` may be null to use defaults.
+#[no_mangle]
+pub unsafe extern "C" fn msitrace_uninstall(
+    product_code: *const c_char,
+    options: *const MsitraceOptions,
+) -> u32 {
+    catch_panic(MSITRACE_ERROR_INTERNAL, || {
+        let product_code = match to_str(product_code, "product_code") {
+            Ok(product_code) => product_code,
+            Err(err) => return err,
+        };
+
+        run(options, |options| {
+            msitrace::uninstall(product_code, options)
+        })
+    })
+}
+
+/// Gets a description of the last error on the calling thread, or null if there was none.
+///
+/// The string is valid until the next call into this library on the same thread.
+#[no_mangle]
+pub extern "C" fn msitrace_last_error() -> *const c_char {
+    catch_panic(std::ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(std::ptr::null(), |err| err.as_ptr())
+        })
+    })
+}
+
+/// Calls `f`, returning `fallback` and setting the last error if it panics.
+fn catch_panic<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        set_error(
+            MSITRACE_ERROR_INTERNAL,
+            &format!("msitrace panicked: {}", message),
+        );
+        fallback
+    })
+}
+
+unsafe fn run(
+    options: *const MsitraceOptions,
+    f: impl FnOnce(&InstallOptions) -> msitrace::Result<()>,
+) -> u32 {
+    let default = InstallOptions::default();
+    let options = options
+        .as_ref()
+        .map_or(&default, |options| &options.options);
+
+    match f(options) {
+        Ok(()) => {
+            clear_error();
+            MSITRACE_SUCCESS
+        }
+        Err(err) => {
+            let code = err.code().unwrap_or(MSITRACE_ERROR_INSTALL_FAILURE);
+            set_error(code, &err)
+        }
+    }
+}
+
+unsafe fn to_str<'a>(value: *const c_char, name: &str) -> Result<&'a str, u32> {
+    if value.is_null() {
+        return Err(invalid_parameter(name));
+    }
+
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| invalid_parameter(name))
+}
+
+fn invalid_parameter(name: &str) -> u32 {
+    set_error(
+        MSITRACE_ERROR_INVALID_PARAMETER,
+        &format!("invalid parameter: {}", name),
+    )
+}
+
+fn set_error(code: u32, err: &dyn std::fmt::Display) -> u32 {
+    let message = CString::new(err.to_string().replace('\0', "")).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    code
+}
+
+fn clear_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
@@ -0,0 +1,27 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use msitrace::CommandLine;
+
+fuzz_target!(|input: (Vec<String>, Vec<(String, String)>)| {
+    let (properties, pairs) = input;
+    let mut command_line = CommandLine::new();
+
+    // Arguments as passed to the CLI in the form NAME=VALUE.
+    for property in &properties {
+        let _ = command_line.set_property(property);
+    }
+
+    for (name, value) in pairs {
+        if command_line.set(name.as_str(), value.as_str()).is_ok() {
+            assert_eq!(command_line.get(&name), Some(value.as_str()));
+        }
+    }
+
+    // Every quote within a value is doubled and the value enclosed in quotes.
+    let formatted = command_line.to_string();
+    assert_eq!(formatted.matches('"').count() % 2, 0);
+});
@@ -0,0 +1,23 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use msitrace::{Progress, ProgressUpdate};
+
+fuzz_target!(|messages: Vec<Option<Vec<i32>>>| {
+    let mut progress = Progress::new();
+
+    // Progress messages as their record fields, or None for action data.
+    for fields in messages {
+        match fields {
+            Some(fields) => progress.update(ProgressUpdate::from_fields(&fields)),
+            None => progress.action_data(),
+        }
+
+        if let Some(percent) = progress.percent() {
+            assert!((0.0..=100.0).contains(&percent));
+        }
+    }
+});
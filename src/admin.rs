@@ -0,0 +1,118 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::signature::check_signature;
+use crate::{
+    ffi, inspect_patch, install, lock, preflight, trace, verify_package, Error, InstallOptions,
+    Label, Result,
+};
+use std::path::Path;
+
+/// Extracts an uncompressed administrative image of a package to `target_dir` and traces
+/// the install.
+///
+/// The target directory is created if it does not exist. It must be an absolute path and
+/// must not contain the package, which would be overwritten.
+pub fn admin_install(
+    path: &str,
+    target_dir: impl AsRef<Path>,
+    options: &InstallOptions,
+) -> Result<()> {
+    let target_dir = target_dir.as_ref();
+    if !target_dir.is_absolute() {
+        return Err(Error::verification(format!(
+            "target directory {} must be an absolute path",
+            target_dir.display()
+        )));
+    }
+    if target_dir.exists() && !target_dir.is_dir() {
+        return Err(Error::verification(format!(
+            "target {} is not a directory",
+            target_dir.display()
+        )));
+    }
+
+    std::fs::create_dir_all(target_dir)?;
+    let target_dir = target_dir.canonicalize()?;
+    if let Some(source_dir) = Path::new(path).parent().and_then(|p| p.canonicalize().ok()) {
+        if source_dir == target_dir {
+            return Err(Error::verification(
+                "target directory cannot be the directory containing the package",
+            ));
+        }
+    }
+    if std::fs::read_dir(&target_dir)?.next().is_some() {
+        eprintln!(
+            "warning: target directory {} is not empty; files may be overwritten",
+            target_dir.display()
+        );
+    }
+
+    // Strip the extended-length prefix canonicalize adds, which Windows Installer does not expect.
+    let target_dir = target_dir.to_string_lossy();
+    let target_dir = match target_dir.strip_prefix(r"\\?\UNC\") {
+        Some(share) => format!(r"\\{}", share),
+        None => target_dir
+            .strip_prefix(r"\\?\")
+            .unwrap_or(&target_dir)
+            .to_owned(),
+    };
+
+    let mut options = options.clone();
+    options.properties.set("ACTION", "ADMIN")?;
+    options.properties.set("TARGETDIR", target_dir)?;
+
+    // Nothing is registered by an administrative install, so there is nothing to verify.
+    options.verify = false;
+    options.verify_hashes = false;
+
+    install(path, &options)
+}
+
+/// Applies patches in order to the package in an administrative image and traces each install,
+/// like `msiexec /a` with `/p`.
+///
+/// Files in the image are updated so products installed from it later include the patches.
+pub fn patch_admin_image(image: &str, patches: &[String], options: &InstallOptions) -> Result<()> {
+    let _lock = lock();
+    if patches.is_empty() {
+        return Err(Error::verification("no patches to apply"));
+    }
+
+    preflight(options)?;
+    verify_package(image)?;
+
+    let mut infos = Vec::with_capacity(patches.len());
+    for patch in patches {
+        infos.push(inspect_patch(patch)?);
+        check_signature(patch, options)?;
+        if let Some(manifest) = &options.manifest {
+            manifest.authorize(patch)?;
+        }
+    }
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Package), image);
+    for (patch, info) in patches.iter().zip(&infos) {
+        println!(
+            "{}: {} ({})",
+            language.label(Label::Patch),
+            patch,
+            info.patch_code
+        );
+    }
+    println!(
+        "{}: {}",
+        language.label(Label::CommandLine),
+        options.properties.redacted()
+    );
+
+    let command_line = options.properties.to_install_string();
+    for patch in patches {
+        trace(patch, options, || {
+            ffi::apply_patch_to_image(patch, image, &command_line)
+        })?;
+    }
+
+    Ok(())
+}
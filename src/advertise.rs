@@ -0,0 +1,97 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::signature::check_signature;
+use crate::{ffi, lock, preflight, trace, verify_package, InstallOptions, Label, Result};
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// Where to advertise a product.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum AdvertiseTarget {
+    /// Advertise the product to the current user.
+    #[default]
+    User,
+
+    /// Advertise the product to every user on the machine. Requires elevation.
+    Machine,
+
+    /// Write an advertise script to apply later instead of advertising the product.
+    Script(PathBuf),
+}
+
+impl Display for AdvertiseTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdvertiseTarget::User => f.write_str("user"),
+            AdvertiseTarget::Machine => f.write_str("machine"),
+            AdvertiseTarget::Script(path) => write!(f, "script {}", path.display()),
+        }
+    }
+}
+
+/// Advertises a package and traces its actions.
+///
+/// Transforms are taken from [`InstallOptions::transforms`] and the `TRANSFORMS` property
+/// in [`InstallOptions::properties`], and `language` is a language identifier, or 0 for the package default. No other
+/// properties can be passed when advertising.
+pub fn advertise(
+    path: &str,
+    target: &AdvertiseTarget,
+    language: u16,
+    options: &InstallOptions,
+) -> Result<()> {
+    let _lock = lock();
+    preflight(options)?;
+
+    verify_package(path)?;
+    check_signature(path, options)?;
+    if let Some(manifest) = &options.manifest {
+        manifest.authorize(path)?;
+    }
+
+    let mut properties = options.properties.clone();
+    properties.add_transforms(&options.transforms)?;
+    let transforms = properties.get("TRANSFORMS");
+    let labels = options.language;
+    println!("{}: {}", labels.label(Label::Package), path);
+    println!("{}: {}", labels.label(Label::Assignment), target);
+    if let Some(transforms) = transforms {
+        println!("TRANSFORMS: {}", transforms);
+    }
+
+    let script = match target {
+        AdvertiseTarget::Script(script) => Some(script.to_string_lossy()),
+        _ => None,
+    };
+    trace(path, options, || {
+        ffi::advertise_product(
+            path,
+            *target == AdvertiseTarget::Machine,
+            script.as_deref(),
+            transforms,
+            language,
+        )
+    })
+}
+
+/// Applies an advertise script written by [`advertise`] with [`AdvertiseTarget::Script`] to
+/// the current user and traces its actions.
+///
+/// Icons are copied to `icon_folder` if specified, and shortcuts are created if `shortcuts`.
+/// If `remove`, items the script advertised are removed instead.
+pub fn process_advertise_script(
+    script: &str,
+    icon_folder: Option<&str>,
+    shortcuts: bool,
+    remove: bool,
+    options: &InstallOptions,
+) -> Result<()> {
+    let _lock = lock();
+    preflight(options)?;
+
+    println!("{}: {}", options.language.label(Label::Script), script);
+    trace(script, options, || {
+        ffi::process_advertise_script(script, icon_folder, shortcuts, remove)
+    })
+}
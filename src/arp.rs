@@ -0,0 +1,150 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::{self, registry, registry::Hive};
+use crate::Result;
+use std::fmt::Display;
+
+const UNINSTALL_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Uninstall";
+const UNINSTALL_WOW64_KEY: &str =
+    r"Software\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall";
+
+/// A difference between Windows Installer product registration and the Uninstall registry keys
+/// that Add/Remove Programs (ARP) displays.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArpIssue {
+    /// An installed product has no Uninstall entry, so users cannot see or remove it.
+    MissingEntry { product_code: String },
+
+    /// An Uninstall entry for a product that Windows Installer does not have registered.
+    Unregistered {
+        product_code: String,
+        display_name: Option<String>,
+    },
+
+    /// The Uninstall entry displays a different version than Windows Installer has registered.
+    VersionMismatch {
+        product_code: String,
+        registered: String,
+        displayed: String,
+    },
+
+    /// An Uninstall entry claims to be a Windows Installer product but is not named by a product code.
+    NotWindowsInstaller {
+        key: String,
+        display_name: Option<String>,
+    },
+}
+
+impl Display for ArpIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArpIssue::MissingEntry { product_code } => {
+                write!(
+                    f,
+                    "{} is installed but has no Uninstall entry",
+                    product_code
+                )
+            }
+            ArpIssue::Unregistered {
+                product_code,
+                display_name,
+            } => write!(
+                f,
+                "{} ({}) has an Uninstall entry but is not registered with Windows Installer",
+                product_code,
+                display_name.as_deref().unwrap_or("unnamed")
+            ),
+            ArpIssue::VersionMismatch {
+                product_code,
+                registered,
+                displayed,
+            } => write!(
+                f,
+                "{} is registered as version {} but its Uninstall entry displays {}",
+                product_code, registered, displayed
+            ),
+            ArpIssue::NotWindowsInstaller { key, display_name } => write!(
+                f,
+                "{} ({}) sets WindowsInstaller=1 but is not named by a product code",
+                key,
+                display_name.as_deref().unwrap_or("unnamed")
+            ),
+        }
+    }
+}
+
+/// Cross-checks products registered with Windows Installer against the Uninstall registry keys.
+pub fn arp_issues() -> Result<Vec<ArpIssue>> {
+    let products = ffi::enum_products()?;
+    let mut seen = Vec::new();
+    let mut issues = Vec::new();
+
+    for (hive, key) in [
+        (Hive::LocalMachine, UNINSTALL_KEY),
+        (Hive::LocalMachine, UNINSTALL_WOW64_KEY),
+        (Hive::CurrentUser, UNINSTALL_KEY),
+    ] {
+        for name in registry::subkeys(hive, key)? {
+            let path = format!(r"{}\{}", key, name);
+            if registry::dword(hive, &path, "WindowsInstaller")? != Some(1) {
+                continue;
+            }
+
+            let display_name = registry::string(hive, &path, "DisplayName")?;
+            if !is_product_code(&name) {
+                issues.push(ArpIssue::NotWindowsInstaller {
+                    key: name,
+                    display_name,
+                });
+                continue;
+            }
+
+            let Some(product_code) = products.iter().find(|p| p.eq_ignore_ascii_case(&name)) else {
+                issues.push(ArpIssue::Unregistered {
+                    product_code: name,
+                    display_name,
+                });
+                continue;
+            };
+            seen.push(product_code.clone());
+
+            let displayed = registry::string(hive, &path, "DisplayVersion")?;
+            if let (Some(registered), Some(displayed)) =
+                (installed_version(product_code), displayed)
+            {
+                if registered != displayed {
+                    issues.push(ArpIssue::VersionMismatch {
+                        product_code: product_code.clone(),
+                        registered,
+                        displayed,
+                    });
+                }
+            }
+        }
+    }
+
+    for product_code in products {
+        // Advertised products are not installed and have no Uninstall entry.
+        if !seen.contains(&product_code) && installed_version(&product_code).is_some() {
+            issues.push(ArpIssue::MissingEntry { product_code });
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Gets the version of an installed product, or `None` if the product is only advertised.
+fn installed_version(product_code: &str) -> Option<String> {
+    ffi::product_info(product_code, "VersionString").ok()
+}
+
+fn is_product_code(value: &str) -> bool {
+    value.len() == 38
+        && value.starts_with('{')
+        && value.ends_with('}')
+        && value[1..37].char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
@@ -0,0 +1,74 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{install, Event, InstallOptions, MessageHandler, Result};
+use futures_core::Stream;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::task::JoinHandle;
+
+/// Installs a package on a blocking task of the current Tokio runtime.
+///
+/// Returns a [`Stream`] of events as messages are traced and an [`InstallTask`] that
+/// completes with the result of the install. The stream ends when the install finishes.
+/// Installs still run one at a time, so concurrent calls wait for earlier installs to finish.
+///
+/// Any [`InstallOptions::handler`] is still called before each event is sent. Messages are
+/// also printed unless [`InstallOptions::quiet`] is set.
+///
+/// # Panics
+///
+/// Panics if not called from within a Tokio runtime.
+pub fn install_async(
+    path: impl Into<String>,
+    mut options: InstallOptions,
+) -> (EventStream, InstallTask) {
+    let path = path.into();
+    let (sender, receiver) = mpsc::unbounded_channel();
+    // The stream may have been dropped if the caller only awaits the result.
+    let handler = MessageHandler::events(move |event| {
+        let _ = sender.send(event);
+    });
+    options.handler = Some(match options.handler.take() {
+        Some(existing) => MessageHandler::chain([existing, handler]),
+        None => handler,
+    });
+
+    let task = tokio::task::spawn_blocking(move || install(&path, &options));
+
+    (EventStream(receiver), InstallTask(task))
+}
+
+/// A [`Stream`] of events from an install started by [`install_async`].
+#[derive(Debug)]
+pub struct EventStream(UnboundedReceiver<Event>);
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+/// Completes with the result of an install started by [`install_async`].
+///
+/// Dropping this does not cancel the install; use [`InstallOptions::cancel`] instead.
+#[derive(Debug)]
+pub struct InstallTask(JoinHandle<Result<()>>);
+
+impl Future for InstallTask {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match Pin::new(&mut self.0).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(err)) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            // Blocking tasks are only cancelled if the runtime shuts down before they start.
+            Poll::Ready(Err(err)) => Poll::Ready(Err(std::io::Error::other(err).into())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
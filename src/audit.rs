@@ -0,0 +1,187 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Database, Result};
+use std::fmt::Display;
+
+const TYPE_DLL: i32 = 0x01;
+const TYPE_EXE: i32 = 0x02;
+const TYPE_JSCRIPT: i32 = 0x05;
+const TYPE_VBSCRIPT: i32 = 0x06;
+const TYPE_INSTALL: i32 = 0x07;
+const TYPE_IN_SCRIPT: i32 = 0x400;
+const TYPE_NO_IMPERSONATE: i32 = 0x800;
+
+const SEQUENCE_TABLES: [&str; 4] = [
+    "InstallUISequence",
+    "InstallExecuteSequence",
+    "AdminUISequence",
+    "AdminExecuteSequence",
+];
+
+/// What a custom action runs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CustomActionCode {
+    /// A function exported from a DLL.
+    Dll,
+    /// An executable.
+    Exe,
+    /// JScript.
+    JScript,
+    /// VBScript.
+    VBScript,
+    /// A nested installation.
+    Install,
+    /// Sets a property or directory, or displays an error, without running code.
+    None,
+}
+
+impl Display for CustomActionCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CustomActionCode::Dll => "DLL",
+            CustomActionCode::Exe => "EXE",
+            CustomActionCode::JScript => "JScript",
+            CustomActionCode::VBScript => "VBScript",
+            CustomActionCode::Install => "nested install",
+            CustomActionCode::None => "none",
+        })
+    }
+}
+
+/// Where a custom action is scheduled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scheduling {
+    /// The sequence table, e.g. `InstallExecuteSequence`.
+    pub table: String,
+
+    /// The sequence number.
+    pub sequence: i32,
+
+    /// Whether the action runs before `CostFinalize`, when directories are not yet resolved.
+    pub before_costing: bool,
+}
+
+/// Security-relevant details of a custom action.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CustomActionAudit {
+    /// The name of the custom action.
+    pub action: String,
+
+    /// The raw custom action type.
+    pub action_type: i32,
+
+    /// What the custom action runs.
+    pub code: CustomActionCode,
+
+    /// Where the code comes from, e.g. a `Binary` table key or a file key.
+    pub source: String,
+
+    /// The function, command line, or script the custom action runs.
+    pub target: String,
+
+    /// Whether the action runs deferred within the install script.
+    pub deferred: bool,
+
+    /// Whether the action is deferred without impersonation, running as LocalSystem
+    /// during per-machine installs.
+    pub elevated: bool,
+
+    /// Where the action is scheduled, if at all.
+    pub scheduling: Vec<Scheduling>,
+}
+
+impl CustomActionAudit {
+    /// Gets whether the action runs an executable or script.
+    pub fn runs_program(&self) -> bool {
+        matches!(
+            self.code,
+            CustomActionCode::Exe | CustomActionCode::JScript | CustomActionCode::VBScript
+        )
+    }
+
+    /// Gets whether the action is scheduled before costing in any sequence.
+    pub fn before_costing(&self) -> bool {
+        self.scheduling.iter().any(|s| s.before_costing)
+    }
+}
+
+/// Audits the custom actions in a package for security review.
+pub fn audit_custom_actions(database: &Database) -> Result<Vec<CustomActionAudit>> {
+    let tables = database.tables()?;
+    if !tables.iter().any(|t| t == "CustomAction") {
+        return Ok(Vec::new());
+    }
+
+    // Get the scheduling of every action in each sequence table.
+    let mut sequences = Vec::new();
+    for table in SEQUENCE_TABLES {
+        if !tables.iter().any(|t| t == table) {
+            continue;
+        }
+
+        let mut actions = Vec::new();
+        let mut cost_finalize = None;
+        for row in database.query(&format!("SELECT `Action`, `Sequence` FROM `{}`", table))? {
+            let row = row?;
+            let action = row[0].to_string();
+            let sequence = row[1].as_integer();
+
+            if action == "CostFinalize" {
+                cost_finalize = sequence;
+            }
+            if let Some(sequence) = sequence {
+                actions.push((action, sequence));
+            }
+        }
+
+        sequences.push((table, actions, cost_finalize));
+    }
+
+    let mut audits = Vec::new();
+    for row in database.query("SELECT `Action`, `Type`, `Source`, `Target` FROM `CustomAction`")? {
+        let row = row?;
+        let action = row[0].to_string();
+        let action_type = row[1].as_integer().unwrap_or_default();
+
+        let scheduling = sequences
+            .iter()
+            .flat_map(|(table, actions, cost_finalize)| {
+                actions
+                    .iter()
+                    .filter(|(a, _)| *a == action)
+                    .map(|(_, sequence)| Scheduling {
+                        table: table.to_string(),
+                        sequence: *sequence,
+                        before_costing: cost_finalize.is_some_and(|c| *sequence < c),
+                    })
+            })
+            .collect();
+
+        let deferred = action_type & TYPE_IN_SCRIPT != 0;
+        audits.push(CustomActionAudit {
+            action,
+            action_type,
+            code: code(action_type),
+            source: row[2].to_string(),
+            target: row[3].to_string(),
+            deferred,
+            elevated: deferred && action_type & TYPE_NO_IMPERSONATE != 0,
+            scheduling,
+        });
+    }
+
+    Ok(audits)
+}
+
+fn code(action_type: i32) -> CustomActionCode {
+    // Text data (3) displays errors or sets directories and properties without running code.
+    match action_type & 0x07 {
+        TYPE_DLL => CustomActionCode::Dll,
+        TYPE_EXE => CustomActionCode::Exe,
+        TYPE_JSCRIPT => CustomActionCode::JScript,
+        TYPE_VBSCRIPT => CustomActionCode::VBScript,
+        TYPE_INSTALL => CustomActionCode::Install,
+        _ => CustomActionCode::None,
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install::{local_path, set_properties};
+use crate::locale;
+use msitrace::{CommandLine, InstallOptions};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the package to extract, or to the package in an administrative image to patch.
+    path: PathBuf,
+
+    /// The directory to extract the administrative image to.
+    #[arg(long, value_name = "DIR", required_unless_present = "patches")]
+    target: Option<PathBuf>,
+
+    /// Apply a patch to the administrative image, after extracting it if --target is passed.
+    /// May be repeated.
+    #[arg(long = "patch", value_name = "PATH")]
+    patches: Vec<PathBuf>,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// Properties to pass to the install, or @PATH to read them from a file.
+    #[arg(last = true)]
+    properties: Vec<String>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let path = local_path(&args.path)?;
+    let target = args
+        .target
+        .map(|target| std::env::current_dir().map(|dir| dir.join(target)))
+        .transpose()?;
+    let patches = args
+        .patches
+        .iter()
+        .map(|patch| local_path(patch))
+        .collect::<Result<Vec<_>, _>>()?;
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+
+    let mut properties = CommandLine::new();
+    set_properties(&mut properties, &args.properties)?;
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        language: locale::current(),
+        properties,
+        ..Default::default()
+    };
+
+    // Patch the package extracted to the target, or else the package passed.
+    let image = match target {
+        Some(target) => {
+            msitrace::admin_install(&path, &target, &options)?;
+            let name = args.path.file_name().unwrap_or_default();
+            target.join(name).to_string_lossy().into_owned()
+        }
+        None => path,
+    };
+    if !patches.is_empty() {
+        msitrace::patch_admin_image(&image, &patches, &options)?;
+    }
+
+    Ok(())
+}
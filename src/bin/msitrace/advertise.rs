@@ -0,0 +1,68 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install::local_path;
+use crate::locale;
+use msitrace::{AdvertiseTarget, CommandLine, InstallOptions};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the package to advertise.
+    path: PathBuf,
+
+    /// Advertise to every user on the machine instead of the current user. Requires elevation.
+    #[arg(long)]
+    machine: bool,
+
+    /// Write an advertise script to this path instead of advertising the product.
+    #[arg(long, value_name = "PATH", conflicts_with = "machine")]
+    script: Option<PathBuf>,
+
+    /// Semicolon-delimited transforms to apply.
+    #[arg(long, value_name = "TRANSFORMS")]
+    transforms: Option<String>,
+
+    /// The language identifier to advertise, e.g. 1033. Defaults to the package language.
+    #[arg(long, value_name = "LANGID", default_value_t = 0)]
+    language: u16,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let path = local_path(&args.path)?;
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+
+    let target = match (args.script, args.machine) {
+        (Some(script), _) => AdvertiseTarget::Script(std::env::current_dir()?.join(script)),
+        (None, true) => AdvertiseTarget::Machine,
+        (None, false) => AdvertiseTarget::User,
+    };
+
+    let mut properties = CommandLine::new();
+    if let Some(transforms) = args.transforms {
+        properties.set("TRANSFORMS", transforms)?;
+    }
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        language: locale::current(),
+        properties,
+        ..Default::default()
+    };
+    msitrace::advertise(&path, &target, args.language, &options)?;
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install::local_path;
+use crate::locale;
+use msitrace::InstallOptions;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to an advertise script written by `advertise --script`.
+    script: PathBuf,
+
+    /// Copy icons for shortcuts and file associations to this directory.
+    #[arg(long, value_name = "DIR")]
+    icons: Option<PathBuf>,
+
+    /// Create shortcuts the script advertises.
+    #[arg(long)]
+    shortcuts: bool,
+
+    /// Remove items the script advertised instead of advertising them.
+    #[arg(long)]
+    remove: bool,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let script = local_path(&args.script)?;
+    let icons = args
+        .icons
+        .map(|icons| std::env::current_dir().map(|dir| dir.join(icons)))
+        .transpose()?;
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        language: locale::current(),
+        ..Default::default()
+    };
+    let icons = icons.map(|icons| icons.to_string_lossy().into_owned());
+    msitrace::process_advertise_script(
+        &script,
+        icons.as_deref(),
+        args.shortcuts,
+        args.remove,
+        &options,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{audit_custom_actions, CustomActionAudit, Database};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the package.
+    path: PathBuf,
+
+    /// Show all custom actions, not just those of concern.
+    #[arg(long)]
+    all: bool,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let database = Database::open(&args.path)?;
+    let audits = audit_custom_actions(&database)?;
+
+    let mut concerns = 0;
+    for audit in &audits {
+        let findings = findings(audit);
+        if findings.is_empty() && !args.all {
+            continue;
+        }
+        if !findings.is_empty() {
+            concerns += 1;
+        }
+
+        println!(
+            "{} (type {}, {})",
+            audit.action, audit.action_type, audit.code
+        );
+        for finding in findings {
+            println!("  ! {}", finding);
+        }
+        if !audit.source.is_empty() {
+            println!("  Source: {}", audit.source);
+        }
+        if !audit.target.is_empty() {
+            println!("  Target: {}", audit.target);
+        }
+        if audit.scheduling.is_empty() {
+            println!("  Not scheduled");
+        }
+        for scheduling in &audit.scheduling {
+            println!("  {}: {}", scheduling.table, scheduling.sequence);
+        }
+        println!();
+    }
+
+    println!("{} custom action(s), {} of concern", audits.len(), concerns);
+
+    Ok(())
+}
+
+fn findings(audit: &CustomActionAudit) -> Vec<&'static str> {
+    let mut findings = Vec::new();
+    if audit.elevated {
+        findings.push("runs deferred without impersonation, elevated during per-machine installs");
+    }
+    if audit.runs_program() {
+        findings.push("runs an executable or script");
+    }
+    if audit.before_costing() {
+        findings.push("scheduled before costing");
+    }
+
+    findings
+}
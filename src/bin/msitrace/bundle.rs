@@ -0,0 +1,46 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Extract the packages and patches attached to a bundle.
+    Extract(ExtractArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ExtractArgs {
+    /// Path to the bundle executable.
+    path: PathBuf,
+
+    /// Directory to extract packages and patches to.
+    #[arg(long, value_name = "DIR")]
+    out: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Command::Extract(args) => extract(args),
+    }
+}
+
+fn extract(args: ExtractArgs) -> Result<(), Box<dyn Error>> {
+    std::fs::create_dir_all(&args.out)?;
+    let packages = msitrace::extract_bundle(&args.path, &args.out)?;
+    if packages.is_empty() {
+        println!("No packages are attached to the bundle");
+    }
+    for package in packages {
+        println!("Extracted {}", package.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install;
+use crate::locale;
+use msitrace::InstallOptions;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The product code of an installed product, e.g. {00000000-0000-0000-0000-000000000000}.
+    #[arg(required = true)]
+    product_code: Option<String>,
+
+    /// Copy the cached package to this directory.
+    #[arg(long, value_name = "DIR")]
+    copy_to: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Re-cache a missing or corrupt cached package from its original source.
+    Repair(RepairArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct RepairArgs {
+    /// The product code of an installed product.
+    product_code: String,
+
+    /// Path to the original package, which must match the installed package code.
+    #[arg(long, value_name = "PATH")]
+    source: PathBuf,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(Command::Repair(args)) = args.command {
+        return repair(args);
+    }
+
+    let product_code = args.product_code.unwrap_or_default();
+    let cached = msitrace::cached_package(&product_code)?;
+
+    println!("Cached package: {}", cached.path.display());
+    println!("Registered package code: {}", cached.package_code);
+    if !cached.exists {
+        return Err("cached package is missing".into());
+    }
+
+    match &cached.actual_package_code {
+        Some(code) => println!("Cached package code: {}", code),
+        None => println!("Cached package code: unknown"),
+    }
+    if !cached.is_valid() {
+        return Err("cached package does not match the registered package code".into());
+    }
+
+    if let Some(dir) = args.copy_to {
+        let file_name = cached
+            .path
+            .file_name()
+            .ok_or("cached package has no file name")?;
+        let destination = dir.join(file_name);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::copy(&cached.path, &destination)?;
+
+        println!("Copied to: {}", destination.display());
+    }
+
+    Ok(())
+}
+
+fn repair(args: RepairArgs) -> Result<(), Box<dyn Error>> {
+    let source = install::local_path(&args.source)?;
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        language: locale::current(),
+        ..Default::default()
+    };
+    msitrace::repair_cache(&args.product_code, &source, &options)?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install::{cancel_on_ctrl_c, events_handler, local_path, numbered_log};
+use crate::locale;
+use msitrace::{ChainManifest, InstallOptions, InstallQueue, MessageFilter};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to a TOML manifest listing the packages to install in order.
+    manifest: PathBuf,
+
+    /// Path to a verbose log file to write. Each package writes its own numbered log.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// Write each message traced from every package to a file as a line of JSON.
+    #[arg(long, value_name = "PATH")]
+    events: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let manifest = ChainManifest::from_file(&args.manifest)?;
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+
+    let options = InstallOptions {
+        ui: args.ui,
+        handler: args
+            .events
+            .map(|events| events_handler(events, MessageFilter::default()))
+            .transpose()?,
+        language: locale::current(),
+        cancel: Some(cancel_on_ctrl_c()?),
+        ..Default::default()
+    };
+
+    let mut queue = InstallQueue::new();
+    if let Some(name) = &manifest.name {
+        println!("Chain: {}", name);
+    }
+    if manifest.transaction {
+        queue.transaction(manifest.name.as_deref().unwrap_or("msitrace chain"));
+    }
+
+    for (i, package) in manifest.packages.iter().enumerate() {
+        let mut options = options.clone();
+        package.apply(&mut options.properties)?;
+
+        // Give each package its own log rather than overwriting one log.
+        if let Some(log) = &log {
+            options.log = Some(numbered_log(&log.to_string_lossy(), i + 1));
+        }
+
+        let path = local_path(&package.path)?;
+        match manifest.continue_on_error(i) {
+            true => queue.push_optional(path, options),
+            false => queue.push(path, options),
+        };
+    }
+    match queue.run() {
+        Ok(()) => Ok(()),
+        // The queue installs every package before returning a reboot code, which counts as success.
+        Err(err) if matches!(err.code(), Some(3010 | 1641)) => {
+            println!("Chain finished; a reboot is required");
+            Err(err.into())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
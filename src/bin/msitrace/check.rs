@@ -0,0 +1,29 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the package to check.
+    path: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let path = args.path.to_string_lossy();
+    let summary = msitrace::verify_package(path.as_ref())?;
+
+    println!("{}: valid installer package", path);
+    if let Some(package_code) = summary.revision_number {
+        println!("Package code: {}", &package_code[..38]);
+    }
+    if let Some(template) = summary.template {
+        println!("Template: {}", template);
+    }
+    if let Some(schema) = summary.page_count {
+        println!("Schema: {}", schema);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{Database, DatabaseDiff, DiffStatus};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the old package.
+    old: PathBuf,
+
+    /// Path to the new package.
+    new: PathBuf,
+
+    /// The format of the report.
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+    Html,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let old = Database::open(&args.old)?;
+    let new = Database::open(&args.new)?;
+    let diff = msitrace::diff(&old, &new)?;
+
+    match args.format {
+        Format::Text => text(&diff),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        Format::Html => html(&diff, &args),
+    }
+
+    Ok(())
+}
+
+fn symbol(status: DiffStatus) -> char {
+    match status {
+        DiffStatus::Added => '+',
+        DiffStatus::Removed => '-',
+        DiffStatus::Changed => '~',
+    }
+}
+
+fn text(diff: &DatabaseDiff) {
+    for table in &diff.tables {
+        println!("{} {}", symbol(table.status), table.name);
+        for row in &table.rows {
+            println!("  {} [{}]", symbol(row.status), row.keys.join(", "));
+            if row.status != DiffStatus::Changed {
+                continue;
+            }
+            for cell in &row.cells {
+                println!(
+                    "      {}: {} -> {}",
+                    cell.column,
+                    cell.old.as_deref().unwrap_or("(null)"),
+                    cell.new.as_deref().unwrap_or("(null)")
+                );
+            }
+        }
+    }
+}
+
+fn html(diff: &DatabaseDiff, args: &Args) {
+    println!("<!DOCTYPE html>");
+    println!("<html>");
+    println!("<head>");
+    println!("<meta charset=\"utf-8\">");
+    println!(
+        "<title>{} to {}</title>",
+        escape(&args.old.to_string_lossy()),
+        escape(&args.new.to_string_lossy())
+    );
+    println!("<style>.added {{ background: #e6ffec; }} .removed {{ background: #ffebe9; }} table {{ border-collapse: collapse; }} td, th {{ border: 1px solid #ccc; padding: 2px 6px; }}</style>");
+    println!("</head>");
+    println!("<body>");
+
+    for table in &diff.tables {
+        println!(
+            "<h2 class=\"{}\">{} {}</h2>",
+            class(table.status),
+            symbol(table.status),
+            escape(&table.name)
+        );
+        println!("<table>");
+        println!("<tr><th>Row</th><th>Column</th><th>Old</th><th>New</th></tr>");
+        for row in &table.rows {
+            for cell in &row.cells {
+                println!(
+                    "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    class(row.status),
+                    escape(&row.keys.join(", ")),
+                    escape(&cell.column),
+                    escape(cell.old.as_deref().unwrap_or_default()),
+                    escape(cell.new.as_deref().unwrap_or_default())
+                );
+            }
+        }
+        println!("</table>");
+    }
+
+    println!("</body>");
+    println!("</html>");
+}
+
+fn class(status: DiffStatus) -> &'static str {
+    match status {
+        DiffStatus::Added => "added",
+        DiffStatus::Removed => "removed",
+        DiffStatus::Changed => "changed",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,100 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::CheckStatus;
+use std::error::Error;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Cross-check Windows Installer products against the Add/Remove Programs entries.
+    Arp,
+
+    /// Check whether Windows Installer can start an install now, failing fast if the service
+    /// is disabled or another install is in progress.
+    Installer,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Some(Command::Arp) => return arp(),
+        Some(Command::Installer) => return installer(args.json),
+        None => {}
+    }
+
+    let checks = msitrace::diagnose();
+    let status = checks
+        .iter()
+        .map(|check| check.status)
+        .max()
+        .unwrap_or(CheckStatus::Pass);
+
+    if args.json {
+        let report = serde_json::json!({
+            "status": status,
+            "checks": checks,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for check in &checks {
+            println!("{}", check);
+        }
+    }
+
+    if status == CheckStatus::Fail {
+        return Err("one or more checks failed".into());
+    }
+
+    Ok(())
+}
+
+fn arp() -> Result<(), Box<dyn Error>> {
+    let issues = msitrace::arp_issues()?;
+    if issues.is_empty() {
+        println!("Uninstall entries: no issues");
+    }
+    for issue in issues {
+        println!("Uninstall entry: {}", issue);
+    }
+
+    Ok(())
+}
+
+fn installer(json: bool) -> Result<(), Box<dyn Error>> {
+    let status = msitrace::installer_status()?;
+    let version = status.version.map(|version| version.to_string());
+
+    if json {
+        let report = serde_json::json!({
+            "service": status.service.to_string(),
+            "busy": status.busy,
+            "version": version,
+            "failure": status.failure_reason(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Installer service: {}", status.service);
+        println!(
+            "Install in progress: {}",
+            if status.busy { "yes" } else { "no" }
+        );
+        println!(
+            "Installer version: {}",
+            version.as_deref().unwrap_or("unknown")
+        );
+    }
+
+    match status.failure_reason() {
+        Some(reason) => Err(reason.into()),
+        None => Ok(()),
+    }
+}
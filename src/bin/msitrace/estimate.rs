@@ -0,0 +1,48 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{Database, InstallStats};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the package.
+    path: PathBuf,
+
+    /// Calibrate the duration from stats written by previous installs with --stats-out.
+    /// May be repeated.
+    #[arg(long, value_name = "PATH")]
+    calibrate: Vec<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let calibration = args
+        .calibrate
+        .iter()
+        .map(InstallStats::from_file)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let database = Database::open(&args.path)?;
+    let estimate = msitrace::estimate(&database, &calibration)?;
+
+    println!("Files: {}", estimate.files);
+    println!("Size: {}", size(estimate.size));
+    for (feature, bytes) in &estimate.features {
+        println!("Feature {}: {}", feature, size(*bytes));
+    }
+    println!(
+        "Duration: about {}s ({})",
+        estimate.duration.as_secs(),
+        match estimate.calibrated {
+            true => "calibrated",
+            false => "uncalibrated",
+        }
+    );
+
+    Ok(())
+}
+
+fn size(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
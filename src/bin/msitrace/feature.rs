@@ -0,0 +1,108 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::locale;
+use crate::repair::parse_mode;
+use msitrace::{FeatureState, InstallOptions, ReinstallMode};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Change the state of a feature of an installed product and trace its actions.
+    Set(SetArgs),
+
+    /// Reinstall a feature of an installed product and trace its actions.
+    Repair(RepairArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct SetArgs {
+    /// The product code of an installed product, e.g. {00000000-0000-0000-0000-000000000000}.
+    product_code: String,
+
+    /// The name of the feature in the Feature table.
+    feature: String,
+
+    /// The state to change the feature to.
+    #[arg(value_enum)]
+    state: FeatureState,
+
+    #[command(flatten)]
+    trace: TraceArgs,
+}
+
+#[derive(Debug, clap::Args)]
+struct RepairArgs {
+    /// The product code of an installed product, e.g. {00000000-0000-0000-0000-000000000000}.
+    product_code: String,
+
+    /// The name of the feature in the Feature table.
+    feature: String,
+
+    /// What to reinstall, as REINSTALLMODE letters, e.g. omus or vomus.
+    #[arg(long, default_value = "omus", value_parser = parse_mode)]
+    mode: ReinstallMode,
+
+    #[command(flatten)]
+    trace: TraceArgs,
+}
+
+/// Options shared by commands that trace changes to a feature.
+#[derive(Debug, clap::Args)]
+struct TraceArgs {
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// A directory containing the original package to resolve the source from if the
+    /// cached source is missing.
+    #[arg(long, value_name = "DIR")]
+    source: Option<PathBuf>,
+}
+
+impl TraceArgs {
+    fn options(self) -> std::io::Result<InstallOptions> {
+        let log = self
+            .log
+            .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+            .transpose()?;
+        let source = self
+            .source
+            .map(|source| std::env::current_dir().map(|dir| dir.join(source)))
+            .transpose()?;
+
+        Ok(InstallOptions {
+            log: log.map(|log| log.to_string_lossy().into_owned()),
+            ui: self.ui,
+            language: locale::current(),
+            source: source.map(|source| source.to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+    }
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Command::Set(args) => {
+            let options = args.trace.options()?;
+            msitrace::configure_feature(&args.product_code, &args.feature, args.state, &options)?;
+        }
+        Command::Repair(args) => {
+            let options = args.trace.options()?;
+            msitrace::repair_feature(&args.product_code, &args.feature, args.mode, &options)?;
+        }
+    }
+
+    Ok(())
+}
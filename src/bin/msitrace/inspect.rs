@@ -0,0 +1,84 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the patch (.msp) or transform (.mst).
+    path: PathBuf,
+
+    /// A package to list the rows a transform adds, changes, or deletes.
+    #[arg(long, value_name = "PATH")]
+    base: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let extension = args
+        .path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("msp") => patch(&args.path),
+        Some("mst") => transform(&args.path, args.base.as_deref()),
+        _ => Err("only patches (.msp) and transforms (.mst) can be inspected".into()),
+    }
+}
+
+fn patch(path: &Path) -> Result<(), Box<dyn Error>> {
+    let patch = msitrace::inspect_patch(path)?;
+    println!("Patch code: {}", patch.patch_code);
+    for code in &patch.obsoletes {
+        println!("Obsoletes: {}", code);
+    }
+    for code in &patch.target_product_codes {
+        println!("Target product: {}", code);
+    }
+
+    for transform in &patch.transforms {
+        let info = &transform.info;
+        println!(
+            "Transform {}: {} {} -> {}",
+            transform.name,
+            info.target_product_code.as_deref().unwrap_or("any product"),
+            info.target_version.as_deref().unwrap_or("any version"),
+            info.upgraded_version.as_deref().unwrap_or("unchanged"),
+        );
+    }
+
+    for file in &patch.files {
+        println!("File: {}", file);
+    }
+
+    Ok(())
+}
+
+fn transform(path: &Path, base: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let transform = msitrace::inspect_transform(path)?;
+    print_field("Target product", &transform.target_product_code);
+    print_field("Target version", &transform.target_version);
+    print_field("Upgraded product", &transform.upgraded_product_code);
+    print_field("Upgraded version", &transform.upgraded_version);
+    print_field("Upgrade code", &transform.upgrade_code);
+    for validation in &transform.validations {
+        println!("Validates: {}", validation);
+    }
+    for error in &transform.suppressed_errors {
+        println!("Ignores: {}", error);
+    }
+
+    if let Some(base) = base {
+        for change in msitrace::transform_changes(base, path)? {
+            println!("{}", change);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_field(name: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        println!("{}: {}", name, value);
+    }
+}
@@ -0,0 +1,590 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::locale;
+use crate::progress::{self, progress_handler};
+use clap::error::ErrorKind;
+use msitrace::{
+    ActionDataMode, CancellationToken, CommandLine, Database, Event, ExternalUI, Hooks,
+    InstallOptions, InstallQueue, InstallStats, Manifest, MessageFilter, MessageHandler,
+    TraceEvent,
+};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const MAX_PATH: usize = 260;
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let paths = args
+        .paths
+        .iter()
+        .map(|path| package_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    if args.stats_out.is_some() && paths.len() > 1 {
+        return Err("--stats-out requires a single package".into());
+    }
+
+    let mut log: Option<String> = None;
+    if args.log.is_some() {
+        let log_path = std::env::current_dir()?.join(args.log.unwrap());
+        let log_path = log_path.to_string_lossy();
+
+        log = Some(String::from(log_path));
+    }
+
+    let mut properties = CommandLine::new();
+    for path in &args.properties_files {
+        properties.set_properties(&std::fs::read_to_string(path)?)?;
+    }
+    set_properties(&mut properties, &args.properties)?;
+    if args.expand_env {
+        properties.expand_env()?;
+    }
+    for name in &args.hide {
+        properties.hide(name.as_str())?;
+    }
+    if let Some(context) = args.context {
+        properties.set_context(context);
+    }
+
+    // Embedded transforms are named with a leading colon; others are local paths.
+    let transforms = args
+        .transforms
+        .iter()
+        .map(|transform| match transform.to_string_lossy() {
+            name if name.starts_with(':') => Ok(name.into_owned()),
+            _ => local_path(transform),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let patches = args
+        .patches
+        .iter()
+        .map(|patch| local_path(patch))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut hooks = Hooks::new();
+    for command in args.on_error {
+        hooks.on_error(command);
+    }
+    for command in args.on_complete {
+        hooks.on_complete(command);
+    }
+    for hook in &args.on_action {
+        hooks.set_action(hook)?;
+    }
+    hooks.timeout(args.hook_timeout);
+
+    // Draw a progress bar instead of every message, even while writing structured events.
+    // Progress cannot be parsed from messages received as text.
+    let progress =
+        !args.no_progress && args.external_ui == ExternalUI::Record && progress::is_supported();
+    let mut handlers = Vec::new();
+    if progress {
+        handlers.push(progress_handler());
+    }
+    if let Some(events) = args.events {
+        // Only write the messages asked for, not those added for the progress bar.
+        handlers.push(events_handler(events, args.messages)?);
+    }
+    let handler = match handlers.len() {
+        0 => None,
+        1 => handlers.pop(),
+        _ => Some(MessageHandler::chain(handlers)),
+    };
+
+    let options = InstallOptions {
+        log,
+        summary_out: args.summary_out,
+        ui: args.ui,
+        properties,
+        product_language: args.language,
+        transforms,
+        patches,
+        require_signed: args.require_signed,
+        publisher_thumbprint: args.publisher_thumbprint,
+        allow_unsigned: args.allow_unsigned,
+        sha256: args.sha256,
+        manifest: args.manifest.map(Manifest::from_file).transpose()?,
+        allow_insecure_source: args.allow_insecure_source,
+        strict_policy: args.strict_policy,
+        close_apps: args.close_apps,
+        files_in_use: args.files_in_use,
+        start_service: args.start_service,
+        require_clean_state: args.require_clean_state,
+        restart_manager: args.restart_manager,
+        reboot: args.reboot,
+        reboot_delay: args.reboot_delay,
+        reboot_message: args.reboot_message,
+        verify: args.verify,
+        verify_hashes: args.verify_hashes,
+        hooks,
+        before_install: None,
+        handler,
+        external_ui: args.external_ui,
+        // The progress bar needs progress and the actions it describes.
+        messages: match progress {
+            true => {
+                args.messages
+                    | MessageFilter::PROGRESS
+                    | MessageFilter::ACTION_START
+                    | MessageFilter::ACTION_DATA
+            }
+            false => args.messages,
+        },
+        language: locale::current(),
+        interactive: args.interactive,
+        transaction: None,
+        wait_busy: args.wait_busy,
+        cancel: Some(cancel_on_ctrl_c()?),
+        logging_policy: args.logging_policy,
+        source: None,
+        action_data: args.action_data,
+        quiet: progress,
+    };
+    if args.elevate && !msitrace::is_elevated()? && requires_elevation(&paths, &options)? {
+        let args: Vec<String> = std::env::args()
+            .skip(1)
+            .filter(|arg| arg != "--elevate")
+            .collect();
+        let code = msitrace::run_elevated(&args, "--attach-output")?;
+        std::process::exit(code as i32);
+    }
+
+    if args.queue || args.transaction.is_some() || paths.len() > 1 {
+        let count = paths.len();
+        let mut queue = InstallQueue::new();
+        if let Some(name) = args.transaction {
+            queue.transaction(name);
+        }
+        for (i, path) in paths.into_iter().enumerate() {
+            // Give each package its own log rather than overwriting one log.
+            let mut options = options.clone();
+            if count > 1 {
+                options.log = options.log.map(|log| numbered_log(&log, i + 1));
+                options.summary_out = options
+                    .summary_out
+                    .map(|path| numbered_log(&path.to_string_lossy(), i + 1).into());
+            }
+            queue.push(path, options);
+        }
+        queue.run()?;
+    } else {
+        let start = Instant::now();
+        msitrace::install(&paths[0], &options)?;
+
+        if let Some(stats_out) = args.stats_out {
+            let estimate = msitrace::estimate(&Database::open(&paths[0])?, &[])?;
+            let stats = InstallStats {
+                size: estimate.size,
+                duration_secs: start.elapsed().as_secs_f64(),
+            };
+            stats.write(stats_out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets whether any package installs per-machine. Packages at URLs are assumed to.
+fn requires_elevation(paths: &[String], options: &InstallOptions) -> msitrace::Result<bool> {
+    for path in paths {
+        if path.contains("://") || msitrace::requires_elevation(path, options)? {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn package_path(path: &Path) -> Result<String, Box<dyn Error>> {
+    let text = path.to_string_lossy();
+
+    // Packages at URLs are downloaded when installed.
+    if text.contains("://") {
+        return Ok(text.into_owned());
+    }
+
+    if !path.exists() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "test");
+        return Err(Box::new(err));
+    }
+
+    Ok(local_path(path)?)
+}
+
+/// Sets properties from `NAME=VALUE` arguments, or reads them from files passed as `@PATH`.
+pub fn set_properties(properties: &mut CommandLine, args: &[String]) -> Result<(), Box<dyn Error>> {
+    for arg in args {
+        match arg.strip_prefix('@') {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|err| format!("failed to read properties from {}: {}", path, err))?;
+                properties.set_properties(&text)?;
+            }
+            None => {
+                properties.set_property(arg)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets a token cancelled when the user presses Ctrl+C, so installs roll back
+/// instead of leaving a product partially installed.
+pub fn cancel_on_ctrl_c() -> msitrace::Result<CancellationToken> {
+    let cancel = CancellationToken::new();
+    cancel.cancel_on_ctrl_c()?;
+
+    Ok(cancel)
+}
+
+/// Writes each traced message of the types in `messages` to a file as a line of JSON.
+pub fn events_handler(path: PathBuf, messages: MessageFilter) -> std::io::Result<MessageHandler> {
+    let file = Mutex::new(BufWriter::new(File::create(path)?));
+
+    Ok(MessageHandler::events(move |event| {
+        let Event::Message {
+            elapsed,
+            message,
+            text,
+            action,
+            ..
+        } = event
+        else {
+            return;
+        };
+        let message_type = message.message_type();
+        if !messages.contains(message_type.into()) {
+            return;
+        }
+
+        let mut event = TraceEvent::new(format!("{:?}", message_type), text);
+        event.elapsed_ms = Some(elapsed.as_millis() as u64);
+        if let Some(action) = action {
+            event.action = Some(action.name);
+            event.action_sequence = Some(action.sequence);
+        }
+        let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // Flush each line so events are not lost if the process is terminated.
+        let result = serde_json::to_writer(&mut *file, &event)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writeln!(file))
+            .and_then(|_| file.flush());
+        if let Err(err) = result {
+            eprintln!("warning: failed to write event: {}", err);
+        }
+    }))
+}
+
+/// Parses comma-separated message type names.
+fn parse_messages(value: &str) -> Result<MessageFilter, String> {
+    value
+        .parse()
+        .map_err(|err: msitrace::Error| err.to_string())
+}
+
+/// Inserts a number before the extension of a log path, e.g. install_2.log.
+pub fn numbered_log(log: &str, n: usize) -> String {
+    let log = Path::new(log);
+    let stem = log.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match log.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, n, extension.to_string_lossy()),
+        None => format!("{}_{}", stem, n),
+    };
+
+    log.with_file_name(name).to_string_lossy().into_owned()
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Paths or URLs to the packages to install. Multiple packages are installed in order.
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Wait for queued installs in other msitrace processes and Windows Installer
+    /// to finish before each install.
+    #[arg(long)]
+    queue: bool,
+
+    /// Install all packages in a transaction with this name, rolling back all of them
+    /// if any fails.
+    #[arg(long, value_name = "NAME")]
+    transaction: Option<String>,
+
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// Install for the current user, the machine, or whichever the package chooses,
+    /// overriding ALLUSERS and MSIINSTALLPERUSER properties.
+    #[arg(long, value_enum)]
+    context: Option<msitrace::InstallContext>,
+
+    /// Install a multi-language package in this language identifier (LCID), e.g. 1031,
+    /// applying its embedded language transform if it has one.
+    #[arg(long, value_name = "LCID")]
+    language: Option<u16>,
+
+    /// Apply a transform, or a transform embedded in the package named with a leading colon,
+    /// e.g. :fr-FR.mst. May be repeated.
+    #[arg(long = "transform", value_name = "PATH")]
+    transforms: Vec<PathBuf>,
+
+    /// Slipstream a patch into the install. May be repeated.
+    #[arg(long = "patch", value_name = "PATH")]
+    patches: Vec<PathBuf>,
+
+    /// Require the package have a trusted Authenticode signature, and show its signer.
+    #[arg(long, visible_alias = "verify-signature")]
+    require_signed: bool,
+
+    /// With --require-signed, also install packages that are not signed at all.
+    /// Signatures that are present must still be trusted.
+    #[arg(long, requires = "require_signed")]
+    allow_unsigned: bool,
+
+    /// Require the package signer certificate have this SHA-1 thumbprint. Implies --require-signed.
+    #[arg(long, value_name = "HEX")]
+    publisher_thumbprint: Option<String>,
+
+    /// Require the package have this SHA-256 digest. Packages at URLs are downloaded
+    /// to a temporary cache and verified before installing.
+    #[arg(long, value_name = "HEX")]
+    sha256: Option<String>,
+
+    /// Only install the package if listed in this JSON manifest with a matching SHA-256 digest.
+    #[arg(long, value_name = "PATH")]
+    manifest: Option<PathBuf>,
+
+    /// Allow installing from plain HTTP URLs or network shares anonymous users can write to.
+    #[arg(long)]
+    allow_insecure_source: bool,
+
+    /// Refuse to install when the AlwaysInstallElevated policy is enabled.
+    #[arg(long)]
+    strict_policy: bool,
+
+    /// Close applications holding files in use and restart them after installing.
+    #[arg(long)]
+    close_apps: bool,
+
+    /// How to respond when applications hold files the install needs to replace.
+    #[arg(long, value_enum, value_name = "POLICY", conflicts_with = "close_apps")]
+    files_in_use: Option<msitrace::FilesInUsePolicy>,
+
+    /// Disable the Restart Manager, or only its shutting down of applications holding
+    /// files in use.
+    #[arg(long, value_enum, conflicts_with = "close_apps")]
+    restart_manager: Option<msitrace::RestartManagerControl>,
+
+    /// Start the Windows Installer service before installing if it is stopped.
+    #[arg(long)]
+    start_service: bool,
+
+    /// Refuse to install when operations from previous installs or updates are pending.
+    #[arg(long)]
+    require_clean_state: bool,
+
+    /// Whether to schedule, suppress, force, or prompt for a reboot the install requires.
+    /// Exits with 3010 when a reboot is required, or 1641 when one was started.
+    #[arg(long, value_enum)]
+    reboot: Option<msitrace::Reboot>,
+
+    /// How long to wait before a scheduled reboot, e.g. 90s, 10m, or 1h.
+    #[arg(long, value_name = "DURATION", default_value = "10m", value_parser = parse_duration)]
+    reboot_delay: Duration,
+
+    /// A message to show users before a scheduled reboot.
+    #[arg(long, value_name = "MESSAGE")]
+    reboot_message: Option<String>,
+
+    /// After a successful install, fail if the key path of any installed component is missing.
+    #[arg(long)]
+    verify: bool,
+
+    /// Also fail if unversioned key files do not match their MsiFileHash. Implies --verify.
+    #[arg(long)]
+    verify_hashes: bool,
+
+    /// If another install is in progress, wait up to this long for it to finish and retry,
+    /// e.g. 90s, 10m, or 1h.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    wait_busy: Option<Duration>,
+
+    /// Prompt how to respond to errors and files in use instead of failing the install.
+    #[arg(long)]
+    interactive: bool,
+
+    /// If a package installs per-machine and msitrace is not elevated, prompt to run
+    /// elevated and show its output here.
+    #[arg(long)]
+    elevate: bool,
+
+    /// Set the machine logging policy to these modes, e.g. voicewarmupx, until the install
+    /// completes so packages it starts in other processes are also logged. Requires elevation.
+    #[arg(long, value_name = "MODES")]
+    logging_policy: Option<String>,
+
+    /// Write each traced message to a file as a line of JSON. See `msitrace schema event`.
+    #[arg(long, value_name = "PATH")]
+    events: Option<PathBuf>,
+
+    /// Trace only these comma-separated types of messages, e.g. Error,Warning,ActionStart,
+    /// or "all" to include Performance messages.
+    #[arg(long, value_name = "TYPES", default_value = "default", value_parser = parse_messages)]
+    messages: MessageFilter,
+
+    /// How to print action data messages.
+    #[arg(
+        long,
+        alias = "actiondata",
+        value_name = "MODE",
+        value_enum,
+        default_value_t
+    )]
+    action_data: ActionDataMode,
+
+    /// Print every message instead of a progress bar when writing to a console.
+    #[arg(long)]
+    no_progress: bool,
+
+    /// Receive each message as its record, or as text formatted by Windows Installer with the
+    /// legacy string handler. Text messages cannot draw a progress bar or list applications
+    /// holding files in use.
+    #[arg(
+        long,
+        value_name = "API",
+        value_enum,
+        default_value_t,
+        conflicts_with_all = ["close_apps", "files_in_use", "interactive"]
+    )]
+    external_ui: ExternalUI,
+
+    /// Run a command when Windows Installer reports an error, with the event as JSON on stdin.
+    /// May be repeated.
+    #[arg(long, value_name = "COMMAND")]
+    on_error: Vec<String>,
+
+    /// Run a command when the install completes, with the event as JSON on stdin.
+    /// May be repeated.
+    #[arg(long, value_name = "COMMAND")]
+    on_complete: Vec<String>,
+
+    /// Run a command when an action matching a regular expression starts,
+    /// with the event as JSON on stdin. May be repeated.
+    #[arg(long, value_name = "REGEX=COMMAND")]
+    on_action: Vec<String>,
+
+    /// How long each hook command may run before it is killed, e.g. 90s, 10m, or 1h.
+    #[arg(long, value_name = "DURATION", default_value = "5m", value_parser = parse_duration)]
+    hook_timeout: Duration,
+
+    /// Write the summary printed after the install as JSON. See `msitrace schema summary`.
+    #[arg(long, value_name = "PATH")]
+    summary_out: Option<PathBuf>,
+
+    /// Write the payload size and duration of the install as JSON to calibrate estimates.
+    /// Requires a single package.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["queue", "transaction"])]
+    stats_out: Option<PathBuf>,
+
+    /// Read NAME=VALUE lines from a file, ignoring blank lines and comments starting with #.
+    /// Properties passed after -- replace those read from files. May be repeated.
+    #[arg(long = "properties-file", value_name = "PATH")]
+    properties_files: Vec<PathBuf>,
+
+    /// Expand environment variables written as %NAME%, $NAME, or ${NAME} in property values.
+    /// Write %% or $$ for a literal % or $.
+    #[arg(long)]
+    expand_env: bool,
+
+    /// Redact the value of a property from all output. May be repeated.
+    #[arg(long, value_name = "PROPERTY")]
+    hide: Vec<String>,
+
+    /// Properties to pass to the install, or @PATH to read them from a file.
+    #[arg(last = true, value_parser = validate_property)]
+    properties: Vec<String>,
+}
+
+/// Gets the canonical path to a local package, stripping the extended-length prefix
+/// unless the path is too long for MAX_PATH.
+pub fn local_path(path: &Path) -> std::io::Result<String> {
+    let path = path.canonicalize()?;
+    let path = path.to_string_lossy();
+
+    // Keep the extended-length prefix on paths too long for MAX_PATH.
+    let short = match path.strip_prefix(r"\\?\UNC\") {
+        Some(path) => format!(r"\\{}", path),
+        None => path
+            .strip_prefix(r"\\?\")
+            .unwrap_or_else(|| path.as_ref())
+            .to_owned(),
+    };
+    match short.encode_utf16().count() < MAX_PATH {
+        true => Ok(short),
+        false => Ok(path.into_owned()),
+    }
+}
+
+fn validate_property(value: &str) -> clap::error::Result<String> {
+    type Error = clap::Error;
+
+    if value.is_empty() {
+        return Err(Error::raw(
+            ErrorKind::ValueValidation,
+            "property cannot be empty",
+        ));
+    }
+
+    // Files are read when properties are set.
+    if value.starts_with('@') {
+        return Ok(value.to_owned());
+    }
+
+    if value.match_indices('=').count() != 1 {
+        return Err(Error::raw(
+            ErrorKind::ValueValidation,
+            "requires PROP= or PROP=VALUE",
+        ));
+    }
+
+    Ok(value.to_owned())
+}
+
+fn parse_duration(value: &str) -> clap::error::Result<Duration> {
+    type Error = clap::Error;
+
+    let (number, unit) = value.split_at(
+        value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len()),
+    );
+    let number: u64 = number
+        .parse()
+        .map_err(|_| Error::raw(ErrorKind::ValueValidation, "requires a number, e.g. 10m"))?;
+    let secs = match unit {
+        "" | "s" => Some(number),
+        "m" => number.checked_mul(60),
+        "h" => number.checked_mul(60 * 60),
+        _ => {
+            return Err(Error::raw(
+                ErrorKind::ValueValidation,
+                "requires a unit of s, m, or h",
+            ))
+        }
+    };
+    let secs = secs.ok_or_else(|| Error::raw(ErrorKind::ValueValidation, "is too long"))?;
+
+    Ok(Duration::from_secs(secs))
+}
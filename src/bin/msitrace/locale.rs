@@ -0,0 +1,221 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::Language;
+use std::sync::OnceLock;
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+// cspell:disable
+/// Translated descriptions of commands: name, German, French, and Spanish.
+const COMMANDS: &[(&str, &str, &str, &str)] = &[
+    (
+        "install",
+        "Ein Paket installieren und seine Aktionen verfolgen.",
+        "Installer un package et tracer ses actions.",
+        "Instalar un paquete y seguir sus acciones.",
+    ),
+    (
+        "chain",
+        "Die in einem Manifest aufgeführten Pakete der Reihe nach installieren und ihre Aktionen verfolgen.",
+        "Installer dans l'ordre les packages listés dans un manifeste et tracer leurs actions.",
+        "Instalar en orden los paquetes enumerados en un manifiesto y seguir sus acciones.",
+    ),
+    (
+        "uninstall",
+        "Ein Produkt deinstallieren und seine Aktionen verfolgen.",
+        "Désinstaller un produit et tracer ses actions.",
+        "Desinstalar un producto y seguir sus acciones.",
+    ),
+    (
+        "repair",
+        "Ein installiertes Produkt reparieren und seine Aktionen verfolgen.",
+        "Réparer un produit installé et tracer ses actions.",
+        "Reparar un producto instalado y seguir sus acciones.",
+    ),
+    (
+        "recover",
+        "Eine fehlende oder beschädigte Komponente oder Datei eines installierten Produkts neu installieren und ihre Aktionen verfolgen.",
+        "Réinstaller un composant ou un fichier manquant ou endommagé d'un produit installé et tracer ses actions.",
+        "Reinstalar un componente o archivo que falta o está dañado de un producto instalado y seguir sus acciones.",
+    ),
+    (
+        "provide",
+        "Eine Komponente bei Bedarf installieren und ihre Aktionen verfolgen, wie es angekündigte Verknüpfungen tun.",
+        "Installer un composant à la demande et tracer ses actions, comme le font les raccourcis publiés.",
+        "Instalar un componente a petición y seguir sus acciones, como hacen los accesos directos anunciados.",
+    ),
+    (
+        "advertise",
+        "Ein Paket ankündigen und seine Aktionen verfolgen.",
+        "Publier un package et tracer ses actions.",
+        "Anunciar un paquete y seguir sus acciones.",
+    ),
+    (
+        "advertise-script",
+        "Ein Ankündigungsskript auf den aktuellen Benutzer anwenden und seine Aktionen verfolgen.",
+        "Appliquer un script de publication à l'utilisateur actuel et tracer ses actions.",
+        "Aplicar un script de anuncio al usuario actual y seguir sus acciones.",
+    ),
+    (
+        "admin-install",
+        "Ein unkomprimiertes administratives Abbild eines Pakets extrahieren und seine Aktionen verfolgen.",
+        "Extraire une image administrative non compressée d'un package et tracer ses actions.",
+        "Extraer una imagen administrativa sin comprimir de un paquete y seguir sus acciones.",
+    ),
+    (
+        "feature",
+        "Features eines installierten Produkts ändern oder reparieren.",
+        "Modifier ou réparer les fonctionnalités d'un produit installé.",
+        "Cambiar o reparar las características de un producto instalado.",
+    ),
+    (
+        "patch",
+        "Patches anwenden und analysieren.",
+        "Appliquer et analyser des correctifs.",
+        "Aplicar y analizar revisiones.",
+    ),
+    (
+        "serve",
+        "Eine lokale JSON-API bereitstellen, um verfolgte Installationsaufträge zu übermitteln und ihre Ereignisse zu streamen.",
+        "Exposer une API JSON locale pour soumettre des installations tracées et diffuser leurs événements.",
+        "Ofrecer una API JSON local para enviar instalaciones seguidas y transmitir sus eventos.",
+    ),
+    (
+        "check",
+        "Vor der Installation prüfen, ob ein Paket gültig ist.",
+        "Vérifier qu'un package est valide avant de l'installer.",
+        "Comprobar que un paquete es válido antes de instalarlo.",
+    ),
+    (
+        "preflight",
+        "Prüfen, ob ein Paket auf diesem Computer installiert werden kann, ohne es zu installieren.",
+        "Vérifier si un package peut être installé sur cet ordinateur sans l'installer.",
+        "Comprobar si un paquete se puede instalar en este equipo sin instalarlo.",
+    ),
+    (
+        "doctor",
+        "Die Umgebung auf Bedingungen prüfen, die häufig zu fehlgeschlagenen Installationen führen.",
+        "Rechercher dans l'environnement les conditions qui font souvent échouer les installations.",
+        "Buscar en el entorno condiciones que suelen hacer fallar las instalaciones.",
+    ),
+    (
+        "cache",
+        "Das zwischengespeicherte Paket eines installierten Produkts suchen, überprüfen und kopieren.",
+        "Rechercher, vérifier et copier le package en cache d'un produit installé.",
+        "Buscar, verificar y copiar el paquete en caché de un producto instalado.",
+    ),
+    (
+        "logging",
+        "Die Protokollierungsrichtlinie des Computers anzeigen oder vorübergehend ändern.",
+        "Afficher ou modifier temporairement la stratégie de journalisation de l'ordinateur.",
+        "Mostrar o cambiar temporalmente la directiva de registro del equipo.",
+    ),
+    (
+        "bundle",
+        "Mit WiX-Burn-Bundles arbeiten.",
+        "Travailler avec des bundles WiX Burn.",
+        "Trabajar con paquetes WiX Burn.",
+    ),
+    (
+        "inspect",
+        "Vor der Bereitstellung anzeigen, worauf ein Patch oder eine Transformation abzielt und was geändert wird.",
+        "Afficher ce qu'un correctif ou une transformation cible et modifie avant de le déployer.",
+        "Mostrar a qué se aplica una revisión o transformación y qué cambia antes de implementarla.",
+    ),
+    (
+        "audit",
+        "Die benutzerdefinierten Aktionen eines Pakets auf erhöhten oder beliebigen Code prüfen.",
+        "Auditer les actions personnalisées d'un package pour du code élevé ou arbitraire.",
+        "Auditar las acciones personalizadas de un paquete en busca de código elevado o arbitrario.",
+    ),
+    (
+        "tables",
+        "Die Tabellen eines Pakets auflisten.",
+        "Lister les tables d'un package.",
+        "Enumerar las tablas de un paquete.",
+    ),
+    (
+        "query",
+        "Ein Paket abfragen und die Ergebniszeilen ausgeben.",
+        "Interroger un package et afficher les lignes obtenues.",
+        "Consultar un paquete e imprimir las filas resultantes.",
+    ),
+    (
+        "summary",
+        "Die Zusammenfassungsinformationen eines Pakets ausgeben.",
+        "Afficher les informations de résumé d'un package.",
+        "Imprimir la información de resumen de un paquete.",
+    ),
+    (
+        "files",
+        "Die Dateien auflisten, die ein Paket installiert.",
+        "Lister les fichiers qu'un package installe.",
+        "Enumerar los archivos que instala un paquete.",
+    ),
+    (
+        "diff",
+        "Zwei Pakete Tabelle für Tabelle und Zeile für Zeile vergleichen.",
+        "Comparer deux packages table par table et ligne par ligne.",
+        "Comparar dos paquetes tabla por tabla y fila por fila.",
+    ),
+    (
+        "estimate",
+        "Schätzen, wie groß ein Paket ist und wie lange seine Installation dauert.",
+        "Estimer la taille d'un package et la durée de son installation.",
+        "Estimar el tamaño de un paquete y cuánto tardará en instalarse.",
+    ),
+    (
+        "schema",
+        "Das JSON-Schema der JSON-Ereignisformate ausgeben, die msitrace schreibt.",
+        "Afficher le schéma JSON des formats d'événements JSON écrits par msitrace.",
+        "Imprimir el esquema JSON de los formatos de eventos JSON que escribe msitrace.",
+    ),
+];
+// cspell:enable
+
+/// Gets the language selected with `--lang`, or the system language.
+///
+/// The language is needed to localize help before arguments are parsed.
+pub fn init() -> Language {
+    let mut args = std::env::args();
+    let mut tag = None;
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--lang" {
+            tag = args.next();
+        } else if let Some(value) = arg.strip_prefix("--lang=") {
+            tag = Some(value.to_owned());
+        }
+    }
+
+    let language = tag
+        .and_then(|tag| Language::from_tag(&tag))
+        .unwrap_or_else(Language::system);
+    *LANGUAGE.get_or_init(|| language)
+}
+
+/// Gets the language selected when arguments were parsed.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn current() -> Language {
+    LANGUAGE.get().copied().unwrap_or_default()
+}
+
+/// Translates the descriptions of commands.
+pub fn localize(mut command: clap::Command, language: Language) -> clap::Command {
+    for (name, german, french, spanish) in COMMANDS {
+        let about = match language {
+            Language::English => return command,
+            Language::German => german,
+            Language::French => french,
+            Language::Spanish => spanish,
+        };
+        if command.find_subcommand(name).is_some() {
+            command = command.mut_subcommand(name, |subcommand| subcommand.about(*about));
+        }
+    }
+
+    command
+}
@@ -0,0 +1,68 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use std::error::Error;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Set the machine logging policy so every install, including those started by other
+    /// processes, writes a verbose log to %TEMP%. Requires elevation.
+    EnablePolicy(EnableArgs),
+
+    /// Restore the machine logging policy saved by enable-policy. Requires elevation.
+    DisablePolicy,
+}
+
+#[derive(Debug, clap::Args)]
+struct EnableArgs {
+    /// The logging modes to set.
+    #[arg(long, default_value = "voicewarmupx")]
+    mode: String,
+
+    /// Also write debug messages to the verbose log.
+    #[arg(long)]
+    debug: bool,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Some(Command::EnablePolicy(args)) => {
+            require_elevation()?;
+            msitrace::enable_logging_policy(&args.mode, args.debug.then_some(7))?;
+            println!("Enabled logging policy; run `msitrace logging disable-policy` to restore it");
+        }
+        Some(Command::DisablePolicy) => {
+            require_elevation()?;
+            match msitrace::disable_logging_policy()? {
+                true => println!("Restored logging policy"),
+                false => println!("Logging policy was not enabled by msitrace"),
+            }
+        }
+        None => {}
+    }
+
+    let policy = msitrace::logging_policy()?;
+    println!(
+        "Logging: {}",
+        policy.logging.as_deref().unwrap_or("not set")
+    );
+    match policy.debug {
+        Some(debug) => println!("Debug: {}", debug),
+        None => println!("Debug: not set"),
+    }
+
+    Ok(())
+}
+
+fn require_elevation() -> Result<(), Box<dyn Error>> {
+    match msitrace::is_elevated()? {
+        true => Ok(()),
+        false => Err("changing the machine logging policy requires elevation".into()),
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::error::Error;
+
+#[cfg(windows)]
+mod admin;
+#[cfg(windows)]
+mod advertise;
+#[cfg(windows)]
+mod advertise_script;
+#[cfg(any(windows, feature = "portable-db"))]
+mod audit;
+#[cfg(windows)]
+mod bundle;
+#[cfg(windows)]
+mod cache;
+#[cfg(windows)]
+mod chain;
+#[cfg(windows)]
+mod check;
+#[cfg(any(windows, feature = "portable-db"))]
+mod diff;
+#[cfg(windows)]
+mod doctor;
+#[cfg(any(windows, feature = "portable-db"))]
+mod estimate;
+#[cfg(windows)]
+mod feature;
+#[cfg(windows)]
+mod inspect;
+#[cfg(windows)]
+mod install;
+mod locale;
+#[cfg(windows)]
+mod logging;
+#[cfg(any(windows, feature = "portable-db"))]
+mod package;
+#[cfg(windows)]
+mod patch;
+#[cfg(windows)]
+mod preflight;
+#[cfg(windows)]
+mod progress;
+#[cfg(windows)]
+mod provide;
+#[cfg(windows)]
+mod recover;
+#[cfg(windows)]
+mod repair;
+mod schema;
+#[cfg(windows)]
+mod serve;
+#[cfg(windows)]
+mod uninstall;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let language = locale::init();
+    let matches = locale::localize(Args::command(), language).get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    #[cfg(windows)]
+    if let Some(pipe) = &args.attach_output {
+        msitrace::attach_output(pipe)?;
+    }
+    let result = match args.command {
+        #[cfg(windows)]
+        Command::Install(args) => install::run(args),
+        #[cfg(windows)]
+        Command::Chain(args) => chain::run(args),
+        #[cfg(windows)]
+        Command::Uninstall(args) => uninstall::run(args),
+        #[cfg(windows)]
+        Command::Repair(args) => repair::run(args),
+        #[cfg(windows)]
+        Command::Recover(args) => recover::run(args),
+        #[cfg(windows)]
+        Command::Provide(args) => provide::run(args),
+        #[cfg(windows)]
+        Command::Advertise(args) => advertise::run(args),
+        #[cfg(windows)]
+        Command::AdvertiseScript(args) => advertise_script::run(args),
+        #[cfg(windows)]
+        Command::AdminInstall(args) => admin::run(args),
+        #[cfg(windows)]
+        Command::Feature(args) => feature::run(args),
+        #[cfg(windows)]
+        Command::Patch(args) => patch::run(args),
+        #[cfg(windows)]
+        Command::Serve(args) => serve::run(args),
+        #[cfg(windows)]
+        Command::Check(args) => check::run(args),
+        #[cfg(windows)]
+        Command::Preflight(args) => preflight::run(args),
+        #[cfg(windows)]
+        Command::Doctor(args) => doctor::run(args),
+        #[cfg(windows)]
+        Command::Cache(args) => cache::run(args),
+        #[cfg(windows)]
+        Command::Logging(args) => logging::run(args),
+        #[cfg(windows)]
+        Command::Bundle(args) => bundle::run(args),
+        #[cfg(windows)]
+        Command::Inspect(args) => inspect::run(args),
+        #[cfg(any(windows, feature = "portable-db"))]
+        Command::Audit(args) => audit::run(args),
+        #[cfg(any(windows, feature = "portable-db"))]
+        Command::Tables(args) => package::tables(args),
+        #[cfg(any(windows, feature = "portable-db"))]
+        Command::Query(args) => package::query(args),
+        #[cfg(any(windows, feature = "portable-db"))]
+        Command::Summary(args) => package::summary(args),
+        #[cfg(any(windows, feature = "portable-db"))]
+        Command::Files(args) => package::files(args),
+        #[cfg(any(windows, feature = "portable-db"))]
+        Command::Diff(args) => diff::run(args),
+        #[cfg(any(windows, feature = "portable-db"))]
+        Command::Estimate(args) => estimate::run(args),
+        Command::Schema(args) => schema::run(args),
+    };
+
+    if let Some(code) = result
+        .as_ref()
+        .err()
+        .and_then(|err| error_code(err.as_ref()))
+    {
+        let success = args.success_codes.contains(&code);
+        match code {
+            ERROR_SUCCESS_REBOOT_REQUIRED => {
+                println!("Succeeded; a reboot is required to complete")
+            }
+            ERROR_SUCCESS_REBOOT_INITIATED => {
+                println!("Succeeded; a reboot was started to complete")
+            }
+            _ if success => println!("Completed with code {}, which counts as success", code),
+            _ => return result,
+        }
+        std::process::exit(match success {
+            true => 0,
+            false => code as i32,
+        });
+    }
+
+    result
+}
+
+/// The operation succeeded but requires a reboot to complete.
+const ERROR_SUCCESS_REBOOT_REQUIRED: u32 = 3010;
+
+/// The operation succeeded and started a reboot to complete.
+const ERROR_SUCCESS_REBOOT_INITIATED: u32 = 1641;
+
+/// Gets the Windows Installer error code an operation failed with, if any.
+fn error_code(err: &(dyn Error + 'static)) -> Option<u32> {
+    err.downcast_ref::<msitrace::Error>()?.code()
+}
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0     Succeeded
+  1     Failed
+  1641  Succeeded and started a reboot to complete
+  3010  Succeeded but requires a reboot to complete
+
+Codes passed to --success-codes exit with 0 instead.";
+
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None, after_help = EXIT_CODES)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+
+    /// The language to show help and trace labels in. Defaults to the system language.
+    #[arg(long, global = true, value_enum)]
+    lang: Option<msitrace::Language>,
+
+    /// Windows Installer error codes that exit with 0, e.g. 3010,1641 when the caller
+    /// handles reboots itself. The actual code is still printed.
+    #[arg(long, global = true, value_name = "CODES", value_delimiter = ',')]
+    success_codes: Vec<u32>,
+
+    /// Write all output to a pipe created by a process that ran msitrace elevated.
+    #[cfg(windows)]
+    #[arg(long, global = true, hide = true, value_name = "PIPE")]
+    attach_output: Option<String>,
+}
+
+// Commands are parsed once, so boxing the larger arguments would not save anything.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Install a package and trace its actions.
+    #[cfg(windows)]
+    Install(install::Args),
+
+    /// Install the packages listed in a manifest in order and trace their actions.
+    #[cfg(windows)]
+    Chain(chain::Args),
+
+    /// Uninstall a product and trace its actions.
+    #[cfg(windows)]
+    Uninstall(uninstall::Args),
+
+    /// Repair an installed product and trace its actions.
+    #[cfg(windows)]
+    Repair(repair::Args),
+
+    /// Reinstall a missing or broken component or file of an installed product and trace its actions.
+    #[cfg(windows)]
+    Recover(recover::Args),
+
+    /// Install a component on demand and trace its actions, as advertised shortcuts do.
+    #[cfg(windows)]
+    Provide(provide::Args),
+
+    /// Advertise a package and trace its actions.
+    #[cfg(windows)]
+    Advertise(advertise::Args),
+
+    /// Apply an advertise script to the current user and trace its actions.
+    #[cfg(windows)]
+    AdvertiseScript(advertise_script::Args),
+
+    /// Extract an uncompressed administrative image of a package and trace its actions.
+    #[cfg(windows)]
+    AdminInstall(admin::Args),
+
+    /// Change or repair features of an installed product.
+    #[cfg(windows)]
+    Feature(feature::Args),
+
+    /// Apply and analyze patches.
+    #[cfg(windows)]
+    Patch(patch::Args),
+
+    /// Serve a local JSON API to submit traced install and uninstall jobs and stream their events.
+    #[cfg(windows)]
+    Serve(serve::Args),
+
+    /// Check that a package is valid before installing it.
+    #[cfg(windows)]
+    Check(check::Args),
+
+    /// Check whether a package can be installed on this machine without installing it.
+    #[cfg(windows)]
+    Preflight(preflight::Args),
+
+    /// Check the environment for conditions that commonly cause installs to fail.
+    #[cfg(windows)]
+    Doctor(doctor::Args),
+
+    /// Find, verify, and copy the cached package of an installed product.
+    #[cfg(windows)]
+    Cache(cache::Args),
+
+    /// Show or temporarily change the machine logging policy.
+    #[cfg(windows)]
+    Logging(logging::Args),
+
+    /// Work with WiX Burn bundles.
+    #[cfg(windows)]
+    Bundle(bundle::Args),
+
+    /// Show what a patch or transform targets and changes before deploying it.
+    #[cfg(windows)]
+    Inspect(inspect::Args),
+
+    /// Audit the custom actions in a package for elevated or arbitrary code.
+    #[cfg(any(windows, feature = "portable-db"))]
+    Audit(audit::Args),
+
+    /// List the tables in a package.
+    #[cfg(any(windows, feature = "portable-db"))]
+    Tables(package::PackageArgs),
+
+    /// Query a package and print the resulting rows.
+    #[cfg(any(windows, feature = "portable-db"))]
+    Query(package::QueryArgs),
+
+    /// Print the summary information of a package.
+    #[cfg(any(windows, feature = "portable-db"))]
+    Summary(package::PackageArgs),
+
+    /// List the files a package installs.
+    #[cfg(any(windows, feature = "portable-db"))]
+    Files(package::PackageArgs),
+
+    /// Compare two packages table by table and row by row.
+    #[cfg(any(windows, feature = "portable-db"))]
+    Diff(diff::Args),
+
+    /// Estimate how large a package is and how long it will take to install.
+    #[cfg(any(windows, feature = "portable-db"))]
+    Estimate(estimate::Args),
+
+    /// Print the JSON Schema of the JSON event formats msitrace writes.
+    Schema(schema::Args),
+}
@@ -0,0 +1,97 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::Database;
+use std::error::Error;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct PackageArgs {
+    /// Path to the package, patch, or transform.
+    path: PathBuf,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct QueryArgs {
+    /// Path to the package, patch, or transform.
+    path: PathBuf,
+
+    /// The SQL query to execute, e.g. "SELECT * FROM `Property`".
+    sql: String,
+}
+
+pub fn tables(args: PackageArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::open(args.path)?;
+
+    let mut tables = database.tables()?;
+    tables.sort();
+    for table in tables {
+        println!("{}", table);
+    }
+
+    Ok(())
+}
+
+pub fn query(args: QueryArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::open(args.path)?;
+    let view = database.query(&args.sql)?;
+
+    let columns: Vec<&str> = view.columns().iter().map(|c| c.name()).collect();
+    println!("{}", columns.join("\t"));
+
+    for row in view {
+        let values: Vec<String> = row?.iter().map(|v| v.to_string()).collect();
+        println!("{}", values.join("\t"));
+    }
+
+    Ok(())
+}
+
+pub fn summary(args: PackageArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::open(args.path)?;
+    let summary = database.summary_info()?;
+
+    print_field("Title", &summary.title);
+    print_field("Subject", &summary.subject);
+    print_field("Author", &summary.author);
+    print_field("Keywords", &summary.keywords);
+    print_field("Comments", &summary.comments);
+    print_field("Template", &summary.template);
+    print_field("Last saved by", &summary.last_saved_by);
+    print_field("Revision number", &summary.revision_number);
+    print_field("Created", &summary.created);
+    print_field("Last saved", &summary.last_saved);
+    print_field("Page count", &summary.page_count);
+    print_field("Word count", &summary.word_count);
+    print_field("Character count", &summary.character_count);
+    print_field("Creating application", &summary.creating_application);
+    print_field("Security", &summary.security);
+
+    Ok(())
+}
+
+pub fn files(args: PackageArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::open(args.path)?;
+    let view = database
+        .query("SELECT `File`, `Component_`, `FileName`, `FileSize`, `Version` FROM `File`")?;
+
+    println!("File\tComponent\tName\tSize\tVersion");
+    for row in view {
+        let row = row?;
+
+        // Long file names follow the short file name, e.g. "EXAMPLE.TXT|example.txt".
+        let name = row[2].to_string();
+        let name = name.split_once('|').map_or(name.as_str(), |(_, long)| long);
+
+        println!("{}\t{}\t{}\t{}\t{}", row[0], row[1], name, row[3], row[4]);
+    }
+
+    Ok(())
+}
+
+fn print_field<T: Display>(name: &str, value: &Option<T>) {
+    if let Some(value) = value {
+        println!("{}: {}", name, value);
+    }
+}
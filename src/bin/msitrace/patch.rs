@@ -0,0 +1,175 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install::{local_path, set_properties};
+use crate::locale;
+use msitrace::{CommandLine, InstallOptions};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Apply patches to installed products in order and trace their actions.
+    Apply(ApplyArgs),
+
+    /// Check which patches apply to a package or installed product, and in what sequence.
+    Check(CheckArgs),
+
+    /// Print the applicability XML and summary information of a patch without applying it.
+    Info(InfoArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ApplyArgs {
+    /// Paths to the patches (.msp) to apply, in order.
+    #[arg(required = true)]
+    patches: Vec<PathBuf>,
+
+    /// The product code of the product to patch. Defaults to every installed product
+    /// the patches target.
+    #[arg(long, value_name = "PRODUCT_CODE")]
+    product: Option<String>,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// Require each patch have a trusted Authenticode signature.
+    #[arg(long)]
+    require_signed: bool,
+
+    /// Properties to pass to the install, or @PATH to read them from a file.
+    #[arg(last = true)]
+    properties: Vec<String>,
+}
+
+#[derive(Debug, clap::Args)]
+struct CheckArgs {
+    /// Paths to the patches (.msp) to check.
+    #[arg(required = true)]
+    patches: Vec<PathBuf>,
+
+    /// Path to a package (.msi), or the product code of an installed product, to check
+    /// the patches against.
+    #[arg(long, value_name = "MSI_OR_PRODUCT_CODE")]
+    target: String,
+}
+
+#[derive(Debug, clap::Args)]
+struct InfoArgs {
+    /// Path to the patch (.msp).
+    patch: PathBuf,
+
+    /// Path to save the applicability XML to instead of printing it.
+    #[arg(long, value_name = "PATH")]
+    xml_out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Command::Apply(args) => apply(args),
+        Command::Check(args) => check(args),
+        Command::Info(args) => info(args),
+    }
+}
+
+fn apply(args: ApplyArgs) -> Result<(), Box<dyn Error>> {
+    let patches = args
+        .patches
+        .iter()
+        .map(|path| local_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+
+    let mut properties = CommandLine::new();
+    set_properties(&mut properties, &args.properties)?;
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        require_signed: args.require_signed,
+        language: locale::current(),
+        properties,
+        ..Default::default()
+    };
+    msitrace::apply_patches(&patches, args.product.as_deref(), &options)?;
+
+    Ok(())
+}
+
+fn check(args: CheckArgs) -> Result<(), Box<dyn Error>> {
+    let patches = args
+        .patches
+        .iter()
+        .map(|path| local_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let target = match args.target.starts_with('{') {
+        true => args.target,
+        false => local_path(args.target.as_ref())?,
+    };
+
+    let results = msitrace::check_patches(&patches, &target)?;
+    for result in &results {
+        println!("{}", result);
+    }
+
+    let applicable = results.iter().filter(|r| r.is_applicable()).count();
+    println!(
+        "{} of {} patches apply to {}",
+        applicable,
+        results.len(),
+        target
+    );
+
+    Ok(())
+}
+
+fn info(args: InfoArgs) -> Result<(), Box<dyn Error>> {
+    let path = local_path(&args.patch)?;
+    let info = msitrace::inspect_patch(&path)?;
+    println!("Patch code: {}", info.patch_code);
+    for code in &info.obsoletes {
+        println!("Obsoletes: {}", code);
+    }
+    for code in &info.target_product_codes {
+        println!("Target product: {}", code);
+    }
+    for sequence in &info.sequences {
+        println!(
+            "Sequence: {} {} in {}{}",
+            sequence.family,
+            sequence.sequence,
+            sequence.product_code.as_deref().unwrap_or("all targets"),
+            match sequence.supersedes {
+                true => ", supersedes earlier patches",
+                false => "",
+            }
+        );
+    }
+
+    let xml = msitrace::patch_xml(&path)?;
+    match args.xml_out {
+        Some(out) => {
+            std::fs::write(&out, xml)?;
+            println!("Saved applicability XML to {}", out.display());
+        }
+        None => println!("{}", xml),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install::{events_handler, local_path, set_properties};
+use crate::locale;
+use msitrace::{CommandLine, InstallOptions, MessageFilter};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Path to the package to check.
+    path: PathBuf,
+
+    /// Check launch conditions for the current user, the machine, or whichever the
+    /// package chooses.
+    #[arg(long, value_enum)]
+    context: Option<msitrace::InstallContext>,
+
+    /// Apply a transform, or a transform embedded in the package named with a leading colon,
+    /// e.g. :fr-FR.mst. May be repeated.
+    #[arg(long = "transform", value_name = "PATH")]
+    transforms: Vec<PathBuf>,
+
+    /// Write each check to a file as a line of JSON. See `msitrace schema event`.
+    #[arg(long, value_name = "PATH")]
+    events: Option<PathBuf>,
+
+    /// Properties to check launch conditions with, or @PATH to read them from a file.
+    #[arg(last = true)]
+    properties: Vec<String>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let path = local_path(&args.path)?;
+
+    let mut properties = CommandLine::new();
+    set_properties(&mut properties, &args.properties)?;
+    if let Some(context) = args.context {
+        properties.set_context(context);
+    }
+
+    let transforms = args
+        .transforms
+        .iter()
+        .map(|transform| match transform.to_string_lossy() {
+            name if name.starts_with(':') => Ok(name.into_owned()),
+            _ => local_path(transform),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let options = InstallOptions {
+        properties,
+        transforms,
+        handler: args
+            .events
+            .map(|events| events_handler(events, MessageFilter::default()))
+            .transpose()?,
+        language: locale::current(),
+        ..Default::default()
+    };
+    msitrace::preflight_package(&path, &options)?;
+
+    Ok(())
+}
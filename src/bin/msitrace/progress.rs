@@ -0,0 +1,103 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{Message, MessageHandler, Progress, ProgressUpdate};
+use std::io::{IsTerminal, Write};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const WIDTH: usize = 30;
+
+/// Redraw at most this often so drawing does not slow the install.
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Gets whether a progress bar can be drawn, which requires a console.
+pub fn is_supported() -> bool {
+    std::io::stdout().is_terminal() && std::io::stderr().is_terminal()
+}
+
+struct Bar {
+    progress: Progress,
+    started: Instant,
+    drawn: Option<Instant>,
+}
+
+/// Draws a progress bar with the current action and estimated time remaining on standard error.
+pub fn progress_handler() -> MessageHandler {
+    let bar = Mutex::new(Bar {
+        progress: Progress::new(),
+        started: Instant::now(),
+        drawn: None,
+    });
+
+    MessageHandler::parsed(move |message| {
+        let mut bar = bar.lock().unwrap_or_else(PoisonError::into_inner);
+        bar.progress.observe(message);
+
+        match message {
+            // Clear the bar so messages printed to the console are not drawn over it.
+            Message::FatalExit { .. }
+            | Message::Error { .. }
+            | Message::Warning { .. }
+            | Message::FilesInUse { .. }
+            | Message::RMFilesInUse { .. }
+            | Message::InstallEnd { .. }
+            | Message::Terminate => bar.clear(),
+            Message::Progress(ProgressUpdate::MasterReset { .. }) => {
+                bar.started = Instant::now();
+                bar.draw();
+            }
+            _ if bar.drawn.is_none_or(|drawn| drawn.elapsed() >= INTERVAL) => bar.draw(),
+            _ => {}
+        }
+    })
+}
+
+impl Bar {
+    fn draw(&mut self) {
+        let Some(percent) = self.progress.percent() else {
+            return;
+        };
+
+        let filled = (percent / 100.0 * WIDTH as f64).round() as usize;
+        let action = match self.progress.is_generating_script() {
+            true => "Generating script",
+            false => self.progress.action().unwrap_or_default(),
+        };
+        let eta = match percent > 0.0 && !self.progress.is_rolling_back() {
+            true => {
+                let elapsed = self.started.elapsed().as_secs_f64();
+                let remaining = elapsed * (100.0 - percent) / percent;
+                format!(" ETA {}", format_duration(remaining as u64))
+            }
+            false => String::new(),
+        };
+
+        let _ = std::io::stdout().flush();
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r\x1b[2K[{}{}] {:3.0}% {}{}",
+            "#".repeat(filled),
+            "-".repeat(WIDTH - filled),
+            percent,
+            action,
+            eta
+        );
+        let _ = stderr.flush();
+        self.drawn = Some(Instant::now());
+    }
+
+    fn clear(&mut self) {
+        if self.drawn.take().is_some() {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }
+    }
+}
+
+/// Formats seconds as minutes and seconds, e.g. 1:05.
+fn format_duration(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -0,0 +1,105 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::locale;
+use msitrace::{InstallOptions, ProvideMode};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Install a component of a product feature if needed, as an advertised shortcut would,
+    /// and print its key path.
+    Component(ComponentArgs),
+
+    /// Install a qualified component if needed and print its key path.
+    Qualified(QualifiedArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ComponentArgs {
+    /// The product code of an installed product, e.g. {00000000-0000-0000-0000-000000000000}.
+    product_code: String,
+
+    /// The name of the feature in the Feature table.
+    feature: String,
+
+    /// The component code of the component to provide.
+    component_code: String,
+
+    #[command(flatten)]
+    trace: TraceArgs,
+}
+
+#[derive(Debug, clap::Args)]
+struct QualifiedArgs {
+    /// The component category GUID from the PublishComponent table.
+    category: String,
+
+    /// The qualifier of the component within the category.
+    qualifier: String,
+
+    #[command(flatten)]
+    trace: TraceArgs,
+}
+
+/// Options shared by commands that provide components.
+#[derive(Debug, clap::Args)]
+struct TraceArgs {
+    /// Whether to install the component if needed, or only get its path.
+    #[arg(long, value_enum, default_value_t)]
+    mode: ProvideMode,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+}
+
+impl TraceArgs {
+    fn options(self) -> std::io::Result<InstallOptions> {
+        let log = self
+            .log
+            .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+            .transpose()?;
+
+        Ok(InstallOptions {
+            log: log.map(|log| log.to_string_lossy().into_owned()),
+            ui: self.ui,
+            language: locale::current(),
+            ..Default::default()
+        })
+    }
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Command::Component(args) => {
+            let mode = args.trace.mode;
+            let options = args.trace.options()?;
+            msitrace::provide_component(
+                &args.product_code,
+                &args.feature,
+                &args.component_code,
+                mode,
+                &options,
+            )?;
+        }
+        Command::Qualified(args) => {
+            let mode = args.trace.mode;
+            let options = args.trace.options()?;
+            msitrace::provide_qualified_component(&args.category, &args.qualifier, mode, &options)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::locale;
+use msitrace::{FeatureState, InstallOptions};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+#[command(group = clap::ArgGroup::new("target").required(true))]
+pub struct Args {
+    /// The product code of an installed product, e.g. {00000000-0000-0000-0000-000000000000}.
+    product_code: String,
+
+    /// The component code of a missing or broken component to reinstall.
+    #[arg(long, value_name = "GUID", group = "target")]
+    component: Option<String>,
+
+    /// The name of a missing or broken file in the File table to reinstall.
+    #[arg(long, value_name = "NAME", group = "target")]
+    file: Option<String>,
+
+    /// Where to install the component: local, source, or default.
+    #[arg(long, value_enum, default_value = "default", requires = "component")]
+    state: FeatureState,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// A directory containing the original package to resolve the source from if the
+    /// cached source is missing.
+    #[arg(long, value_name = "DIR")]
+    source: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+    let source = args
+        .source
+        .map(|source| std::env::current_dir().map(|dir| dir.join(source)))
+        .transpose()?;
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        language: locale::current(),
+        source: source.map(|source| source.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+
+    match (args.component, args.file) {
+        (Some(component), _) => {
+            msitrace::recover_component(&args.product_code, &component, args.state, &options)?
+        }
+        (None, Some(file)) => msitrace::recover_file(&args.product_code, &file, &options)?,
+        (None, None) => unreachable!("clap requires --component or --file"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::locale;
+use msitrace::{InstallOptions, ReinstallMode};
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The product code of an installed product, e.g. {00000000-0000-0000-0000-000000000000}.
+    product_code: String,
+
+    /// What to reinstall, as REINSTALLMODE letters, e.g. omus or vomus.
+    #[arg(long, default_value = "omus", value_parser = parse_mode)]
+    mode: ReinstallMode,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// A directory containing the original package to resolve the source from if the
+    /// cached source is missing.
+    #[arg(long, value_name = "DIR")]
+    source: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+    let source = args
+        .source
+        .map(|source| std::env::current_dir().map(|dir| dir.join(source)))
+        .transpose()?;
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        language: locale::current(),
+        source: source.map(|source| source.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    msitrace::repair(&args.product_code, args.mode, &options)?;
+
+    Ok(())
+}
+
+/// Parses REINSTALLMODE letters.
+pub fn parse_mode(value: &str) -> Result<ReinstallMode, String> {
+    value
+        .parse()
+        .map_err(|err: msitrace::Error| err.to_string())
+}
@@ -0,0 +1,36 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{HookEvent, InstallSummary, TraceEvent};
+use std::error::Error;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The format to print the JSON Schema of.
+    #[arg(value_enum, default_value_t)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+enum Format {
+    /// Trace events written as JSON lines by `install --events`.
+    #[default]
+    Event,
+
+    /// Events passed on stdin to `install --on-error`, `--on-complete`, and `--on-action` commands.
+    Hook,
+
+    /// The summary written by `install --summary-out`.
+    Summary,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let schema = match args.format {
+        Format::Event => schemars::schema_for!(TraceEvent),
+        Format::Hook => schemars::schema_for!(HookEvent),
+        Format::Summary => schemars::schema_for!(InstallSummary),
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+
+    Ok(())
+}
@@ -0,0 +1,643 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The loopback address and port to listen on.
+    #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:7080")]
+    listen: SocketAddr,
+
+    /// The bearer token clients must pass in the Authorization header. A random token is
+    /// generated and printed if not specified.
+    #[arg(long, value_name = "TOKEN")]
+    token: Option<String>,
+
+    /// The directory job logs are written to. Jobs may only name a log file in it.
+    #[arg(long, value_name = "DIR")]
+    log_dir: Option<PathBuf>,
+}
+
+/// The largest request body accepted, which is plenty for a job.
+const MAX_BODY: usize = 64 * 1024;
+
+/// The largest request line and headers accepted, read before the client is authorized.
+const MAX_HEADERS: u64 = 16 * 1024;
+
+/// How long to wait for a client to send more of its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many connections to handle at once.
+const MAX_CONNECTIONS: usize = 32;
+
+/// How many finished jobs to keep for clients to query.
+const MAX_FINISHED_JOBS: usize = 100;
+
+/// How many jobs may be queued or running at once.
+const MAX_PENDING_JOBS: usize = 100;
+
+/// How many of the latest events to keep for each job.
+const MAX_EVENTS: usize = 10_000;
+
+/// Settings every request is checked against.
+#[derive(Debug)]
+struct Server {
+    token: String,
+    log_dir: PathBuf,
+}
+
+/// A job submitted with `POST /jobs`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Request {
+    Install {
+        package: String,
+        #[serde(default)]
+        properties: Vec<String>,
+        log: Option<String>,
+    },
+    Uninstall {
+        product_code: String,
+        #[serde(default)]
+        properties: Vec<String>,
+        log: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// A line of trace output from a job.
+#[derive(Clone, Debug, Serialize)]
+struct Event {
+    stream: &'static str,
+    text: String,
+}
+
+#[derive(Debug)]
+struct Job {
+    id: u32,
+    request: Request,
+    status: Status,
+    exit_code: Option<i32>,
+    events: VecDeque<Event>,
+
+    /// How many of the oldest events were dropped beyond [`MAX_EVENTS`].
+    dropped: usize,
+}
+
+impl Job {
+    fn is_done(&self) -> bool {
+        matches!(self.status, Status::Succeeded | Status::Failed)
+    }
+
+    fn push(&mut self, event: Event) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Gets how many events the job has traced, including dropped events.
+    fn event_count(&self) -> usize {
+        self.dropped + self.events.len()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let (kind, target) = match &self.request {
+            Request::Install { package, .. } => ("install", package),
+            Request::Uninstall { product_code, .. } => ("uninstall", product_code),
+        };
+
+        serde_json::json!({
+            "id": self.id,
+            "kind": kind,
+            "target": target,
+            "status": self.status,
+            "exit_code": self.exit_code,
+            "events": self.event_count(),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Jobs {
+    jobs: Mutex<HashMap<u32, Job>>,
+    changed: Condvar,
+    last_id: AtomicU32,
+}
+
+impl Jobs {
+    /// Removes the oldest finished jobs beyond [`MAX_FINISHED_JOBS`].
+    fn evict(jobs: &mut HashMap<u32, Job>) {
+        let mut finished: Vec<u32> = jobs
+            .values()
+            .filter(|job| job.is_done())
+            .map(|job| job.id)
+            .collect();
+        if finished.len() > MAX_FINISHED_JOBS {
+            finished.sort_unstable();
+            for id in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+                jobs.remove(id);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u32, Job>> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update(&self, id: u32, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.lock().get_mut(&id) {
+            f(job);
+        }
+        self.changed.notify_all();
+    }
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    // Jobs run elevated installs, so never accept connections from other machines.
+    if !args.listen.ip().is_loopback() {
+        return Err("--listen requires a loopback address".into());
+    }
+
+    let log_dir = match args.log_dir {
+        Some(dir) => dir,
+        None => std::env::temp_dir().join("msitrace"),
+    };
+    std::fs::create_dir_all(&log_dir)?;
+    let token = match args.token {
+        Some(token) => token,
+        None => random_token()?,
+    };
+    let server = Arc::new(Server { token, log_dir });
+
+    let listener = TcpListener::bind(args.listen)?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    println!("Authorization: Bearer {}", server.token);
+    println!("Logs are written to {}", server.log_dir.display());
+
+    let jobs = Arc::new(Jobs::default());
+    let queue = worker(jobs.clone(), server.clone());
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("warning: failed to accept connection: {}", err);
+                continue;
+            }
+        };
+
+        let Some(connection) = Connection::acquire(&connections) else {
+            let _ = error(
+                &mut stream,
+                "503 Service Unavailable",
+                "too many connections",
+            );
+            continue;
+        };
+
+        let jobs = jobs.clone();
+        let queue = queue.clone();
+        let server = server.clone();
+        thread::spawn(move || {
+            let _connection = connection;
+            if let Err(err) = handle(stream, &server, &jobs, &queue) {
+                eprintln!("warning: failed to handle request: {}", err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Counts a connection toward [`MAX_CONNECTIONS`] until dropped.
+struct Connection(Arc<AtomicUsize>);
+
+impl Connection {
+    fn acquire(connections: &Arc<AtomicUsize>) -> Option<Self> {
+        let connection = Connection(connections.clone());
+        // Dropping the connection undoes the increment if there are too many.
+        (connections.fetch_add(1, Ordering::AcqRel) < MAX_CONNECTIONS).then_some(connection)
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Runs queued jobs one at a time, since installs cannot overlap.
+fn worker(jobs: Arc<Jobs>, server: Arc<Server>) -> Sender<u32> {
+    let (sender, receiver) = mpsc::channel::<u32>();
+    thread::spawn(move || {
+        for id in receiver {
+            let request = match jobs.lock().get(&id) {
+                Some(job) => job.request.clone(),
+                None => continue,
+            };
+
+            jobs.update(id, |job| job.status = Status::Running);
+            let exit_code = match execute(&jobs, &server, id, &request) {
+                Ok(code) => code,
+                Err(err) => {
+                    let text = format!("failed to start job: {}", err);
+                    jobs.update(id, |job| {
+                        job.push(Event {
+                            stream: "stderr",
+                            text,
+                        })
+                    });
+                    None
+                }
+            };
+            jobs.update(id, |job| {
+                job.exit_code = exit_code;
+                job.status = match exit_code {
+                    Some(0) => Status::Succeeded,
+                    _ => Status::Failed,
+                };
+            });
+            Jobs::evict(&mut jobs.lock());
+        }
+    });
+
+    sender
+}
+
+/// Traces a job in a child process so each install has its own process-wide handler state.
+///
+/// Requests are validated by [`Request::validate`] before they are queued.
+fn execute(
+    jobs: &Arc<Jobs>,
+    server: &Server,
+    id: u32,
+    request: &Request,
+) -> Result<Option<i32>, Box<dyn Error>> {
+    let mut command = Command::new(std::env::current_exe()?);
+    let (properties, log) = match request {
+        Request::Install {
+            package,
+            properties,
+            log,
+        } => {
+            command.arg("install").arg(package);
+            (properties, log)
+        }
+        Request::Uninstall {
+            product_code,
+            properties,
+            log,
+        } => {
+            command.arg("uninstall").arg(product_code);
+            (properties, log)
+        }
+    };
+    if let Some(log) = log {
+        command.arg("--log").arg(server.log_dir.join(log));
+    }
+    command.arg("--").args(properties);
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| capture(jobs, id, "stdout", out));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| capture(jobs, id, "stderr", err));
+    let status = child.wait()?;
+    for reader in stdout.into_iter().chain(stderr) {
+        let _ = reader.join();
+    }
+
+    Ok(status.code())
+}
+
+fn capture(
+    jobs: &Arc<Jobs>,
+    id: u32,
+    stream: &'static str,
+    output: impl Read + Send + 'static,
+) -> thread::JoinHandle<()> {
+    let jobs = jobs.clone();
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let Ok(text) = line else {
+                break;
+            };
+            jobs.update(id, |job| job.push(Event { stream, text }));
+        }
+    })
+}
+
+impl Request {
+    /// Checks that the request cannot pass options to msitrace, read local files, or write
+    /// logs outside the log directory.
+    fn validate(&self) -> Result<(), String> {
+        let (properties, log) = match self {
+            Request::Install {
+                package,
+                properties,
+                log,
+            } => {
+                if package.is_empty() || package.starts_with('-') {
+                    return Err(format!("invalid package {:?}", package));
+                }
+                (properties, log)
+            }
+            Request::Uninstall {
+                product_code,
+                properties,
+                log,
+            } => {
+                if !is_guid(product_code) {
+                    return Err(format!("invalid product code {:?}", product_code));
+                }
+                (properties, log)
+            }
+        };
+
+        // Properties read from @PATH would echo local files back in events.
+        if let Some(property) = properties.iter().find(|p| p.starts_with('@')) {
+            return Err(format!("property files are not supported: {:?}", property));
+        }
+
+        if let Some(log) = log {
+            let mut components = Path::new(log).components();
+            if !matches!(
+                (components.next(), components.next()),
+                (Some(Component::Normal(_)), None)
+            ) {
+                return Err(format!("log must be a file name: {:?}", log));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets whether `value` is a braced GUID like `{12345678-1234-1234-1234-123456789012}`.
+fn is_guid(value: &str) -> bool {
+    let Some(inner) = value
+        .strip_prefix('{')
+        .and_then(|value| value.strip_suffix('}'))
+    else {
+        return false;
+    };
+
+    let groups: Vec<&str> = inner.split('-').collect();
+    groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+        && groups
+            .iter()
+            .all(|group| group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Generates a token from 32 bytes of the system RNG.
+fn random_token() -> msitrace::Result<String> {
+    let mut bytes = [0u8; 32];
+    msitrace::random_bytes(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn handle(
+    mut stream: TcpStream,
+    server: &Server,
+    jobs: &Jobs,
+    queue: &Sender<u32>,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    // Limit what is read before the client is authorized; the limit is raised for the body.
+    let mut reader = BufReader::new(stream.try_clone()?).take(MAX_HEADERS);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let path = parts.next().unwrap_or_default().to_owned();
+
+    let mut content_length = 0usize;
+    let mut content_type = String::new();
+    let mut authorization = String::new();
+    let mut origin = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            if reader.limit() == 0 {
+                return error(
+                    &mut stream,
+                    "431 Request Header Fields Too Large",
+                    "request headers are too large",
+                );
+            }
+            break;
+        }
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let (name, value) = (name.trim(), value.trim());
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or_default();
+            } else if name.eq_ignore_ascii_case("content-type") {
+                content_type = value.to_ascii_lowercase();
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = value.to_owned();
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = true;
+            }
+        }
+    }
+
+    // Browsers send an Origin with cross-site requests, which must never start installs.
+    if origin {
+        return error(
+            &mut stream,
+            "403 Forbidden",
+            "cross-origin requests are not allowed",
+        );
+    }
+    let authorized = authorization
+        .strip_prefix("Bearer ")
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), server.token.as_bytes()));
+    if !authorized {
+        return error(
+            &mut stream,
+            "401 Unauthorized",
+            "invalid or missing bearer token",
+        );
+    }
+    if content_length > MAX_BODY {
+        return error(
+            &mut stream,
+            "413 Payload Too Large",
+            "request body is too large",
+        );
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.set_limit(content_length as u64);
+    reader.read_exact(&mut body)?;
+
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (method.as_str(), segments.as_slice()) {
+        ("POST", ["jobs"]) if content_type.split(';').next() != Some("application/json") => error(
+            &mut stream,
+            "415 Unsupported Media Type",
+            "requests must be application/json",
+        ),
+        ("POST", ["jobs"]) => match serde_json::from_slice::<Request>(&body)
+            .map_err(|err| err.to_string())
+            .and_then(|request| request.validate().map(|_| request))
+        {
+            Ok(request) => {
+                let mut guard = jobs.lock();
+                if guard.values().filter(|job| !job.is_done()).count() >= MAX_PENDING_JOBS {
+                    drop(guard);
+                    return error(
+                        &mut stream,
+                        "503 Service Unavailable",
+                        "too many jobs are queued",
+                    );
+                }
+
+                let id = jobs.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+                guard.insert(
+                    id,
+                    Job {
+                        id,
+                        request,
+                        status: Status::Queued,
+                        exit_code: None,
+                        events: VecDeque::new(),
+                        dropped: 0,
+                    },
+                );
+                drop(guard);
+                let _ = queue.send(id);
+                respond(&mut stream, "201 Created", &serde_json::json!({ "id": id }))
+            }
+            Err(err) => error(&mut stream, "400 Bad Request", &err),
+        },
+        ("GET", ["jobs"]) => {
+            let jobs = jobs.lock();
+            let mut list: Vec<&Job> = jobs.values().collect();
+            list.sort_by_key(|job| job.id);
+            let list: Vec<_> = list.into_iter().map(Job::to_json).collect();
+            respond(&mut stream, "200 OK", &serde_json::Value::from(list))
+        }
+        ("GET", ["jobs", job]) => match job
+            .parse()
+            .ok()
+            .and_then(|id| jobs.lock().get(&id).map(Job::to_json))
+        {
+            Some(job) => respond(&mut stream, "200 OK", &job),
+            None => not_found(&mut stream),
+        },
+        ("GET", ["jobs", job, "events"]) => match job.parse() {
+            Ok(id) if jobs.lock().contains_key(&id) => events(&mut stream, jobs, id),
+            _ => not_found(&mut stream),
+        },
+        _ => not_found(&mut stream),
+    }
+}
+
+/// Streams job events as server-sent events until the job finishes.
+///
+/// Events dropped beyond [`MAX_EVENTS`] before they were sent are skipped.
+fn events(stream: &mut TcpStream, jobs: &Jobs, id: u32) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n"
+    )?;
+
+    let mut sent = 0usize;
+    let mut guard = jobs.lock();
+    loop {
+        let Some(job) = guard.get(&id) else {
+            return Ok(());
+        };
+
+        let start = sent.saturating_sub(job.dropped);
+        let pending: Vec<Event> = job.events.range(start..).cloned().collect();
+        let done = job.is_done();
+        let summary = job.to_json();
+        sent = job.event_count();
+        drop(guard);
+
+        for event in pending {
+            let data = serde_json::to_string(&event)?;
+            write!(stream, "event: trace\ndata: {}\n\n", data)?;
+        }
+        if done {
+            write!(stream, "event: end\ndata: {}\n\n", summary)?;
+            return stream.flush();
+        }
+        stream.flush()?;
+
+        guard = jobs.lock();
+        while guard
+            .get(&id)
+            .is_some_and(|job| job.event_count() == sent && !job.is_done())
+        {
+            guard = jobs
+                .changed
+                .wait(guard)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &serde_json::Value) -> std::io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn error(stream: &mut TcpStream, status: &str, message: &str) -> std::io::Result<()> {
+    respond(stream, status, &serde_json::json!({ "error": message }))
+}
+
+fn not_found(stream: &mut TcpStream) -> std::io::Result<()> {
+    error(stream, "404 Not Found", "not found")
+}
+
+/// Compares tokens without returning early, so timing does not reveal matching prefixes.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
@@ -0,0 +1,137 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::install::set_properties;
+use crate::locale;
+use msitrace::{CommandLine, InstallOptions, InstalledProduct};
+use std::error::Error;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, clap::Args)]
+#[command(group = clap::ArgGroup::new("products").required(true))]
+pub struct Args {
+    /// The product code of an installed product, e.g. {00000000-0000-0000-0000-000000000000}.
+    #[arg(group = "products")]
+    product_code: Option<String>,
+
+    /// Uninstall every installed product with a name matching this pattern, where * matches
+    /// any characters and ? matches one character, e.g. "Contoso App*".
+    #[arg(long, value_name = "PATTERN", group = "products")]
+    name: Option<String>,
+
+    /// Uninstall every installed product with this upgrade code.
+    #[arg(long, value_name = "GUID", group = "products")]
+    upgrade_code: Option<String>,
+
+    /// Uninstall matching products without asking to confirm.
+    #[arg(long, short)]
+    yes: bool,
+
+    /// Path to a verbose log file to write.
+    #[arg(long)]
+    log: Option<PathBuf>,
+
+    /// The user interface level to show.
+    #[arg(long, value_enum, default_value_t)]
+    ui: msitrace::UILevel,
+
+    /// Properties to pass to the uninstall, or @PATH to read them from a file.
+    #[arg(last = true)]
+    properties: Vec<String>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let log = args
+        .log
+        .map(|log| std::env::current_dir().map(|dir| dir.join(log)))
+        .transpose()?;
+
+    let mut properties = CommandLine::new();
+    set_properties(&mut properties, &args.properties)?;
+
+    let options = InstallOptions {
+        log: log.map(|log| log.to_string_lossy().into_owned()),
+        ui: args.ui,
+        language: locale::current(),
+        properties,
+        ..Default::default()
+    };
+
+    let products = match (args.product_code, args.name, args.upgrade_code) {
+        (Some(product_code), _, _) => {
+            msitrace::uninstall(&product_code, &options)?;
+            return Ok(());
+        }
+        (None, Some(name), _) => msitrace::find_products(&name)?,
+        (None, None, Some(upgrade_code)) => msitrace::related_products(&upgrade_code)?,
+        (None, None, None) => {
+            unreachable!("clap requires a product code, --name, or --upgrade-code")
+        }
+    };
+
+    uninstall_all(&products, &options, args.yes)
+}
+
+/// Uninstalls each product after confirming, continuing after any fails.
+fn uninstall_all(
+    products: &[InstalledProduct],
+    options: &InstallOptions,
+    yes: bool,
+) -> Result<(), Box<dyn Error>> {
+    if products.is_empty() {
+        return Err("no installed products match".into());
+    }
+
+    println!("Products to uninstall:");
+    for product in products {
+        println!("  {}", product);
+    }
+    if !yes && !confirm(products.len())? {
+        return Err("uninstall cancelled".into());
+    }
+
+    let mut failed = 0;
+    let mut reboot = None;
+    for (i, product) in products.iter().enumerate() {
+        println!("Uninstalling {} of {}: {}", i + 1, products.len(), product);
+        match msitrace::uninstall(&product.product_code, options) {
+            Ok(()) => println!("Uninstalled {}", product.product_code),
+            // Reboot codes still succeeded, so report them after all are uninstalled.
+            Err(err) if matches!(err.code(), Some(3010 | 1641)) => {
+                println!("Uninstalled {}; a reboot is required", product.product_code);
+                reboot = Some(err);
+            }
+            Err(err) => {
+                eprintln!(
+                    "error: failed to uninstall {}: {}",
+                    product.product_code, err
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} uninstalls failed", failed, products.len()).into());
+    }
+    match reboot {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
+}
+
+/// Asks whether to uninstall products, failing if there is no terminal to ask on.
+fn confirm(count: usize) -> Result<bool, Box<dyn Error>> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return Err("pass --yes to uninstall without confirming".into());
+    }
+
+    print!("Uninstall {} products? [y/N] ", count);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
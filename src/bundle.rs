@@ -0,0 +1,240 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::cabinet;
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+const BURN_SECTION: &[u8; 8] = b".wixburn";
+const BURN_SECTION_MAGIC: u32 = 0x00f1_4300;
+const BURN_FORMAT_CABINET: u32 = 1;
+
+/// The `.wixburn` section header describing the containers attached to a Burn bundle.
+#[derive(Debug)]
+struct BurnSection {
+    /// Offset of the first container, immediately after the bundle executable.
+    stub_size: u64,
+
+    /// Sizes of each container; the first is the UX container with the bundle manifest.
+    containers: Vec<u64>,
+}
+
+/// Gets whether a file is a WiX Burn bundle rather than a Windows Installer package.
+pub fn is_bundle(path: impl AsRef<Path>) -> Result<bool> {
+    let mut file = File::open(path)?;
+    Ok(burn_section(&mut file)?.is_some())
+}
+
+/// Extracts the packages and patches attached to a WiX Burn bundle into a directory,
+/// and gets their paths.
+///
+/// Packages downloaded by the bundle rather than attached to it are not extracted.
+pub fn extract_bundle(path: impl AsRef<Path>, directory: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let directory = directory.as_ref();
+    let mut file = File::open(path)?;
+    let section = burn_section(&mut file)?
+        .ok_or_else(|| Error::verification("not a WiX bundle: no .wixburn section"))?;
+
+    let temp = std::env::temp_dir().join(format!("msitrace-bundle-{}", std::process::id()));
+    std::fs::create_dir_all(&temp)?;
+    let result = extract_containers(&mut file, &section, &temp, directory);
+    let _ = std::fs::remove_dir_all(&temp);
+
+    result
+}
+
+fn extract_containers(
+    file: &mut File,
+    section: &BurnSection,
+    temp: &Path,
+    directory: &Path,
+) -> Result<Vec<PathBuf>> {
+    // Extract every container; payloads reference files by their names in any attached container.
+    let mut offset = section.stub_size;
+    for (i, &size) in section.containers.iter().enumerate() {
+        let cabinet = temp.join(format!("{}.cab", i));
+        file.seek(SeekFrom::Start(offset))?;
+        std::io::copy(&mut file.take(size), &mut File::create(&cabinet)?)?;
+
+        let extracted = temp.join(i.to_string());
+        std::fs::create_dir_all(&extracted)?;
+        cabinet::extract(&cabinet.to_string_lossy(), &extracted.to_string_lossy())?;
+
+        offset += size;
+    }
+
+    let manifest = manifest(&temp.join("0"))?;
+    let mut packages = Vec::new();
+    for payload in payloads(&manifest) {
+        let is_package = Path::new(&payload.file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("msi") || e.eq_ignore_ascii_case("msp"));
+        if !is_package {
+            continue;
+        }
+
+        // Paths come from the bundle, so a crafted bundle must not read or write elsewhere.
+        let file_path = relative_path(&payload.file_path)?;
+        let source_path = relative_path(&payload.source_path)?;
+
+        let source = (1..section.containers.len())
+            .map(|i| temp.join(i.to_string()).join(source_path))
+            .find(|path| path.is_file());
+        let Some(source) = source else {
+            continue;
+        };
+
+        let destination = directory.join(file_path);
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, &destination)?;
+        packages.push(destination);
+    }
+
+    Ok(packages)
+}
+
+fn burn_section(file: &mut File) -> Result<Option<BurnSection>> {
+    let dos = read_at(file, 0, 0x40)?;
+    if dos.len() < 0x40 || &dos[..2] != b"MZ" {
+        return Ok(None);
+    }
+
+    let pe = u32_at(&dos, 0x3c) as u64;
+    let headers = read_at(file, pe, 24)?;
+    if headers.len() < 24 || &headers[..4] != b"PE\0\0" {
+        return Ok(None);
+    }
+
+    let section_count = u16::from_le_bytes([headers[6], headers[7]]) as u64;
+    let optional_header_size = u16::from_le_bytes([headers[20], headers[21]]) as u64;
+    let sections = read_at(file, pe + 24 + optional_header_size, section_count * 40)?;
+
+    let (sections, _) = sections.as_chunks::<40>();
+    let Some(section) = sections
+        .iter()
+        .find(|section| &section[..8] == BURN_SECTION)
+    else {
+        return Ok(None);
+    };
+
+    let header = read_at(file, u32_at(section, 20) as u64, 48)?;
+    if header.len() < 48 || u32_at(&header, 0) != BURN_SECTION_MAGIC {
+        return Err(Error::verification(
+            "not a WiX bundle: invalid .wixburn section",
+        ));
+    }
+    if u32_at(&header, 40) != BURN_FORMAT_CABINET {
+        return Err(Error::verification(
+            "unsupported WiX bundle container format",
+        ));
+    }
+
+    let container_count = u32_at(&header, 44) as u64;
+    let sizes = read_at(file, u32_at(section, 20) as u64 + 48, container_count * 4)?;
+    if sizes.len() as u64 != container_count * 4 {
+        return Err(Error::verification(
+            "not a WiX bundle: truncated .wixburn section",
+        ));
+    }
+
+    Ok(Some(BurnSection {
+        stub_size: u32_at(&header, 24) as u64,
+        containers: sizes
+            .as_chunks::<4>()
+            .0
+            .iter()
+            .map(|size| u32::from_le_bytes(*size) as u64)
+            .collect(),
+    }))
+}
+
+fn read_at(file: &mut File, offset: u64, len: u64) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(len).read_to_end(&mut buffer)?;
+
+    Ok(buffer)
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Gets a path from the bundle manifest if it is relative and cannot leave the directory it is
+/// joined to, i.e. it has no root, drive, or `..` components.
+fn relative_path(path: &str) -> Result<&Path> {
+    let relative = Path::new(path);
+    let mut components = relative.components().peekable();
+    if components.peek().is_some() && components.all(|c| matches!(c, Component::Normal(_))) {
+        return Ok(relative);
+    }
+
+    Err(Error::verification(format!(
+        "WiX bundle payload path is not relative: {:?}",
+        path
+    )))
+}
+
+/// Reads the bundle manifest from the extracted UX container.
+fn manifest(ux: &Path) -> Result<String> {
+    for entry in std::fs::read_dir(ux)? {
+        let text = match std::fs::read_to_string(entry?.path()) {
+            Ok(text) => text,
+            Err(_) => continue,
+        };
+        if text.contains("<BurnManifest") {
+            return Ok(text);
+        }
+    }
+
+    Err(Error::verification("WiX bundle manifest not found"))
+}
+
+/// A file the bundle manifest declares, from a `Payload` element.
+#[derive(Debug)]
+struct Payload {
+    /// The relative path the bundle would extract the file to.
+    file_path: String,
+
+    /// The name of the file within its container.
+    source_path: String,
+}
+
+fn payloads(manifest: &str) -> Vec<Payload> {
+    manifest
+        .split("<Payload")
+        .skip(1)
+        .filter(|element| element.starts_with(char::is_whitespace))
+        .filter_map(|element| {
+            let element = &element[..element.find('>').unwrap_or(element.len())];
+            Some(Payload {
+                file_path: attribute(element, "FilePath")?,
+                source_path: attribute(element, "SourcePath")?,
+            })
+        })
+        .collect()
+}
+
+fn attribute(element: &str, name: &str) -> Option<String> {
+    let start = element.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = start + element[start..].find('"')?;
+
+    Some(
+        element[start..end]
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
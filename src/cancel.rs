@@ -0,0 +1,78 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::console::{self, CTRL_BREAK_EVENT, CTRL_C_EVENT};
+use crate::ffi::Win32Bool;
+use crate::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// Tokens to cancel when the user presses Ctrl+C or Ctrl+Break.
+static CTRL_C_TOKENS: OnceLock<Mutex<Vec<CancellationToken>>> = OnceLock::new();
+
+/// Cancels operations it is passed to through [`InstallOptions::cancel`](crate::InstallOptions::cancel).
+///
+/// Clones share the same state, so an operation can be cancelled from any thread. Windows
+/// Installer is told to cancel on the next message it sends, and rolls back the operation,
+/// which then fails with an error for which [`Error::is_cancelled`](crate::Error::is_cancelled)
+/// returns `true`.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a [`CancellationToken`] that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests that operations using this token be cancelled.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Gets whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Cancels this token when the user presses Ctrl+C or Ctrl+Break in the console
+    /// instead of terminating the process.
+    ///
+    /// Pressing either again after every token is cancelled terminates the process as usual.
+    pub fn cancel_on_ctrl_c(&self) -> Result<()> {
+        let mut result = Ok(());
+        let tokens = CTRL_C_TOKENS.get_or_init(|| {
+            result = console::add_ctrl_handler(ctrl_handler);
+            Mutex::new(Vec::new())
+        });
+        result?;
+
+        tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(self.clone());
+
+        Ok(())
+    }
+}
+
+extern "system" fn ctrl_handler(event: u32) -> Win32Bool {
+    if !matches!(event, CTRL_C_EVENT | CTRL_BREAK_EVENT) {
+        return false.into();
+    }
+
+    let Some(tokens) = CTRL_C_TOKENS.get() else {
+        return false.into();
+    };
+    let tokens = tokens.lock().unwrap_or_else(PoisonError::into_inner);
+    if tokens.iter().all(CancellationToken::is_cancelled) {
+        return false.into();
+    }
+
+    eprintln!("Canceling; press Ctrl+C again to terminate without rolling back");
+    for token in tokens.iter() {
+        token.cancel();
+    }
+
+    true.into()
+}
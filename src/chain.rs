@@ -0,0 +1,126 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#[cfg(feature = "toml")]
+use crate::Error;
+use crate::{CommandLine, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+#[cfg(feature = "toml")]
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Packages to install in sequence, read from a TOML manifest like:
+///
+/// ```toml
+/// name = "Example suite"
+/// transaction = true
+///
+/// [[package]]
+/// path = "runtime.msi"
+///
+/// [[package]]
+/// path = "example.msi"
+/// properties = { INSTALLDIR = 'C:\Example' }
+/// hide = ["LICENSEKEY"]
+///
+/// [[package]]
+/// path = "samples.msi"
+/// continue_on_error = true
+/// ```
+///
+/// Relative package paths are resolved against the directory containing the manifest.
+/// Packages that require a reboot did not fail, so later packages are still installed
+/// and the reboot is reported after the chain finishes.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ChainManifest {
+    /// The name of the chain, also used to name its transaction.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Install all packages in one transaction so they are rolled back together.
+    #[serde(default)]
+    pub transaction: bool,
+
+    /// Continue installing later packages when any package fails.
+    #[serde(default)]
+    pub continue_on_error: bool,
+
+    /// The packages to install, in order.
+    #[serde(default, rename = "package")]
+    pub packages: Vec<ChainPackage>,
+}
+
+/// A package in a [`ChainManifest`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ChainPackage {
+    /// The path to the package.
+    pub path: PathBuf,
+
+    /// Properties to pass to the install of this package.
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+
+    /// Properties whose values are redacted from all output.
+    #[serde(default)]
+    pub hide: Vec<String>,
+
+    /// Continue installing later packages if this package fails, overriding the chain.
+    #[serde(default)]
+    pub continue_on_error: Option<bool>,
+}
+
+impl ChainPackage {
+    /// Adds the properties of this package to a command line.
+    pub fn apply(&self, properties: &mut CommandLine) -> Result<()> {
+        for (name, value) in &self.properties {
+            properties.set(name, value)?;
+        }
+        for name in &self.hide {
+            properties.hide(name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ChainManifest {
+    /// Reads a manifest from a TOML file, resolving package paths against its directory.
+    #[cfg(feature = "toml")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut manifest = Self::from_toml(&std::fs::read_to_string(path)?)?;
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for package in &mut manifest.packages {
+            if package.path.is_relative() {
+                package.path = dir.join(&package.path);
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Parses a manifest from a TOML string.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        let manifest: ChainManifest = toml::from_str(toml).map_err(|err| {
+            Error::from(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        })?;
+        if manifest.packages.is_empty() {
+            return Err(Error::verification("chain manifest has no packages"));
+        }
+
+        Ok(manifest)
+    }
+
+    /// Gets whether installing should continue after the package at `index` fails.
+    pub fn continue_on_error(&self, index: usize) -> bool {
+        self.packages
+            .get(index)
+            .and_then(|package| package.continue_on_error)
+            .unwrap_or(self.continue_on_error)
+    }
+}
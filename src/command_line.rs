@@ -0,0 +1,436 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Error, Result};
+use std::fmt::Display;
+
+/// Replaces the values of hidden properties, as Windows Installer does in its own logs.
+pub const REDACTED: &str = "**********";
+
+/// Public properties passed to an installation on the command line.
+///
+/// Values are quoted as Windows Installer expects when the command line is formatted,
+/// so names and values can be passed as-is. Properties marked hidden are redacted
+/// anywhere msitrace writes them; see [`CommandLine::hide`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommandLine {
+    properties: Vec<(String, String)>,
+    hidden: Vec<String>,
+}
+
+impl CommandLine {
+    /// Creates an empty [`CommandLine`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a property, replacing any previous value of a property with the same name.
+    ///
+    /// Property names are case-sensitive and must start with a letter or underscore
+    /// followed by letters, digits, underscores, or periods.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) -> Result<&mut Self> {
+        let name = name.into();
+        let value = value.into();
+
+        if !is_valid_name(&name) {
+            return Err(Error::invalid_property(name));
+        }
+
+        self.replace(name, value);
+        Ok(self)
+    }
+
+    /// Parses and sets a property in the form `NAME=VALUE`.
+    ///
+    /// Only the first `=` separates the name from the value; the value may be empty.
+    pub fn set_property(&mut self, property: &str) -> Result<&mut Self> {
+        match property.split_once('=') {
+            Some((name, value)) => self.set(name, value),
+            None => Err(Error::invalid_property(property)),
+        }
+    }
+
+    /// Parses and sets properties from lines of `NAME=VALUE`, such as in a response file.
+    ///
+    /// Blank lines and lines starting with `#` are ignored. Whitespace around names and values
+    /// is trimmed unless the value is enclosed in double quotes, within which a double quote
+    /// is escaped by doubling it as on the command line.
+    pub fn set_properties(&mut self, text: &str) -> Result<&mut Self> {
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, value) = line.split_once('=').ok_or_else(|| {
+                Error::verification(format!("line {}: expected NAME=VALUE", i + 1))
+            })?;
+            let value = value.trim();
+            let value = match value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+            {
+                Some(quoted) => quoted.replace("\"\"", "\""),
+                None => value.to_owned(),
+            };
+
+            self.set(name.trim(), value)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Expands environment variables in property values.
+    ///
+    /// Variables are written as `%NAME%`, `$NAME`, or `${NAME}`, and `%%` or `$$` escape
+    /// a literal `%` or `$`. Expanding an undefined variable is an error.
+    ///
+    /// Names start with a letter or underscore followed by letters, digits, or underscores;
+    /// `%NAME%` may also contain parentheses, as in `%ProgramFiles(x86)%`. Percent signs not
+    /// around such a name are literal, e.g. in "50% now or 20% later".
+    pub fn expand_env(&mut self) -> Result<&mut Self> {
+        self.expand_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Expands variables in property values like [`CommandLine::expand_env`], getting the
+    /// value of each variable from `lookup`.
+    pub fn expand_vars(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<&mut Self> {
+        for (_, value) in &mut self.properties {
+            *value = expand(value, &lookup)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Gets the value of a property, if set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Removes a property and returns its value, if set.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self.properties.iter().position(|(n, _)| n == name)?;
+        Some(self.properties.remove(index).1)
+    }
+
+    /// Gets an iterator over property names and values in the order they were first set.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.properties
+            .iter()
+            .map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    /// Gets whether no properties are set.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Sets `ALLUSERS` and `MSIINSTALLPERUSER` to install in the given context,
+    /// replacing any values already set.
+    pub fn set_context(&mut self, context: InstallContext) -> &mut Self {
+        const ALLUSERS: &str = "ALLUSERS";
+        const MSIINSTALLPERUSER: &str = "MSIINSTALLPERUSER";
+
+        let (all_users, per_user) = match context {
+            InstallContext::User => ("2", Some("1")),
+            InstallContext::Machine => ("1", None),
+            InstallContext::All => ("2", None),
+        };
+
+        self.replace(ALLUSERS.to_owned(), all_users.to_owned());
+        match per_user {
+            Some(value) => self.replace(MSIINSTALLPERUSER.to_owned(), value.to_owned()),
+            None => {
+                self.remove(MSIINSTALLPERUSER);
+            }
+        }
+
+        self
+    }
+
+    /// Appends transforms to the semicolon-delimited `TRANSFORMS` property.
+    ///
+    /// Transforms embedded in a package are named with a leading colon, e.g. `:fr-FR.mst`;
+    /// others are paths. Names and paths cannot be empty or contain semicolons.
+    pub fn add_transforms<I, S>(&mut self, transforms: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.append_list("TRANSFORMS", "transform", transforms, |t| t != ":")
+    }
+
+    /// Appends patches to the semicolon-delimited `PATCH` property to slipstream them
+    /// into an install.
+    ///
+    /// Patches must be full paths, which cannot contain semicolons.
+    pub fn add_patches<I, S>(&mut self, patches: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.append_list("PATCH", "patch", patches, |_| true)
+    }
+
+    /// Marks a property hidden, whether or not it is set.
+    ///
+    /// Values of hidden properties are redacted from the trace, and the property is added to
+    /// `MSIHIDDENPROPERTIES` so Windows Installer also redacts it from the verbose log.
+    pub fn hide(&mut self, name: impl Into<String>) -> Result<&mut Self> {
+        let name = name.into();
+        if !is_valid_name(&name) {
+            return Err(Error::invalid_property(name));
+        }
+
+        if !self.is_hidden(&name) {
+            self.hidden.push(name);
+        }
+
+        Ok(self)
+    }
+
+    /// Gets whether a property is marked hidden.
+    pub fn is_hidden(&self, name: &str) -> bool {
+        self.hidden.iter().any(|n| n == name)
+    }
+
+    /// Gets a displayable command line with the values of hidden properties redacted.
+    pub fn redacted(&self) -> Redacted<'_> {
+        Redacted(self)
+    }
+
+    /// Replaces any values of hidden properties within `text`.
+    pub fn redact(&self, text: &str) -> String {
+        let mut secrets: Vec<&str> = self
+            .properties
+            .iter()
+            .filter(|(n, v)| !v.is_empty() && self.is_hidden(n))
+            .map(|(_, v)| v.as_str())
+            .collect();
+
+        // Replace longer values first in case one secret contains another.
+        secrets.sort_by_key(|v| std::cmp::Reverse(v.len()));
+
+        let mut text = text.to_owned();
+        for secret in secrets {
+            text = text.replace(secret, REDACTED);
+        }
+
+        text
+    }
+
+    /// Gets the command line to pass to Windows Installer, with hidden property names
+    /// merged into `MSIHIDDENPROPERTIES`.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn to_install_string(&self) -> String {
+        if self.hidden.is_empty() {
+            return self.to_string();
+        }
+
+        // cspell:ignore MSIHIDDENPROPERTIES
+        const MSIHIDDENPROPERTIES: &str = "MSIHIDDENPROPERTIES";
+        let mut hidden: Vec<&str> = self
+            .get(MSIHIDDENPROPERTIES)
+            .map(|v| v.split(';').filter(|n| !n.is_empty()).collect())
+            .unwrap_or_default();
+        for name in &self.hidden {
+            if !hidden.contains(&name.as_str()) {
+                hidden.push(name);
+            }
+        }
+
+        let mut command_line = self.clone();
+        command_line
+            .properties
+            .retain(|(n, _)| n != MSIHIDDENPROPERTIES);
+        command_line
+            .properties
+            .push((MSIHIDDENPROPERTIES.to_owned(), hidden.join(";")));
+
+        command_line.to_string()
+    }
+
+    fn append_list<I, S>(
+        &mut self,
+        name: &str,
+        kind: &str,
+        items: I,
+        is_valid: impl Fn(&str) -> bool,
+    ) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut value: Vec<String> = self
+            .get(name)
+            .map(|v| {
+                v.split(';')
+                    .filter(|item| !item.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        for item in items {
+            let item = item.as_ref();
+            if item.is_empty() || item.contains(';') || !is_valid(item) {
+                return Err(Error::verification(format!("invalid {} {:?}", kind, item)));
+            }
+            value.push(item.to_owned());
+        }
+
+        if !value.is_empty() {
+            self.replace(name.to_owned(), value.join(";"));
+        }
+
+        Ok(self)
+    }
+
+    fn replace(&mut self, name: String, value: String) {
+        match self.properties.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.properties.push((name, value)),
+        }
+    }
+
+    fn write(&self, f: &mut std::fmt::Formatter<'_>, redact: bool) -> std::fmt::Result {
+        for (i, (name, value)) in self.properties.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+
+            write!(f, "{}=", name)?;
+            if redact && self.is_hidden(name) {
+                f.write_str(REDACTED)?;
+            } else if needs_quotes(value) {
+                write!(f, "\"{}\"", value.replace('"', "\"\""))?;
+            } else {
+                f.write_str(value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for CommandLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(f, false)
+    }
+}
+
+/// The context in which to install a package.
+///
+/// Packages authored for a single context may ignore or fail with another context.
+/// See [Single Package Authoring](https://learn.microsoft.com/windows/win32/msi/single-package-authoring).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum InstallContext {
+    /// Install for only the current user without elevation,
+    /// setting `ALLUSERS=2` and `MSIINSTALLPERUSER=1`.
+    User,
+
+    /// Install for all users of the machine, setting `ALLUSERS=1`.
+    Machine,
+
+    /// Install for all users unless a dual-purpose package's user interface switches
+    /// to the current user, setting `ALLUSERS=2`.
+    All,
+}
+
+/// A [`CommandLine`] displayed with the values of hidden properties redacted.
+#[derive(Clone, Copy, Debug)]
+pub struct Redacted<'a>(&'a CommandLine);
+
+impl Display for Redacted<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.write(f, true)
+    }
+}
+
+fn expand(value: &str, lookup: &impl Fn(&str) -> Option<String>) -> Result<String> {
+    let var = |name: &str| {
+        lookup(name).ok_or_else(|| {
+            Error::verification(format!("environment variable {} is not defined", name))
+        })
+    };
+
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(i) = rest.find(['%', '$']) {
+        expanded.push_str(&rest[..i]);
+        let (sigil, after) = (&rest[i..i + 1], &rest[i + 1..]);
+
+        rest = if let Some(after) = after.strip_prefix(sigil) {
+            expanded.push_str(sigil);
+            after
+        } else if sigil == "%" {
+            // A percent sign not starting a variable name, e.g. in "50% now", is literal.
+            match after.split_once('%') {
+                Some((name, after)) if is_variable_name(name, "_()") => {
+                    expanded.push_str(&var(name)?);
+                    after
+                }
+                _ => {
+                    expanded.push('%');
+                    after
+                }
+            }
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.split_once('}') {
+                Some((name, after)) if !name.is_empty() => {
+                    expanded.push_str(&var(name)?);
+                    after
+                }
+                _ => {
+                    return Err(Error::verification(format!(
+                        "unterminated variable in {:?}",
+                        value
+                    )))
+                }
+            }
+        } else {
+            let len = after
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(after.len());
+            match is_variable_name(&after[..len], "_") {
+                true => {
+                    expanded.push_str(&var(&after[..len])?);
+                    &after[len..]
+                }
+                false => {
+                    expanded.push('$');
+                    after
+                }
+            }
+        };
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Gets whether a name starts with a letter or underscore and otherwise contains only
+/// letters, digits, or `symbols`.
+fn is_variable_name(name: &str, symbols: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || symbols.contains(c))
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"')
+}
@@ -0,0 +1,279 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::Result;
+use std::fmt::Display;
+use std::ops::Index;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+#[cfg(windows)]
+mod native;
+#[cfg(windows)]
+use native as backend;
+
+#[cfg(not(windows))]
+mod portable;
+#[cfg(not(windows))]
+use portable as backend;
+
+/// A read-only installer database: a package, patch, or transform.
+///
+/// On Windows this uses msi.dll. On other platforms it requires the `portable-db` feature,
+/// which reads databases with the [`msi`](https://docs.rs/msi) crate and supports only
+/// simple `SELECT` queries.
+pub struct Database {
+    inner: backend::Database,
+}
+
+impl Database {
+    /// Opens a database read-only.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Database {
+            inner: backend::Database::open(path.as_ref())?,
+        })
+    }
+
+    /// Opens a patch read-only.
+    #[cfg(windows)]
+    pub fn open_patch(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Database {
+            inner: backend::Database::open_patch(path.as_ref())?,
+        })
+    }
+
+    /// Creates a new, empty database that is not written to `path` until committed.
+    ///
+    /// Changes made with [`Database::execute`] are visible to queries immediately.
+    /// Requires the `test-support` feature.
+    #[cfg(all(windows, feature = "test-support"))]
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Database {
+            inner: backend::Database::create(path.as_ref())?,
+        })
+    }
+
+    /// Applies a transform to the database in memory.
+    ///
+    /// `error_conditions` are `MSITRANSFORM_ERROR_*` flags, which may include
+    /// `MSITRANSFORM_ERROR_VIEWTRANSFORM` to describe the changes in the `_TransformView` table
+    /// rather than making them.
+    #[cfg(windows)]
+    pub fn apply_transform(&self, path: impl AsRef<Path>, error_conditions: u32) -> Result<()> {
+        self.inner.apply_transform(path.as_ref(), error_conditions)
+    }
+
+    /// Executes a statement that returns no rows, e.g. `CREATE TABLE` or `INSERT`.
+    /// Requires the `test-support` feature.
+    #[cfg(all(windows, feature = "test-support"))]
+    pub fn execute(&self, sql: &str) -> Result<()> {
+        self.inner.execute(sql)
+    }
+
+    /// Executes a query and reads the stream in the first column of the first row,
+    /// or returns `None` if there are no rows.
+    ///
+    /// Each `?` in `sql` is replaced with the next of `params`, which need no quoting.
+    #[cfg(windows)]
+    pub fn read_stream(&self, sql: &str, params: &[&str]) -> Result<Option<Vec<u8>>> {
+        self.inner.read_stream(sql, params)
+    }
+
+    /// Gets the names of all tables in the database.
+    pub fn tables(&self) -> Result<Vec<String>> {
+        self.inner.tables()
+    }
+
+    /// Gets the names of the primary key columns of a table.
+    pub fn primary_keys(&self, table: &str) -> Result<Vec<String>> {
+        self.inner.primary_keys(table)
+    }
+
+    /// Executes a query and returns a [`View`] over the resulting rows.
+    pub fn query(&self, sql: &str) -> Result<View> {
+        self.inner.query(sql)
+    }
+
+    /// Gets the summary information of the database.
+    pub fn summary_info(&self) -> Result<SummaryInfo> {
+        self.inner.summary_info()
+    }
+
+    /// Exports a table to `folder` as an archive file named after the table, e.g. _File.idt_.
+    pub fn export(&self, table: &str, folder: impl AsRef<Path>) -> Result<PathBuf> {
+        let file_name = format!("{}.idt", table);
+        self.inner.export(table, folder.as_ref(), &file_name)?;
+
+        Ok(folder.as_ref().join(file_name))
+    }
+}
+
+/// A column in a [`View`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Column {
+    name: String,
+    definition: String,
+}
+
+impl Column {
+    pub(crate) fn new(name: impl Into<String>, definition: impl Into<String>) -> Self {
+        Column {
+            name: name.into(),
+            definition: definition.into(),
+        }
+    }
+
+    /// Gets the name of the column.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the column definition as used in archive files, e.g. `s72` or `I2`.
+    pub fn definition(&self) -> &str {
+        &self.definition
+    }
+
+    /// Gets whether the column contains integers.
+    pub fn is_integer(&self) -> bool {
+        self.definition.starts_with(['i', 'I', 'j', 'J'])
+    }
+
+    /// Gets whether the column contains binary streams.
+    pub fn is_stream(&self) -> bool {
+        self.definition.starts_with(['v', 'V'])
+    }
+}
+
+/// The value of a field in a [`Row`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i32),
+    String(String),
+}
+
+impl Value {
+    /// Gets the integer value, if any.
+    pub fn as_integer(&self) -> Option<i32> {
+        match self {
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Gets the string value, if any.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Gets whether the value is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::String(s) => f.write_str(s),
+        }
+    }
+}
+
+/// A row of values returned from a [`View`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Row(Vec<Value>);
+
+impl Row {
+    pub(crate) fn new(values: Vec<Value>) -> Self {
+        Row(values)
+    }
+
+    /// Gets the value at the 0-based column index, if any.
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.0.get(index)
+    }
+
+    /// Gets the number of values in the row.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Gets whether the row contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Gets an iterator over the values in the row.
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.0.iter()
+    }
+}
+
+impl Index<usize> for Row {
+    type Output = Value;
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+/// The results of a query, iterated as [`Row`]s.
+pub struct View {
+    columns: Vec<Column>,
+    inner: backend::View,
+}
+
+impl View {
+    pub(crate) fn new(columns: Vec<Column>, inner: backend::View) -> Self {
+        View { columns, inner }
+    }
+
+    /// Gets the columns selected by the query.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+}
+
+impl Iterator for View {
+    type Item = Result<Row>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next(&self.columns)
+    }
+}
+
+/// Summary information describing a package, patch, or transform.
+///
+/// The meaning of some properties depends on the type of database,
+/// e.g. `revision_number` is the PackageCode of a package.
+#[derive(Clone, Debug, Default)]
+pub struct SummaryInfo {
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub author: Option<String>,
+    pub keywords: Option<String>,
+    pub comments: Option<String>,
+    pub template: Option<String>,
+    pub last_saved_by: Option<String>,
+    pub revision_number: Option<String>,
+    pub created: Option<OffsetDateTime>,
+    pub last_saved: Option<OffsetDateTime>,
+    pub page_count: Option<i32>,
+    pub word_count: Option<i32>,
+    pub character_count: Option<i32>,
+    pub creating_application: Option<String>,
+    pub security: Option<i32>,
+}
+
+impl SummaryInfo {
+    /// Reads the summary information of any installer file, including transforms
+    /// that cannot be opened as a [`Database`].
+    #[cfg(windows)]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        backend::file_summary_info(path.as_ref())
+    }
+}
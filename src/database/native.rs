@@ -0,0 +1,188 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{Column, Row, SummaryInfo, Value};
+use crate::ffi::{self, ColumnInfo, OwnedMsiHandle, SummaryValue};
+use crate::Result;
+use std::path::Path;
+use time::OffsetDateTime;
+
+const PID_TITLE: u32 = 2;
+const PID_SUBJECT: u32 = 3;
+const PID_AUTHOR: u32 = 4;
+const PID_KEYWORDS: u32 = 5;
+const PID_COMMENTS: u32 = 6;
+const PID_TEMPLATE: u32 = 7;
+const PID_LASTAUTHOR: u32 = 8;
+const PID_REVNUMBER: u32 = 9;
+const PID_CREATE_DTM: u32 = 12;
+const PID_LASTSAVE_DTM: u32 = 13;
+const PID_PAGECOUNT: u32 = 14;
+const PID_WORDCOUNT: u32 = 15;
+const PID_CHARCOUNT: u32 = 16;
+const PID_APPNAME: u32 = 18;
+const PID_SECURITY: u32 = 19;
+
+pub struct Database(OwnedMsiHandle);
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self> {
+        let path = path.to_string_lossy();
+        let handle = ffi::open_database(path.as_ref(), ffi::MSIDBOPEN_READONLY)?;
+
+        Ok(Database(handle))
+    }
+
+    #[cfg(feature = "test-support")]
+    pub fn create(path: &Path) -> Result<Self> {
+        let path = path.to_string_lossy();
+        let handle = ffi::open_database(path.as_ref(), ffi::MSIDBOPEN_CREATE)?;
+
+        Ok(Database(handle))
+    }
+
+    pub fn open_patch(path: &Path) -> Result<Self> {
+        let path = path.to_string_lossy();
+        let handle = ffi::open_database(path.as_ref(), ffi::MSIDBOPEN_READONLY_PATCHFILE)?;
+
+        Ok(Database(handle))
+    }
+
+    pub fn read_stream(&self, sql: &str, params: &[&str]) -> Result<Option<Vec<u8>>> {
+        let view = ffi::open_view(*self.0, sql)?;
+        ffi::execute_view_with(*view, params)?;
+
+        match ffi::fetch_view(*view)? {
+            Some(record) => Ok(Some(record.stream_data(1)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn apply_transform(&self, path: &Path, error_conditions: u32) -> Result<()> {
+        ffi::apply_transform(*self.0, &path.to_string_lossy(), error_conditions)
+    }
+
+    #[cfg(feature = "test-support")]
+    pub fn execute(&self, sql: &str) -> Result<()> {
+        let view = ffi::open_view(*self.0, sql)?;
+        ffi::execute_view(*view)
+    }
+
+    pub fn tables(&self) -> Result<Vec<String>> {
+        let view = self.query("SELECT `Name` FROM `_Tables`")?;
+        view.map(|row| Ok(row?[0].to_string())).collect()
+    }
+
+    pub fn primary_keys(&self, table: &str) -> Result<Vec<String>> {
+        // Field 0 is the table name.
+        let keys = ffi::primary_keys(*self.0, table)?;
+        (1..=keys.field_count())
+            .map(|i| keys.string_data(i))
+            .collect()
+    }
+
+    pub fn query(&self, sql: &str) -> Result<super::View> {
+        let view = ffi::open_view(*self.0, sql)?;
+        ffi::execute_view(*view)?;
+
+        let names = ffi::view_column_info(*view, ColumnInfo::Names)?;
+        let types = ffi::view_column_info(*view, ColumnInfo::Types)?;
+        let columns = (1..=names.field_count())
+            .map(|i| Ok(Column::new(names.string_data(i)?, types.string_data(i)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(super::View::new(columns, View(view)))
+    }
+
+    pub fn summary_info(&self) -> Result<SummaryInfo> {
+        summary_info(ffi::summary_information(*self.0)?)
+    }
+
+    pub fn export(&self, table: &str, folder: &Path, file_name: &str) -> Result<()> {
+        let folder = folder.to_string_lossy();
+        ffi::export_table(*self.0, table, folder.as_ref(), file_name)
+    }
+}
+
+pub struct View(OwnedMsiHandle);
+
+impl View {
+    pub fn next(&mut self, columns: &[Column]) -> Option<Result<Row>> {
+        let record = match ffi::fetch_view(*self.0) {
+            Ok(Some(record)) => record,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let values = columns
+            .iter()
+            .zip(1u32..)
+            .map(|(column, i)| {
+                if record.is_null(i) {
+                    return Ok(Value::Null);
+                }
+                if column.is_stream() {
+                    return Ok(Value::String("[Binary Data]".to_owned()));
+                }
+                if column.is_integer() {
+                    return Ok(record.integer_data(i).map_or(Value::Null, Value::Integer));
+                }
+                Ok(Value::String(record.string_data(i)?))
+            })
+            .collect::<Result<Vec<_>>>();
+
+        Some(values.map(Row::new))
+    }
+}
+
+/// Reads the summary information of any installer file, including transforms.
+pub fn file_summary_info(path: &Path) -> Result<SummaryInfo> {
+    summary_info(ffi::summary_information_from_path(&path.to_string_lossy())?)
+}
+
+fn summary_info(handle: OwnedMsiHandle) -> Result<SummaryInfo> {
+    let string = |pid| -> Result<Option<String>> {
+        match ffi::summary_info_property(*handle, pid)? {
+            Some(SummaryValue::String(s)) => Ok(Some(s)),
+            _ => Ok(None),
+        }
+    };
+    let integer = |pid| -> Result<Option<i32>> {
+        match ffi::summary_info_property(*handle, pid)? {
+            Some(SummaryValue::Integer(i)) => Ok(Some(i)),
+            _ => Ok(None),
+        }
+    };
+    let time = |pid| -> Result<Option<OffsetDateTime>> {
+        match ffi::summary_info_property(*handle, pid)? {
+            Some(SummaryValue::FileTime(t)) => Ok(from_file_time(t)),
+            _ => Ok(None),
+        }
+    };
+
+    Ok(SummaryInfo {
+        title: string(PID_TITLE)?,
+        subject: string(PID_SUBJECT)?,
+        author: string(PID_AUTHOR)?,
+        keywords: string(PID_KEYWORDS)?,
+        comments: string(PID_COMMENTS)?,
+        template: string(PID_TEMPLATE)?,
+        last_saved_by: string(PID_LASTAUTHOR)?,
+        revision_number: string(PID_REVNUMBER)?,
+        created: time(PID_CREATE_DTM)?,
+        last_saved: time(PID_LASTSAVE_DTM)?,
+        page_count: integer(PID_PAGECOUNT)?,
+        word_count: integer(PID_WORDCOUNT)?,
+        character_count: integer(PID_CHARCOUNT)?,
+        creating_application: string(PID_APPNAME)?,
+        security: integer(PID_SECURITY)?,
+    })
+}
+
+fn from_file_time(time: u64) -> Option<OffsetDateTime> {
+    // 100-nanosecond intervals between 1601-01-01 and 1970-01-01.
+    const UNIX_EPOCH: i128 = 116_444_736_000_000_000;
+    let nanos = (time as i128 - UNIX_EPOCH) * 100;
+
+    OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()
+}
@@ -0,0 +1,334 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{Column, Row, SummaryInfo, Value};
+use crate::Result;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use time::OffsetDateTime;
+
+pub struct Database(RefCell<msi::Package<File>>);
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self> {
+        let package = msi::open(path)?;
+        Ok(Database(RefCell::new(package)))
+    }
+
+    pub fn tables(&self) -> Result<Vec<String>> {
+        let package = self.0.borrow();
+        Ok(package.tables().map(|t| t.name().to_owned()).collect())
+    }
+
+    pub fn primary_keys(&self, table: &str) -> Result<Vec<String>> {
+        let package = self.0.borrow();
+        let table = package
+            .get_table(table)
+            .ok_or_else(|| invalid_query(format!("table `{}` not found", table)))?;
+
+        Ok(table
+            .columns()
+            .iter()
+            .filter(|c| c.is_primary_key())
+            .map(|c| c.name().to_owned())
+            .collect())
+    }
+
+    pub fn query(&self, sql: &str) -> Result<super::View> {
+        let select = Select::parse(sql)?;
+        let mut package = self.0.borrow_mut();
+
+        let (all, indices, conditions) = {
+            let table = package
+                .get_table(&select.table)
+                .ok_or_else(|| invalid_query(format!("table `{}` not found", select.table)))?;
+            let all: Vec<Column> = table.columns().iter().map(column).collect();
+            let index_of = |name: &str| {
+                all.iter()
+                    .position(|c| c.name() == name)
+                    .ok_or_else(|| invalid_query(format!("column `{}` not found", name)))
+            };
+
+            let indices = match &select.columns {
+                Some(names) => names
+                    .iter()
+                    .map(|n| index_of(n))
+                    .collect::<io::Result<Vec<_>>>()?,
+                None => (0..all.len()).collect(),
+            };
+            let conditions = select
+                .conditions
+                .iter()
+                .map(|(n, v)| Ok((index_of(n)?, v.clone())))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            (all, indices, conditions)
+        };
+
+        let mut rows = Vec::new();
+        for row in package.select_rows(msi::Select::table(select.table.as_str()))? {
+            let values: Vec<Value> = (0..row.len()).map(|i| value(&row[i])).collect();
+            if conditions.iter().all(|(i, v)| values[*i] == *v) {
+                rows.push(Row::new(
+                    indices.iter().map(|&i| values[i].clone()).collect(),
+                ));
+            }
+        }
+
+        let columns = indices.iter().map(|&i| all[i].clone()).collect();
+        Ok(super::View::new(columns, View(rows.into_iter())))
+    }
+
+    pub fn summary_info(&self) -> Result<SummaryInfo> {
+        let package = self.0.borrow();
+        let summary = package.summary_info();
+
+        let languages: Vec<String> = summary
+            .languages()
+            .iter()
+            .map(|l| l.code().to_string())
+            .collect();
+        let template = format!(
+            "{};{}",
+            summary.arch().unwrap_or_default(),
+            languages.join(",")
+        );
+
+        Ok(SummaryInfo {
+            title: summary.title().map(str::to_owned),
+            subject: summary.subject().map(str::to_owned),
+            author: summary.author().map(str::to_owned),
+            comments: summary.comments().map(str::to_owned),
+            template: Some(template),
+            revision_number: summary
+                .uuid()
+                .map(|u| format!("{{{}}}", u.to_string().to_uppercase())),
+            created: summary.creation_time().map(OffsetDateTime::from),
+            creating_application: summary.creating_application().map(str::to_owned),
+            ..Default::default()
+        })
+    }
+
+    pub fn export(&self, table: &str, folder: &Path, file_name: &str) -> Result<()> {
+        let (columns, keys) = {
+            let package = self.0.borrow();
+            let table = package
+                .get_table(table)
+                .ok_or_else(|| invalid_query(format!("table `{}` not found", table)))?;
+            let columns: Vec<Column> = table.columns().iter().map(column).collect();
+            let keys: Vec<&str> = table
+                .columns()
+                .iter()
+                .filter(|c| c.is_primary_key())
+                .map(|c| c.name())
+                .collect();
+
+            (columns, keys.join("\t"))
+        };
+
+        let mut file = BufWriter::new(File::create(folder.join(file_name))?);
+        let names: Vec<&str> = columns.iter().map(|c| c.name()).collect();
+        let definitions: Vec<&str> = columns.iter().map(|c| c.definition()).collect();
+        write!(
+            file,
+            "{}\r\n{}\r\n{}\t{}\r\n",
+            names.join("\t"),
+            definitions.join("\t"),
+            table,
+            keys
+        )?;
+
+        for row in self.query(&format!("SELECT * FROM `{}`", table))? {
+            let values: Vec<String> = row?.iter().map(|v| escape(&v.to_string())).collect();
+            write!(file, "{}\r\n", values.join("\t"))?;
+        }
+
+        file.flush()?;
+        Ok(())
+    }
+}
+
+pub struct View(std::vec::IntoIter<Row>);
+
+impl View {
+    pub fn next(&mut self, _columns: &[Column]) -> Option<Result<Row>> {
+        self.0.next().map(Ok)
+    }
+}
+
+fn column(column: &msi::Column) -> Column {
+    let (kind, size) = match column.coltype() {
+        msi::ColumnType::Int16 => ('i', 2),
+        msi::ColumnType::Int32 => ('i', 4),
+        msi::ColumnType::Str(size) if column.is_localizable() => ('l', size),
+        msi::ColumnType::Str(size) => ('s', size),
+    };
+    let kind = match column.is_nullable() {
+        true => kind.to_ascii_uppercase(),
+        false => kind,
+    };
+
+    Column::new(column.name(), format!("{}{}", kind, size))
+}
+
+fn value(value: &msi::Value) -> Value {
+    match value {
+        msi::Value::Null => Value::Null,
+        msi::Value::Int(i) => Value::Integer(*i),
+        msi::Value::Str(s) => Value::String(s.clone()),
+    }
+}
+
+/// Escapes control characters as archive files require.
+fn escape(value: &str) -> String {
+    value
+        .replace('\t', "\x15")
+        .replace('\n', "\x11")
+        .replace('\r', "\x19")
+}
+
+fn invalid_query(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// The subset of SQL supported without msi.dll:
+/// `SELECT * | columns FROM table [WHERE column = value [AND ...]]`.
+#[derive(Debug)]
+struct Select {
+    table: String,
+    columns: Option<Vec<String>>,
+    conditions: Vec<(String, Value)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Identifier(String),
+    String(String),
+    Integer(i32),
+    Comma,
+    Star,
+    Equals,
+}
+
+impl Select {
+    fn parse(sql: &str) -> io::Result<Self> {
+        let mut tokens = tokenize(sql)?.into_iter().peekable();
+
+        expect_keyword(tokens.next(), "SELECT")?;
+        let columns = match tokens.peek() {
+            Some(Token::Star) => {
+                tokens.next();
+                None
+            }
+            _ => {
+                let mut columns = vec![identifier(tokens.next())?];
+                while tokens.peek() == Some(&Token::Comma) {
+                    tokens.next();
+                    columns.push(identifier(tokens.next())?);
+                }
+                Some(columns)
+            }
+        };
+
+        expect_keyword(tokens.next(), "FROM")?;
+        let table = identifier(tokens.next())?;
+
+        let mut conditions = Vec::new();
+        if let Some(token) = tokens.next() {
+            expect_keyword(Some(token), "WHERE")?;
+            loop {
+                let column = identifier(tokens.next())?;
+                if tokens.next() != Some(Token::Equals) {
+                    return Err(invalid_query(
+                        "only `=` comparisons are supported".to_owned(),
+                    ));
+                }
+                let value = match tokens.next() {
+                    Some(Token::String(s)) => Value::String(s),
+                    Some(Token::Integer(i)) => Value::Integer(i),
+                    Some(Token::Word(w)) if w.eq_ignore_ascii_case("NULL") => Value::Null,
+                    _ => return Err(invalid_query("expected a value".to_owned())),
+                };
+                conditions.push((column, value));
+
+                match tokens.next() {
+                    None => break,
+                    token => expect_keyword(token, "AND")?,
+                }
+            }
+        }
+
+        Ok(Select {
+            table,
+            columns,
+            conditions,
+        })
+    }
+}
+
+fn tokenize(sql: &str) -> io::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ',' => tokens.push(Token::Comma),
+            '*' => tokens.push(Token::Star),
+            '=' => tokens.push(Token::Equals),
+            '`' | '\'' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => text.push(ch),
+                        None => return Err(invalid_query(format!("unterminated {}", c))),
+                    }
+                }
+                tokens.push(match c {
+                    '`' => Token::Identifier(text),
+                    _ => Token::String(text),
+                });
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut text = String::from(c);
+                while let Some(ch) = chars.next_if(char::is_ascii_digit) {
+                    text.push(ch);
+                }
+                let i = text
+                    .parse()
+                    .map_err(|_| invalid_query(format!("invalid integer {}", text)))?;
+                tokens.push(Token::Integer(i));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut text = String::from(c);
+                while let Some(ch) =
+                    chars.next_if(|ch| ch.is_alphanumeric() || *ch == '_' || *ch == '.')
+                {
+                    text.push(ch);
+                }
+                tokens.push(Token::Word(text));
+            }
+            c => return Err(invalid_query(format!("unsupported character {:?}", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn expect_keyword(token: Option<Token>, keyword: &str) -> io::Result<()> {
+    match token {
+        Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword) => Ok(()),
+        _ => Err(invalid_query(format!("expected {}", keyword))),
+    }
+}
+
+fn identifier(token: Option<Token>) -> io::Result<String> {
+    match token {
+        Some(Token::Word(w) | Token::Identifier(w)) => Ok(w),
+        _ => Err(invalid_query("expected a table or column name".to_owned())),
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Database, Result, Value};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Whether a table, row, or cell was added, removed, or changed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStatus {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// The differences between two databases, e.g. two builds of a package.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct DatabaseDiff {
+    /// Tables that were added, removed, or have changed rows, ordered by name.
+    pub tables: Vec<TableDiff>,
+}
+
+/// The differences in a table.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct TableDiff {
+    pub name: String,
+    pub status: DiffStatus,
+
+    /// Rows that were added, removed, or changed, ordered by primary keys.
+    pub rows: Vec<RowDiff>,
+}
+
+/// The differences in a row, identified by its primary keys.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RowDiff {
+    pub keys: Vec<String>,
+    pub status: DiffStatus,
+
+    /// Cells that differ. Rows that were added or removed list every cell.
+    pub cells: Vec<CellDiff>,
+}
+
+/// The old and new values of a cell. Null values are `None`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CellDiff {
+    pub column: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Rows of a table keyed by their primary keys, with values by column name.
+type Rows = BTreeMap<Vec<String>, Vec<(String, Option<String>)>>;
+
+/// Compares every table and row of two databases.
+///
+/// Streams are not compared, since their contents are not read.
+pub fn diff(old: &Database, new: &Database) -> Result<DatabaseDiff> {
+    let old_tables = old.tables()?;
+    let new_tables = new.tables()?;
+    let mut names: Vec<&String> = old_tables.iter().chain(&new_tables).collect();
+    names.sort();
+    names.dedup();
+
+    let mut tables = Vec::new();
+    for name in names {
+        let old_rows = match old_tables.contains(name) {
+            true => Some(rows(old, name)?),
+            false => None,
+        };
+        let new_rows = match new_tables.contains(name) {
+            true => Some(rows(new, name)?),
+            false => None,
+        };
+
+        let status = match (&old_rows, &new_rows) {
+            (None, _) => DiffStatus::Added,
+            (_, None) => DiffStatus::Removed,
+            _ => DiffStatus::Changed,
+        };
+        let rows = diff_rows(old_rows.unwrap_or_default(), new_rows.unwrap_or_default());
+        if status == DiffStatus::Changed && rows.is_empty() {
+            continue;
+        }
+
+        tables.push(TableDiff {
+            name: name.clone(),
+            status,
+            rows,
+        });
+    }
+
+    Ok(DatabaseDiff { tables })
+}
+
+fn rows(database: &Database, table: &str) -> Result<Rows> {
+    let keys = database.primary_keys(table)?;
+    let view = database.query(&format!("SELECT * FROM `{}`", table))?;
+    let columns: Vec<String> = view.columns().iter().map(|c| c.name().to_owned()).collect();
+    let key_indices: Vec<usize> = keys
+        .iter()
+        .filter_map(|key| columns.iter().position(|c| c == key))
+        .collect();
+
+    let mut rows = Rows::new();
+    for row in view {
+        let values: Vec<Option<String>> = row?.iter().map(text).collect();
+        let keys = key_indices
+            .iter()
+            .map(|&i| values[i].clone().unwrap_or_default())
+            .collect();
+        rows.insert(keys, columns.iter().cloned().zip(values).collect());
+    }
+
+    Ok(rows)
+}
+
+fn diff_rows(mut old: Rows, new: Rows) -> Vec<RowDiff> {
+    let mut rows = Vec::new();
+    for (keys, new_values) in new {
+        let Some(old_values) = old.remove(&keys) else {
+            rows.push(RowDiff {
+                keys,
+                status: DiffStatus::Added,
+                cells: new_values
+                    .into_iter()
+                    .map(|(column, new)| CellDiff {
+                        column,
+                        old: None,
+                        new,
+                    })
+                    .collect(),
+            });
+            continue;
+        };
+
+        let mut cells = Vec::new();
+        for (column, new) in new_values {
+            let old = old_values
+                .iter()
+                .find(|(c, _)| *c == column)
+                .and_then(|(_, v)| v.clone());
+            if old != new {
+                cells.push(CellDiff { column, old, new });
+            }
+        }
+        if !cells.is_empty() {
+            rows.push(RowDiff {
+                keys,
+                status: DiffStatus::Changed,
+                cells,
+            });
+        }
+    }
+
+    for (keys, old_values) in old {
+        rows.push(RowDiff {
+            keys,
+            status: DiffStatus::Removed,
+            cells: old_values
+                .into_iter()
+                .map(|(column, old)| CellDiff {
+                    column,
+                    old,
+                    new: None,
+                })
+                .collect(),
+        });
+    }
+
+    rows.sort_by(|a, b| a.keys.cmp(&b.keys));
+    rows
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::disk;
+use crate::{
+    always_install_elevated, installer_policy, installer_service, installer_version, is_elevated,
+    is_installer_busy, pending_operations, CommandLine, Result,
+};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Volumes with less free space than this will likely fail to install.
+const FAIL_FREE_SPACE: u64 = 200 * 1024 * 1024;
+
+/// Volumes with less free space than this may fail to install larger packages.
+const WARN_FREE_SPACE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// The outcome of an environment [`Check`].
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+        })
+    }
+}
+
+/// A check of the environment for a condition that commonly causes installs to fail.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Check {
+    /// The name of what was checked.
+    pub name: &'static str,
+
+    /// Whether the check passed.
+    pub status: CheckStatus,
+
+    /// What was found.
+    pub message: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Check {
+            name,
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.status, self.name, self.message)
+    }
+}
+
+/// Runs all environment checks.
+///
+/// A check that cannot be completed is reported as a warning rather than failing the others.
+pub fn diagnose() -> Vec<Check> {
+    let mut checks = vec![
+        check("Elevation", elevation),
+        check("Installer service", service),
+        check("Installer version", version),
+        check("Install in progress", busy),
+        check("Pending operations", pending),
+        check("Policies", policies),
+        check("AlwaysInstallElevated", elevated_installs),
+    ];
+
+    let system = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_owned());
+    checks.push(check("System volume", || free_space(&system)));
+    let temp = std::env::temp_dir();
+    checks.push(check("Temp volume", || free_space(&temp.to_string_lossy())));
+
+    checks
+}
+
+fn check<F>(name: &'static str, f: F) -> Check
+where
+    F: FnOnce() -> Result<(CheckStatus, String)>,
+{
+    match f() {
+        Ok((status, message)) => Check::new(name, status, message),
+        Err(err) => Check::new(name, CheckStatus::Warn, format!("could not check: {}", err)),
+    }
+}
+
+fn elevation() -> Result<(CheckStatus, String)> {
+    Ok(match is_elevated()? {
+        true => (CheckStatus::Pass, "elevated".to_owned()),
+        false => (
+            CheckStatus::Warn,
+            "not elevated; per-machine installs will prompt or fail".to_owned(),
+        ),
+    })
+}
+
+fn service() -> Result<(CheckStatus, String)> {
+    let state = installer_service()?;
+    Ok(match state.failure_reason() {
+        Some(reason) => (CheckStatus::Fail, reason.to_owned()),
+        None => (CheckStatus::Pass, state.to_string()),
+    })
+}
+
+fn version() -> Result<(CheckStatus, String)> {
+    Ok((CheckStatus::Pass, installer_version()?.to_string()))
+}
+
+fn busy() -> Result<(CheckStatus, String)> {
+    Ok(match is_installer_busy()? {
+        true => (
+            CheckStatus::Fail,
+            "another install is in progress; installs will fail with 1618".to_owned(),
+        ),
+        false => (CheckStatus::Pass, "none".to_owned()),
+    })
+}
+
+fn pending() -> Result<(CheckStatus, String)> {
+    let pending = pending_operations()?;
+    Ok(match pending.is_empty() {
+        true => (CheckStatus::Pass, "none".to_owned()),
+        false => {
+            let pending: Vec<String> = pending.iter().map(ToString::to_string).collect();
+            (CheckStatus::Warn, pending.join("; "))
+        }
+    })
+}
+
+fn policies() -> Result<(CheckStatus, String)> {
+    let policy = installer_policy()?;
+    let mut message = format!(
+        "DisableMSI={}, DisablePatch={}, DisableUserInstalls={}, Logging={}, Debug={}",
+        policy.disable_msi,
+        policy.disable_patch,
+        policy.disable_user_installs,
+        policy.logging.as_deref().unwrap_or("not set"),
+        policy.debug,
+    );
+    for conflict in policy.conflicts(&CommandLine::new()) {
+        message.push_str("; ");
+        message.push_str(conflict);
+    }
+
+    // Managed installs still work when DisableMSI is 1, so only disabling all installs fails.
+    Ok(match policy.disable_msi {
+        0 => (CheckStatus::Pass, message),
+        1 => (CheckStatus::Warn, message),
+        _ => (CheckStatus::Fail, message),
+    })
+}
+
+fn elevated_installs() -> Result<(CheckStatus, String)> {
+    let policy = always_install_elevated()?;
+    Ok(match policy.is_enabled() {
+        true => (
+            CheckStatus::Warn,
+            format!("{}; any user can install packages as LocalSystem", policy),
+        ),
+        false => (CheckStatus::Pass, policy.to_string()),
+    })
+}
+
+fn free_space(path: &str) -> Result<(CheckStatus, String)> {
+    let available = disk::free_space(path)?;
+    let message = format!(
+        "{:.1} GB free on {}",
+        available as f64 / (1024.0 * 1024.0 * 1024.0),
+        path
+    );
+
+    Ok(match available {
+        n if n < FAIL_FREE_SPACE => (CheckStatus::Fail, message),
+        n if n < WARN_FREE_SPACE => (CheckStatus::Warn, message),
+        _ => (CheckStatus::Pass, message),
+    })
+}
@@ -0,0 +1,83 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::{bcrypt, urlmon};
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Downloads a package into the msitrace cache in the temporary directory and returns its path.
+///
+/// If `sha256` is passed, a cached package with the same digest is reused, and a downloaded
+/// package is deleted and an error returned if its digest does not match.
+pub fn download(url: &str, sha256: Option<&str>) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join("msitrace").join("cache");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name(url));
+
+    if let Some(expected) = sha256 {
+        if path.exists() && sha256_digest(&path)?.eq_ignore_ascii_case(expected) {
+            println!("Using cached {}", path.display());
+            return Ok(path);
+        }
+    }
+
+    println!("Downloading {}", url);
+    urlmon::download(url, &path.to_string_lossy())?;
+
+    if let Some(expected) = sha256 {
+        if let Err(err) = verify_digest(&path, expected) {
+            let _ = std::fs::remove_file(&path);
+            return Err(err);
+        }
+    }
+
+    Ok(path)
+}
+
+/// Fails if the SHA-256 digest of a file does not match the expected hexadecimal digest.
+pub(crate) fn verify_digest(path: &Path, expected: &str) -> Result<()> {
+    let digest = sha256_digest(path)?;
+    match digest.eq_ignore_ascii_case(expected) {
+        true => Ok(()),
+        false => Err(Error::verification(format!(
+            "{} has SHA-256 digest {} but {} was expected",
+            path.display(),
+            digest,
+            expected
+        ))),
+    }
+}
+
+/// Gets the lowercase hexadecimal SHA-256 digest of a file.
+pub(crate) fn sha256_digest(path: &Path) -> Result<String> {
+    let digest = bcrypt::sha256(BufReader::new(File::open(path)?))?;
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Gets the file name from the last segment of a URL's path.
+///
+/// Characters not valid in file names are replaced so the name cannot refer to another
+/// directory, and names of only dots fall back to a default name.
+fn file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    let name: String = path
+        .split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    // Windows ignores trailing dots and spaces, so "..", "." and " " would name the directory.
+    match name.trim_end_matches(['.', ' ']) {
+        "" => "package.msi".to_owned(),
+        _ => name,
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::{process, security};
+use crate::{install_properties, Database, InstallOptions, Result};
+use std::io::Write;
+
+/// Word count bit set in packages that do not require elevated privileges to install.
+const SOURCE_TYPE_LUA_PACKAGE: i32 = 8;
+
+/// Gets whether msitrace is running elevated.
+///
+/// Per-machine installs started without elevation prompt for consent, or fail when
+/// the user interface is hidden.
+pub fn is_elevated() -> Result<bool> {
+    security::is_elevated()
+}
+
+/// Gets whether a package installs per-machine and so requires elevation.
+///
+/// Properties passed in `options`, including the install context, override those
+/// authored in the package.
+pub fn requires_elevation(path: &str, options: &InstallOptions) -> Result<bool> {
+    let database = Database::open(path)?;
+    let summary = database.summary_info()?;
+    if summary.word_count.unwrap_or_default() & SOURCE_TYPE_LUA_PACKAGE != 0 {
+        return Ok(false);
+    }
+
+    let properties = install_properties(options)?;
+    let property = |name: &str| -> Result<Option<String>> {
+        if let Some(value) = properties.get(name) {
+            return Ok(Some(value.to_owned()));
+        }
+
+        Ok(database
+            .query(&format!(
+                "SELECT `Value` FROM `Property` WHERE `Property` = '{}'",
+                name
+            ))?
+            .next()
+            .transpose()?
+            .and_then(|row| row.get(0).and_then(|v| v.as_str().map(str::to_owned))))
+    };
+
+    // ALLUSERS=2 installs per-machine unless MSIINSTALLPERUSER=1 asks for per-user.
+    Ok(match property("ALLUSERS")?.as_deref() {
+        Some("1") => true,
+        Some("2") => property("MSIINSTALLPERUSER")?.as_deref() != Some("1"),
+        _ => false,
+    })
+}
+
+/// Runs this program again elevated with `args`, copying its output to this process,
+/// and gets its exit code.
+///
+/// The user is prompted for consent. The elevated process runs without a visible console,
+/// so the name of a pipe is passed after `output_arg` for it to pass to [`attach_output`].
+pub fn run_elevated(args: &[String], output_arg: &str) -> Result<u32> {
+    let name = format!(r"\\.\pipe\msitrace.elevated.{}", std::process::id());
+    let pipe = process::NamedPipe::create_inbound(&name)?;
+
+    let mut parameters = vec![quote_argument(output_arg), quote_argument(&name)];
+    parameters.extend(args.iter().map(|arg| quote_argument(arg)));
+    let child = process::run_as(
+        &std::env::current_exe()?,
+        &parameters.join(" "),
+        &std::env::current_dir()?,
+    )?;
+
+    let reader = std::thread::spawn(move || -> std::io::Result<()> {
+        let mut pipe = pipe.connect()?;
+        let mut stdout = std::io::stdout();
+        std::io::copy(&mut pipe, &mut stdout)?;
+        stdout.flush()?;
+        Ok(())
+    });
+
+    let code = child.wait()?;
+
+    // Connect to the pipe ourselves in case the process exited before it connected,
+    // which would otherwise leave the reader waiting forever.
+    let _ = std::fs::OpenOptions::new().write(true).open(&name);
+    reader.join().expect("output reader panicked")?;
+
+    Ok(code)
+}
+
+/// Writes all output of this process to a pipe created by [`run_elevated`].
+pub fn attach_output(pipe: &str) -> Result<()> {
+    let file = std::fs::OpenOptions::new().write(true).open(pipe)?;
+    process::redirect_output(file)
+}
+
+/// Quotes an argument to be parsed like the C runtime parses command lines.
+///
+/// Arguments without spaces, tabs, or quotes are returned as-is.
+pub fn quote_argument(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_owned();
+    }
+
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Escape preceding backslashes and the quote.
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+
+    // Escape trailing backslashes so they do not escape the closing quote.
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,122 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#[cfg(feature = "json")]
+use crate::Error;
+use crate::{Database, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "json")]
+use std::fs::File;
+#[cfg(feature = "json")]
+use std::io::{BufReader, BufWriter};
+#[cfg(feature = "json")]
+use std::path::Path;
+use std::time::Duration;
+
+/// Time spent costing, validating, and registering regardless of payload size.
+const DEFAULT_OVERHEAD: Duration = Duration::from_secs(15);
+
+/// A conservative rate for copying files from compressed cabinets.
+const DEFAULT_SECS_PER_MB: f64 = 0.1;
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// An estimate of the size and duration of an install, read from the package alone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    /// The number of files in the `File` table.
+    pub files: usize,
+
+    /// The total size in bytes of all files.
+    pub size: u64,
+
+    /// The size in bytes of files in each feature, ordered by feature.
+    ///
+    /// Components shared by multiple features are counted in each.
+    pub features: Vec<(String, u64)>,
+
+    /// A rough estimate of how long installing all files will take.
+    pub duration: Duration,
+
+    /// Whether `duration` was calibrated from previous installs.
+    pub calibrated: bool,
+}
+
+/// How long a previous install took, written with `install --stats-out` to calibrate estimates.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct InstallStats {
+    /// The total size in bytes of files in the package.
+    pub size: u64,
+
+    /// How long the install took in seconds.
+    pub duration_secs: f64,
+}
+
+impl InstallStats {
+    /// Reads stats from a JSON file.
+    #[cfg(feature = "json")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|err| Error::verification(format!("invalid install stats: {}", err)))
+    }
+
+    /// Writes stats to a JSON file.
+    #[cfg(feature = "json")]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)
+            .map_err(|err| Error::from(std::io::Error::from(err)))
+    }
+}
+
+/// Estimates the size and duration of installing a package without opening an install session.
+///
+/// Durations are based on a fixed overhead plus a rate per MB, which `calibration` from
+/// previous installs on similar machines replaces with their average rate. Calibration
+/// without any size or duration is ignored.
+pub fn estimate(database: &Database, calibration: &[InstallStats]) -> Result<Estimate> {
+    let mut components: HashMap<String, u64> = HashMap::new();
+    let mut files = 0;
+    for row in database.query("SELECT `Component_`, `FileSize` FROM `File`")? {
+        let row = row?;
+        let size = row[1].as_integer().unwrap_or_default().max(0) as u64;
+        *components.entry(row[0].to_string()).or_default() += size;
+        files += 1;
+    }
+    let size = components.values().sum();
+
+    let mut features: HashMap<String, u64> = HashMap::new();
+    if database.tables()?.iter().any(|t| t == "FeatureComponents") {
+        for row in database.query("SELECT `Feature_`, `Component_` FROM `FeatureComponents`")? {
+            let row = row?;
+            let component = components.get(&row[1].to_string()).copied();
+            *features.entry(row[0].to_string()).or_default() += component.unwrap_or_default();
+        }
+    }
+    let mut features: Vec<(String, u64)> = features.into_iter().collect();
+    features.sort();
+
+    let calibrated_size: u64 = calibration.iter().map(|s| s.size).sum();
+    let calibrated_secs: f64 = calibration.iter().map(|s| s.duration_secs).sum();
+    // Stats may be edited by hand, and zero or negative durations cannot calibrate a rate.
+    let calibrated_duration = match calibrated_size > 0 && calibrated_secs > 0.0 {
+        true => {
+            Duration::try_from_secs_f64(size as f64 * calibrated_secs / calibrated_size as f64).ok()
+        }
+        false => None,
+    };
+    let calibrated = calibrated_duration.is_some();
+    let duration = calibrated_duration.unwrap_or_else(|| {
+        DEFAULT_OVERHEAD + Duration::from_secs_f64(size as f64 / MB * DEFAULT_SECS_PER_MB)
+    });
+
+    Ok(Estimate {
+        files,
+        size,
+        features,
+        duration,
+        calibrated,
+    })
+}
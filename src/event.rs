@@ -0,0 +1,285 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#[cfg(feature = "json")]
+use crate::{Error, Result};
+use crate::{Label, Language};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+#[cfg(feature = "json")]
+use std::fs::File;
+#[cfg(feature = "json")]
+use std::io::BufWriter;
+#[cfg(feature = "json")]
+use std::path::Path;
+use std::time::Duration;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// The version of the JSON event schema, incremented when fields are removed or change meaning.
+///
+/// New optional fields may be added without changing the version, so consumers should ignore
+/// fields they do not recognize.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A message traced during an install, written as one line of JSON by `install --events`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TraceEvent {
+    /// The version of the schema this event conforms to.
+    pub schema_version: u32,
+
+    /// When the message was received, in RFC 3339 format.
+    pub time: String,
+
+    /// How many milliseconds after tracing started the message was received, measured with a
+    /// monotonic clock so durations are not affected by changes to the system clock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+
+    /// The type of message, e.g. `ActionStart` or `Error`.
+    pub message_type: String,
+
+    /// The formatted message with the values of hidden properties redacted.
+    pub text: String,
+
+    /// The action running when the message was received, e.g. `InstallFiles`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+
+    /// The position of `action` among the actions the install ran, starting at 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_sequence: Option<u32>,
+}
+
+impl TraceEvent {
+    /// Creates a [`TraceEvent`] received now.
+    pub fn new(message_type: impl Into<String>, text: impl Into<String>) -> Self {
+        TraceEvent {
+            schema_version: SCHEMA_VERSION,
+            time: now(),
+            elapsed_ms: None,
+            message_type: message_type.into(),
+            text: text.into(),
+            action: None,
+            action_sequence: None,
+        }
+    }
+}
+
+/// An install event passed to hook commands as JSON.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HookEvent {
+    /// The version of the schema this event conforms to.
+    pub schema_version: u32,
+
+    /// The kind of event: `error`, `complete`, or `action`.
+    pub event: &'static str,
+
+    /// When the event occurred, in RFC 3339 format.
+    pub time: String,
+
+    /// The package being installed.
+    pub package: String,
+
+    /// The name of the action that started, for `action` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+
+    /// The formatted message, for `error` and `action` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// The result of the install, for `complete` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+}
+
+impl HookEvent {
+    #[cfg_attr(not(all(windows, feature = "hooks")), allow(dead_code))]
+    pub(crate) fn new(event: &'static str, package: &str) -> Self {
+        HookEvent {
+            schema_version: SCHEMA_VERSION,
+            event,
+            time: now(),
+            package: package.to_owned(),
+            action: None,
+            message: None,
+            code: None,
+        }
+    }
+}
+
+/// How many of the slowest actions an [`InstallSummary`] lists.
+const SLOWEST_ACTIONS: usize = 5;
+
+/// A summary of an install printed after it completes, or written as JSON by
+/// `install --summary-out`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InstallSummary {
+    /// The version of the schema this summary conforms to.
+    pub schema_version: u32,
+
+    /// The package or product that was installed.
+    pub package: String,
+
+    /// The result of the install, e.g. 0, 1603, or 3010.
+    pub code: u32,
+
+    /// The system description of `code`.
+    pub meaning: String,
+
+    /// How long the install took in seconds.
+    pub duration_secs: f64,
+
+    /// The number of error messages.
+    pub errors: usize,
+
+    /// The number of warning messages.
+    pub warnings: usize,
+
+    /// The slowest actions, slowest first.
+    pub slowest_actions: Vec<ActionDuration>,
+
+    /// The path of the verbose log, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+
+    /// Whether a reboot is required, or was started, to complete the install.
+    pub reboot_required: bool,
+}
+
+/// How long an action ran, in an [`InstallSummary`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ActionDuration {
+    /// The name of the action, e.g. `InstallFiles`.
+    pub action: String,
+
+    /// How long the action ran in seconds, until the next action started.
+    pub duration_secs: f64,
+}
+
+impl InstallSummary {
+    /// Creates a summary of an install that completed with `code` after `duration`.
+    pub fn new(package: impl Into<String>, code: u32, duration: Duration) -> Self {
+        InstallSummary {
+            schema_version: SCHEMA_VERSION,
+            package: package.into(),
+            code,
+            meaning: describe_error(code),
+            duration_secs: duration.as_secs_f64(),
+            errors: 0,
+            warnings: 0,
+            slowest_actions: Vec::new(),
+            log: None,
+            // 3010 requires a reboot and 1641 started one.
+            reboot_required: matches!(code, 3010 | 1641),
+        }
+    }
+
+    /// Keeps the slowest of the actions that ran.
+    pub fn set_actions(&mut self, actions: impl IntoIterator<Item = (String, Duration)>) {
+        let mut actions: Vec<(String, Duration)> = actions.into_iter().collect();
+        actions.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+        self.slowest_actions = actions
+            .into_iter()
+            .take(SLOWEST_ACTIONS)
+            .map(|(action, duration)| ActionDuration {
+                action,
+                duration_secs: duration.as_secs_f64(),
+            })
+            .collect();
+    }
+
+    /// Gets a [`Display`] of the summary with labels in `language`.
+    ///
+    /// Formatting the summary directly uses English labels.
+    pub fn localized(&self, language: Language) -> LocalizedSummary<'_> {
+        LocalizedSummary {
+            summary: self,
+            language,
+        }
+    }
+
+    /// Writes the summary to a JSON file.
+    #[cfg(feature = "json")]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)
+            .map_err(|err| Error::from(std::io::Error::from(err)))
+    }
+}
+
+impl Display for InstallSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.localized(Language::English).fmt(f)
+    }
+}
+
+/// Displays an [`InstallSummary`] with labels in a [`Language`].
+///
+/// Returned by [`InstallSummary::localized`].
+#[derive(Clone, Copy, Debug)]
+pub struct LocalizedSummary<'a> {
+    summary: &'a InstallSummary,
+    language: Language,
+}
+
+impl Display for LocalizedSummary<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let summary = self.summary;
+        let label = |label| self.language.label(label);
+
+        writeln!(
+            f,
+            "  {}: {} {}",
+            label(Label::Result),
+            summary.code,
+            summary.meaning
+        )?;
+        writeln!(
+            f,
+            "  {}: {:.1}s",
+            label(Label::Duration),
+            summary.duration_secs
+        )?;
+        writeln!(f, "  {}: {}", label(Label::Errors), summary.errors)?;
+        writeln!(f, "  {}: {}", label(Label::Warnings), summary.warnings)?;
+        if !summary.slowest_actions.is_empty() {
+            writeln!(f, "  {}:", label(Label::SlowestActions))?;
+            for action in &summary.slowest_actions {
+                writeln!(f, "    {} {:.1}s", action.action, action.duration_secs)?;
+            }
+        }
+        if let Some(log) = &summary.log {
+            writeln!(f, "  {}: {}", label(Label::Log), log)?;
+        }
+        writeln!(
+            f,
+            "  {}: {}",
+            label(Label::RebootRequired),
+            match summary.reboot_required {
+                true => label(Label::Yes),
+                false => label(Label::No),
+            }
+        )
+    }
+}
+
+/// Gets the system description of an error code, e.g. 1603.
+pub(crate) fn describe_error(code: u32) -> String {
+    let text = std::io::Error::from_raw_os_error(code as i32).to_string();
+    text.strip_suffix(&format!(" (os error {})", code))
+        .unwrap_or(&text)
+        .trim()
+        .to_owned()
+}
+
+/// Gets the current local time, or UTC if the local offset cannot be determined, in RFC 3339 format.
+pub(crate) fn now() -> String {
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    now.format(&Rfc3339).unwrap_or_default()
+}
@@ -0,0 +1,88 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::source::supply_source;
+use crate::{ffi, lock, preflight, trace, InstallOptions, Label, ReinstallMode, Result};
+use std::fmt::Display;
+
+/// The state to change a feature to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[repr(i32)]
+pub enum FeatureState {
+    /// Advertise the feature so it is installed on first use.
+    Advertised = 1,
+
+    /// Remove the feature.
+    Absent = 2,
+
+    /// Install the feature on the local computer.
+    Local = 3,
+
+    /// Run the feature from source.
+    Source = 4,
+
+    /// Install the feature to the default location authored for it.
+    Default = 5,
+}
+
+impl Display for FeatureState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FeatureState::Advertised => "advertised",
+            FeatureState::Absent => "absent",
+            FeatureState::Local => "local",
+            FeatureState::Source => "source",
+            FeatureState::Default => "default",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Changes the state of a feature of an installed product and traces the install.
+///
+/// Properties cannot be passed when configuring a feature, so [`InstallOptions::properties`]
+/// are only redacted.
+pub fn configure_feature(
+    product_code: &str,
+    feature: &str,
+    state: FeatureState,
+    options: &InstallOptions,
+) -> Result<()> {
+    let _lock = lock();
+    preflight(options)?;
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Product), product_code);
+    supply_source(product_code, options)?;
+    println!("{}: {}", language.label(Label::Feature), feature);
+    println!("{}: {}", language.label(Label::FeatureState), state);
+
+    trace(product_code, options, || {
+        ffi::configure_feature(product_code, feature, state as i32)
+    })
+}
+
+/// Reinstalls a feature of an installed product and traces the install.
+///
+/// Properties cannot be passed when reinstalling a feature, so [`InstallOptions::properties`]
+/// are only redacted.
+pub fn repair_feature(
+    product_code: &str,
+    feature: &str,
+    mode: ReinstallMode,
+    options: &InstallOptions,
+) -> Result<()> {
+    let _lock = lock();
+    preflight(options)?;
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Product), product_code);
+    supply_source(product_code, options)?;
+    println!("{}: {}", language.label(Label::Feature), feature);
+    println!("{}: {}", language.label(Label::ReinstallMode), mode);
+
+    trace(product_code, options, || {
+        ffi::reinstall_feature(product_code, feature, mode)
+    })
+}
@@ -2,15 +2,132 @@
 // Licensed under the MIT License. See LICENSE.txt in the project root for license information.
 
 use crate::{Error, Result};
-use std::ffi::{c_char, c_void, CString};
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, OsStr};
 use std::fmt::Display;
 use std::ops::{BitOr, Deref, Not};
+use std::os::windows::ffi::OsStrExt;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
 
+pub mod bcrypt;
+pub mod cabinet;
+pub mod console;
+pub mod disk;
+#[cfg(debug_assertions)]
+mod handles;
+pub mod locale;
+pub mod mutex;
+pub mod process;
+pub mod registry;
+pub mod restart_manager;
+pub mod security;
+pub mod service;
+pub mod shutdown;
+#[cfg(feature = "windows-sys")]
+mod sys;
+pub mod urlmon;
+pub mod wintrust;
+
+#[cfg(feature = "windows-sys")]
+use sys::*;
+#[cfg(feature = "windows-sys")]
+pub use sys::{ERROR_INVALID_HANDLE, ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS};
+
+#[cfg(not(feature = "windows-sys"))]
 pub const ERROR_SUCCESS: u32 = 0;
+#[cfg(not(feature = "windows-sys"))]
+pub const ERROR_INVALID_HANDLE: u32 = 6;
+#[cfg(not(feature = "windows-sys"))]
 pub const ERROR_MORE_DATA: u32 = 234;
+#[cfg(not(feature = "windows-sys"))]
+pub const ERROR_NO_MORE_ITEMS: u32 = 259;
 pub const MSI_NULL_INTEGER: i32 = -0x8000_0000;
-pub type LPSTR = *mut c_char;
+#[allow(clippy::upper_case_acronyms)]
 pub type LPCSTR = *const c_char;
+#[allow(clippy::upper_case_acronyms)]
+pub type LPWSTR = *mut u16;
+#[allow(clippy::upper_case_acronyms)]
+pub type LPCWSTR = *const u16;
+
+/// A nul-terminated UTF-16 string to pass to the Unicode (W) APIs.
+#[derive(Clone, Debug)]
+pub struct WideString(Vec<u16>);
+
+impl WideString {
+    /// Converts a string to UTF-16, failing if it contains a nul character.
+    pub fn new(value: impl AsRef<OsStr>) -> Result<Self> {
+        let mut value: Vec<u16> = value.as_ref().encode_wide().collect();
+        if value.contains(&0) {
+            return Err(Error::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "string contains a nul character",
+            )));
+        }
+
+        value.push(0);
+        Ok(WideString(value))
+    }
+
+    /// Converts a path to UTF-16, adding the extended-length prefix to absolute paths
+    /// too long for `MAX_PATH`.
+    pub fn from_path(path: &str) -> Result<Self> {
+        // Directories must leave room for an 8.3 file name within MAX_PATH.
+        const MAX_DIRECTORY_PATH: usize = 260 - 12;
+
+        if path.encode_utf16().count() < MAX_DIRECTORY_PATH || path.starts_with(r"\\?\") {
+            return Self::new(path);
+        }
+
+        // Extended-length paths are not normalized, so separators must be backslashes.
+        let path = path.replace('/', "\\");
+        match path.strip_prefix(r"\\") {
+            Some(unc) => Self::new(format!(r"\\?\UNC\{}", unc)),
+            None if path.get(1..3) == Some(":\\") => Self::new(format!(r"\\?\{}", path)),
+            None => Self::new(path),
+        }
+    }
+
+    pub fn as_ptr(&self) -> LPCWSTR {
+        self.0.as_ptr()
+    }
+}
+
+/// Converts a UTF-16 buffer returned from a Unicode (W) API, without its nul terminator.
+pub fn from_wide(value: &[u16]) -> Result<String> {
+    Ok(String::from_utf16(value)?)
+}
+
+/// Gets a string from a function following the Windows Installer buffer protocol.
+///
+/// The function is passed a buffer and its length in characters including the nul terminator.
+/// It returns `ERROR_SUCCESS` with the length excluding the nul terminator, which may be 0,
+/// or `ERROR_MORE_DATA` with the length required for a second call excluding the nul terminator.
+fn get_string<F>(f: F) -> Result<String>
+where
+    F: Fn(LPWSTR, *mut u32) -> u32,
+{
+    // Most strings fit without a second call.
+    const INITIAL_LEN: usize = 256;
+
+    let mut value: Vec<u16> = vec![0; INITIAL_LEN];
+    let mut value_len = value.len() as u32;
+
+    let mut ret = f(value.as_mut_ptr(), &mut value_len as *mut u32);
+    if ret == ERROR_MORE_DATA {
+        value_len += 1;
+        value.resize(value_len as usize, 0);
+
+        ret = f(value.as_mut_ptr(), &mut value_len as *mut u32);
+    }
+    if ret != ERROR_SUCCESS {
+        return Err(Error::from(ret));
+    }
+
+    value.truncate(value_len as usize);
+    from_wide(&value)
+}
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
 #[repr(transparent)]
@@ -18,6 +135,9 @@ pub struct MsiHandle(u32);
 
 impl MsiHandle {
     pub fn to_owned(self) -> OwnedMsiHandle {
+        #[cfg(debug_assertions)]
+        handles::track(self);
+
         OwnedMsiHandle(self)
     }
 }
@@ -49,6 +169,9 @@ impl Deref for OwnedMsiHandle {
 
 impl Drop for OwnedMsiHandle {
     fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        handles::untrack(**self);
+
         unsafe {
             MsiCloseHandle(**self);
         }
@@ -56,51 +179,52 @@ impl Drop for OwnedMsiHandle {
 }
 
 /// A collection of fields containing strings and integers.
+///
+/// Methods are safe to call on handles Windows Installer has since invalidated: strings return
+/// an error, integers return `None`, and the field count is 0.
+#[derive(Debug)]
+pub struct Record(RecordHandle);
+
 #[derive(Debug)]
-pub struct Record(OwnedMsiHandle);
+enum RecordHandle {
+    Owned(OwnedMsiHandle),
+    /// Owned by Windows Installer, such as records passed to an external UI handler.
+    Borrowed(MsiHandle),
+}
 
 impl Record {
+    /// Takes ownership of a record handle, or returns `None` if the handle is null.
+    pub fn from_handle(handle: MsiHandle) -> Option<Record> {
+        match *handle {
+            0 => None,
+            _ => Some(Record(RecordHandle::Owned(handle.to_owned()))),
+        }
+    }
+
+    /// Borrows a record handle Windows Installer will close, or returns `None` if the handle is null.
+    fn borrowed(handle: MsiHandle) -> Option<Record> {
+        match *handle {
+            0 => None,
+            _ => Some(Record(RecordHandle::Borrowed(handle))),
+        }
+    }
+
     /// Gets the count of fields in the record.
     pub fn field_count(&self) -> u32 {
-        unsafe { MsiRecordGetFieldCount(**self) }
+        // Invalid handles return -1.
+        match unsafe { MsiRecordGetFieldCount(**self) } {
+            u32::MAX => 0,
+            count => count,
+        }
     }
 
     /// Gets a string field from a [`Record`].
     ///
     /// Field indices are 1-based, though you can get a template string from field 0.
     pub fn string_data(&self, field: u32) -> Result<String> {
-        unsafe {
-            let mut value_len = 0u32;
-            let value = CString::default();
-
-            let mut ret = MsiRecordGetString(
-                **self,
-                field,
-                value.as_ptr() as LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ERROR_MORE_DATA {
-                return Err(Error::from(ret));
-            }
-
-            let mut value_len = value_len + 1u32;
-            let mut value: Vec<u8> = vec![0; value_len as usize];
-
-            ret = MsiRecordGetString(
-                **self,
-                field,
-                value.as_mut_ptr() as LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ERROR_SUCCESS {
-                return Err(Error::from(ret));
-            }
-
-            value.truncate(value_len as usize);
-            let text = String::from_utf8(value)?;
-
-            Ok(text)
-        }
+        get_string(|value, value_len| unsafe {
+            MsiRecordGetString(**self, field, value, value_len)
+        })
     }
 
     /// Gets an integer field from a [`Record`].
@@ -122,46 +246,39 @@ impl Record {
         unsafe { MsiRecordIsNull(**self, field).into() }
     }
 
-    fn format_text(&self) -> Result<String> {
-        unsafe {
-            let mut value_len = 0u32;
-            let value = CString::default();
-
-            let mut ret = MsiFormatRecord(
-                MsiHandle::default(),
-                **self,
-                value.as_ptr() as LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ERROR_MORE_DATA {
-                return Err(Error::from(ret));
-            }
+    /// Reads all the binary data from a stream field in a [`Record`].
+    ///
+    /// Field indices are 1-based.
+    pub fn stream_data(&self, field: u32) -> Result<Vec<u8>> {
+        let size = unsafe { MsiRecordDataSize(**self, field) };
+        let mut data: Vec<u8> = vec![0; size as usize];
 
-            let mut value_len = value_len + 1u32;
-            let mut value: Vec<u8> = vec![0; value_len as usize];
-
-            ret = MsiFormatRecord(
-                MsiHandle::default(),
-                **self,
-                value.as_mut_ptr() as LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ERROR_SUCCESS {
-                return Err(Error::from(ret));
+        let mut data_len = size;
+        unsafe {
+            match MsiRecordReadStream(**self, field, data.as_mut_ptr(), &mut data_len as *mut u32) {
+                ERROR_SUCCESS => {}
+                err => return Err(crate::Error::from(err)),
             }
+        }
 
-            value.truncate(value_len as usize);
-            let text = String::from_utf8(value)?;
+        data.truncate(data_len as usize);
+        Ok(data)
+    }
 
-            Ok(text)
-        }
+    fn format_text(&self) -> Result<String> {
+        get_string(|value, value_len| unsafe {
+            MsiFormatRecord(MsiHandle::default(), **self, value, value_len)
+        })
     }
 }
 
 impl Deref for Record {
     type Target = MsiHandle;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        match &self.0 {
+            RecordHandle::Owned(handle) => handle,
+            RecordHandle::Borrowed(handle) => handle,
+        }
     }
 }
 
@@ -209,45 +326,377 @@ impl PartialEq<bool> for Win32Bool {
     }
 }
 
+// Buttons in the low bits of error, warning, and user messages.
+const MB_TYPEMASK: u32 = 0x0000_000f;
+pub(crate) const MB_OK: u32 = 0x0000_0000;
+const MB_OKCANCEL: u32 = 0x0000_0001;
+const MB_ABORTRETRYIGNORE: u32 = 0x0000_0002;
+const MB_YESNOCANCEL: u32 = 0x0000_0003;
+const MB_YESNO: u32 = 0x0000_0004;
+const MB_RETRYCANCEL: u32 = 0x0000_0005;
+
+// Icons in the low bits of error, warning, and user messages.
+const MB_ICONMASK: u32 = 0x0000_00f0;
+pub(crate) const MB_ICONHAND: u32 = 0x0000_0010;
+const MB_ICONQUESTION: u32 = 0x0000_0020;
+const MB_ICONEXCLAMATION: u32 = 0x0000_0030;
+const MB_ICONASTERISK: u32 = 0x0000_0040;
+
+/// The buttons a message box would show for an error, warning, or user message, from the
+/// flags in the low bits of its type.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Buttons {
+    #[default]
+    OK,
+    OKCancel,
+    AbortRetryIgnore,
+    YesNoCancel,
+    YesNo,
+    RetryCancel,
+
+    /// Buttons msitrace does not recognize, which accept no response but the default.
+    Unknown(u32),
+}
+
+impl Buttons {
+    /// Gets the buttons from message box flags.
+    pub fn from_flags(flags: u32) -> Self {
+        match flags & MB_TYPEMASK {
+            MB_OK => Buttons::OK,
+            MB_OKCANCEL => Buttons::OKCancel,
+            MB_ABORTRETRYIGNORE => Buttons::AbortRetryIgnore,
+            MB_YESNOCANCEL => Buttons::YesNoCancel,
+            MB_YESNO => Buttons::YesNo,
+            MB_RETRYCANCEL => Buttons::RetryCancel,
+            buttons => Buttons::Unknown(buttons),
+        }
+    }
+
+    /// Gets the responses for each button, in the order they would be shown.
+    pub fn responses(self) -> &'static [HandlerResult] {
+        use HandlerResult::*;
+
+        match self {
+            Buttons::OK => &[OK],
+            Buttons::OKCancel => &[OK, Cancel],
+            Buttons::AbortRetryIgnore => &[Abort, Retry, Ignore],
+            Buttons::YesNoCancel => &[Yes, No, Cancel],
+            Buttons::YesNo => &[Yes, No],
+            Buttons::RetryCancel => &[Retry, Cancel],
+            Buttons::Unknown(_) => &[],
+        }
+    }
+}
+
+/// The icon a message box would show for an error, warning, or user message, from the flags
+/// in the low bits of its type.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Icon {
+    #[default]
+    None,
+    Error,
+    Question,
+    Warning,
+    Information,
+
+    /// An icon msitrace does not recognize.
+    Unknown(u32),
+}
+
+impl Icon {
+    /// Gets the icon from message box flags.
+    pub fn from_flags(flags: u32) -> Self {
+        match flags & MB_ICONMASK {
+            0 => Icon::None,
+            MB_ICONHAND => Icon::Error,
+            MB_ICONQUESTION => Icon::Question,
+            MB_ICONEXCLAMATION => Icon::Warning,
+            MB_ICONASTERISK => Icon::Information,
+            icon => Icon::Unknown(icon),
+        }
+    }
+}
+
+/// How a handler responds to a message, like the button a user would click.
+///
+/// [`HandlerResult::Default`] is always safe: Windows Installer handles the message as if there
+/// were no handler, which usually fails an install that needs an answer. Otherwise,
+/// [`HandlerResult::is_valid_for`] describes which responses each type of message accepts.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 #[repr(u32)]
 pub enum HandlerResult {
+    /// The message was not handled.
+    #[default]
     Default = 0u32,
-    OK,
-    Cancel,
+
+    /// `IDOK`, which also closes applications in response to [`MessageType::RMFilesInUse`].
+    OK = 1,
+
+    /// `IDCANCEL`, which cancels the install and rolls it back in response to any message
+    /// other than [`MessageType::ResolveSource`].
+    Cancel = 2,
+
+    /// `IDABORT`.
+    Abort = 3,
+
+    /// `IDRETRY`, which also checks files again in response to [`MessageType::FilesInUse`].
+    Retry = 4,
+
+    /// `IDIGNORE`, which also continues without closing applications in response to
+    /// [`MessageType::FilesInUse`].
+    Ignore = 5,
+
+    /// `IDYES`.
+    Yes = 6,
+
+    /// `IDNO`, which also continues without closing applications in response to
+    /// [`MessageType::RMFilesInUse`].
+    No = 7,
+
+    /// The handler failed, which Windows Installer treats like no handler.
+    Error = u32::MAX,
 }
 
-#[derive(Debug)]
-#[repr(u32)]
+impl HandlerResult {
+    /// Closes applications holding files in use with the Restart Manager.
+    pub const CLOSE_APPLICATIONS: Self = HandlerResult::OK;
+
+    /// Leaves applications the Restart Manager found holding files in use running,
+    /// which replaces the files when the machine reboots.
+    pub const KEEP_APPLICATIONS: Self = HandlerResult::No;
+
+    /// Gets whether Windows Installer accepts this response to a type of message with the
+    /// message box flags in its low bits.
+    ///
+    /// Error, warning, and user messages accept the buttons in their flags. Files-in-use
+    /// messages accept Retry, Ignore, or Cancel; Restart Manager files-in-use messages
+    /// accept OK, No, or Cancel; and ResolveSource messages accept only the default.
+    /// All other messages accept the default or Cancel.
+    pub fn is_valid_for(self, message: MessageType, flags: u32) -> bool {
+        use HandlerResult::*;
+
+        if matches!(self, Default | Error) {
+            return true;
+        }
+
+        let valid: &[HandlerResult] = match message {
+            MessageType::FatalExit
+            | MessageType::Error
+            | MessageType::Warning
+            | MessageType::User => Buttons::from_flags(flags).responses(),
+            MessageType::FilesInUse => &[Retry, Ignore, Cancel],
+            MessageType::RMFilesInUse => &[OK, No, Cancel],
+            MessageType::ResolveSource => &[],
+            _ => &[Cancel],
+        };
+        valid.contains(&self)
+    }
+}
+
+/// The type of a message Windows Installer sends while installing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MessageType {
-    FatalExit = 0x00000000,
-    Error = 0x01000000,
-    Warning = 0x02000000,
-    User = 0x03000000,
-    Info = 0x04000000,
-    ActionStart = 0x08000000,
-    ActionData = 0x09000000,
-    CommonData = 0x0B000000,
-    Initialize = 0x0C000000,
-    Terminate = 0x0D000000,
-    InstallStart = 0x1A000000,
-    InstallEnd = 0x1B000000,
+    FatalExit,
+    Error,
+    Warning,
+    User,
+    Info,
+    FilesInUse,
+    ResolveSource,
+    OutOfDiskSpace,
+    ActionStart,
+    ActionData,
+    Progress,
+    CommonData,
+    Initialize,
+    Terminate,
+    ShowDialog,
+    Performance,
+    RMFilesInUse,
+    InstallStart,
+    InstallEnd,
+
+    /// A message type msitrace does not recognize, with its raw value.
+    Unknown(u32),
+}
+
+impl MessageType {
+    /// All message types msitrace recognizes.
+    pub const ALL: [MessageType; 19] = {
+        use MessageType::*;
+        [
+            FatalExit,
+            Error,
+            Warning,
+            User,
+            Info,
+            FilesInUse,
+            ResolveSource,
+            OutOfDiskSpace,
+            ActionStart,
+            ActionData,
+            Progress,
+            CommonData,
+            Initialize,
+            Terminate,
+            ShowDialog,
+            Performance,
+            RMFilesInUse,
+            InstallStart,
+            InstallEnd,
+        ]
+    };
+
+    /// Gets the type of a message without the message box flags in its low bits.
+    pub fn from_message(message: u32) -> Self {
+        let value = message & 0xff00_0000;
+        Self::ALL
+            .into_iter()
+            .find(|message_type| message_type.value() == value)
+            .unwrap_or(MessageType::Unknown(value))
+    }
+
+    /// Gets the `INSTALLMESSAGE` value, e.g. 0x0A000000 for [`MessageType::Progress`].
+    pub fn value(self) -> u32 {
+        use MessageType::*;
+
+        match self {
+            FatalExit => 0x00000000,
+            Error => 0x01000000,
+            Warning => 0x02000000,
+            User => 0x03000000,
+            Info => 0x04000000,
+            FilesInUse => 0x05000000,
+            ResolveSource => 0x06000000,
+            OutOfDiskSpace => 0x07000000,
+            ActionStart => 0x08000000,
+            ActionData => 0x09000000,
+            Progress => 0x0A000000,
+            CommonData => 0x0B000000,
+            Initialize => 0x0C000000,
+            Terminate => 0x0D000000,
+            ShowDialog => 0x0E000000,
+            Performance => 0x0F000000,
+            RMFilesInUse => 0x19000000,
+            InstallStart => 0x1A000000,
+            InstallEnd => 0x1B000000,
+            Unknown(value) => value,
+        }
+    }
+
+    /// Gets the `INSTALLLOGMODE` bit that selects this type of message in a handler filter.
+    pub fn log_mode(self) -> u32 {
+        1u32.checked_shl(self.value() >> 24).unwrap_or_default()
+    }
 }
 
+/// Combines an `INSTALLMESSAGE` value with message box flags.
+///
+/// Deprecated: use [`MessageType::value`] instead. Trait implementations cannot be marked
+/// `#[deprecated]`.
 impl BitOr<MessageType> for u32 {
     type Output = u32;
     fn bitor(self, rhs: MessageType) -> Self::Output {
-        self | (rhs as u32)
+        self | rhs.value()
     }
 }
 
+/// Combines message types into a [`MessageFilter`].
+///
+/// Deprecated: combine [`MessageFilter`] values instead. Trait implementations cannot be
+/// marked `#[deprecated]`.
 impl BitOr for MessageType {
-    type Output = u32;
+    type Output = MessageFilter;
     fn bitor(self, rhs: Self) -> Self::Output {
-        (self as u32) | (rhs as u32)
+        MessageFilter::from(self) | MessageFilter::from(rhs)
+    }
+}
+
+/// Adds a message type to a [`MessageFilter`], so more than two message types can be combined.
+impl BitOr<MessageType> for MessageFilter {
+    type Output = MessageFilter;
+    fn bitor(self, rhs: MessageType) -> Self::Output {
+        self | MessageFilter::from(rhs)
+    }
+}
+
+bitflags::bitflags! {
+    /// Which types of messages an external UI handler receives, as `INSTALLLOGMODE` bits.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct MessageFilter: u32 {
+        const FATAL_EXIT = 1 << 0;
+        const ERROR = 1 << 1;
+        const WARNING = 1 << 2;
+        const USER = 1 << 3;
+        const INFO = 1 << 4;
+        const FILES_IN_USE = 1 << 5;
+        const RESOLVE_SOURCE = 1 << 6;
+        const OUT_OF_DISK_SPACE = 1 << 7;
+        const ACTION_START = 1 << 8;
+        const ACTION_DATA = 1 << 9;
+        const PROGRESS = 1 << 10;
+        const COMMON_DATA = 1 << 11;
+        const INITIALIZE = 1 << 12;
+        const TERMINATE = 1 << 13;
+        const SHOW_DIALOG = 1 << 14;
+        const PERFORMANCE = 1 << 15;
+        const RM_FILES_IN_USE = 1 << 25;
+        const INSTALL_START = 1 << 26;
+        const INSTALL_END = 1 << 27;
+
+        /// Extra debugging information. Windows Installer shares this bit with
+        /// [`MessageFilter::TERMINATE`] and only sends the extra information to logs.
+        const EXTRA_DEBUG = 1 << 13;
+    }
+}
+
+impl Default for MessageFilter {
+    /// Every type of message except performance messages, which are only sent when
+    /// logging with the `+` mode and are of no use to a trace.
+    fn default() -> Self {
+        MessageFilter::all() - MessageFilter::PERFORMANCE
+    }
+}
+
+impl From<MessageType> for MessageFilter {
+    fn from(message_type: MessageType) -> Self {
+        MessageFilter::from_bits_truncate(message_type.log_mode())
     }
 }
 
-#[derive(clap::ValueEnum, Clone, Debug)]
+impl std::str::FromStr for MessageFilter {
+    type Err = crate::Error;
+
+    /// Parses comma-separated message type names like "Error,Warning,ActionStart"
+    /// case-insensitively, or "all" or "default".
+    fn from_str(s: &str) -> Result<Self> {
+        let mut filter = MessageFilter::empty();
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let key = name.to_ascii_lowercase().replace(['-', '_'], "");
+            filter |= match key.as_str() {
+                "all" => MessageFilter::all(),
+                "default" => MessageFilter::default(),
+                "extradebug" => MessageFilter::EXTRA_DEBUG,
+                key => MessageType::ALL
+                    .into_iter()
+                    .find(|message_type| format!("{:?}", message_type).to_ascii_lowercase() == key)
+                    .map(MessageFilter::from)
+                    .ok_or_else(|| {
+                        crate::Error::verification(format!("invalid message type {:?}", name))
+                    })?,
+            };
+        }
+
+        match filter.is_empty() {
+            true => Err(crate::Error::verification("message filter cannot be empty")),
+            false => Ok(filter),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 #[repr(u32)]
 pub enum UILevel {
     Default = 1,
@@ -263,61 +712,257 @@ impl Default for UILevel {
     }
 }
 
-pub fn set_external_handler<F>(handler: F) -> Result<()>
+/// Which Windows Installer function registers the external user interface handler.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ExternalUI {
+    /// Receive the record of each message with `MsiSetExternalUIRecord`, from which its
+    /// fields are parsed.
+    #[default]
+    Record,
+
+    /// Receive the text of each message as Windows Installer formats it with `MsiSetExternalUI`.
+    ///
+    /// Messages have no fields, so e.g. progress and files in use cannot be parsed.
+    String,
+}
+
+/// The content of a message passed to a handler.
+#[derive(Clone, Copy)]
+pub enum MessageData<'a> {
+    /// The record of the message, which some messages do not have.
+    Record(Option<&'a Record>),
+
+    /// The text of the message as Windows Installer formatted it.
+    Text(&'a str),
+}
+
+/// Receives the type of a message, the message box flags in its low bits, and its content.
+type Handler<'a> = Box<dyn Fn(MessageType, u32, MessageData) -> HandlerResult + 'a>;
+
+/// The context passed to Windows Installer with the handler and any panic it raised.
+struct Context<'a> {
+    handler: Handler<'a>,
+    panic: RefCell<Option<Box<dyn Any + Send>>>,
+}
+
+/// The context and filter of the handler msitrace most recently set, so they can be restored
+/// along with the handler when a later handler is dropped.
+struct Registration {
+    context: AtomicPtr<c_void>,
+    filter: AtomicU32,
+}
+
+impl Registration {
+    const fn new() -> Self {
+        Registration {
+            context: AtomicPtr::new(std::ptr::null_mut()),
+            filter: AtomicU32::new(0),
+        }
+    }
+}
+
+static RECORD_REGISTRATION: Registration = Registration::new();
+static STRING_REGISTRATION: Registration = Registration::new();
+
+/// The handler registered before an [`ExternalHandler`].
+enum PreviousHandler {
+    Record(Option<UIRecordHandler>),
+    String(Option<UIHandler>),
+}
+
+/// Restores the previous external UI handler when dropped.
+///
+/// Handlers must be dropped in the reverse order they were set, which holding them in scopes
+/// ensures. See [`set_external_handler`] for why the guard must not be leaked.
+#[must_use]
+pub struct ExternalHandler<'a> {
+    // Boxed so the context passed to Windows Installer has a stable address.
+    context: Box<Context<'a>>,
+    previous: PreviousHandler,
+    previous_context: *mut c_void,
+    previous_filter: u32,
+    filter: u32,
+}
+
+impl ExternalHandler<'_> {
+    /// Resumes a panic raised by the handler, which was caught so it would not unwind into
+    /// Windows Installer.
+    pub fn resume_panic(&self) {
+        if let Some(panic) = self.context.panic.take() {
+            std::panic::resume_unwind(panic);
+        }
+    }
+
+    /// Gets the filter and context to restore with the `previous` handler.
+    fn previous_registration(&self, previous: Option<usize>, ours: usize) -> (u32, *const c_void) {
+        // Windows Installer does not return the previous context or filter, so only those of a
+        // previous msitrace handler can be restored; other handlers get no context.
+        match previous {
+            Some(previous) if previous == ours => {
+                (self.previous_filter, self.previous_context as *const c_void)
+            }
+            Some(_) => (self.filter, std::ptr::null()),
+            None => (0, std::ptr::null()),
+        }
+    }
+}
+
+impl Drop for ExternalHandler<'_> {
+    fn drop(&mut self) {
+        let registration = match self.previous {
+            PreviousHandler::Record(previous) => {
+                let (filter, context) = self.previous_registration(
+                    previous.map(|previous| previous as usize),
+                    proc as UIRecordHandler as usize,
+                );
+                unsafe {
+                    MsiSetExternalUIRecord(previous, filter, context, std::ptr::null_mut());
+                }
+                &RECORD_REGISTRATION
+            }
+            PreviousHandler::String(previous) => {
+                let (filter, context) = self.previous_registration(
+                    previous.map(|previous| previous as usize),
+                    string_proc as UIHandler as usize,
+                );
+                unsafe {
+                    MsiSetExternalUI(previous, filter, context);
+                }
+                &STRING_REGISTRATION
+            }
+        };
+        registration
+            .context
+            .store(self.previous_context, Ordering::SeqCst);
+        registration
+            .filter
+            .store(self.previous_filter, Ordering::SeqCst);
+    }
+}
+
+extern "system" fn proc(context: *mut c_void, message: u32, handle: MsiHandle) -> u32 {
+    let record = Record::borrowed(handle);
+    call(context, message, MessageData::Record(record.as_ref()))
+}
+
+extern "system" fn string_proc(context: *mut c_void, message: u32, text: LPCWSTR) -> u32 {
+    let text = match text.is_null() {
+        true => String::new(),
+        false => unsafe {
+            let len = (0..).take_while(|&i| *text.add(i) != 0).count();
+            String::from_utf16_lossy(std::slice::from_raw_parts(text, len))
+        },
+    };
+    call(context, message, MessageData::Text(&text))
+}
+
+/// Calls the handler in `context` for a message.
+fn call(context: *mut c_void, message: u32, data: MessageData) -> u32 {
+    if context.is_null() {
+        return HandlerResult::Default as u32;
+    }
+
+    let context = unsafe { &*(context as *const Context) };
+    // A handler that panicked may be in an inconsistent state, so it is not called again.
+    if context.panic.borrow().is_some() {
+        return HandlerResult::Default as u32;
+    }
+
+    let message_type = MessageType::from_message(message);
+    // Unwinding into Windows Installer is undefined, so the panic is resumed after it returns.
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        (context.handler)(message_type, message & 0x00ff_ffff, data)
+    }));
+    match result {
+        Ok(result) => result as u32,
+        Err(panic) => {
+            context.panic.replace(Some(panic));
+            HandlerResult::Default as u32
+        }
+    }
+}
+
+/// Sets a handler with `ui` to receive the types of messages in `filter` until the returned
+/// guard is dropped, when the previous handler is restored.
+///
+/// # Safety
+///
+/// The returned guard must be dropped before anything `handler` borrows, and must not be
+/// leaked, e.g. with [`std::mem::forget`]. Windows Installer keeps calling the handler until
+/// the guard restores the previous handler, so a leaked guard would leave it calling a
+/// handler whose borrows, and whose context, no longer exist.
+pub unsafe fn set_external_handler<'a, F>(
+    filter: MessageFilter,
+    ui: ExternalUI,
+    handler: F,
+) -> Result<ExternalHandler<'a>>
 where
-    F: Fn(MessageType, &Record) -> HandlerResult,
+    F: Fn(MessageType, u32, MessageData) -> HandlerResult + 'a,
 {
-    #[derive(Copy, Clone)]
-    struct Context<'a> {
-        handler: &'a dyn Fn(MessageType, &Record) -> HandlerResult,
-    }
-    let context = Context { handler: &handler };
-
-    extern "C" fn proc(context: *mut c_void, message: MessageType, handle: MsiHandle) -> u32 {
-        let context = unsafe { *(context as *const Context) };
-        let record = Record(handle.to_owned());
-        (context.handler)(message, &record) as u32
-    }
-
-    // All MessageTypes we want to support.
-    let filter: u32 = MessageType::FatalExit
-        | MessageType::Error
-        | MessageType::Warning
-        | MessageType::User
-        | MessageType::Info
-        | MessageType::ActionStart
-        | MessageType::ActionData
-        | MessageType::CommonData
-        | MessageType::Initialize
-        | MessageType::Terminate
-        | MessageType::InstallStart
-        | MessageType::InstallEnd;
-
-    unsafe {
-        let previous_handler: *mut c_void = std::ptr::null_mut();
-        let ret = MsiSetExternalUIRecord(
-            proc,
-            filter,
-            &context as *const Context as *const c_void,
-            previous_handler,
-        );
-        if ret != ERROR_SUCCESS {
-            return Err(Error::from(ret));
+    let context = Box::new(Context {
+        handler: Box::new(handler),
+        panic: RefCell::new(None),
+    });
+    let pointer = &*context as *const Context as *mut c_void;
+
+    let (previous, registration) = unsafe {
+        match ui {
+            ExternalUI::Record => {
+                let mut previous: Option<UIRecordHandler> = None;
+                let ret = MsiSetExternalUIRecord(
+                    Some(proc),
+                    filter.bits(),
+                    pointer,
+                    &mut previous as *mut Option<UIRecordHandler> as *mut c_void,
+                );
+                if ret != ERROR_SUCCESS {
+                    return Err(Error::from(ret));
+                }
+                (PreviousHandler::Record(previous), &RECORD_REGISTRATION)
+            }
+            ExternalUI::String => {
+                let previous = MsiSetExternalUI(Some(string_proc), filter.bits(), pointer);
+                (PreviousHandler::String(previous), &STRING_REGISTRATION)
+            }
+        }
+    };
+
+    Ok(ExternalHandler {
+        context,
+        previous,
+        previous_context: registration.context.swap(pointer, Ordering::SeqCst),
+        previous_filter: registration.filter.swap(filter.bits(), Ordering::SeqCst),
+        filter: filter.bits(),
+    })
+}
+
+/// Restores the previous internal UI level when dropped.
+#[must_use]
+pub struct InternalUI {
+    previous: u32,
+}
+
+impl Drop for InternalUI {
+    fn drop(&mut self) {
+        unsafe {
+            MsiSetInternalUI(self.previous, std::ptr::null_mut());
         }
-        Ok(())
     }
 }
 
-pub fn set_internal_ui(ui: UILevel) {
+pub fn set_internal_ui(ui: UILevel) -> InternalUI {
     let handle: *mut c_void = std::ptr::null_mut();
     unsafe {
-        MsiSetInternalUI(ui, handle);
+        InternalUI {
+            previous: MsiSetInternalUI(ui as u32, handle),
+        }
     }
 }
 
 pub fn enable_log(path: &str) -> Result<()> {
     const VERBOSE: u32 = 0x1000;
-    let path = CString::new(path)?;
+    let path = WideString::from_path(path)?;
 
     unsafe {
         match MsiEnableLog(VERBOSE, path.as_ptr(), 0) {
@@ -328,8 +973,8 @@ pub fn enable_log(path: &str) -> Result<()> {
 }
 
 pub fn install_package(path: &str, command_line: &str) -> Result<()> {
-    let path = CString::new(path)?;
-    let command_line = CString::new(command_line)?;
+    let path = WideString::from_path(path)?;
+    let command_line = WideString::new(command_line)?;
 
     unsafe {
         match MsiInstallProduct(path.as_ptr(), command_line.as_ptr()) {
@@ -339,42 +984,1396 @@ pub fn install_package(path: &str, command_line: &str) -> Result<()> {
     }
 }
 
-type UIRecordHandler = extern "C" fn(*mut c_void, MessageType, MsiHandle) -> u32;
+bitflags::bitflags! {
+    /// What to reinstall when repairing a product, like the letters of `REINSTALLMODE`.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct ReinstallMode: u32 {
+        /// `p`: reinstall only missing files.
+        const FILE_MISSING = 0x0000_0002;
 
-#[link(name = "msi")]
-extern "C" {
+        /// `o`: reinstall files that are missing or an older version.
+        const FILE_OLDER_VERSION = 0x0000_0004;
 
-    fn MsiRecordGetFieldCount(h: MsiHandle) -> u32;
+        /// `e`: reinstall files that are missing or an equal or older version.
+        const FILE_EQUAL_VERSION = 0x0000_0008;
 
-    #[link_name = "MsiRecordGetStringA"]
-    fn MsiRecordGetString(h: MsiHandle, index: u32, value: LPSTR, value_len: *mut u32) -> u32;
+        /// `d`: reinstall files that are missing or a different version.
+        const FILE_EXACT = 0x0000_0010;
 
-    fn MsiRecordGetInteger(h: MsiHandle, index: u32) -> i32;
+        /// `c`: reinstall files that are missing or whose checksum does not match.
+        const FILE_VERIFY = 0x0000_0020;
 
-    fn MsiRecordIsNull(h: MsiHandle, index: u32) -> Win32Bool;
+        /// `a`: reinstall all files regardless of version.
+        const FILE_REPLACE = 0x0000_0040;
 
-    fn MsiCloseHandle(h: MsiHandle) -> u32;
+        /// `m`: rewrite machine registry values.
+        const MACHINE_DATA = 0x0000_0080;
 
-    fn MsiSetExternalUIRecord(
-        handler: UIRecordHandler,
-        filter: u32,
-        context: *const c_void,
-        previous_handler: *mut c_void,
-    ) -> u32;
+        /// `u`: rewrite user registry values.
+        const USER_DATA = 0x0000_0100;
 
-    fn MsiSetInternalUI(level: UILevel, parent: *mut c_void) -> UILevel;
+        /// `s`: reinstall shortcuts and icons.
+        const SHORTCUT = 0x0000_0200;
 
-    #[link_name = "MsiEnableLogA"]
-    fn MsiEnableLog(mode: u32, path: LPCSTR, attributes: u32) -> u32;
+        /// `v`: re-cache the package from its source.
+        const PACKAGE = 0x0000_0400;
+    }
+}
 
-    #[link_name = "MsiInstallProductA"]
-    fn MsiInstallProduct(packagePath: LPCSTR, commandLine: LPCSTR) -> u32;
+impl ReinstallMode {
+    const LETTERS: [(char, ReinstallMode); 10] = [
+        ('p', ReinstallMode::FILE_MISSING),
+        ('o', ReinstallMode::FILE_OLDER_VERSION),
+        ('e', ReinstallMode::FILE_EQUAL_VERSION),
+        ('d', ReinstallMode::FILE_EXACT),
+        ('c', ReinstallMode::FILE_VERIFY),
+        ('a', ReinstallMode::FILE_REPLACE),
+        ('u', ReinstallMode::USER_DATA),
+        ('m', ReinstallMode::MACHINE_DATA),
+        ('s', ReinstallMode::SHORTCUT),
+        ('v', ReinstallMode::PACKAGE),
+    ];
+}
 
-    #[link_name = "MsiFormatRecordA"]
-    fn MsiFormatRecord(
-        install: MsiHandle,
-        record: MsiHandle,
-        value: LPSTR,
-        value_len: *mut u32,
-    ) -> u32;
+impl Default for ReinstallMode {
+    /// The default `REINSTALLMODE` of "omus".
+    fn default() -> Self {
+        ReinstallMode::FILE_OLDER_VERSION
+            | ReinstallMode::MACHINE_DATA
+            | ReinstallMode::USER_DATA
+            | ReinstallMode::SHORTCUT
+    }
+}
+
+impl std::str::FromStr for ReinstallMode {
+    type Err = crate::Error;
+
+    /// Parses letters like "omus" or "vomus" case-insensitively.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut mode = ReinstallMode::empty();
+        for c in s.chars() {
+            let c = c.to_ascii_lowercase();
+            match ReinstallMode::LETTERS
+                .iter()
+                .find(|(letter, _)| *letter == c)
+            {
+                Some((_, flag)) => mode |= *flag,
+                None => {
+                    return Err(crate::Error::verification(format!(
+                        "invalid reinstall mode {:?}: use letters from \"poedcaumsv\"",
+                        s
+                    )))
+                }
+            }
+        }
+
+        match mode.is_empty() {
+            true => Err(crate::Error::verification("reinstall mode cannot be empty")),
+            false => Ok(mode),
+        }
+    }
+}
+
+impl std::fmt::Display for ReinstallMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (letter, flag) in ReinstallMode::LETTERS {
+            if self.contains(flag) {
+                write!(f, "{}", letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reinstalls an installed product.
+pub fn reinstall_product(product_code: &str, mode: ReinstallMode) -> Result<()> {
+    let product_code = WideString::new(product_code)?;
+
+    unsafe {
+        match MsiReinstallProduct(product_code.as_ptr(), mode.bits()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Applies patches to a product, or to every product they target if `product_code` is `None`.
+pub fn apply_multiple_patches(
+    patches: &[String],
+    product_code: Option<&str>,
+    command_line: &str,
+) -> Result<()> {
+    let patches = WideString::new(patches.join(";"))?;
+    let product_code = product_code.map(WideString::new).transpose()?;
+    let command_line = WideString::new(command_line)?;
+
+    unsafe {
+        match MsiApplyMultiplePatches(
+            patches.as_ptr(),
+            product_code
+                .as_ref()
+                .map_or(std::ptr::null(), WideString::as_ptr),
+            command_line.as_ptr(),
+        ) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Patches an administrative image instead of an installed product.
+const INSTALLTYPE_NETWORK_IMAGE: i32 = 1;
+
+/// Applies a patch to the package in an administrative image, updating its files.
+pub fn apply_patch_to_image(patch: &str, image: &str, command_line: &str) -> Result<()> {
+    let patch = WideString::from_path(patch)?;
+    let image = WideString::from_path(image)?;
+    let command_line = WideString::new(command_line)?;
+
+    unsafe {
+        match MsiApplyPatch(
+            patch.as_ptr(),
+            image.as_ptr(),
+            INSTALLTYPE_NETWORK_IMAGE,
+            command_line.as_ptr(),
+        ) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Advertises a product to every user on the machine when passed as the script path.
+const ADVERTISEFLAGS_MACHINEASSIGN: usize = 0;
+
+/// Advertises a product to the current user when passed as the script path.
+const ADVERTISEFLAGS_USERASSIGN: usize = 1;
+
+/// Advertises a product to the machine or current user, or writes an advertise script if
+/// `script` is set.
+pub fn advertise_product(
+    path: &str,
+    machine: bool,
+    script: Option<&str>,
+    transforms: Option<&str>,
+    language: u16,
+) -> Result<()> {
+    let path = WideString::from_path(path)?;
+    let script = script.map(WideString::from_path).transpose()?;
+    let transforms = transforms.map(WideString::new).transpose()?;
+
+    // Assignment is passed as a reserved pointer value in place of a script path.
+    let script_ptr = match (&script, machine) {
+        (Some(script), _) => script.as_ptr(),
+        (None, true) => ADVERTISEFLAGS_MACHINEASSIGN as LPCWSTR,
+        (None, false) => ADVERTISEFLAGS_USERASSIGN as LPCWSTR,
+    };
+
+    unsafe {
+        match MsiAdvertiseProductEx(
+            path.as_ptr(),
+            script_ptr,
+            transforms
+                .as_ref()
+                .map_or(std::ptr::null(), WideString::as_ptr),
+            language,
+            0,
+            0,
+        ) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Applies an advertise script to the current user, as a logon script would.
+pub fn process_advertise_script(
+    script: &str,
+    icon_folder: Option<&str>,
+    shortcuts: bool,
+    remove: bool,
+) -> Result<()> {
+    let script = WideString::from_path(script)?;
+    let icon_folder = icon_folder.map(WideString::from_path).transpose()?;
+
+    unsafe {
+        match MsiProcessAdvertiseScript(
+            script.as_ptr(),
+            icon_folder
+                .as_ref()
+                .map_or(std::ptr::null(), WideString::as_ptr),
+            std::ptr::null_mut(),
+            shortcuts.into(),
+            remove.into(),
+        ) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Changes the install state of a feature of an installed product.
+pub fn configure_feature(product_code: &str, feature: &str, state: i32) -> Result<()> {
+    let product_code = WideString::new(product_code)?;
+    let feature = WideString::new(feature)?;
+
+    unsafe {
+        match MsiConfigureFeature(product_code.as_ptr(), feature.as_ptr(), state) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Reinstalls a feature of an installed product.
+pub fn reinstall_feature(product_code: &str, feature: &str, mode: ReinstallMode) -> Result<()> {
+    let product_code = WideString::new(product_code)?;
+    let feature = WideString::new(feature)?;
+
+    unsafe {
+        match MsiReinstallFeature(product_code.as_ptr(), feature.as_ptr(), mode.bits()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Reinstalls a missing or broken component of an installed product.
+pub fn install_missing_component(product_code: &str, component: &str, state: i32) -> Result<()> {
+    let product_code = WideString::new(product_code)?;
+    let component = WideString::new(component)?;
+
+    unsafe {
+        match MsiInstallMissingComponent(product_code.as_ptr(), component.as_ptr(), state) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Reinstalls a missing or broken file of an installed product.
+pub fn install_missing_file(product_code: &str, file: &str) -> Result<()> {
+    let product_code = WideString::new(product_code)?;
+    let file = WideString::new(file)?;
+
+    unsafe {
+        match MsiInstallMissingFile(product_code.as_ptr(), file.as_ptr()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+const MSITRANSACTIONSTATE_ROLLBACK: u32 = 0;
+const MSITRANSACTIONSTATE_COMMIT: u32 = 1;
+
+/// A multiple-package transaction that rolls back every package installed in it
+/// unless committed.
+///
+/// Packages installed by this process while the transaction is open are installed in it.
+/// Another process can install packages in it by joining it with its
+/// [`handle`](Transaction::handle).
+#[derive(Debug)]
+#[must_use]
+pub struct Transaction {
+    handle: MsiHandle,
+    change_of_owner: *mut c_void,
+    ended: bool,
+}
+
+impl Transaction {
+    /// Begins a transaction with a name shown to users in the installer UI.
+    pub fn begin(name: &str) -> Result<Self> {
+        let name = WideString::new(name)?;
+
+        let mut handle = MsiHandle::default();
+        let mut change_of_owner: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            match MsiBeginTransaction(
+                name.as_ptr(),
+                0,
+                &mut handle as *mut MsiHandle,
+                &mut change_of_owner as *mut *mut c_void,
+            ) {
+                ERROR_SUCCESS => Ok(Transaction {
+                    handle,
+                    change_of_owner,
+                    ended: false,
+                }),
+                err => Err(crate::Error::from(err)),
+            }
+        }
+    }
+
+    /// Gets the handle another process passes to join the transaction.
+    pub fn handle(&self) -> u32 {
+        *self.handle
+    }
+
+    /// Commits every package installed in the transaction.
+    pub fn commit(mut self) -> Result<()> {
+        self.ended = true;
+        unsafe {
+            match MsiEndTransaction(MSITRANSACTIONSTATE_COMMIT) {
+                ERROR_SUCCESS => Ok(()),
+                err => Err(crate::Error::from(err)),
+            }
+        }
+    }
+
+    /// Rolls back every package installed in the transaction.
+    pub fn rollback(mut self) -> Result<()> {
+        self.ended = true;
+        unsafe {
+            match MsiEndTransaction(MSITRANSACTIONSTATE_ROLLBACK) {
+                ERROR_SUCCESS => Ok(()),
+                err => Err(crate::Error::from(err)),
+            }
+        }
+    }
+}
+
+/// Makes this process the owner of a transaction begun by another process, so packages
+/// it installs are committed or rolled back with the transaction.
+pub fn join_transaction(handle: u32) -> Result<()> {
+    let mut change_of_owner: *mut c_void = std::ptr::null_mut();
+    unsafe {
+        match MsiJoinTransaction(
+            MsiHandle(handle),
+            0,
+            &mut change_of_owner as *mut *mut c_void,
+        ) {
+            ERROR_SUCCESS => {
+                if !change_of_owner.is_null() {
+                    CloseHandle(change_of_owner);
+                }
+                Ok(())
+            }
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ended {
+                MsiEndTransaction(MSITRANSACTIONSTATE_ROLLBACK);
+            }
+            MsiCloseHandle(self.handle);
+            if !self.change_of_owner.is_null() {
+                CloseHandle(self.change_of_owner);
+            }
+        }
+    }
+}
+
+pub fn verify_package(path: &str) -> Result<()> {
+    let path = WideString::from_path(path)?;
+
+    unsafe {
+        match MsiVerifyPackage(path.as_ptr()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Opens a database read-only.
+pub const MSIDBOPEN_READONLY: LPCWSTR = std::ptr::null();
+
+/// Creates a new database, overwriting any existing file when committed.
+#[cfg(feature = "test-support")]
+pub const MSIDBOPEN_CREATE: LPCWSTR = 3 as LPCWSTR;
+
+/// Opens a patch read-only; `MSIDBOPEN_PATCHFILE` added to [`MSIDBOPEN_READONLY`].
+pub const MSIDBOPEN_READONLY_PATCHFILE: LPCWSTR = 16 as LPCWSTR;
+
+pub fn open_database(path: &str, persist: LPCWSTR) -> Result<OwnedMsiHandle> {
+    let path = WideString::from_path(path)?;
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiOpenDatabase(path.as_ptr(), persist, &mut handle as *mut MsiHandle) {
+            ERROR_SUCCESS => Ok(handle.to_owned()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+pub fn open_view(database: MsiHandle, query: &str) -> Result<OwnedMsiHandle> {
+    let query = WideString::new(query)?;
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiDatabaseOpenView(database, query.as_ptr(), &mut handle as *mut MsiHandle) {
+            ERROR_SUCCESS => Ok(handle.to_owned()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+pub fn execute_view(view: MsiHandle) -> Result<()> {
+    unsafe {
+        match MsiViewExecute(view, MsiHandle::default()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Executes a view, replacing each `?` parameter in its query with the next of `params`.
+pub fn execute_view_with(view: MsiHandle, params: &[&str]) -> Result<()> {
+    let record = unsafe { MsiCreateRecord(params.len() as u32) };
+    let record = Record::from_handle(record).ok_or_else(|| Error::from(ERROR_INVALID_HANDLE))?;
+    for (i, param) in params.iter().enumerate() {
+        let param = WideString::new(param)?;
+        unsafe {
+            match MsiRecordSetString(*record, i as u32 + 1, param.as_ptr()) {
+                ERROR_SUCCESS => {}
+                err => return Err(crate::Error::from(err)),
+            }
+        }
+    }
+
+    unsafe {
+        match MsiViewExecute(view, *record) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Fetches the next [`Record`] from an executed view, or `None` when no more records remain.
+pub fn fetch_view(view: MsiHandle) -> Result<Option<Record>> {
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiViewFetch(view, &mut handle as *mut MsiHandle) {
+            ERROR_SUCCESS => Record::from_handle(handle)
+                .map(Some)
+                .ok_or_else(|| Error::from(ERROR_INVALID_HANDLE)),
+            ERROR_NO_MORE_ITEMS => Ok(None),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(u32)]
+pub enum ColumnInfo {
+    Names = 0,
+    Types = 1,
+}
+
+pub fn view_column_info(view: MsiHandle, info: ColumnInfo) -> Result<Record> {
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiViewGetColumnInfo(view, info, &mut handle as *mut MsiHandle) {
+            ERROR_SUCCESS => {
+                Record::from_handle(handle).ok_or_else(|| Error::from(ERROR_INVALID_HANDLE))
+            }
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+pub fn primary_keys(database: MsiHandle, table: &str) -> Result<Record> {
+    let table = WideString::new(table)?;
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiDatabaseGetPrimaryKeys(database, table.as_ptr(), &mut handle as *mut MsiHandle) {
+            ERROR_SUCCESS => {
+                Record::from_handle(handle).ok_or_else(|| Error::from(ERROR_INVALID_HANDLE))
+            }
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+pub fn export_table(database: MsiHandle, table: &str, folder: &str, file: &str) -> Result<()> {
+    let table = WideString::new(table)?;
+    let folder = WideString::from_path(folder)?;
+    let file = WideString::new(file)?;
+
+    unsafe {
+        match MsiDatabaseExport(database, table.as_ptr(), folder.as_ptr(), file.as_ptr()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+pub fn apply_transform(database: MsiHandle, path: &str, error_conditions: u32) -> Result<()> {
+    let path = WideString::from_path(path)?;
+
+    unsafe {
+        match MsiDatabaseApplyTransform(database, path.as_ptr(), error_conditions) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+pub fn summary_information(database: MsiHandle) -> Result<OwnedMsiHandle> {
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiGetSummaryInformation(database, std::ptr::null(), 0, &mut handle as *mut MsiHandle)
+        {
+            ERROR_SUCCESS => Ok(handle.to_owned()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// Opens the summary information of any installer file, including transforms
+/// that cannot be opened as a database.
+pub fn summary_information_from_path(path: &str) -> Result<OwnedMsiHandle> {
+    let path = WideString::from_path(path)?;
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiGetSummaryInformation(
+            MsiHandle::default(),
+            path.as_ptr(),
+            0,
+            &mut handle as *mut MsiHandle,
+        ) {
+            ERROR_SUCCESS => Ok(handle.to_owned()),
+            err => Err(crate::Error::from(err)),
+        }
+    }
+}
+
+/// A property value from the summary information stream.
+#[derive(Debug)]
+pub enum SummaryValue {
+    Integer(i32),
+    String(String),
+    /// Time in 100-nanosecond intervals since January 1, 1601 (UTC).
+    FileTime(u64),
+}
+
+const VT_EMPTY: u32 = 0;
+const VT_I2: u32 = 2;
+const VT_I4: u32 = 3;
+const VT_LPSTR: u32 = 30;
+const VT_FILETIME: u32 = 64;
+
+#[derive(Debug, Default)]
+#[repr(C)]
+struct FileTime {
+    low: u32,
+    high: u32,
+}
+
+/// Gets a summary information property, or `None` if the property is not set.
+pub fn summary_info_property(summary: MsiHandle, property: u32) -> Result<Option<SummaryValue>> {
+    unsafe {
+        let mut data_type = 0u32;
+        let mut integer = 0i32;
+        let mut time = FileTime::default();
+        let mut value_len = 0u32;
+
+        // A null buffer only gets the length of a string value.
+        let mut ret = MsiSummaryInfoGetProperty(
+            summary,
+            property,
+            &mut data_type as *mut u32,
+            &mut integer as *mut i32,
+            &mut time as *mut FileTime,
+            std::ptr::null_mut(),
+            &mut value_len as *mut u32,
+        );
+
+        match (ret, data_type) {
+            (ERROR_SUCCESS, VT_EMPTY) => return Ok(None),
+            (ERROR_SUCCESS, VT_I2 | VT_I4) => return Ok(Some(SummaryValue::Integer(integer))),
+            (ERROR_SUCCESS, VT_FILETIME) => {
+                let time = ((time.high as u64) << 32) | time.low as u64;
+                return Ok(Some(SummaryValue::FileTime(time)));
+            }
+            (ERROR_SUCCESS | ERROR_MORE_DATA, VT_LPSTR) => {}
+            (ERROR_SUCCESS, _) => return Ok(None),
+            (err, _) => return Err(Error::from(err)),
+        }
+
+        let mut value_len = value_len + 1u32;
+        let mut value: Vec<u16> = vec![0; value_len as usize];
+
+        ret = MsiSummaryInfoGetProperty(
+            summary,
+            property,
+            &mut data_type as *mut u32,
+            &mut integer as *mut i32,
+            &mut time as *mut FileTime,
+            value.as_mut_ptr(),
+            &mut value_len as *mut u32,
+        );
+        if ret != ERROR_SUCCESS {
+            return Err(Error::from(ret));
+        }
+
+        value.truncate(value_len as usize);
+        let text = from_wide(&value)?;
+
+        Ok(Some(SummaryValue::String(text)))
+    }
+}
+
+/// Gets a property of an installed product, e.g. `LocalPackage` or `PackageCode`.
+pub fn product_info(product_code: &str, property: &str) -> Result<String> {
+    let product_code = WideString::new(product_code)?;
+    let property = WideString::new(property)?;
+
+    get_string(|value, value_len| unsafe {
+        MsiGetProductInfo(product_code.as_ptr(), property.as_ptr(), value, value_len)
+    })
+}
+
+/// Installs a component if needed, like an advertised shortcut would, and gets its key path.
+pub fn provide_component(
+    product_code: &str,
+    feature: &str,
+    component_code: &str,
+    mode: u32,
+) -> Result<String> {
+    let product_code = WideString::new(product_code)?;
+    let feature = WideString::new(feature)?;
+    let component_code = WideString::new(component_code)?;
+
+    get_string(|value, value_len| unsafe {
+        MsiProvideComponent(
+            product_code.as_ptr(),
+            feature.as_ptr(),
+            component_code.as_ptr(),
+            mode,
+            value,
+            value_len,
+        )
+    })
+}
+
+/// Installs a qualified component if needed and gets its key path.
+pub fn provide_qualified_component(category: &str, qualifier: &str, mode: u32) -> Result<String> {
+    let category = WideString::new(category)?;
+    let qualifier = WideString::new(qualifier)?;
+
+    get_string(|value, value_len| unsafe {
+        MsiProvideQualifiedComponent(
+            category.as_ptr(),
+            qualifier.as_ptr(),
+            mode,
+            value,
+            value_len,
+        )
+    })
+}
+
+/// The component is installed locally.
+pub const INSTALLSTATE_LOCAL: i32 = 3;
+
+/// The component runs from source.
+pub const INSTALLSTATE_SOURCE: i32 = 4;
+
+/// The buffer was too small; returned by functions that return an install state.
+const INSTALLSTATE_MOREDATA: i32 = -3;
+
+/// Gets the install state and key path of a component installed by a product.
+///
+/// The path is empty for components that are not installed.
+pub fn component_path(product_code: &str, component_code: &str) -> Result<(i32, String)> {
+    let product_code = WideString::new(product_code)?;
+    let component_code = WideString::new(component_code)?;
+
+    let state = std::cell::Cell::new(0);
+    let path = get_string(|value, value_len| unsafe {
+        state.set(MsiGetComponentPath(
+            product_code.as_ptr(),
+            component_code.as_ptr(),
+            value,
+            value_len,
+        ));
+        match state.get() {
+            INSTALLSTATE_MOREDATA => ERROR_MORE_DATA,
+            _ => ERROR_SUCCESS,
+        }
+    })?;
+
+    Ok((state.get(), path))
+}
+
+const ERROR_UNKNOWN_PRODUCT: u32 = 1605;
+const ERROR_UNKNOWN_PATCH: u32 = 1647;
+
+/// Contexts a product can be installed in, from most to least common.
+const INSTALL_CONTEXTS: [u32; 3] = [
+    4, // MSIINSTALLCONTEXT_MACHINE
+    2, // MSIINSTALLCONTEXT_USERUNMANAGED
+    1, // MSIINSTALLCONTEXT_USERMANAGED
+];
+
+/// Gets a property of a patch registered for a product, e.g. `State`, or `None` if the patch
+/// is not registered for the product in any context for the current user.
+pub fn patch_info(patch_code: &str, product_code: &str, property: &str) -> Result<Option<String>> {
+    let patch_code = WideString::new(patch_code)?;
+    let product_code = WideString::new(product_code)?;
+    let property = WideString::new(property)?;
+
+    for context in INSTALL_CONTEXTS {
+        let result = get_string(|value, value_len| unsafe {
+            MsiGetPatchInfoEx(
+                patch_code.as_ptr(),
+                product_code.as_ptr(),
+                std::ptr::null(),
+                context,
+                property.as_ptr(),
+                value,
+                value_len,
+            )
+        });
+        match result {
+            Ok(value) => return Ok(Some(value)),
+            Err(err)
+                if matches!(
+                    err.code(),
+                    Some(ERROR_UNKNOWN_PATCH | ERROR_UNKNOWN_PRODUCT)
+                ) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(None)
+}
+
+/// The source is a network location or local directory.
+const MSISOURCETYPE_NETWORK: u32 = 0x0000_0001;
+
+/// The code identifies a product rather than a patch.
+const MSICODE_PRODUCT: u32 = 0;
+
+/// Adds a directory to the end of the network source list of an installed product.
+pub fn add_network_source(product_code: &str, source: &str) -> Result<()> {
+    let product_code = WideString::new(product_code)?;
+    let source = WideString::new(source)?;
+
+    let mut ret = ERROR_UNKNOWN_PRODUCT;
+    for context in INSTALL_CONTEXTS {
+        ret = unsafe {
+            MsiSourceListAddSourceEx(
+                product_code.as_ptr(),
+                std::ptr::null(),
+                context,
+                MSISOURCETYPE_NETWORK | MSICODE_PRODUCT,
+                source.as_ptr(),
+                0,
+            )
+        };
+        if ret != ERROR_UNKNOWN_PRODUCT {
+            break;
+        }
+    }
+
+    match ret {
+        ERROR_SUCCESS => Ok(()),
+        err => Err(Error::from(err)),
+    }
+}
+
+/// Forces Windows Installer to search the source list of an installed product for a valid
+/// source the next time it needs one, instead of using the last source it used.
+pub fn force_source_resolution(product_code: &str) -> Result<()> {
+    let product_code = WideString::new(product_code)?;
+
+    let mut ret = ERROR_UNKNOWN_PRODUCT;
+    for context in INSTALL_CONTEXTS {
+        ret = unsafe {
+            MsiSourceListForceResolutionEx(
+                product_code.as_ptr(),
+                std::ptr::null(),
+                context,
+                MSICODE_PRODUCT,
+            )
+        };
+        if ret != ERROR_UNKNOWN_PRODUCT {
+            break;
+        }
+    }
+
+    match ret {
+        ERROR_SUCCESS => Ok(()),
+        err => Err(Error::from(err)),
+    }
+}
+
+/// Gets the XML describing which products a patch applies to.
+pub fn extract_patch_xml(path: &str) -> Result<String> {
+    let path = WideString::from_path(path)?;
+
+    get_string(|value, value_len| unsafe {
+        MsiExtractPatchXMLData(path.as_ptr(), 0, value, value_len)
+    })
+}
+
+/// MSIPATCHSEQUENCEINFOW used to determine which patches apply and in what order.
+#[repr(C)]
+struct PatchSequenceInfo {
+    patch_data: LPCWSTR,
+    data_type: i32,
+    order: u32,
+    status: u32,
+}
+
+/// The patch data is the path to a patch file.
+const MSIPATCH_DATATYPE_PATCHFILE: i32 = 0;
+
+/// Gets the order in which each patch would be applied to a package or installed product,
+/// or `None` with the error code explaining why a patch does not apply.
+///
+/// Patches are applied to an installed product if `target` is a product code.
+pub fn patch_sequence(patches: &[String], target: &str) -> Result<Vec<(Option<u32>, u32)>> {
+    let paths = patches
+        .iter()
+        .map(|patch| WideString::from_path(patch))
+        .collect::<Result<Vec<_>>>()?;
+    let mut infos: Vec<PatchSequenceInfo> = paths
+        .iter()
+        .map(|path| PatchSequenceInfo {
+            patch_data: path.as_ptr(),
+            data_type: MSIPATCH_DATATYPE_PATCHFILE,
+            order: 0,
+            status: 0,
+        })
+        .collect();
+    let count = infos.len() as u32;
+
+    let ret = match target.starts_with('{') {
+        true => {
+            let product_code = WideString::new(target)?;
+            let mut ret = ERROR_UNKNOWN_PRODUCT;
+            for context in INSTALL_CONTEXTS {
+                ret = unsafe {
+                    MsiDeterminePatchSequence(
+                        product_code.as_ptr(),
+                        std::ptr::null(),
+                        context,
+                        count,
+                        infos.as_mut_ptr(),
+                    )
+                };
+                if ret != ERROR_UNKNOWN_PRODUCT {
+                    break;
+                }
+            }
+            ret
+        }
+        false => {
+            let package = WideString::from_path(target)?;
+            unsafe { MsiDetermineApplicablePatches(package.as_ptr(), count, infos.as_mut_ptr()) }
+        }
+    };
+
+    match ret {
+        ERROR_SUCCESS => Ok(infos
+            .iter()
+            .map(|info| match info.order {
+                u32::MAX => (None, info.status),
+                order => (Some(order), info.status),
+            })
+            .collect()),
+        err => Err(Error::from(err)),
+    }
+}
+
+/// MSIFILEHASHINFO used by MsiGetFileHash.
+#[repr(C)]
+struct FileHashInfo {
+    size: u32,
+    data: [u32; 4],
+}
+
+/// Gets the hash Windows Installer computes for unversioned files, as stored in `MsiFileHash`.
+pub fn file_hash(path: &str) -> Result<[i32; 4]> {
+    let path = WideString::from_path(path)?;
+    let mut hash = FileHashInfo {
+        size: std::mem::size_of::<FileHashInfo>() as u32,
+        data: [0; 4],
+    };
+
+    unsafe {
+        match MsiGetFileHash(path.as_ptr(), 0, &mut hash as *mut FileHashInfo) {
+            ERROR_SUCCESS => Ok(hash.data.map(|part| part as i32)),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Gets the product codes of products installed or advertised for the current user or machine.
+pub fn enum_products() -> Result<Vec<String>> {
+    let mut products = Vec::new();
+    for index in 0.. {
+        // Product codes are always 38 characters plus a nul terminator.
+        let mut value = [0u16; 39];
+        match unsafe { MsiEnumProducts(index, value.as_mut_ptr()) } {
+            ERROR_SUCCESS => products.push(from_wide(&value[..38])?),
+            ERROR_NO_MORE_ITEMS => break,
+            err => return Err(Error::from(err)),
+        }
+    }
+
+    Ok(products)
+}
+
+/// Gets the product codes of products installed or advertised with an upgrade code.
+pub fn related_products(upgrade_code: &str) -> Result<Vec<String>> {
+    let upgrade_code = WideString::new(upgrade_code)?;
+
+    let mut products = Vec::new();
+    for index in 0.. {
+        let mut value = [0u16; 39];
+        match unsafe { MsiEnumRelatedProducts(upgrade_code.as_ptr(), 0, index, value.as_mut_ptr()) }
+        {
+            ERROR_SUCCESS => products.push(from_wide(&value[..38])?),
+            ERROR_NO_MORE_ITEMS => break,
+            err => return Err(Error::from(err)),
+        }
+    }
+
+    Ok(products)
+}
+
+/// Opens a package in a session that evaluates conditions and runs actions without installing.
+pub fn open_package(path: &str) -> Result<OwnedMsiHandle> {
+    let path = WideString::from_path(path)?;
+    let mut handle = MsiHandle::default();
+
+    unsafe {
+        match MsiOpenPackageEx(path.as_ptr(), 0, &mut handle as *mut MsiHandle) {
+            ERROR_SUCCESS => Ok(handle.to_owned()),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Sets a property in a session opened with [`open_package`].
+pub fn set_property(install: MsiHandle, name: &str, value: &str) -> Result<()> {
+    let name = WideString::new(name)?;
+    let value = WideString::new(value)?;
+
+    unsafe {
+        match MsiSetProperty(install, name.as_ptr(), value.as_ptr()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Runs an action, such as `AppSearch`, in a session opened with [`open_package`].
+pub fn do_action(install: MsiHandle, action: &str) -> Result<()> {
+    let action = WideString::new(action)?;
+
+    unsafe {
+        match MsiDoAction(install, action.as_ptr()) {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+const MSICONDITION_FALSE: i32 = 0;
+const MSICONDITION_TRUE: i32 = 1;
+const MSICONDITION_NONE: i32 = 2;
+
+/// Evaluates a conditional expression in a session opened with [`open_package`].
+///
+/// Empty conditions are true.
+pub fn evaluate_condition(install: MsiHandle, condition: &str) -> Result<bool> {
+    let text = WideString::new(condition)?;
+
+    unsafe {
+        match MsiEvaluateCondition(install, text.as_ptr()) {
+            MSICONDITION_FALSE => Ok(false),
+            MSICONDITION_TRUE | MSICONDITION_NONE => Ok(true),
+            _ => Err(Error::verification(format!(
+                "invalid condition {:?}",
+                condition
+            ))),
+        }
+    }
+}
+
+/// DLLVERSIONINFO returned by DllGetVersion.
+#[derive(Default)]
+#[repr(C)]
+struct DllVersionInfo {
+    size: u32,
+    major: u32,
+    minor: u32,
+    build: u32,
+    platform_id: u32,
+}
+
+/// Gets the major, minor, and build version of msi.dll.
+pub fn installer_version() -> Result<(u32, u32, u32)> {
+    type DllGetVersion = extern "system" fn(*mut DllVersionInfo) -> i32;
+
+    let name = WideString::new("msi.dll")?;
+    unsafe {
+        let msi = LoadLibrary(name.as_ptr());
+        if msi.is_null() {
+            return Err(Error::from(std::io::Error::last_os_error()));
+        }
+
+        let proc = GetProcAddress(msi, c"DllGetVersion".as_ptr());
+        if proc.is_null() {
+            return Err(Error::from(std::io::Error::last_os_error()));
+        }
+
+        let dll_get_version: DllGetVersion = std::mem::transmute(proc);
+        let mut info = DllVersionInfo {
+            size: std::mem::size_of::<DllVersionInfo>() as u32,
+            ..Default::default()
+        };
+        match dll_get_version(&mut info as *mut DllVersionInfo) {
+            0 => Ok((info.major, info.minor, info.build)),
+            hr => Err(Error::from(hr as u32)),
+        }
+    }
+}
+
+/// Gets the version of Wine exported from ntdll.dll, if running under Wine.
+pub fn wine_version() -> Option<String> {
+    type WineGetVersion = extern "C" fn() -> LPCSTR;
+
+    unsafe {
+        let ntdll = GetModuleHandle(WideString::new("ntdll.dll").ok()?.as_ptr());
+        if ntdll.is_null() {
+            return None;
+        }
+
+        let proc = GetProcAddress(ntdll, c"wine_get_version".as_ptr());
+        if proc.is_null() {
+            return None;
+        }
+
+        let wine_get_version: WineGetVersion = std::mem::transmute(proc);
+        let version = wine_get_version();
+        if version.is_null() {
+            return None;
+        }
+
+        Some(CStr::from_ptr(version).to_string_lossy().into_owned())
+    }
+}
+
+type UIRecordHandler = extern "system" fn(*mut c_void, u32, MsiHandle) -> u32;
+type UIHandler = extern "system" fn(*mut c_void, u32, LPCWSTR) -> u32;
+
+#[cfg(not(feature = "windows-sys"))]
+#[link(name = "msi")]
+extern "C" {
+
+    fn MsiRecordGetFieldCount(h: MsiHandle) -> u32;
+
+    #[link_name = "MsiRecordGetStringW"]
+    fn MsiRecordGetString(h: MsiHandle, index: u32, value: LPWSTR, value_len: *mut u32) -> u32;
+
+    fn MsiRecordGetInteger(h: MsiHandle, index: u32) -> i32;
+
+    fn MsiCreateRecord(params: u32) -> MsiHandle;
+
+    #[link_name = "MsiRecordSetStringW"]
+    fn MsiRecordSetString(h: MsiHandle, index: u32, value: LPCWSTR) -> u32;
+
+    fn MsiRecordIsNull(h: MsiHandle, index: u32) -> Win32Bool;
+
+    fn MsiRecordDataSize(h: MsiHandle, index: u32) -> u32;
+
+    fn MsiRecordReadStream(h: MsiHandle, index: u32, data: *mut u8, data_len: *mut u32) -> u32;
+
+    fn MsiCloseHandle(h: MsiHandle) -> u32;
+
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    fn MsiCloseAllHandles() -> u32;
+
+    fn MsiSetExternalUIRecord(
+        handler: Option<UIRecordHandler>,
+        filter: u32,
+        context: *const c_void,
+        previous_handler: *mut c_void,
+    ) -> u32;
+
+    #[link_name = "MsiSetExternalUIW"]
+    fn MsiSetExternalUI(
+        handler: Option<UIHandler>,
+        filter: u32,
+        context: *const c_void,
+    ) -> Option<UIHandler>;
+
+    fn MsiSetInternalUI(level: u32, parent: *mut c_void) -> u32;
+
+    #[link_name = "MsiEnableLogW"]
+    fn MsiEnableLog(mode: u32, path: LPCWSTR, attributes: u32) -> u32;
+
+    #[link_name = "MsiInstallProductW"]
+    fn MsiInstallProduct(packagePath: LPCWSTR, commandLine: LPCWSTR) -> u32;
+
+    #[link_name = "MsiVerifyPackageW"]
+    fn MsiVerifyPackage(packagePath: LPCWSTR) -> u32;
+
+    #[link_name = "MsiApplyMultiplePatchesW"]
+    fn MsiApplyMultiplePatches(
+        patchPackages: LPCWSTR,
+        productCode: LPCWSTR,
+        propertiesList: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = "MsiGetPatchInfoExW"]
+    fn MsiGetPatchInfoEx(
+        patchCode: LPCWSTR,
+        productCode: LPCWSTR,
+        userSid: LPCWSTR,
+        context: u32,
+        property: LPCWSTR,
+        value: LPWSTR,
+        value_len: *mut u32,
+    ) -> u32;
+
+    #[link_name = "MsiApplyPatchW"]
+    fn MsiApplyPatch(
+        patchPackage: LPCWSTR,
+        installPackage: LPCWSTR,
+        installType: i32,
+        commandLine: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = "MsiProcessAdvertiseScriptW"]
+    fn MsiProcessAdvertiseScript(
+        scriptFile: LPCWSTR,
+        iconFolder: LPCWSTR,
+        regData: *mut c_void,
+        shortcuts: Win32Bool,
+        removeItems: Win32Bool,
+    ) -> u32;
+
+    #[link_name = "MsiAdvertiseProductExW"]
+    fn MsiAdvertiseProductEx(
+        packagePath: LPCWSTR,
+        scriptFilePath: LPCWSTR,
+        transforms: LPCWSTR,
+        language: u16,
+        platform: u32,
+        options: u32,
+    ) -> u32;
+
+    #[link_name = "MsiConfigureFeatureW"]
+    fn MsiConfigureFeature(product: LPCWSTR, feature: LPCWSTR, installState: i32) -> u32;
+
+    #[link_name = "MsiReinstallFeatureW"]
+    fn MsiReinstallFeature(product: LPCWSTR, feature: LPCWSTR, reinstallMode: u32) -> u32;
+
+    #[link_name = "MsiInstallMissingComponentW"]
+    fn MsiInstallMissingComponent(product: LPCWSTR, component: LPCWSTR, installState: i32) -> u32;
+
+    #[link_name = "MsiInstallMissingFileW"]
+    fn MsiInstallMissingFile(product: LPCWSTR, file: LPCWSTR) -> u32;
+
+    #[link_name = "MsiBeginTransactionW"]
+    fn MsiBeginTransaction(
+        name: LPCWSTR,
+        attributes: u32,
+        transaction: *mut MsiHandle,
+        changeOfOwnerEvent: *mut *mut c_void,
+    ) -> u32;
+
+    fn MsiJoinTransaction(
+        transaction: MsiHandle,
+        attributes: u32,
+        changeOfOwnerEvent: *mut *mut c_void,
+    ) -> u32;
+
+    fn MsiEndTransaction(state: u32) -> u32;
+
+    #[link_name = "MsiReinstallProductW"]
+    fn MsiReinstallProduct(product: LPCWSTR, reinstallMode: u32) -> u32;
+
+    #[link_name = "MsiOpenDatabaseW"]
+    fn MsiOpenDatabase(databasePath: LPCWSTR, persist: LPCWSTR, database: *mut MsiHandle) -> u32;
+
+    #[link_name = "MsiDatabaseOpenViewW"]
+    fn MsiDatabaseOpenView(database: MsiHandle, query: LPCWSTR, view: *mut MsiHandle) -> u32;
+
+    fn MsiViewExecute(view: MsiHandle, record: MsiHandle) -> u32;
+
+    fn MsiViewFetch(view: MsiHandle, record: *mut MsiHandle) -> u32;
+
+    fn MsiViewGetColumnInfo(view: MsiHandle, info: ColumnInfo, record: *mut MsiHandle) -> u32;
+
+    #[link_name = "MsiDatabaseGetPrimaryKeysW"]
+    fn MsiDatabaseGetPrimaryKeys(
+        database: MsiHandle,
+        tableName: LPCWSTR,
+        record: *mut MsiHandle,
+    ) -> u32;
+
+    #[link_name = "MsiDatabaseExportW"]
+    fn MsiDatabaseExport(
+        database: MsiHandle,
+        tableName: LPCWSTR,
+        folderPath: LPCWSTR,
+        fileName: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = "MsiDatabaseApplyTransformW"]
+    fn MsiDatabaseApplyTransform(
+        database: MsiHandle,
+        transformFile: LPCWSTR,
+        errorConditions: u32,
+    ) -> u32;
+
+    #[link_name = "MsiGetSummaryInformationW"]
+    fn MsiGetSummaryInformation(
+        database: MsiHandle,
+        databasePath: LPCWSTR,
+        updateCount: u32,
+        summaryInfo: *mut MsiHandle,
+    ) -> u32;
+
+    #[link_name = "MsiSummaryInfoGetPropertyW"]
+    fn MsiSummaryInfoGetProperty(
+        summaryInfo: MsiHandle,
+        property: u32,
+        dataType: *mut u32,
+        integer: *mut i32,
+        time: *mut FileTime,
+        value: LPWSTR,
+        value_len: *mut u32,
+    ) -> u32;
+
+    #[link_name = "MsiGetProductInfoW"]
+    fn MsiGetProductInfo(
+        product: LPCWSTR,
+        property: LPCWSTR,
+        value: LPWSTR,
+        value_len: *mut u32,
+    ) -> u32;
+
+    #[link_name = "MsiGetComponentPathW"]
+    fn MsiGetComponentPath(
+        product: LPCWSTR,
+        component: LPCWSTR,
+        path: LPWSTR,
+        path_len: *mut u32,
+    ) -> i32;
+
+    #[link_name = "MsiProvideComponentW"]
+    fn MsiProvideComponent(
+        product: LPCWSTR,
+        feature: LPCWSTR,
+        component: LPCWSTR,
+        installMode: u32,
+        path: LPWSTR,
+        path_len: *mut u32,
+    ) -> u32;
+
+    #[link_name = "MsiProvideQualifiedComponentW"]
+    fn MsiProvideQualifiedComponent(
+        category: LPCWSTR,
+        qualifier: LPCWSTR,
+        installMode: u32,
+        path: LPWSTR,
+        path_len: *mut u32,
+    ) -> u32;
+
+    #[link_name = "MsiSourceListAddSourceExW"]
+    fn MsiSourceListAddSourceEx(
+        productCode: LPCWSTR,
+        userSid: LPCWSTR,
+        context: u32,
+        options: u32,
+        source: LPCWSTR,
+        index: u32,
+    ) -> u32;
+
+    #[link_name = "MsiSourceListForceResolutionExW"]
+    fn MsiSourceListForceResolutionEx(
+        productCode: LPCWSTR,
+        userSid: LPCWSTR,
+        context: u32,
+        options: u32,
+    ) -> u32;
+
+    #[link_name = "MsiExtractPatchXMLDataW"]
+    fn MsiExtractPatchXMLData(
+        patchPath: LPCWSTR,
+        reserved: u32,
+        xmlData: LPWSTR,
+        xmlData_len: *mut u32,
+    ) -> u32;
+
+    #[link_name = "MsiDetermineApplicablePatchesW"]
+    fn MsiDetermineApplicablePatches(
+        productPackagePath: LPCWSTR,
+        patchCount: u32,
+        patchInfo: *mut PatchSequenceInfo,
+    ) -> u32;
+
+    #[link_name = "MsiDeterminePatchSequenceW"]
+    fn MsiDeterminePatchSequence(
+        productCode: LPCWSTR,
+        userSid: LPCWSTR,
+        context: u32,
+        patchCount: u32,
+        patchInfo: *mut PatchSequenceInfo,
+    ) -> u32;
+
+    #[link_name = "MsiGetFileHashW"]
+    fn MsiGetFileHash(filePath: LPCWSTR, options: u32, hash: *mut FileHashInfo) -> u32;
+
+    #[link_name = "MsiEnumProductsW"]
+    fn MsiEnumProducts(index: u32, productCode: LPWSTR) -> u32;
+
+    #[link_name = "MsiEnumRelatedProductsW"]
+    fn MsiEnumRelatedProducts(
+        upgradeCode: LPCWSTR,
+        reserved: u32,
+        index: u32,
+        productCode: LPWSTR,
+    ) -> u32;
+
+    #[link_name = "MsiOpenPackageExW"]
+    fn MsiOpenPackageEx(packagePath: LPCWSTR, options: u32, product: *mut MsiHandle) -> u32;
+
+    #[link_name = "MsiSetPropertyW"]
+    fn MsiSetProperty(install: MsiHandle, name: LPCWSTR, value: LPCWSTR) -> u32;
+
+    #[link_name = "MsiDoActionW"]
+    fn MsiDoAction(install: MsiHandle, action: LPCWSTR) -> u32;
+
+    #[link_name = "MsiEvaluateConditionW"]
+    fn MsiEvaluateCondition(install: MsiHandle, condition: LPCWSTR) -> i32;
+
+    #[link_name = "MsiFormatRecordW"]
+    fn MsiFormatRecord(
+        install: MsiHandle,
+        record: MsiHandle,
+        value: LPWSTR,
+        value_len: *mut u32,
+    ) -> u32;
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    #[link_name = "GetModuleHandleW"]
+    fn GetModuleHandle(moduleName: LPCWSTR) -> *mut c_void;
+
+    #[link_name = "LoadLibraryW"]
+    fn LoadLibrary(fileName: LPCWSTR) -> *mut c_void;
+
+    fn GetProcAddress(module: *mut c_void, procName: LPCSTR) -> *mut c_void;
+
+    fn CloseHandle(handle: *mut c_void) -> Win32Bool;
 }
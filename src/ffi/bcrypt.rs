@@ -0,0 +1,117 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Error, Result};
+use std::ffi::c_void;
+use std::io::Read;
+
+type Handle = *mut c_void;
+
+const BCRYPT_USE_SYSTEM_PREFERRED_RNG: u32 = 0x2;
+
+/// Computes the SHA-256 digest of all data read from `reader`.
+pub fn sha256(mut reader: impl Read) -> Result<[u8; 32]> {
+    let algorithm_id: Vec<u16> = "SHA256".encode_utf16().chain(Some(0)).collect();
+    let mut algorithm: Handle = std::ptr::null_mut();
+    let mut hash: Handle = std::ptr::null_mut();
+
+    unsafe {
+        check(BCryptOpenAlgorithmProvider(
+            &mut algorithm as *mut Handle,
+            algorithm_id.as_ptr(),
+            std::ptr::null(),
+            0,
+        ))?;
+
+        let result = (|| {
+            check(BCryptCreateHash(
+                algorithm,
+                &mut hash as *mut Handle,
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                0,
+                0,
+            ))?;
+
+            let mut buffer = vec![0u8; 64 * 1024];
+            loop {
+                let len = reader.read(&mut buffer)?;
+                if len == 0 {
+                    break;
+                }
+                check(BCryptHashData(hash, buffer.as_mut_ptr(), len as u32, 0))?;
+            }
+
+            let mut digest = [0u8; 32];
+            check(BCryptFinishHash(
+                hash,
+                digest.as_mut_ptr(),
+                digest.len() as u32,
+                0,
+            ))?;
+
+            Ok(digest)
+        })();
+
+        if !hash.is_null() {
+            BCryptDestroyHash(hash);
+        }
+        BCryptCloseAlgorithmProvider(algorithm, 0);
+
+        result
+    }
+}
+
+/// Fills `buffer` with cryptographically secure random bytes from the system RNG.
+pub fn random_bytes(buffer: &mut [u8]) -> Result<()> {
+    for chunk in buffer.chunks_mut(u32::MAX as usize) {
+        unsafe {
+            check(BCryptGenRandom(
+                std::ptr::null_mut(),
+                chunk.as_mut_ptr(),
+                chunk.len() as u32,
+                BCRYPT_USE_SYSTEM_PREFERRED_RNG,
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn check(status: i32) -> Result<()> {
+    match status {
+        0 => Ok(()),
+        status => Err(Error::from(status as u32)),
+    }
+}
+
+#[link(name = "bcrypt")]
+extern "C" {
+    fn BCryptOpenAlgorithmProvider(
+        algorithm: *mut Handle,
+        algId: *const u16,
+        implementation: *const u16,
+        flags: u32,
+    ) -> i32;
+
+    fn BCryptCreateHash(
+        algorithm: Handle,
+        hash: *mut Handle,
+        hashObject: *mut u8,
+        hashObject_len: u32,
+        secret: *mut u8,
+        secret_len: u32,
+        flags: u32,
+    ) -> i32;
+
+    fn BCryptHashData(hash: Handle, input: *mut u8, input_len: u32, flags: u32) -> i32;
+
+    fn BCryptFinishHash(hash: Handle, output: *mut u8, output_len: u32, flags: u32) -> i32;
+
+    fn BCryptDestroyHash(hash: Handle) -> i32;
+
+    fn BCryptCloseAlgorithmProvider(algorithm: Handle, flags: u32) -> i32;
+
+    fn BCryptGenRandom(algorithm: Handle, buffer: *mut u8, buffer_len: u32, flags: u32) -> i32;
+}
@@ -0,0 +1,156 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, Win32Bool, ERROR_SUCCESS, LPCWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+const MAX_PATH: usize = 260;
+const SPFILENOTIFY_FILEEXTRACTED: u32 = 0x0000_0013;
+const SPFILENOTIFY_FILEINCABINET: u32 = 0x0000_0019;
+const SPFILENOTIFY_NEEDNEWCABINET: u32 = 0x0000_001a;
+const FILEOP_ABORT: u32 = 0;
+const FILEOP_DOIT: u32 = 1;
+const FILEOP_SKIP: u32 = 2;
+const ERROR_FILENAME_EXCED_RANGE: u32 = 206;
+const ERROR_NOT_SUPPORTED: u32 = 50;
+
+#[repr(C)]
+struct FileInCabinetInfo {
+    name_in_cabinet: LPCWSTR,
+    file_size: u32,
+    win32_error: u32,
+    dos_date: u16,
+    dos_time: u16,
+    dos_attributes: u16,
+    full_target_name: [u16; MAX_PATH],
+}
+
+#[repr(C)]
+struct FilePaths {
+    target: LPCWSTR,
+    source: LPCWSTR,
+    win32_error: u32,
+    flags: u32,
+}
+
+type FileCallback = extern "system" fn(*mut c_void, u32, usize, usize) -> u32;
+
+/// Extracts all files from a single cabinet into a directory, keeping the names in the cabinet.
+pub fn extract(cabinet: &str, directory: &str) -> Result<()> {
+    extern "system" fn callback(
+        context: *mut c_void,
+        notification: u32,
+        param1: usize,
+        _: usize,
+    ) -> u32 {
+        unsafe {
+            let directory = &*(context as *const Vec<u16>);
+            match notification {
+                SPFILENOTIFY_FILEINCABINET => {
+                    let info = &mut *(param1 as *mut FileInCabinetInfo);
+                    let name = std::slice::from_raw_parts(
+                        info.name_in_cabinet,
+                        (0..)
+                            .take_while(|&i| *info.name_in_cabinet.add(i) != 0)
+                            .count(),
+                    );
+
+                    // Leave room for the separator and nul terminator.
+                    if directory.len() + name.len() + 2 > MAX_PATH {
+                        info.win32_error = ERROR_FILENAME_EXCED_RANGE;
+                        return FILEOP_ABORT;
+                    }
+
+                    let target = directory
+                        .iter()
+                        .chain(&[b'\\' as u16])
+                        .chain(name)
+                        .chain(&[0]);
+                    for (i, c) in target.enumerate() {
+                        info.full_target_name[i] = *c;
+                    }
+
+                    FILEOP_DOIT
+                }
+                SPFILENOTIFY_FILEEXTRACTED => (*(param1 as *const FilePaths)).win32_error,
+                // Bundle containers are never split across cabinets.
+                SPFILENOTIFY_NEEDNEWCABINET => ERROR_NOT_SUPPORTED,
+                _ => ERROR_SUCCESS,
+            }
+        }
+    }
+
+    let cabinet = WideString::from_path(cabinet)?;
+    let directory: Vec<u16> = directory.encode_utf16().collect();
+
+    unsafe {
+        let ret = SetupIterateCabinet(
+            cabinet.as_ptr(),
+            0,
+            callback,
+            &directory as *const Vec<u16> as *mut c_void,
+        );
+        match ret == true {
+            true => Ok(()),
+            false => Err(Error::from(std::io::Error::last_os_error())),
+        }
+    }
+}
+
+/// Gets the names of all files in a single cabinet without extracting them.
+pub fn list(cabinet: &str) -> Result<Vec<String>> {
+    extern "system" fn callback(
+        context: *mut c_void,
+        notification: u32,
+        param1: usize,
+        _: usize,
+    ) -> u32 {
+        unsafe {
+            let names = &mut *(context as *mut Vec<String>);
+            match notification {
+                SPFILENOTIFY_FILEINCABINET => {
+                    let info = &*(param1 as *const FileInCabinetInfo);
+                    let name = std::slice::from_raw_parts(
+                        info.name_in_cabinet,
+                        (0..)
+                            .take_while(|&i| *info.name_in_cabinet.add(i) != 0)
+                            .count(),
+                    );
+                    names.push(String::from_utf16_lossy(name));
+
+                    FILEOP_SKIP
+                }
+                SPFILENOTIFY_NEEDNEWCABINET => ERROR_NOT_SUPPORTED,
+                _ => ERROR_SUCCESS,
+            }
+        }
+    }
+
+    let cabinet = WideString::from_path(cabinet)?;
+    let mut names: Vec<String> = Vec::new();
+
+    unsafe {
+        let ret = SetupIterateCabinet(
+            cabinet.as_ptr(),
+            0,
+            callback,
+            &mut names as *mut Vec<String> as *mut c_void,
+        );
+        match ret == true {
+            true => Ok(names),
+            false => Err(Error::from(std::io::Error::last_os_error())),
+        }
+    }
+}
+
+#[link(name = "setupapi")]
+extern "C" {
+    #[link_name = "SetupIterateCabinetW"]
+    fn SetupIterateCabinet(
+        cabinetFile: LPCWSTR,
+        reserved: u32,
+        msgHandler: FileCallback,
+        context: *mut c_void,
+    ) -> Win32Bool;
+}
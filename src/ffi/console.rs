@@ -0,0 +1,26 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::Win32Bool;
+use crate::{Error, Result};
+
+pub const CTRL_C_EVENT: u32 = 0;
+pub const CTRL_BREAK_EVENT: u32 = 1;
+
+/// Handles console control events, returning true if the event was handled.
+pub type CtrlHandler = extern "system" fn(u32) -> Win32Bool;
+
+/// Adds a handler called before the default handler, which terminates the process.
+pub fn add_ctrl_handler(handler: CtrlHandler) -> Result<()> {
+    unsafe {
+        match SetConsoleCtrlHandler(Some(handler), true.into()) == true {
+            true => Ok(()),
+            false => Err(Error::from(std::io::Error::last_os_error())),
+        }
+    }
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    fn SetConsoleCtrlHandler(handler: Option<CtrlHandler>, add: Win32Bool) -> Win32Bool;
+}
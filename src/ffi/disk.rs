@@ -0,0 +1,35 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, Win32Bool, LPCWSTR};
+use crate::{Error, Result};
+
+/// Gets the bytes available to the current user on the volume containing `path`.
+pub fn free_space(path: &str) -> Result<u64> {
+    let path = WideString::from_path(path)?;
+
+    let mut available = 0u64;
+    unsafe {
+        let ret = GetDiskFreeSpaceEx(
+            path.as_ptr(),
+            &mut available as *mut u64,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        match ret == true {
+            true => Ok(available),
+            false => Err(Error::from(std::io::Error::last_os_error())),
+        }
+    }
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    #[link_name = "GetDiskFreeSpaceExW"]
+    fn GetDiskFreeSpaceEx(
+        directoryName: LPCWSTR,
+        freeBytesAvailable: *mut u64,
+        totalNumberOfBytes: *mut u64,
+        totalNumberOfFreeBytes: *mut u64,
+    ) -> Win32Bool;
+}
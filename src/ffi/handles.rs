@@ -0,0 +1,52 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Tracks open handles in debug builds to report any left open at process exit.
+
+use super::{MsiCloseAllHandles, MsiHandle};
+use std::backtrace::Backtrace;
+use std::sync::{Mutex, Once, PoisonError};
+
+static HANDLES: Mutex<Vec<(MsiHandle, Backtrace)>> = Mutex::new(Vec::new());
+
+/// Records where a handle was opened.
+///
+/// Backtraces are only captured when `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` is set.
+pub fn track(handle: MsiHandle) {
+    static REPORT: Once = Once::new();
+    REPORT.call_once(|| unsafe {
+        atexit(report);
+    });
+
+    HANDLES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((handle, Backtrace::capture()));
+}
+
+/// Forgets a handle when it is closed.
+pub fn untrack(handle: MsiHandle) {
+    HANDLES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|(h, _)| *h != handle);
+}
+
+extern "C" fn report() {
+    let handles = HANDLES.lock().unwrap_or_else(PoisonError::into_inner);
+    if handles.is_empty() {
+        return;
+    }
+
+    eprintln!("warning: {} handle(s) were not closed", handles.len());
+    for (handle, backtrace) in handles.iter() {
+        eprintln!("{} opened at:\n{}", handle, backtrace);
+    }
+
+    let closed = unsafe { MsiCloseAllHandles() };
+    eprintln!("warning: closed {} handle(s) still open", closed);
+}
+
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> i32;
+}
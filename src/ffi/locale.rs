@@ -0,0 +1,24 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{from_wide, LPWSTR};
+
+const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+/// Gets the locale name of the current user, e.g. "de-DE".
+pub fn user_locale() -> Option<String> {
+    let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+    unsafe {
+        // Returned length includes the null terminator.
+        let len = GetUserDefaultLocaleName(name.as_mut_ptr(), name.len() as i32);
+        match len {
+            0 => None,
+            len => from_wide(&name[..len as usize - 1]).ok(),
+        }
+    }
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    fn GetUserDefaultLocaleName(localeName: LPWSTR, localeName_len: i32) -> i32;
+}
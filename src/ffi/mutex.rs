@@ -0,0 +1,114 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, Win32Bool, LPCWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+use std::time::Duration;
+
+const SYNCHRONIZE: u32 = 0x0010_0000;
+const ERROR_FILE_NOT_FOUND: i32 = 2;
+const WAIT_OBJECT_0: u32 = 0;
+const WAIT_ABANDONED: u32 = 0x80;
+const WAIT_TIMEOUT: u32 = 0x102;
+const INFINITE: u32 = u32::MAX;
+
+/// A named mutex shared across processes.
+pub struct NamedMutex(*mut c_void);
+
+impl NamedMutex {
+    /// Creates a named mutex, or opens it if it already exists.
+    pub fn create(name: &str) -> Result<Self> {
+        let name = WideString::new(name)?;
+
+        unsafe {
+            let handle = CreateMutex(std::ptr::null_mut(), false.into(), name.as_ptr());
+            match handle.is_null() {
+                true => Err(Error::from(std::io::Error::last_os_error())),
+                false => Ok(NamedMutex(handle)),
+            }
+        }
+    }
+
+    /// Opens an existing named mutex only to wait on it, or `None` if it does not exist.
+    pub fn open(name: &str) -> Result<Option<Self>> {
+        let name = WideString::new(name)?;
+
+        unsafe {
+            let handle = OpenMutex(SYNCHRONIZE, false.into(), name.as_ptr());
+            if handle.is_null() {
+                let err = std::io::Error::last_os_error();
+                return match err.raw_os_error() {
+                    Some(ERROR_FILE_NOT_FOUND) => Ok(None),
+                    _ => Err(Error::from(err)),
+                };
+            }
+
+            Ok(Some(NamedMutex(handle)))
+        }
+    }
+
+    /// Takes ownership of the mutex if no other thread owns it.
+    pub fn try_lock(&self) -> Result<Option<NamedMutexGuard<'_>>> {
+        self.wait(0)
+    }
+
+    /// Waits to take ownership of the mutex.
+    ///
+    /// A mutex abandoned by a thread that exited while owning it is also taken.
+    pub fn lock(&self) -> Result<NamedMutexGuard<'_>> {
+        self.wait(INFINITE)
+            .map(|guard| guard.expect("infinite wait timed out"))
+    }
+
+    /// Waits up to `timeout` to take ownership of the mutex, or returns `None` if it timed out.
+    pub fn try_lock_for(&self, timeout: Duration) -> Result<Option<NamedMutexGuard<'_>>> {
+        // Waits shorter than INFINITE must be less than u32::MAX milliseconds.
+        let timeout = timeout.as_millis().min((INFINITE - 1) as u128) as u32;
+        self.wait(timeout)
+    }
+
+    fn wait(&self, timeout: u32) -> Result<Option<NamedMutexGuard<'_>>> {
+        unsafe {
+            match WaitForSingleObject(self.0, timeout) {
+                WAIT_OBJECT_0 | WAIT_ABANDONED => Ok(Some(NamedMutexGuard(self))),
+                WAIT_TIMEOUT => Ok(None),
+                _ => Err(Error::from(std::io::Error::last_os_error())),
+            }
+        }
+    }
+}
+
+impl Drop for NamedMutex {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.0);
+        }
+    }
+}
+
+/// Releases ownership of a [`NamedMutex`] when dropped.
+pub struct NamedMutexGuard<'a>(&'a NamedMutex);
+
+impl Drop for NamedMutexGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            ReleaseMutex(self.0 .0);
+        }
+    }
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    #[link_name = "CreateMutexW"]
+    fn CreateMutex(attributes: *mut c_void, initialOwner: Win32Bool, name: LPCWSTR) -> *mut c_void;
+
+    #[link_name = "OpenMutexW"]
+    fn OpenMutex(access: u32, inheritHandle: Win32Bool, name: LPCWSTR) -> *mut c_void;
+
+    fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+
+    fn ReleaseMutex(mutex: *mut c_void) -> Win32Bool;
+
+    fn CloseHandle(handle: *mut c_void) -> Win32Bool;
+}
@@ -0,0 +1,249 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, Win32Bool, LPCWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+use std::fs::File;
+use std::os::windows::io::{FromRawHandle, IntoRawHandle};
+use std::path::Path;
+
+const SEE_MASK_NOCLOSEPROCESS: u32 = 0x0000_0040;
+const SEE_MASK_NOASYNC: u32 = 0x0000_0100;
+const SW_HIDE: i32 = 0;
+const INFINITE: u32 = u32::MAX;
+const WAIT_OBJECT_0: u32 = 0;
+
+const PIPE_ACCESS_INBOUND: u32 = 0x0000_0001;
+const FILE_FLAG_FIRST_PIPE_INSTANCE: u32 = 0x0008_0000;
+const PIPE_TYPE_BYTE: u32 = 0;
+const PIPE_REJECT_REMOTE_CLIENTS: u32 = 0x0000_0008;
+const PIPE_BUFFER_SIZE: u32 = 64 * 1024;
+const INVALID_HANDLE_VALUE: isize = -1;
+const ERROR_PIPE_CONNECTED: i32 = 535;
+
+const SDDL_REVISION_1: u32 = 1;
+
+/// Grants access to the pipe only to its owner, administrators, and LocalSystem, and
+/// blocks inheriting other access.
+const PIPE_SECURITY: &str = "D:P(A;;GA;;;OW)(A;;GA;;;BA)(A;;GA;;;SY)";
+
+const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+
+/// A process started by this process.
+pub struct Process(*mut c_void);
+
+impl Process {
+    /// Waits for the process to exit and gets its exit code.
+    pub fn wait(&self) -> Result<u32> {
+        unsafe {
+            if WaitForSingleObject(self.0, INFINITE) != WAIT_OBJECT_0 {
+                return Err(Error::from(std::io::Error::last_os_error()));
+            }
+
+            let mut code = 0u32;
+            match GetExitCodeProcess(self.0, &mut code as *mut u32) == true {
+                true => Ok(code),
+                false => Err(Error::from(std::io::Error::last_os_error())),
+            }
+        }
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.0);
+        }
+    }
+}
+
+/// Starts a program elevated, which prompts the user for consent, with its window hidden.
+pub fn run_as(file: &Path, parameters: &str, directory: &Path) -> Result<Process> {
+    let verb = WideString::new("runas")?;
+    let file = WideString::new(file)?;
+    let parameters = WideString::new(parameters)?;
+    let directory = WideString::new(directory)?;
+
+    let mut info = ShellExecuteInfo {
+        size: std::mem::size_of::<ShellExecuteInfo>() as u32,
+        mask: SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC,
+        window: std::ptr::null_mut(),
+        verb: verb.as_ptr(),
+        file: file.as_ptr(),
+        parameters: parameters.as_ptr(),
+        directory: directory.as_ptr(),
+        show: SW_HIDE,
+        instance: std::ptr::null_mut(),
+        id_list: std::ptr::null_mut(),
+        class: std::ptr::null(),
+        class_key: std::ptr::null_mut(),
+        hot_key: 0,
+        icon_or_monitor: std::ptr::null_mut(),
+        process: std::ptr::null_mut(),
+    };
+
+    unsafe {
+        match ShellExecuteExW(&mut info as *mut ShellExecuteInfo) == true {
+            true if !info.process.is_null() => Ok(Process(info.process)),
+            true => Err(Error::verification("elevated process was not started")),
+            false => Err(Error::from(std::io::Error::last_os_error())),
+        }
+    }
+}
+
+/// The server end of a named pipe that only reads.
+pub struct NamedPipe(*mut c_void);
+
+// The handle is owned and may be used from any thread.
+unsafe impl Send for NamedPipe {}
+
+impl NamedPipe {
+    /// Creates a named pipe for a single local client to write to, e.g. `\\.\pipe\name`.
+    ///
+    /// Fails if a pipe with the same name already exists, so another process cannot create
+    /// it first to read what is written to it.
+    pub fn create_inbound(name: &str) -> Result<Self> {
+        let name = WideString::new(name)?;
+        let sddl = WideString::new(PIPE_SECURITY)?;
+
+        unsafe {
+            let mut descriptor: *mut c_void = std::ptr::null_mut();
+            if ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                sddl.as_ptr(),
+                SDDL_REVISION_1,
+                &mut descriptor as *mut *mut c_void,
+                std::ptr::null_mut(),
+            ) != true
+            {
+                return Err(Error::from(std::io::Error::last_os_error()));
+            }
+
+            let mut attributes = SecurityAttributes {
+                length: std::mem::size_of::<SecurityAttributes>() as u32,
+                security_descriptor: descriptor,
+                inherit_handle: Win32Bool::from(false),
+            };
+            let handle = CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_INBOUND | FILE_FLAG_FIRST_PIPE_INSTANCE,
+                PIPE_TYPE_BYTE | PIPE_REJECT_REMOTE_CLIENTS,
+                1,
+                0,
+                PIPE_BUFFER_SIZE,
+                0,
+                &mut attributes as *mut SecurityAttributes as *mut c_void,
+            );
+            let result = match handle as isize {
+                INVALID_HANDLE_VALUE => Err(Error::from(std::io::Error::last_os_error())),
+                _ => Ok(NamedPipe(handle)),
+            };
+            LocalFree(descriptor);
+
+            result
+        }
+    }
+
+    /// Waits for a client to connect and gets the pipe as a file to read.
+    pub fn connect(self) -> std::io::Result<File> {
+        unsafe {
+            if ConnectNamedPipe(self.0, std::ptr::null_mut()) != true {
+                let err = std::io::Error::last_os_error();
+                if err.raw_os_error() != Some(ERROR_PIPE_CONNECTED) {
+                    return Err(err);
+                }
+            }
+
+            let file = File::from_raw_handle(self.0);
+            std::mem::forget(self);
+            Ok(file)
+        }
+    }
+}
+
+impl Drop for NamedPipe {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.0);
+        }
+    }
+}
+
+/// Replaces standard output and standard error of this process with a file.
+///
+/// The file is left open for the life of the process.
+pub fn redirect_output(file: File) -> Result<()> {
+    let handle = file.into_raw_handle();
+
+    unsafe {
+        for std_handle in [STD_OUTPUT_HANDLE, STD_ERROR_HANDLE] {
+            if SetStdHandle(std_handle, handle) != true {
+                return Err(Error::from(std::io::Error::last_os_error()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[repr(C)]
+struct ShellExecuteInfo {
+    size: u32,
+    mask: u32,
+    window: *mut c_void,
+    verb: LPCWSTR,
+    file: LPCWSTR,
+    parameters: LPCWSTR,
+    directory: LPCWSTR,
+    show: i32,
+    instance: *mut c_void,
+    id_list: *mut c_void,
+    class: LPCWSTR,
+    class_key: *mut c_void,
+    hot_key: u32,
+    icon_or_monitor: *mut c_void,
+    process: *mut c_void,
+}
+
+#[repr(C)]
+struct SecurityAttributes {
+    length: u32,
+    security_descriptor: *mut c_void,
+    inherit_handle: Win32Bool,
+}
+
+#[link(name = "shell32")]
+extern "C" {
+    fn ShellExecuteExW(info: *mut ShellExecuteInfo) -> Win32Bool;
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    fn CreateNamedPipeW(
+        name: LPCWSTR,
+        open_mode: u32,
+        pipe_mode: u32,
+        max_instances: u32,
+        out_buffer_size: u32,
+        in_buffer_size: u32,
+        default_timeout: u32,
+        security_attributes: *mut c_void,
+    ) -> *mut c_void;
+    fn ConnectNamedPipe(pipe: *mut c_void, overlapped: *mut c_void) -> Win32Bool;
+    fn SetStdHandle(std_handle: u32, handle: *mut c_void) -> Win32Bool;
+    fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+    fn GetExitCodeProcess(process: *mut c_void, exit_code: *mut u32) -> Win32Bool;
+    fn CloseHandle(handle: *mut c_void) -> Win32Bool;
+    fn LocalFree(mem: *mut c_void) -> *mut c_void;
+}
+
+#[link(name = "advapi32")]
+extern "C" {
+    fn ConvertStringSecurityDescriptorToSecurityDescriptorW(
+        security_descriptor: LPCWSTR,
+        revision: u32,
+        descriptor: *mut *mut c_void,
+        size: *mut u32,
+    ) -> Win32Bool;
+}
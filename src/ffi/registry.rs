@@ -0,0 +1,301 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{from_wide, WideString, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, LPCWSTR, LPWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+const ERROR_FILE_NOT_FOUND: u32 = 2;
+const RRF_RT_REG_SZ: u32 = 0x0000_0002;
+const RRF_RT_REG_DWORD: u32 = 0x0000_0010;
+const RRF_RT_ANY: u32 = 0x0000_ffff;
+const RRF_SUBKEY_WOW6464KEY: u32 = 0x0001_0000;
+const REG_SZ: u32 = 1;
+const REG_DWORD: u32 = 4;
+const KEY_QUERY_VALUE: u32 = 0x0001;
+const KEY_ENUMERATE_SUB_KEYS: u32 = 0x0008;
+const KEY_WOW64_64KEY: u32 = 0x0100;
+
+/// Predefined registry root keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(usize)]
+pub enum Hive {
+    CurrentUser = 0x8000_0001,
+    LocalMachine = 0x8000_0002,
+}
+
+/// Gets a `REG_DWORD` value, or `None` if the key or value does not exist.
+pub fn dword(hive: Hive, key: &str, name: &str) -> Result<Option<u32>> {
+    let key = WideString::new(key)?;
+    let name = WideString::new(name)?;
+
+    let mut value = 0u32;
+    let mut value_len = std::mem::size_of::<u32>() as u32;
+    unsafe {
+        let ret = RegGetValue(
+            hive as usize as *mut c_void,
+            key.as_ptr(),
+            name.as_ptr(),
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            &mut value as *mut u32 as *mut c_void,
+            &mut value_len as *mut u32,
+        );
+
+        match ret {
+            ERROR_SUCCESS => Ok(Some(value)),
+            ERROR_FILE_NOT_FOUND => Ok(None),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Gets a `REG_SZ` value, or `None` if the key or value does not exist.
+pub fn string(hive: Hive, key: &str, name: &str) -> Result<Option<String>> {
+    let key = WideString::new(key)?;
+    let name = WideString::new(name)?;
+
+    let get = |value: *mut c_void, value_len: &mut u32| unsafe {
+        RegGetValue(
+            hive as usize as *mut c_void,
+            key.as_ptr(),
+            name.as_ptr(),
+            RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            value,
+            value_len as *mut u32,
+        )
+    };
+
+    // Sizes are in bytes and include the nul terminator.
+    let mut value_len = 0u32;
+    match get(std::ptr::null_mut(), &mut value_len) {
+        ERROR_SUCCESS => {}
+        ERROR_FILE_NOT_FOUND => return Ok(None),
+        err => return Err(Error::from(err)),
+    }
+
+    let mut value: Vec<u16> = vec![0; (value_len as usize).div_ceil(2)];
+    match get(value.as_mut_ptr() as *mut c_void, &mut value_len) {
+        ERROR_SUCCESS => {}
+        ERROR_FILE_NOT_FOUND => return Ok(None),
+        err => return Err(Error::from(err)),
+    }
+
+    value.truncate((value_len as usize / 2).saturating_sub(1));
+    Ok(Some(from_wide(&value)?))
+}
+
+/// Sets a `REG_DWORD` value, creating the key if it does not exist.
+pub fn set_dword(hive: Hive, key: &str, name: &str, value: u32) -> Result<()> {
+    let key = WideString::new(key)?;
+    let name = WideString::new(name)?;
+
+    unsafe {
+        let ret = RegSetKeyValue(
+            hive as usize as *mut c_void,
+            key.as_ptr(),
+            name.as_ptr(),
+            REG_DWORD,
+            &value as *const u32 as *const c_void,
+            std::mem::size_of::<u32>() as u32,
+        );
+
+        match ret {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Sets a `REG_SZ` value, creating the key if it does not exist.
+pub fn set_string(hive: Hive, key: &str, name: &str, value: &str) -> Result<()> {
+    let key = WideString::new(key)?;
+    let name = WideString::new(name)?;
+
+    // Sizes are in bytes and include the nul terminator.
+    let value_len = (value.encode_utf16().count() + 1) * std::mem::size_of::<u16>();
+    let value = WideString::new(value)?;
+    unsafe {
+        let ret = RegSetKeyValue(
+            hive as usize as *mut c_void,
+            key.as_ptr(),
+            name.as_ptr(),
+            REG_SZ,
+            value.as_ptr() as *const c_void,
+            value_len as u32,
+        );
+
+        match ret {
+            ERROR_SUCCESS => Ok(()),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Deletes a value, succeeding if the key or value does not exist.
+pub fn delete_value(hive: Hive, key: &str, name: &str) -> Result<()> {
+    let key = WideString::new(key)?;
+    let name = WideString::new(name)?;
+
+    unsafe {
+        let ret = RegDeleteKeyValue(hive as usize as *mut c_void, key.as_ptr(), name.as_ptr());
+
+        match ret {
+            ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => Ok(()),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Gets whether a key exists in the 64-bit registry view.
+pub fn key_exists(hive: Hive, key: &str) -> Result<bool> {
+    let key = WideString::new(key)?;
+
+    unsafe {
+        let mut handle: *mut c_void = std::ptr::null_mut();
+        let ret = RegOpenKeyEx(
+            hive as usize as *mut c_void,
+            key.as_ptr(),
+            0,
+            KEY_QUERY_VALUE | KEY_WOW64_64KEY,
+            &mut handle as *mut *mut c_void,
+        );
+
+        match ret {
+            ERROR_SUCCESS => {
+                RegCloseKey(handle);
+                Ok(true)
+            }
+            ERROR_FILE_NOT_FOUND => Ok(false),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Gets the size in bytes of a value of any type in the 64-bit registry view,
+/// or `None` if the key or value does not exist.
+pub fn value_len(hive: Hive, key: &str, name: &str) -> Result<Option<u32>> {
+    let key = WideString::new(key)?;
+    let name = WideString::new(name)?;
+
+    let mut value_len = 0u32;
+    unsafe {
+        let ret = RegGetValue(
+            hive as usize as *mut c_void,
+            key.as_ptr(),
+            name.as_ptr(),
+            RRF_RT_ANY | RRF_SUBKEY_WOW6464KEY,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut value_len as *mut u32,
+        );
+
+        match ret {
+            ERROR_SUCCESS => Ok(Some(value_len)),
+            ERROR_FILE_NOT_FOUND => Ok(None),
+            err => Err(Error::from(err)),
+        }
+    }
+}
+
+/// Gets the names of the subkeys of a key in the 64-bit registry view,
+/// or an empty list if the key does not exist.
+pub fn subkeys(hive: Hive, key: &str) -> Result<Vec<String>> {
+    let key = WideString::new(key)?;
+
+    unsafe {
+        let mut handle: *mut c_void = std::ptr::null_mut();
+        let ret = RegOpenKeyEx(
+            hive as usize as *mut c_void,
+            key.as_ptr(),
+            0,
+            KEY_ENUMERATE_SUB_KEYS | KEY_WOW64_64KEY,
+            &mut handle as *mut *mut c_void,
+        );
+        match ret {
+            ERROR_SUCCESS => {}
+            ERROR_FILE_NOT_FOUND => return Ok(Vec::new()),
+            err => return Err(Error::from(err)),
+        }
+
+        let mut names = Vec::new();
+        let result = loop {
+            // Key names are at most 255 characters.
+            let mut name = [0u16; 256];
+            let mut name_len = name.len() as u32;
+            let ret = RegEnumKeyEx(
+                handle,
+                names.len() as u32,
+                name.as_mut_ptr(),
+                &mut name_len as *mut u32,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+
+            match ret {
+                ERROR_SUCCESS => match from_wide(&name[..name_len as usize]) {
+                    Ok(name) => names.push(name),
+                    Err(err) => break Err(err),
+                },
+                ERROR_NO_MORE_ITEMS => break Ok(names),
+                err => break Err(Error::from(err)),
+            }
+        };
+
+        RegCloseKey(handle);
+        result
+    }
+}
+
+#[link(name = "advapi32")]
+extern "C" {
+    #[link_name = "RegGetValueW"]
+    fn RegGetValue(
+        key: *mut c_void,
+        subKey: LPCWSTR,
+        value: LPCWSTR,
+        flags: u32,
+        valueType: *mut u32,
+        data: *mut c_void,
+        data_len: *mut u32,
+    ) -> u32;
+
+    #[link_name = "RegSetKeyValueW"]
+    fn RegSetKeyValue(
+        key: *mut c_void,
+        subKey: LPCWSTR,
+        value: LPCWSTR,
+        valueType: u32,
+        data: *const c_void,
+        data_len: u32,
+    ) -> u32;
+
+    #[link_name = "RegDeleteKeyValueW"]
+    fn RegDeleteKeyValue(key: *mut c_void, subKey: LPCWSTR, value: LPCWSTR) -> u32;
+
+    #[link_name = "RegOpenKeyExW"]
+    fn RegOpenKeyEx(
+        key: *mut c_void,
+        subKey: LPCWSTR,
+        options: u32,
+        desired: u32,
+        result: *mut *mut c_void,
+    ) -> u32;
+
+    #[link_name = "RegEnumKeyExW"]
+    fn RegEnumKeyEx(
+        key: *mut c_void,
+        index: u32,
+        name: LPWSTR,
+        name_len: *mut u32,
+        reserved: *mut u32,
+        class: LPWSTR,
+        class_len: *mut u32,
+        lastWriteTime: *mut c_void,
+    ) -> u32;
+
+    fn RegCloseKey(key: *mut c_void) -> u32;
+}
@@ -0,0 +1,174 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{from_wide, Win32Bool, ERROR_SUCCESS, LPCWSTR, LPWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+const CCH_RM_SESSION_KEY: usize = 32;
+const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+struct FileTime {
+    low: u32,
+    high: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+struct UniqueProcess {
+    process_id: u32,
+    start_time: FileTime,
+}
+
+/// A Restart Manager session, ended when dropped.
+#[derive(Debug)]
+pub struct Session {
+    handle: u32,
+    key: String,
+}
+
+impl Session {
+    pub fn start() -> Result<Self> {
+        let mut handle = 0u32;
+        let mut key = [0u16; CCH_RM_SESSION_KEY + 1];
+
+        unsafe {
+            let ret = RmStartSession(&mut handle as *mut u32, 0, key.as_mut_ptr());
+            if ret != ERROR_SUCCESS {
+                return Err(Error::from(ret));
+            }
+        }
+
+        let len = key.iter().position(|c| *c == 0).unwrap_or(key.len());
+        Ok(Session {
+            handle,
+            key: from_wide(&key[..len])?,
+        })
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Registers running processes to shut down and restart, skipping any that have exited.
+    pub fn register_processes(&self, process_ids: &[u32]) -> Result<()> {
+        let processes: Vec<UniqueProcess> = process_ids
+            .iter()
+            .filter_map(|id| unique_process(*id))
+            .collect();
+
+        unsafe {
+            match RmRegisterResources(
+                self.handle,
+                0,
+                std::ptr::null(),
+                processes.len() as u32,
+                processes.as_ptr(),
+                0,
+                std::ptr::null(),
+            ) {
+                ERROR_SUCCESS => Ok(()),
+                err => Err(Error::from(err)),
+            }
+        }
+    }
+
+    /// Shuts down registered applications, failing if any refuse.
+    pub fn shutdown(&self) -> Result<()> {
+        unsafe {
+            match RmShutdown(self.handle, 0, None) {
+                ERROR_SUCCESS => Ok(()),
+                err => Err(Error::from(err)),
+            }
+        }
+    }
+
+    /// Restarts applications that were shut down and registered for restart.
+    pub fn restart(&self) -> Result<()> {
+        unsafe {
+            match RmRestart(self.handle, 0, None) {
+                ERROR_SUCCESS => Ok(()),
+                err => Err(Error::from(err)),
+            }
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        unsafe {
+            RmEndSession(self.handle);
+        }
+    }
+}
+
+/// Identifies a process by its ID and start time, since process IDs are reused.
+fn unique_process(process_id: u32) -> Option<UniqueProcess> {
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false.into(), process_id);
+        if process.is_null() {
+            return None;
+        }
+
+        let mut start_time = FileTime::default();
+        let mut unused = FileTime::default();
+        let ret = GetProcessTimes(
+            process,
+            &mut start_time as *mut FileTime,
+            &mut unused as *mut FileTime,
+            &mut unused as *mut FileTime,
+            &mut unused as *mut FileTime,
+        );
+        CloseHandle(process);
+
+        match ret == true {
+            true => Some(UniqueProcess {
+                process_id,
+                start_time,
+            }),
+            false => None,
+        }
+    }
+}
+
+type WriteStatusCallback = extern "system" fn(u32);
+
+#[link(name = "rstrtmgr")]
+extern "C" {
+    fn RmStartSession(sessionHandle: *mut u32, sessionFlags: u32, sessionKey: LPWSTR) -> u32;
+
+    fn RmRegisterResources(
+        sessionHandle: u32,
+        files: u32,
+        filenames: *const LPCWSTR,
+        applications: u32,
+        processes: *const UniqueProcess,
+        services: u32,
+        serviceNames: *const LPCWSTR,
+    ) -> u32;
+
+    fn RmShutdown(sessionHandle: u32, actionFlags: u32, status: Option<WriteStatusCallback>)
+        -> u32;
+
+    fn RmRestart(sessionHandle: u32, restartFlags: u32, status: Option<WriteStatusCallback>)
+        -> u32;
+
+    fn RmEndSession(sessionHandle: u32) -> u32;
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    fn OpenProcess(desiredAccess: u32, inheritHandle: Win32Bool, processId: u32) -> *mut c_void;
+
+    fn GetProcessTimes(
+        process: *mut c_void,
+        creationTime: *mut FileTime,
+        exitTime: *mut FileTime,
+        kernelTime: *mut FileTime,
+        userTime: *mut FileTime,
+    ) -> Win32Bool;
+
+    fn CloseHandle(handle: *mut c_void) -> Win32Bool;
+}
@@ -0,0 +1,186 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, Win32Bool, ERROR_SUCCESS, LPCWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+const SE_FILE_OBJECT: u32 = 1;
+const DACL_SECURITY_INFORMATION: u32 = 4;
+const ACCESS_ALLOWED_ACE_TYPE: u8 = 0;
+const TOKEN_QUERY: u32 = 0x0008;
+const TOKEN_ELEVATION: u32 = 20;
+const INHERIT_ONLY_ACE: u8 = 0x08;
+
+const FILE_WRITE_DATA: u32 = 0x0002;
+const FILE_APPEND_DATA: u32 = 0x0004;
+const WRITE_DAC: u32 = 0x0004_0000;
+const WRITE_OWNER: u32 = 0x0008_0000;
+const GENERIC_ALL: u32 = 0x1000_0000;
+const GENERIC_WRITE: u32 = 0x4000_0000;
+const WRITE_ACCESS: u32 =
+    FILE_WRITE_DATA | FILE_APPEND_DATA | WRITE_DAC | WRITE_OWNER | GENERIC_ALL | GENERIC_WRITE;
+
+/// Well-known security identifiers from WELL_KNOWN_SID_TYPE.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum WellKnownSid {
+    Everyone = 1,
+    Anonymous = 13,
+    Guests = 28,
+}
+
+impl WellKnownSid {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WellKnownSid::Everyone => "Everyone",
+            WellKnownSid::Anonymous => "ANONYMOUS LOGON",
+            WellKnownSid::Guests => "Guests",
+        }
+    }
+}
+
+#[repr(C)]
+struct Acl {
+    revision: u8,
+    reserved1: u8,
+    size: u16,
+    ace_count: u16,
+    reserved2: u16,
+}
+
+#[repr(C)]
+struct AccessAllowedAce {
+    ace_type: u8,
+    flags: u8,
+    size: u16,
+    mask: u32,
+    sid_start: u32,
+}
+
+/// Gets which of the `sids` are granted write access to a file or directory.
+///
+/// A missing DACL grants everyone full access, so all `sids` are returned.
+pub fn writable_by(path: &str, sids: &[WellKnownSid]) -> Result<Vec<WellKnownSid>> {
+    let path = WideString::from_path(path)?;
+
+    let mut dacl: *mut Acl = std::ptr::null_mut();
+    let mut descriptor: *mut c_void = std::ptr::null_mut();
+
+    unsafe {
+        let ret = GetNamedSecurityInfo(
+            path.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut dacl as *mut *mut Acl,
+            std::ptr::null_mut(),
+            &mut descriptor as *mut *mut c_void,
+        );
+        if ret != ERROR_SUCCESS {
+            return Err(Error::from(ret));
+        }
+
+        let mut writable = Vec::new();
+        if dacl.is_null() {
+            writable.extend_from_slice(sids);
+        } else {
+            for i in 0..(*dacl).ace_count as u32 {
+                let mut ace: *mut AccessAllowedAce = std::ptr::null_mut();
+                if GetAce(dacl, i, &mut ace as *mut *mut AccessAllowedAce) != true {
+                    continue;
+                }
+
+                let ace = &*ace;
+                if ace.ace_type != ACCESS_ALLOWED_ACE_TYPE
+                    || ace.flags & INHERIT_ONLY_ACE != 0
+                    || ace.mask & WRITE_ACCESS == 0
+                {
+                    continue;
+                }
+
+                let sid = &ace.sid_start as *const u32 as *const c_void;
+                for well_known in sids {
+                    if !writable.contains(well_known) && IsWellKnownSid(sid, *well_known) == true {
+                        writable.push(*well_known);
+                    }
+                }
+            }
+        }
+
+        LocalFree(descriptor);
+        Ok(writable)
+    }
+}
+
+/// Gets whether this process is running elevated.
+pub fn is_elevated() -> Result<bool> {
+    unsafe {
+        let mut token: *mut c_void = std::ptr::null_mut();
+        if OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_QUERY,
+            &mut token as *mut *mut c_void,
+        ) != true
+        {
+            return Err(Error::from(std::io::Error::last_os_error()));
+        }
+
+        // TOKEN_ELEVATION contains only a DWORD.
+        let mut elevated = 0u32;
+        let mut len = 0u32;
+        let ret = GetTokenInformation(
+            token,
+            TOKEN_ELEVATION,
+            &mut elevated as *mut u32 as *mut c_void,
+            std::mem::size_of::<u32>() as u32,
+            &mut len as *mut u32,
+        );
+        let err = std::io::Error::last_os_error();
+        CloseHandle(token);
+
+        match ret == true {
+            true => Ok(elevated != 0),
+            false => Err(Error::from(err)),
+        }
+    }
+}
+
+#[link(name = "advapi32")]
+extern "C" {
+    #[link_name = "GetNamedSecurityInfoW"]
+    fn GetNamedSecurityInfo(
+        objectName: LPCWSTR,
+        objectType: u32,
+        securityInfo: u32,
+        owner: *mut *mut c_void,
+        group: *mut *mut c_void,
+        dacl: *mut *mut Acl,
+        sacl: *mut *mut c_void,
+        securityDescriptor: *mut *mut c_void,
+    ) -> u32;
+
+    fn GetAce(acl: *mut Acl, index: u32, ace: *mut *mut AccessAllowedAce) -> Win32Bool;
+
+    fn IsWellKnownSid(sid: *const c_void, sidType: WellKnownSid) -> Win32Bool;
+
+    fn OpenProcessToken(process: *mut c_void, access: u32, token: *mut *mut c_void) -> Win32Bool;
+
+    fn GetTokenInformation(
+        token: *mut c_void,
+        informationClass: u32,
+        information: *mut c_void,
+        informationLength: u32,
+        returnLength: *mut u32,
+    ) -> Win32Bool;
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    fn LocalFree(mem: *mut c_void) -> *mut c_void;
+
+    fn GetCurrentProcess() -> *mut c_void;
+
+    fn CloseHandle(handle: *mut c_void) -> Win32Bool;
+}
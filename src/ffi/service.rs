@@ -0,0 +1,163 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, Win32Bool, LPCWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+const SC_MANAGER_CONNECT: u32 = 0x0001;
+const SERVICE_QUERY_CONFIG: u32 = 0x0001;
+const SERVICE_QUERY_STATUS: u32 = 0x0004;
+const SERVICE_START: u32 = 0x0010;
+const ERROR_INSUFFICIENT_BUFFER: i32 = 122;
+const ERROR_SERVICE_DOES_NOT_EXIST: i32 = 1060;
+
+pub const SERVICE_DISABLED: u32 = 4;
+pub const SERVICE_STOPPED: u32 = 1;
+pub const SERVICE_RUNNING: u32 = 4;
+
+#[derive(Debug, Default)]
+#[repr(C)]
+struct ServiceStatus {
+    service_type: u32,
+    current_state: u32,
+    controls_accepted: u32,
+    win32_exit_code: u32,
+    service_specific_exit_code: u32,
+    check_point: u32,
+    wait_hint: u32,
+}
+
+/// Leading fields of QUERY_SERVICE_CONFIGW.
+#[repr(C)]
+struct ServiceConfig {
+    service_type: u32,
+    start_type: u32,
+}
+
+/// The configured start type and current state of a service.
+#[derive(Clone, Copy, Debug)]
+pub struct Status {
+    pub start_type: u32,
+    pub current_state: u32,
+}
+
+struct Handle(*mut c_void);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe {
+            CloseServiceHandle(self.0);
+        }
+    }
+}
+
+/// Gets the status of a service, or `None` if the service does not exist.
+pub fn status(name: &str) -> Result<Option<Status>> {
+    let service = match open(name, SERVICE_QUERY_CONFIG | SERVICE_QUERY_STATUS)? {
+        Some(service) => service,
+        None => return Ok(None),
+    };
+
+    unsafe {
+        let mut status = ServiceStatus::default();
+        if QueryServiceStatus(service.0, &mut status as *mut ServiceStatus) != true {
+            return Err(Error::from(std::io::Error::last_os_error()));
+        }
+
+        let mut config_len = 0u32;
+        if QueryServiceConfig(
+            service.0,
+            std::ptr::null_mut(),
+            0,
+            &mut config_len as *mut u32,
+        ) != true
+        {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER) {
+                return Err(Error::from(err));
+            }
+        }
+
+        // Align the buffer for the pointers that follow the leading fields.
+        let mut config: Vec<u64> = vec![0; (config_len as usize).div_ceil(8)];
+        if QueryServiceConfig(
+            service.0,
+            config.as_mut_ptr() as *mut ServiceConfig,
+            config_len,
+            &mut config_len as *mut u32,
+        ) != true
+        {
+            return Err(Error::from(std::io::Error::last_os_error()));
+        }
+        let config = &*(config.as_ptr() as *const ServiceConfig);
+
+        Ok(Some(Status {
+            start_type: config.start_type,
+            current_state: status.current_state,
+        }))
+    }
+}
+
+/// Starts a service that is stopped.
+pub fn start(name: &str) -> Result<()> {
+    let service = open(name, SERVICE_START)?.ok_or_else(|| {
+        Error::from(std::io::Error::from_raw_os_error(
+            ERROR_SERVICE_DOES_NOT_EXIST,
+        ))
+    })?;
+
+    unsafe {
+        match StartService(service.0, 0, std::ptr::null()) == true {
+            true => Ok(()),
+            false => Err(Error::from(std::io::Error::last_os_error())),
+        }
+    }
+}
+
+fn open(name: &str, access: u32) -> Result<Option<Handle>> {
+    let name = WideString::new(name)?;
+
+    unsafe {
+        let manager = OpenSCManager(std::ptr::null(), std::ptr::null(), SC_MANAGER_CONNECT);
+        if manager.is_null() {
+            return Err(Error::from(std::io::Error::last_os_error()));
+        }
+        let manager = Handle(manager);
+
+        let service = OpenService(manager.0, name.as_ptr(), access);
+        if service.is_null() {
+            let err = std::io::Error::last_os_error();
+            return match err.raw_os_error() {
+                Some(ERROR_SERVICE_DOES_NOT_EXIST) => Ok(None),
+                _ => Err(Error::from(err)),
+            };
+        }
+
+        Ok(Some(Handle(service)))
+    }
+}
+
+#[link(name = "advapi32")]
+extern "C" {
+    #[link_name = "OpenSCManagerW"]
+    fn OpenSCManager(machineName: LPCWSTR, databaseName: LPCWSTR, access: u32) -> *mut c_void;
+
+    #[link_name = "OpenServiceW"]
+    fn OpenService(manager: *mut c_void, serviceName: LPCWSTR, access: u32) -> *mut c_void;
+
+    fn QueryServiceStatus(service: *mut c_void, status: *mut ServiceStatus) -> Win32Bool;
+
+    #[link_name = "QueryServiceConfigW"]
+    fn QueryServiceConfig(
+        service: *mut c_void,
+        config: *mut ServiceConfig,
+        config_len: u32,
+        bytesNeeded: *mut u32,
+    ) -> Win32Bool;
+
+    #[link_name = "StartServiceW"]
+    fn StartService(service: *mut c_void, argc: u32, argv: *const LPCWSTR) -> Win32Bool;
+
+    fn CloseServiceHandle(handle: *mut c_void) -> Win32Bool;
+}
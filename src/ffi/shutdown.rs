@@ -0,0 +1,135 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, Win32Bool, LPCWSTR, LPWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+const TOKEN_ADJUST_PRIVILEGES: u32 = 0x0020;
+const TOKEN_QUERY: u32 = 0x0008;
+const SE_PRIVILEGE_ENABLED: u32 = 0x0002;
+
+const SHTDN_REASON_MAJOR_APPLICATION: u32 = 0x0004_0000;
+const SHTDN_REASON_MINOR_INSTALLATION: u32 = 0x0000_0002;
+const SHTDN_REASON_FLAG_PLANNED: u32 = 0x8000_0000;
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+struct Luid {
+    low: u32,
+    high: i32,
+}
+
+#[repr(C)]
+struct TokenPrivileges {
+    count: u32,
+    luid: Luid,
+    attributes: u32,
+}
+
+/// Schedules a planned restart of this computer for an application installation.
+pub fn schedule_reboot(timeout_secs: u32, message: Option<&str>) -> Result<()> {
+    enable_shutdown_privilege()?;
+
+    let message = message.map(WideString::new).transpose()?;
+    let message = message
+        .as_ref()
+        .map_or(std::ptr::null_mut(), |m| m.as_ptr() as LPWSTR);
+
+    unsafe {
+        let ret = InitiateSystemShutdownEx(
+            std::ptr::null_mut(),
+            message,
+            timeout_secs,
+            false.into(),
+            true.into(),
+            SHTDN_REASON_MAJOR_APPLICATION
+                | SHTDN_REASON_MINOR_INSTALLATION
+                | SHTDN_REASON_FLAG_PLANNED,
+        );
+        match ret == true {
+            true => Ok(()),
+            false => Err(Error::from(std::io::Error::last_os_error())),
+        }
+    }
+}
+
+fn enable_shutdown_privilege() -> Result<()> {
+    let name = WideString::new("SeShutdownPrivilege")?;
+
+    unsafe {
+        let mut token: *mut c_void = std::ptr::null_mut();
+        if OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY,
+            &mut token as *mut *mut c_void,
+        ) != true
+        {
+            return Err(Error::from(std::io::Error::last_os_error()));
+        }
+
+        let mut privileges = TokenPrivileges {
+            count: 1,
+            luid: Luid::default(),
+            attributes: SE_PRIVILEGE_ENABLED,
+        };
+        let mut ret = LookupPrivilegeValue(
+            std::ptr::null(),
+            name.as_ptr(),
+            &mut privileges.luid as *mut Luid,
+        );
+        if ret == true {
+            ret = AdjustTokenPrivileges(
+                token,
+                false.into(),
+                &privileges as *const TokenPrivileges,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+        }
+
+        // AdjustTokenPrivileges succeeds even when the privilege is not held.
+        let err = std::io::Error::last_os_error();
+        CloseHandle(token);
+
+        match (ret == true, err.raw_os_error()) {
+            (true, Some(0)) => Ok(()),
+            _ => Err(Error::from(err)),
+        }
+    }
+}
+
+#[link(name = "advapi32")]
+extern "C" {
+    #[link_name = "InitiateSystemShutdownExW"]
+    fn InitiateSystemShutdownEx(
+        machineName: LPWSTR,
+        message: LPWSTR,
+        timeout: u32,
+        forceAppsClosed: Win32Bool,
+        rebootAfterShutdown: Win32Bool,
+        reason: u32,
+    ) -> Win32Bool;
+
+    fn OpenProcessToken(process: *mut c_void, access: u32, token: *mut *mut c_void) -> Win32Bool;
+
+    #[link_name = "LookupPrivilegeValueW"]
+    fn LookupPrivilegeValue(systemName: LPCWSTR, name: LPCWSTR, luid: *mut Luid) -> Win32Bool;
+
+    fn AdjustTokenPrivileges(
+        token: *mut c_void,
+        disableAllPrivileges: Win32Bool,
+        newState: *const TokenPrivileges,
+        bufferLength: u32,
+        previousState: *mut TokenPrivileges,
+        returnLength: *mut u32,
+    ) -> Win32Bool;
+}
+
+#[link(name = "kernel32")]
+extern "C" {
+    fn GetCurrentProcess() -> *mut c_void;
+
+    fn CloseHandle(handle: *mut c_void) -> Win32Bool;
+}
@@ -0,0 +1,480 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Windows Installer functions backed by `windows-sys` declarations, with the same signatures
+//! as the hand-written bindings they replace.
+
+#![allow(non_snake_case)]
+
+use super::{
+    ColumnInfo, FileHashInfo, FileTime, MsiHandle, PatchSequenceInfo, UIHandler, UIRecordHandler,
+    Win32Bool, LPCWSTR, LPWSTR,
+};
+use std::ffi::c_void;
+use windows_sys::Win32::Foundation::FILETIME;
+use windows_sys::Win32::System::ApplicationInstallationAndServicing as msi;
+
+pub use windows_sys::Win32::Foundation::{
+    ERROR_INVALID_HANDLE, ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS,
+};
+
+pub unsafe fn MsiRecordGetFieldCount(h: MsiHandle) -> u32 {
+    msi::MsiRecordGetFieldCount(*h)
+}
+
+pub unsafe fn MsiRecordGetString(
+    h: MsiHandle,
+    index: u32,
+    value: LPWSTR,
+    value_len: *mut u32,
+) -> u32 {
+    msi::MsiRecordGetStringW(*h, index, value, value_len)
+}
+
+pub unsafe fn MsiRecordGetInteger(h: MsiHandle, index: u32) -> i32 {
+    msi::MsiRecordGetInteger(*h, index)
+}
+
+pub unsafe fn MsiCreateRecord(params: u32) -> MsiHandle {
+    MsiHandle(msi::MsiCreateRecord(params))
+}
+
+pub unsafe fn MsiRecordSetString(h: MsiHandle, index: u32, value: LPCWSTR) -> u32 {
+    msi::MsiRecordSetStringW(*h, index, value)
+}
+
+pub unsafe fn MsiRecordIsNull(h: MsiHandle, index: u32) -> Win32Bool {
+    Win32Bool(msi::MsiRecordIsNull(*h, index))
+}
+
+pub unsafe fn MsiRecordDataSize(h: MsiHandle, index: u32) -> u32 {
+    msi::MsiRecordDataSize(*h, index)
+}
+
+pub unsafe fn MsiRecordReadStream(
+    h: MsiHandle,
+    index: u32,
+    data: *mut u8,
+    data_len: *mut u32,
+) -> u32 {
+    msi::MsiRecordReadStream(*h, index, data, data_len)
+}
+
+pub unsafe fn MsiCloseHandle(h: MsiHandle) -> u32 {
+    msi::MsiCloseHandle(*h)
+}
+
+#[cfg_attr(not(debug_assertions), allow(dead_code))]
+pub unsafe fn MsiCloseAllHandles() -> u32 {
+    msi::MsiCloseAllHandles()
+}
+
+pub unsafe fn MsiSetExternalUIRecord(
+    handler: Option<UIRecordHandler>,
+    filter: u32,
+    context: *const c_void,
+    previous_handler: *mut c_void,
+) -> u32 {
+    // The handler takes its message type as a raw u32, MsiHandle is
+    // #[repr(transparent)] over u32, and the u32 result is returned in the same
+    // register as an i32.
+    let handler =
+        std::mem::transmute::<Option<UIRecordHandler>, msi::PINSTALLUI_HANDLER_RECORD>(handler);
+
+    // The previous handler is declared as the handler type itself though it is an
+    // out pointer to one.
+    msi::MsiSetExternalUIRecord(
+        handler,
+        filter,
+        context,
+        std::mem::transmute::<*mut c_void, msi::PINSTALLUI_HANDLER_RECORD>(previous_handler),
+    )
+}
+
+pub unsafe fn MsiSetExternalUI(
+    handler: Option<UIHandler>,
+    filter: u32,
+    context: *const c_void,
+) -> Option<UIHandler> {
+    // The u32 result is returned in the same register as an i32.
+    let handler = std::mem::transmute::<Option<UIHandler>, msi::INSTALLUI_HANDLERW>(handler);
+    std::mem::transmute::<msi::INSTALLUI_HANDLERW, Option<UIHandler>>(msi::MsiSetExternalUIW(
+        handler, filter, context,
+    ))
+}
+
+pub unsafe fn MsiSetInternalUI(level: u32, parent: *mut c_void) -> u32 {
+    msi::MsiSetInternalUI(level as msi::INSTALLUILEVEL, parent as *mut _) as u32
+}
+
+pub unsafe fn MsiEnableLog(mode: u32, path: LPCWSTR, attributes: u32) -> u32 {
+    msi::MsiEnableLogW(mode, path, attributes)
+}
+
+pub unsafe fn MsiInstallProduct(packagePath: LPCWSTR, commandLine: LPCWSTR) -> u32 {
+    msi::MsiInstallProductW(packagePath, commandLine)
+}
+
+pub unsafe fn MsiApplyMultiplePatches(
+    patchPackages: LPCWSTR,
+    productCode: LPCWSTR,
+    propertiesList: LPCWSTR,
+) -> u32 {
+    msi::MsiApplyMultiplePatchesW(patchPackages, productCode, propertiesList)
+}
+
+pub unsafe fn MsiGetPatchInfoEx(
+    patchCode: LPCWSTR,
+    productCode: LPCWSTR,
+    userSid: LPCWSTR,
+    context: u32,
+    property: LPCWSTR,
+    value: LPWSTR,
+    value_len: *mut u32,
+) -> u32 {
+    msi::MsiGetPatchInfoExW(
+        patchCode,
+        productCode,
+        userSid,
+        context as msi::MSIINSTALLCONTEXT,
+        property,
+        value,
+        value_len,
+    )
+}
+
+pub unsafe fn MsiAdvertiseProductEx(
+    packagePath: LPCWSTR,
+    scriptFilePath: LPCWSTR,
+    transforms: LPCWSTR,
+    language: u16,
+    platform: u32,
+    options: u32,
+) -> u32 {
+    msi::MsiAdvertiseProductExW(
+        packagePath,
+        scriptFilePath,
+        transforms,
+        language,
+        platform,
+        options,
+    )
+}
+
+pub unsafe fn MsiApplyPatch(
+    patchPackage: LPCWSTR,
+    installPackage: LPCWSTR,
+    installType: i32,
+    commandLine: LPCWSTR,
+) -> u32 {
+    msi::MsiApplyPatchW(patchPackage, installPackage, installType, commandLine)
+}
+
+pub unsafe fn MsiProcessAdvertiseScript(
+    scriptFile: LPCWSTR,
+    iconFolder: LPCWSTR,
+    regData: *mut c_void,
+    shortcuts: Win32Bool,
+    removeItems: Win32Bool,
+) -> u32 {
+    msi::MsiProcessAdvertiseScriptW(scriptFile, iconFolder, regData, shortcuts.0, removeItems.0)
+}
+
+pub unsafe fn MsiConfigureFeature(product: LPCWSTR, feature: LPCWSTR, installState: i32) -> u32 {
+    msi::MsiConfigureFeatureW(product, feature, installState)
+}
+
+pub unsafe fn MsiReinstallFeature(product: LPCWSTR, feature: LPCWSTR, reinstallMode: u32) -> u32 {
+    msi::MsiReinstallFeatureW(product, feature, reinstallMode)
+}
+
+pub unsafe fn MsiInstallMissingComponent(
+    product: LPCWSTR,
+    component: LPCWSTR,
+    installState: i32,
+) -> u32 {
+    msi::MsiInstallMissingComponentW(product, component, installState)
+}
+
+pub unsafe fn MsiInstallMissingFile(product: LPCWSTR, file: LPCWSTR) -> u32 {
+    msi::MsiInstallMissingFileW(product, file)
+}
+
+pub unsafe fn MsiBeginTransaction(
+    name: LPCWSTR,
+    attributes: u32,
+    transaction: *mut MsiHandle,
+    changeOfOwnerEvent: *mut *mut c_void,
+) -> u32 {
+    msi::MsiBeginTransactionW(
+        name,
+        attributes,
+        transaction as *mut msi::MSIHANDLE,
+        changeOfOwnerEvent,
+    )
+}
+
+pub unsafe fn MsiJoinTransaction(
+    transaction: MsiHandle,
+    attributes: u32,
+    changeOfOwnerEvent: *mut *mut c_void,
+) -> u32 {
+    msi::MsiJoinTransaction(*transaction, attributes, changeOfOwnerEvent)
+}
+
+pub unsafe fn MsiEndTransaction(state: u32) -> u32 {
+    msi::MsiEndTransaction(state)
+}
+
+pub unsafe fn MsiReinstallProduct(product: LPCWSTR, reinstallMode: u32) -> u32 {
+    msi::MsiReinstallProductW(product, reinstallMode)
+}
+
+pub unsafe fn MsiVerifyPackage(packagePath: LPCWSTR) -> u32 {
+    msi::MsiVerifyPackageW(packagePath)
+}
+
+pub unsafe fn MsiOpenDatabase(
+    databasePath: LPCWSTR,
+    persist: LPCWSTR,
+    database: *mut MsiHandle,
+) -> u32 {
+    msi::MsiOpenDatabaseW(databasePath, persist, database as *mut msi::MSIHANDLE)
+}
+
+pub unsafe fn MsiDatabaseOpenView(
+    database: MsiHandle,
+    query: LPCWSTR,
+    view: *mut MsiHandle,
+) -> u32 {
+    msi::MsiDatabaseOpenViewW(*database, query, view as *mut msi::MSIHANDLE)
+}
+
+pub unsafe fn MsiViewExecute(view: MsiHandle, record: MsiHandle) -> u32 {
+    msi::MsiViewExecute(*view, *record)
+}
+
+pub unsafe fn MsiViewFetch(view: MsiHandle, record: *mut MsiHandle) -> u32 {
+    msi::MsiViewFetch(*view, record as *mut msi::MSIHANDLE)
+}
+
+pub unsafe fn MsiViewGetColumnInfo(
+    view: MsiHandle,
+    info: ColumnInfo,
+    record: *mut MsiHandle,
+) -> u32 {
+    msi::MsiViewGetColumnInfo(
+        *view,
+        info as msi::MSICOLINFO,
+        record as *mut msi::MSIHANDLE,
+    )
+}
+
+pub unsafe fn MsiDatabaseGetPrimaryKeys(
+    database: MsiHandle,
+    tableName: LPCWSTR,
+    record: *mut MsiHandle,
+) -> u32 {
+    msi::MsiDatabaseGetPrimaryKeysW(*database, tableName, record as *mut msi::MSIHANDLE)
+}
+
+pub unsafe fn MsiDatabaseExport(
+    database: MsiHandle,
+    tableName: LPCWSTR,
+    folderPath: LPCWSTR,
+    fileName: LPCWSTR,
+) -> u32 {
+    msi::MsiDatabaseExportW(*database, tableName, folderPath, fileName)
+}
+
+pub unsafe fn MsiDatabaseApplyTransform(
+    database: MsiHandle,
+    transformFile: LPCWSTR,
+    errorConditions: u32,
+) -> u32 {
+    msi::MsiDatabaseApplyTransformW(*database, transformFile, errorConditions as _)
+}
+
+pub unsafe fn MsiGetSummaryInformation(
+    database: MsiHandle,
+    databasePath: LPCWSTR,
+    updateCount: u32,
+    summaryInfo: *mut MsiHandle,
+) -> u32 {
+    msi::MsiGetSummaryInformationW(
+        *database,
+        databasePath,
+        updateCount,
+        summaryInfo as *mut msi::MSIHANDLE,
+    )
+}
+
+pub unsafe fn MsiSummaryInfoGetProperty(
+    summaryInfo: MsiHandle,
+    property: u32,
+    dataType: *mut u32,
+    integer: *mut i32,
+    time: *mut FileTime,
+    value: LPWSTR,
+    value_len: *mut u32,
+) -> u32 {
+    msi::MsiSummaryInfoGetPropertyW(
+        *summaryInfo,
+        property,
+        dataType,
+        integer,
+        time as *mut FILETIME,
+        value,
+        value_len,
+    )
+}
+
+pub unsafe fn MsiFormatRecord(
+    install: MsiHandle,
+    record: MsiHandle,
+    value: LPWSTR,
+    value_len: *mut u32,
+) -> u32 {
+    msi::MsiFormatRecordW(*install, *record, value, value_len)
+}
+
+pub unsafe fn MsiGetProductInfo(
+    product: LPCWSTR,
+    property: LPCWSTR,
+    value: LPWSTR,
+    value_len: *mut u32,
+) -> u32 {
+    msi::MsiGetProductInfoW(product, property, value, value_len)
+}
+
+pub unsafe fn MsiEnumProducts(index: u32, productCode: LPWSTR) -> u32 {
+    msi::MsiEnumProductsW(index, productCode)
+}
+
+pub unsafe fn MsiEnumRelatedProducts(
+    upgradeCode: LPCWSTR,
+    reserved: u32,
+    index: u32,
+    productCode: LPWSTR,
+) -> u32 {
+    msi::MsiEnumRelatedProductsW(upgradeCode, reserved, index, productCode)
+}
+
+pub unsafe fn MsiOpenPackageEx(packagePath: LPCWSTR, options: u32, product: *mut MsiHandle) -> u32 {
+    msi::MsiOpenPackageExW(packagePath, options, product as *mut msi::MSIHANDLE)
+}
+
+pub unsafe fn MsiSetProperty(install: MsiHandle, name: LPCWSTR, value: LPCWSTR) -> u32 {
+    msi::MsiSetPropertyW(*install, name, value)
+}
+
+pub unsafe fn MsiDoAction(install: MsiHandle, action: LPCWSTR) -> u32 {
+    msi::MsiDoActionW(*install, action)
+}
+
+pub unsafe fn MsiEvaluateCondition(install: MsiHandle, condition: LPCWSTR) -> i32 {
+    msi::MsiEvaluateConditionW(*install, condition)
+}
+
+pub unsafe fn MsiGetComponentPath(
+    product: LPCWSTR,
+    component: LPCWSTR,
+    path: LPWSTR,
+    path_len: *mut u32,
+) -> i32 {
+    msi::MsiGetComponentPathW(product, component, path, path_len)
+}
+
+pub unsafe fn MsiProvideComponent(
+    product: LPCWSTR,
+    feature: LPCWSTR,
+    component: LPCWSTR,
+    installMode: u32,
+    path: LPWSTR,
+    path_len: *mut u32,
+) -> u32 {
+    msi::MsiProvideComponentW(product, feature, component, installMode, path, path_len)
+}
+
+pub unsafe fn MsiProvideQualifiedComponent(
+    category: LPCWSTR,
+    qualifier: LPCWSTR,
+    installMode: u32,
+    path: LPWSTR,
+    path_len: *mut u32,
+) -> u32 {
+    msi::MsiProvideQualifiedComponentW(category, qualifier, installMode, path, path_len)
+}
+
+pub unsafe fn MsiSourceListAddSourceEx(
+    productCode: LPCWSTR,
+    userSid: LPCWSTR,
+    context: u32,
+    options: u32,
+    source: LPCWSTR,
+    index: u32,
+) -> u32 {
+    msi::MsiSourceListAddSourceExW(
+        productCode,
+        userSid,
+        context as msi::MSIINSTALLCONTEXT,
+        options,
+        source,
+        index,
+    )
+}
+
+pub unsafe fn MsiSourceListForceResolutionEx(
+    productCode: LPCWSTR,
+    userSid: LPCWSTR,
+    context: u32,
+    options: u32,
+) -> u32 {
+    msi::MsiSourceListForceResolutionExW(
+        productCode,
+        userSid,
+        context as msi::MSIINSTALLCONTEXT,
+        options,
+    )
+}
+
+pub unsafe fn MsiExtractPatchXMLData(
+    patchPath: LPCWSTR,
+    reserved: u32,
+    xmlData: LPWSTR,
+    xmlData_len: *mut u32,
+) -> u32 {
+    msi::MsiExtractPatchXMLDataW(patchPath, reserved, xmlData, xmlData_len)
+}
+
+pub unsafe fn MsiDetermineApplicablePatches(
+    productPackagePath: LPCWSTR,
+    patchCount: u32,
+    patchInfo: *mut PatchSequenceInfo,
+) -> u32 {
+    msi::MsiDetermineApplicablePatchesW(
+        productPackagePath,
+        patchCount,
+        patchInfo as *mut msi::MSIPATCHSEQUENCEINFOW,
+    )
+}
+
+pub unsafe fn MsiDeterminePatchSequence(
+    productCode: LPCWSTR,
+    userSid: LPCWSTR,
+    context: u32,
+    patchCount: u32,
+    patchInfo: *mut PatchSequenceInfo,
+) -> u32 {
+    msi::MsiDeterminePatchSequenceW(
+        productCode,
+        userSid,
+        context as msi::MSIINSTALLCONTEXT,
+        patchCount,
+        patchInfo as *mut msi::MSIPATCHSEQUENCEINFOW,
+    )
+}
+
+pub unsafe fn MsiGetFileHash(filePath: LPCWSTR, options: u32, hash: *mut FileHashInfo) -> u32 {
+    msi::MsiGetFileHashW(filePath, options, hash as *mut msi::MSIFILEHASHINFO)
+}
@@ -0,0 +1,36 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{WideString, LPCWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+/// Downloads a URL to a file, using the proxy and cache settings of the current user.
+pub fn download(url: &str, path: &str) -> Result<()> {
+    let url = WideString::new(url)?;
+    let path = WideString::from_path(path)?;
+
+    unsafe {
+        match URLDownloadToFileW(
+            std::ptr::null_mut(),
+            url.as_ptr(),
+            path.as_ptr(),
+            0,
+            std::ptr::null_mut(),
+        ) {
+            0 => Ok(()),
+            hr => Err(Error::from(hr as u32)),
+        }
+    }
+}
+
+#[link(name = "urlmon")]
+extern "C" {
+    fn URLDownloadToFileW(
+        caller: *mut c_void,
+        url: LPCWSTR,
+        fileName: LPCWSTR,
+        reserved: u32,
+        callback: *mut c_void,
+    ) -> i32;
+}
@@ -0,0 +1,260 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{from_wide, WideString, Win32Bool, LPWSTR};
+use crate::{Error, Result};
+use std::ffi::c_void;
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+// cspell:ignore AAC
+/// {00AAC56B-CD44-11d0-8CC2-00C04FC295EE}
+const WINTRUST_ACTION_GENERIC_VERIFY_V2: Guid = Guid {
+    data1: 0x00aac56b,
+    data2: 0xcd44,
+    data3: 0x11d0,
+    data4: [0x8c, 0xc2, 0x00, 0xc0, 0x4f, 0xc2, 0x95, 0xee],
+};
+
+const WTD_UI_NONE: u32 = 2;
+const WTD_REVOKE_WHOLECHAIN: u32 = 1;
+const WTD_CHOICE_FILE: u32 = 1;
+const WTD_STATEACTION_VERIFY: u32 = 1;
+const WTD_STATEACTION_CLOSE: u32 = 2;
+
+const TRUST_E_PROVIDER_UNKNOWN: i32 = 0x800B0001u32 as i32;
+const TRUST_E_SUBJECT_FORM_UNKNOWN: i32 = 0x800B0003u32 as i32;
+const TRUST_E_NOSIGNATURE: i32 = 0x800B0100u32 as i32;
+const TRUST_E_BAD_DIGEST: i32 = 0x80096010u32 as i32;
+const CERT_E_EXPIRED: i32 = 0x800B0101u32 as i32;
+const CERT_E_UNTRUSTEDROOT: i32 = 0x800B0109u32 as i32;
+const TRUST_E_EXPLICIT_DISTRUST: i32 = 0x800B0111u32 as i32;
+
+const CERT_NAME_SIMPLE_DISPLAY_TYPE: u32 = 4;
+const CERT_NAME_ISSUER_FLAG: u32 = 1;
+const CERT_SHA1_HASH_PROP_ID: u32 = 3;
+
+#[repr(C)]
+struct WinTrustFileInfo {
+    size: u32,
+    file_path: *const u16,
+    file: *mut c_void,
+    known_subject: *const Guid,
+}
+
+#[repr(C)]
+struct WinTrustData {
+    size: u32,
+    policy_callback_data: *mut c_void,
+    sip_client_data: *mut c_void,
+    ui_choice: u32,
+    revocation_checks: u32,
+    union_choice: u32,
+    file: *mut WinTrustFileInfo,
+    state_action: u32,
+    state_data: *mut c_void,
+    url_reference: *mut u16,
+    provider_flags: u32,
+    ui_context: u32,
+    signature_settings: *mut c_void,
+}
+
+/// Leading fields of CRYPT_PROVIDER_CERT.
+#[repr(C)]
+struct ProviderCert {
+    size: u32,
+    cert: *const c_void,
+}
+
+/// The signer certificate of a trusted file.
+#[derive(Debug)]
+pub struct SignerCertificate {
+    pub subject: String,
+    pub issuer: String,
+    pub thumbprint: Vec<u8>,
+}
+
+/// Verifies the Authenticode signature of a file is trusted and gets the signer certificate,
+/// or `None` if the file is not signed.
+pub fn verify_file(path: &str) -> Result<Option<SignerCertificate>> {
+    let path = WideString::from_path(path)?;
+
+    let mut file = WinTrustFileInfo {
+        size: std::mem::size_of::<WinTrustFileInfo>() as u32,
+        file_path: path.as_ptr(),
+        file: std::ptr::null_mut(),
+        known_subject: std::ptr::null(),
+    };
+    let mut data = WinTrustData {
+        size: std::mem::size_of::<WinTrustData>() as u32,
+        policy_callback_data: std::ptr::null_mut(),
+        sip_client_data: std::ptr::null_mut(),
+        ui_choice: WTD_UI_NONE,
+        revocation_checks: WTD_REVOKE_WHOLECHAIN,
+        union_choice: WTD_CHOICE_FILE,
+        file: &mut file as *mut WinTrustFileInfo,
+        state_action: WTD_STATEACTION_VERIFY,
+        state_data: std::ptr::null_mut(),
+        url_reference: std::ptr::null_mut(),
+        provider_flags: 0,
+        ui_context: 0,
+        signature_settings: std::ptr::null_mut(),
+    };
+
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+    unsafe {
+        let ret = WinVerifyTrust(
+            std::ptr::null_mut(),
+            &mut action as *mut Guid,
+            &mut data as *mut WinTrustData as *mut c_void,
+        );
+
+        let result = match ret {
+            0 => signer_certificate(data.state_data).map(Some),
+            TRUST_E_NOSIGNATURE | TRUST_E_SUBJECT_FORM_UNKNOWN | TRUST_E_PROVIDER_UNKNOWN => {
+                Ok(None)
+            }
+            err => Err(Error::verification(describe(err))),
+        };
+
+        // Always release the state data, even when verification failed.
+        data.state_action = WTD_STATEACTION_CLOSE;
+        WinVerifyTrust(
+            std::ptr::null_mut(),
+            &mut action as *mut Guid,
+            &mut data as *mut WinTrustData as *mut c_void,
+        );
+
+        result
+    }
+}
+
+unsafe fn signer_certificate(state_data: *mut c_void) -> Result<SignerCertificate> {
+    let provider_data = WTHelperProvDataFromStateData(state_data);
+    if provider_data.is_null() {
+        return Err(Error::verification("signer information not available"));
+    }
+
+    let signer = WTHelperGetProvSignerFromChain(provider_data, 0, false.into(), 0);
+    if signer.is_null() {
+        return Err(Error::verification("signer information not available"));
+    }
+
+    let cert = WTHelperGetProvCertFromChain(signer, 0);
+    if cert.is_null() || (*cert).cert.is_null() {
+        return Err(Error::verification("signer certificate not available"));
+    }
+    let cert = (*cert).cert;
+
+    let subject = name_string(cert, 0)?;
+    let issuer = name_string(cert, CERT_NAME_ISSUER_FLAG)?;
+
+    let mut thumbprint_len = 0u32;
+    if CertGetCertificateContextProperty(
+        cert,
+        CERT_SHA1_HASH_PROP_ID,
+        std::ptr::null_mut(),
+        &mut thumbprint_len as *mut u32,
+    ) != true
+    {
+        return Err(Error::verification("signer thumbprint not available"));
+    }
+
+    let mut thumbprint: Vec<u8> = vec![0; thumbprint_len as usize];
+    if CertGetCertificateContextProperty(
+        cert,
+        CERT_SHA1_HASH_PROP_ID,
+        thumbprint.as_mut_ptr() as *mut c_void,
+        &mut thumbprint_len as *mut u32,
+    ) != true
+    {
+        return Err(Error::verification("signer thumbprint not available"));
+    }
+    thumbprint.truncate(thumbprint_len as usize);
+
+    Ok(SignerCertificate {
+        subject,
+        issuer,
+        thumbprint,
+    })
+}
+
+unsafe fn name_string(cert: *const c_void, flags: u32) -> Result<String> {
+    let len = CertGetNameString(
+        cert,
+        CERT_NAME_SIMPLE_DISPLAY_TYPE,
+        flags,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+        0,
+    );
+
+    let mut value: Vec<u16> = vec![0; len as usize];
+    let len = CertGetNameString(
+        cert,
+        CERT_NAME_SIMPLE_DISPLAY_TYPE,
+        flags,
+        std::ptr::null_mut(),
+        value.as_mut_ptr(),
+        len,
+    );
+
+    // Returned length includes the null terminator.
+    value.truncate(len.saturating_sub(1) as usize);
+    let text = from_wide(&value)?;
+
+    Ok(text)
+}
+
+fn describe(code: i32) -> String {
+    match code {
+        TRUST_E_BAD_DIGEST => "package signature is invalid or the package was modified".to_owned(),
+        CERT_E_EXPIRED => "signer certificate has expired".to_owned(),
+        CERT_E_UNTRUSTEDROOT => "signer certificate is not trusted".to_owned(),
+        TRUST_E_EXPLICIT_DISTRUST => "signer certificate is explicitly distrusted".to_owned(),
+        code => format!("package signature is not trusted (0x{:08X})", code),
+    }
+}
+
+#[link(name = "wintrust")]
+extern "C" {
+    fn WinVerifyTrust(hwnd: *mut c_void, action: *mut Guid, data: *mut c_void) -> i32;
+
+    fn WTHelperProvDataFromStateData(stateData: *mut c_void) -> *mut c_void;
+
+    fn WTHelperGetProvSignerFromChain(
+        providerData: *mut c_void,
+        signerIndex: u32,
+        counterSigner: Win32Bool,
+        counterSignerIndex: u32,
+    ) -> *mut c_void;
+
+    fn WTHelperGetProvCertFromChain(signer: *mut c_void, certIndex: u32) -> *const ProviderCert;
+}
+
+#[link(name = "crypt32")]
+extern "C" {
+    #[link_name = "CertGetNameStringW"]
+    fn CertGetNameString(
+        cert: *const c_void,
+        nameType: u32,
+        flags: u32,
+        typePara: *mut c_void,
+        name: LPWSTR,
+        name_len: u32,
+    ) -> u32;
+
+    fn CertGetCertificateContextProperty(
+        cert: *const c_void,
+        propId: u32,
+        data: *mut c_void,
+        data_len: *mut u32,
+    ) -> Win32Bool;
+}
@@ -0,0 +1,84 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::restart_manager::Session;
+use crate::ffi::Record;
+use crate::Result;
+use std::fmt::Display;
+
+/// An application holding files an install needs to replace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Application {
+    /// The process ID of the application.
+    pub process_id: u32,
+
+    /// The friendly name of the application.
+    pub name: String,
+}
+
+impl Display for Application {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.process_id)
+    }
+}
+
+/// Gets the applications listed in a files-in-use message.
+///
+/// Fields come in pairs of a process ID and application name, in either order.
+pub(crate) fn applications(record: &Record) -> Vec<Application> {
+    let field = |i| record.string_data(i).unwrap_or_default();
+
+    (1..record.field_count())
+        .step_by(2)
+        .filter_map(|i| {
+            let (first, second) = (field(i), field(i + 1));
+            match (first.trim().parse(), second.trim().parse()) {
+                (Ok(process_id), _) => Some(Application {
+                    process_id,
+                    name: second,
+                }),
+                (_, Ok(process_id)) => Some(Application {
+                    process_id,
+                    name: first,
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Applications shut down with the Restart Manager to be restarted after an install.
+#[derive(Debug)]
+pub(crate) struct ClosedApplications {
+    session: Session,
+    applications: Vec<Application>,
+}
+
+impl ClosedApplications {
+    /// Shuts down applications so the files they hold can be replaced without a reboot.
+    pub fn close(applications: Vec<Application>) -> Result<Self> {
+        let session = Session::start()?;
+        let process_ids: Vec<u32> = applications.iter().map(|a| a.process_id).collect();
+        session.register_processes(&process_ids)?;
+        session.shutdown()?;
+
+        Ok(ClosedApplications {
+            session,
+            applications,
+        })
+    }
+
+    pub fn session_key(&self) -> &str {
+        self.session.key()
+    }
+
+    pub fn applications(&self) -> &[Application] {
+        &self.applications
+    }
+
+    /// Restarts the applications that were shut down.
+    pub fn restart(self) -> Result<Vec<Application>> {
+        self.session.restart()?;
+        Ok(self.applications)
+    }
+}
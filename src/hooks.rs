@@ -0,0 +1,182 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Error, HookEvent, Result};
+use regex::Regex;
+use std::io::Write;
+use std::os::windows::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a hook command may run if no timeout is set.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How often to check whether a hook command has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Commands to run when install events occur.
+///
+/// Each command is run by the shell with the triggering [`HookEvent`] serialized as JSON on
+/// stdin. The install waits for each command to exit so it can collect evidence before
+/// continuing, up to a [`timeout`](Self::timeout) after which the command is killed; failures
+/// are reported as warnings and do not fail the install.
+#[derive(Clone, Debug, Default)]
+pub struct Hooks {
+    on_error: Vec<String>,
+    on_complete: Vec<String>,
+    on_action: Vec<(Regex, String)>,
+    timeout: Option<Duration>,
+}
+
+impl Hooks {
+    /// Creates an empty [`Hooks`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs a command when Windows Installer reports an error.
+    pub fn on_error(&mut self, command: impl Into<String>) -> &mut Self {
+        self.on_error.push(command.into());
+        self
+    }
+
+    /// Runs a command when the install completes, whether or not it succeeded.
+    pub fn on_complete(&mut self, command: impl Into<String>) -> &mut Self {
+        self.on_complete.push(command.into());
+        self
+    }
+
+    /// Runs a command when an action with a name matching `pattern` starts.
+    pub fn on_action(&mut self, pattern: &str, command: impl Into<String>) -> Result<&mut Self> {
+        let pattern = Regex::new(pattern).map_err(|err| {
+            Error::verification(format!("invalid action pattern {:?}: {}", pattern, err))
+        })?;
+        self.on_action.push((pattern, command.into()));
+        Ok(self)
+    }
+
+    /// Parses and adds an action hook in the form `PATTERN=COMMAND`.
+    ///
+    /// Only the first `=` separates the pattern from the command.
+    pub fn set_action(&mut self, hook: &str) -> Result<&mut Self> {
+        match hook.split_once('=') {
+            Some((pattern, command)) => self.on_action(pattern, command),
+            None => Err(Error::verification(format!(
+                "invalid action hook {:?}: requires PATTERN=COMMAND",
+                hook
+            ))),
+        }
+    }
+
+    /// Sets how long each command may run before it is killed. The default is 5 minutes.
+    ///
+    /// Only the shell is killed; processes the command started may keep running.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Gets whether no hooks are set.
+    pub fn is_empty(&self) -> bool {
+        self.on_error.is_empty() && self.on_complete.is_empty() && self.on_action.is_empty()
+    }
+
+    pub(crate) fn error(&self, event: &HookEvent) {
+        self.run(&self.on_error, event);
+    }
+
+    pub(crate) fn complete(&self, event: &HookEvent) {
+        self.run(&self.on_complete, event);
+    }
+
+    pub(crate) fn action(&self, event: &HookEvent) {
+        let action = event.action.as_deref().unwrap_or_default();
+        for (pattern, command) in &self.on_action {
+            if pattern.is_match(action) {
+                self.run(std::slice::from_ref(command), event);
+            }
+        }
+    }
+
+    fn run(&self, commands: &[String], event: &HookEvent) {
+        run(commands, event, self.timeout.unwrap_or(DEFAULT_TIMEOUT));
+    }
+}
+
+fn run(commands: &[String], event: &HookEvent, timeout: Duration) {
+    if commands.is_empty() {
+        return;
+    }
+
+    let json = match serde_json::to_vec(event) {
+        Ok(json) => json,
+        Err(err) => {
+            eprintln!(
+                "warning: failed to serialize {} event: {}",
+                event.event, err
+            );
+            return;
+        }
+    };
+
+    for command in commands {
+        if let Err(err) = spawn(command, &json, timeout) {
+            eprintln!(
+                "warning: {} hook {:?} failed: {}",
+                event.event, command, err
+            );
+        }
+    }
+}
+
+fn spawn(command: &str, stdin: &[u8], timeout: Duration) -> std::io::Result<()> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+
+    // Write stdin from another thread so a command that exits or stops reading before the
+    // whole event is written cannot block the install.
+    let writer = child.stdin.take().map(|mut pipe| {
+        let stdin = stdin.to_vec();
+        std::thread::spawn(move || pipe.write_all(&stdin))
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("timed out after {:?}", timeout),
+            ));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    // Processes the command started may still hold stdin open, so only check a finished write.
+    // The command may exit without reading stdin, so ignore a broken pipe.
+    if let Some(writer) = writer.filter(|writer| writer.is_finished()) {
+        match writer.join() {
+            Ok(Err(err)) if err.kind() != std::io::ErrorKind::BrokenPipe => return Err(err),
+            _ => {}
+        }
+    }
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(std::io::Error::other(format!("exited with {}", status))),
+    }
+}
+
+fn shell(command: &str) -> Command {
+    // Pass the command line as-is so cmd.exe handles quoting.
+    let mut shell = Command::new("cmd.exe");
+    shell.arg("/C").raw_arg(command);
+    shell
+}
@@ -0,0 +1,96 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{install, Event, InstallOptions, MessageHandler, Result};
+use std::sync::mpsc::Receiver;
+use std::thread::JoinHandle;
+use std::time::{Instant, SystemTime};
+
+/// Installs a package on another thread and yields its events as they are traced.
+///
+/// ```no_run
+/// use msitrace::{Event, InstallOptions, Installer};
+///
+/// let options = InstallOptions {
+///     quiet: true,
+///     ..Default::default()
+/// };
+/// for event in Installer::new("example.msi", options).run() {
+///     match event {
+///         Event::Message { text, .. } => println!("{}", text),
+///         Event::Completed { result, .. } => result?,
+///         _ => {}
+///     }
+/// }
+/// # Ok::<(), msitrace::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Installer {
+    path: String,
+    options: InstallOptions,
+}
+
+impl Installer {
+    /// Creates an [`Installer`] for the package at `path`.
+    pub fn new(path: impl Into<String>, options: InstallOptions) -> Self {
+        Installer {
+            path: path.into(),
+            options,
+        }
+    }
+
+    /// Starts installing and returns an iterator of its events, ending with
+    /// [`Event::Completed`].
+    ///
+    /// Any [`InstallOptions::handler`] is still called before each message is yielded.
+    /// Messages are also printed unless [`InstallOptions::quiet`] is set.
+    pub fn run(self) -> InstallEvents {
+        let Installer { path, mut options } = self;
+        let start = Instant::now();
+        let (handler, events) = MessageHandler::channel();
+        options.handler = Some(match options.handler.take() {
+            Some(existing) => MessageHandler::chain([existing, handler]),
+            None => handler,
+        });
+
+        let thread = std::thread::spawn(move || install(&path, &options));
+
+        InstallEvents {
+            events,
+            thread: Some(thread),
+            start,
+        }
+    }
+}
+
+/// Events from an install started by [`Installer::run`].
+///
+/// Dropping this before [`Event::Completed`] does not cancel the install; use
+/// [`InstallOptions::cancel`] instead.
+#[derive(Debug)]
+pub struct InstallEvents {
+    events: Receiver<Event>,
+    thread: Option<JoinHandle<Result<()>>>,
+    start: Instant,
+}
+
+impl Iterator for InstallEvents {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        // The channel disconnects when the install drops its options.
+        if let Ok(event) = self.events.recv() {
+            return Some(event);
+        }
+
+        let result = match self.thread.take()?.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        };
+        Some(Event::Completed {
+            time: SystemTime::now(),
+            elapsed: self.start.elapsed(),
+            result,
+        })
+    }
+}
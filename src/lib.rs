@@ -1,19 +1,149 @@
 // Copyright 2022 Heath Stewart.
 // Licensed under the MIT License. See LICENSE.txt in the project root for license information.
 
+#[cfg(windows)]
+use files_in_use::ClosedApplications;
+#[cfg(windows)]
+use std::cell::{Cell, RefCell};
 use std::ffi::NulError;
 use std::fmt::Display;
-use std::string::FromUtf8Error;
-use time::OffsetDateTime;
+#[cfg(windows)]
+use std::panic::AssertUnwindSafe;
+#[cfg(windows)]
+use std::path::Path;
+#[cfg(all(windows, feature = "json"))]
+use std::path::PathBuf;
+use std::string::{FromUtf16Error, FromUtf8Error};
+#[cfg(windows)]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(windows)]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(windows)]
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
+#[cfg(windows)]
+use std::time::{Duration, Instant, SystemTime};
+#[cfg(windows)]
+use time::{OffsetDateTime, UtcOffset};
+#[cfg(windows)]
+use version::require_installer;
 
+#[cfg(windows)]
+mod admin;
+#[cfg(windows)]
+mod advertise;
+#[cfg(windows)]
+mod arp;
+#[cfg(all(windows, feature = "async"))]
+mod asynchronous;
+#[cfg(any(windows, feature = "portable-db"))]
+mod audit;
+#[cfg(windows)]
+mod bundle;
+#[cfg(windows)]
+mod cancel;
+mod chain;
+mod command_line;
+#[cfg(any(windows, feature = "portable-db"))]
+mod database;
+#[cfg(any(windows, feature = "portable-db"))]
+mod diff;
+#[cfg(windows)]
+mod doctor;
+#[cfg(windows)]
+mod download;
+#[cfg(windows)]
+mod elevation;
+#[cfg(any(windows, feature = "portable-db"))]
+mod estimate;
+mod event;
+#[cfg(windows)]
+mod feature;
+#[cfg(windows)]
 mod ffi;
+#[cfg(windows)]
+mod files_in_use;
+#[cfg(all(windows, feature = "hooks"))]
+mod hooks;
+#[cfg(windows)]
+mod installer;
+mod locale;
+#[cfg(windows)]
+mod manifest;
+#[cfg(windows)]
+mod message;
+#[cfg(windows)]
+mod package;
+#[cfg(windows)]
+mod patch;
+#[cfg(windows)]
+mod pending;
+#[cfg(windows)]
+mod policy;
+#[cfg(windows)]
+mod preflight;
+#[cfg(windows)]
+mod product;
+mod progress;
+#[cfg(windows)]
+mod prompt;
+#[cfg(windows)]
+mod provide;
+#[cfg(windows)]
+mod queue;
+#[cfg(windows)]
+mod recover;
+#[cfg(windows)]
+mod service;
+#[cfg(windows)]
+mod signature;
+#[cfg(windows)]
+mod source;
+#[cfg(windows)]
+mod transform;
+#[cfg(windows)]
+mod verify;
+#[cfg(windows)]
+mod version;
+#[cfg(windows)]
+mod wine;
+
+#[cfg(windows)]
+pub use admin::{admin_install, patch_admin_image};
+#[cfg(windows)]
+pub use advertise::{advertise, process_advertise_script, AdvertiseTarget};
+#[cfg(windows)]
+pub use arp::{arp_issues, ArpIssue};
+#[cfg(all(windows, feature = "async"))]
+pub use asynchronous::{install_async, EventStream, InstallTask};
+#[cfg(any(windows, feature = "portable-db"))]
+pub use audit::{audit_custom_actions, CustomActionAudit, CustomActionCode, Scheduling};
+#[cfg(windows)]
+pub use bundle::{extract_bundle, is_bundle};
+#[cfg(windows)]
+pub use cancel::CancellationToken;
+pub use chain::{ChainManifest, ChainPackage};
+pub use command_line::{CommandLine, InstallContext, Redacted, REDACTED};
+#[cfg(any(windows, feature = "portable-db"))]
+pub use database::{Column, Database, Row, SummaryInfo, Value, View};
+#[cfg(any(windows, feature = "portable-db"))]
+pub use diff::{diff, CellDiff, DatabaseDiff, DiffStatus, RowDiff, TableDiff};
+#[cfg(windows)]
+pub use download::download;
+#[cfg(any(windows, feature = "portable-db"))]
+pub use estimate::{estimate, Estimate, InstallStats};
+pub use event::{
+    ActionDuration, HookEvent, InstallSummary, LocalizedSummary, TraceEvent, SCHEMA_VERSION,
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 enum ErrorKind {
     ErrorCode(u32),
-    Other(Box<dyn std::error::Error>),
+    InvalidProperty(String),
+    Verification(String),
+    Cancelled,
+    Other(Box<dyn std::error::Error + Send + Sync>),
 }
 
 #[derive(Debug)]
@@ -25,11 +155,59 @@ impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.kind {
             ErrorKind::ErrorCode(code) => write!(f, "{}", code),
+            ErrorKind::InvalidProperty(property) => {
+                write!(
+                    f,
+                    "invalid property {:?}: requires PROP= or PROP=VALUE",
+                    property
+                )
+            }
+            ErrorKind::Verification(message) => f.write_str(message),
+            ErrorKind::Cancelled => f.write_str("the operation was cancelled"),
             ErrorKind::Other(err) => write!(f, "{:?}", err),
         }
     }
 }
 
+impl Error {
+    /// Gets the Windows error code, if any, such as the result of an install.
+    ///
+    /// Cancelled operations return `ERROR_INSTALL_USEREXIT` (1602).
+    pub fn code(&self) -> Option<u32> {
+        match self.kind {
+            ErrorKind::ErrorCode(code) => Some(code),
+            ErrorKind::Cancelled => Some(ERROR_INSTALL_USEREXIT),
+            _ => None,
+        }
+    }
+
+    /// Gets whether the operation failed because it was cancelled, e.g. with a
+    /// `CancellationToken`.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.kind, ErrorKind::Cancelled)
+    }
+
+    pub(crate) fn invalid_property(property: impl Into<String>) -> Self {
+        Error {
+            kind: ErrorKind::InvalidProperty(property.into()),
+        }
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn verification(message: impl Into<String>) -> Self {
+        Error {
+            kind: ErrorKind::Verification(message.into()),
+        }
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn cancelled() -> Self {
+        Error {
+            kind: ErrorKind::Cancelled,
+        }
+    }
+}
+
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &self.kind {
@@ -55,6 +233,22 @@ impl From<FromUtf8Error> for Error {
     }
 }
 
+impl From<FromUtf16Error> for Error {
+    fn from(err: FromUtf16Error) -> Self {
+        Error {
+            kind: ErrorKind::Other(Box::new(err)),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error {
+            kind: ErrorKind::Other(Box::new(err)),
+        }
+    }
+}
+
 impl From<NulError> for Error {
     fn from(err: NulError) -> Self {
         Error {
@@ -63,26 +257,1184 @@ impl From<NulError> for Error {
     }
 }
 
-pub use ffi::UILevel;
-pub fn install(
-    path: &str,
-    log: Option<String>,
-    ui: UILevel,
-    properties: Vec<String>,
+#[cfg(windows)]
+pub use doctor::{diagnose, Check, CheckStatus};
+#[cfg(windows)]
+pub use elevation::{attach_output, is_elevated, quote_argument, requires_elevation, run_elevated};
+#[cfg(windows)]
+pub use feature::{configure_feature, repair_feature, FeatureState};
+#[cfg(windows)]
+pub use ffi::bcrypt::random_bytes;
+#[cfg(windows)]
+pub use ffi::{
+    Buttons, ExternalUI, HandlerResult, Icon, MessageFilter, MessageType, ReinstallMode,
+    Transaction, UILevel,
+};
+#[cfg(windows)]
+pub use files_in_use::Application;
+#[cfg(all(windows, feature = "hooks"))]
+pub use hooks::Hooks;
+#[cfg(windows)]
+pub use installer::{InstallEvents, Installer};
+pub use locale::{Label, Language};
+#[cfg(windows)]
+pub use manifest::{Authorization, Manifest, ManifestEntry};
+#[cfg(windows)]
+pub use message::{ActionDataSampler, ActionScope, ActionTracker, CommonData, Event, Message};
+#[cfg(windows)]
+pub use package::{is_valid_package, select_language, verify_package, LanguageSelection};
+#[cfg(windows)]
+pub use patch::{
+    apply_patches, check_patches, inspect_patch, patch_xml, PatchApplicability, PatchInfo,
+    PatchSequence, PatchTransform,
+};
+#[cfg(windows)]
+pub use pending::{pending_operations, PendingOperation};
+#[cfg(windows)]
+pub use policy::{
+    always_install_elevated, disable_logging_policy, enable_logging_policy, installer_policy,
+    logging_policy, AlwaysInstallElevated, InstallerPolicy, LoggingPolicy, LoggingPolicyGuard,
+};
+#[cfg(windows)]
+pub use preflight::{preflight_package, PreflightCheck};
+#[cfg(windows)]
+pub use product::{
+    cached_package, find_products, matches_glob, related_products, repair, repair_cache, uninstall,
+    CachedPackage, InstalledProduct,
+};
+pub use progress::{Progress, ProgressUpdate};
+#[cfg(windows)]
+pub use provide::{provide_component, provide_qualified_component, ProvideMode};
+#[cfg(windows)]
+pub use queue::{is_installer_busy, InstallQueue};
+#[cfg(windows)]
+pub use recover::{recover_component, recover_file};
+#[cfg(windows)]
+pub use service::{
+    installer_service, installer_status, start_installer_service, InstallerStatus, ServiceState,
+};
+#[cfg(windows)]
+pub use signature::{verify_signature, verify_signature_if_signed, Signer};
+#[cfg(windows)]
+pub use source::{add_product_source, evaluate_source, Source};
+#[cfg(windows)]
+pub use transform::{inspect_transform, transform_changes, TransformChange, TransformInfo};
+#[cfg(windows)]
+pub use verify::{verify_install, ComponentCheck};
+#[cfg(windows)]
+pub use version::{installer_version, InstallerVersion};
+#[cfg(windows)]
+pub use wine::wine_version;
+
+/// The install succeeded but requires a reboot to complete.
+#[cfg(windows)]
+const ERROR_SUCCESS_REBOOT_REQUIRED: u32 = 3010;
+
+/// Messages traced for the summary and hooks whether or not [`InstallOptions::messages`]
+/// selects them.
+#[cfg(windows)]
+const SUMMARY_MESSAGES: MessageFilter = MessageFilter::FATAL_EXIT
+    .union(MessageFilter::ERROR)
+    .union(MessageFilter::WARNING)
+    .union(MessageFilter::ACTION_START);
+
+/// The user or a `CancellationToken` cancelled the install.
+const ERROR_INSTALL_USEREXIT: u32 = 1602;
+
+/// Another install is already in progress.
+#[cfg(windows)]
+const ERROR_INSTALL_ALREADY_RUNNING: u32 = 1618;
+
+/// The installation source for a product is not available.
+#[cfg(windows)]
+const ERROR_INSTALL_SOURCE_ABSENT: u32 = 1612;
+
+/// A cached patch package could not be opened, commonly because it was deleted.
+#[cfg(windows)]
+const ERROR_PATCH_PACKAGE_OPEN_FAILED: u32 = 1635;
+
+/// Options for [`install`].
+#[cfg(windows)]
+#[derive(Clone, Debug, Default)]
+pub struct InstallOptions {
+    /// Path to a verbose log file to write.
+    pub log: Option<String>,
+
+    /// Path to write a JSON [`InstallSummary`] to after the install completes.
+    #[cfg(feature = "json")]
+    pub summary_out: Option<PathBuf>,
+
+    /// The user interface level to show.
+    pub ui: UILevel,
+
+    /// Properties to pass to the install.
+    pub properties: CommandLine,
+
+    /// The language identifier (LCID) to install a multi-language package in, e.g. 1031.
+    /// Sets `ProductLanguage` and applies the matching embedded language transform, if any.
+    pub product_language: Option<u16>,
+
+    /// Transforms to apply, appended to any `TRANSFORMS` in `properties`. Transforms embedded
+    /// in the package are named with a leading colon, e.g. `:fr-FR.mst`.
+    pub transforms: Vec<String>,
+
+    /// Full paths of patches to slipstream into the install, appended to any `PATCH`
+    /// in `properties`.
+    pub patches: Vec<String>,
+
+    /// Require the package have a trusted Authenticode signature before installing.
+    pub require_signed: bool,
+
+    /// Require the signer certificate have this SHA-1 thumbprint. Implies `require_signed`.
+    pub publisher_thumbprint: Option<String>,
+
+    /// With `require_signed`, also allow packages that are not signed at all.
+    /// Signatures that are present must still be trusted.
+    pub allow_unsigned: bool,
+
+    /// Require the package, or the package downloaded from a URL, have this SHA-256 digest.
+    pub sha256: Option<String>,
+
+    /// Require the package be listed in this manifest with a matching SHA-256 digest.
+    pub manifest: Option<Manifest>,
+
+    /// Allow installing from plain HTTP URLs or network shares anonymous users can write to.
+    pub allow_insecure_source: bool,
+
+    /// Refuse to install when the `AlwaysInstallElevated` policy is enabled instead of warning.
+    pub strict_policy: bool,
+
+    /// Shut down applications holding files in use with the Restart Manager, and restart them
+    /// after installing, to avoid a reboot.
+    pub close_apps: bool,
+
+    /// Start the Windows Installer service before installing if it is stopped.
+    pub start_service: bool,
+
+    /// Refuse to install when operations from previous installs or updates are pending
+    /// instead of warning.
+    pub require_clean_state: bool,
+
+    /// How to respond when files the install needs to replace are in use. Overrides
+    /// `close_apps` and `interactive` for files-in-use messages.
+    pub files_in_use: Option<FilesInUsePolicy>,
+
+    /// Limit how Windows Installer uses the Restart Manager to detect and close
+    /// applications holding files in use.
+    pub restart_manager: Option<RestartManagerControl>,
+
+    /// What to do when the install requires a reboot.
+    pub reboot: Option<Reboot>,
+
+    /// How long to wait before a scheduled reboot.
+    pub reboot_delay: Duration,
+
+    /// A message to show users before a scheduled reboot.
+    pub reboot_message: Option<String>,
+
+    /// After a successful install, verify the key path of every installed component exists.
+    pub verify: bool,
+
+    /// Also verify unversioned key files match their `MsiFileHash`. Implies `verify`.
+    pub verify_hashes: bool,
+
+    /// Commands to run when install events occur.
+    #[cfg(feature = "hooks")]
+    pub hooks: Hooks,
+
+    /// Called with the package path and the assembled properties just before installing,
+    /// and may change them.
+    pub before_install: Option<CommandLineHook>,
+
+    /// Receives each message traced during the install. Use [`MessageHandler::chain`]
+    /// to pass messages to several handlers.
+    pub handler: Option<MessageHandler>,
+
+    /// Which Windows Installer function receives traced messages. Messages received as text
+    /// are not parsed from their fields, so e.g. a progress bar cannot be drawn.
+    ///
+    /// [`ExternalUI::String`] cannot be used with `close_apps`, `files_in_use`, or `interactive`,
+    /// which need the applications listed in files-in-use records.
+    pub external_ui: ExternalUI,
+
+    /// Which types of messages to trace. Windows Installer handles the rest itself,
+    /// e.g. showing errors in its own user interface if one is shown.
+    ///
+    /// Errors, warnings, and action starts are still received for the summary and hooks, as
+    /// are files in use for the options that respond to them, but are not printed or passed
+    /// to `handler` unless selected.
+    pub messages: MessageFilter,
+
+    /// The language to render trace labels in.
+    pub language: Language,
+
+    /// Prompt on the terminal how to respond to errors and files in use instead of returning
+    /// the default response, which usually fails an install without a user interface.
+    pub interactive: bool,
+
+    /// The handle of a [`Transaction`] begun by another process to join before installing,
+    /// so the package is committed or rolled back with the rest of the transaction.
+    pub transaction: Option<u32>,
+
+    /// If another install is in progress, wait up to this long for it to finish and retry
+    /// instead of failing with error 1618.
+    pub wait_busy: Option<Duration>,
+
+    /// Cancels the operation and rolls it back when cancelled.
+    pub cancel: Option<CancellationToken>,
+
+    /// Set the `Logging` machine policy to these modes, e.g. "voicewarmupx", during the install
+    /// so packages it starts in other processes are also logged.
+    pub logging_policy: Option<String>,
+
+    /// A directory containing the original package to add to the source list of an installed
+    /// product, so repairs and maintenance installs do not fail when the cached source is missing.
+    pub source: Option<String>,
+
+    /// How to print action data messages. Handlers still receive every one.
+    pub action_data: ActionDataMode,
+
+    /// Print only errors and warnings as they are traced, e.g. while [`handler`](Self::handler)
+    /// shows a progress bar. The header and summary are still printed.
+    pub quiet: bool,
+}
+
+/// Receives the path of a package and its assembled properties just before it is installed,
+/// e.g. to set a computed `INSTALLDIR` or hide a property so its value is redacted.
+///
+/// Returning an error fails the install before it starts.
+#[cfg(windows)]
+#[derive(Clone)]
+pub struct CommandLineHook(Arc<CommandLineHookFn>);
+
+#[cfg(windows)]
+type CommandLineHookFn = dyn Fn(&str, &mut CommandLine) -> Result<()> + Send + Sync;
+
+#[cfg(windows)]
+impl CommandLineHook {
+    /// Creates a [`CommandLineHook`] that calls `f` with the package path and its properties.
+    pub fn new(f: impl Fn(&str, &mut CommandLine) -> Result<()> + Send + Sync + 'static) -> Self {
+        CommandLineHook(Arc::new(f))
+    }
+
+    pub(crate) fn call(&self, path: &str, properties: &mut CommandLine) -> Result<()> {
+        (self.0)(path, properties)
+    }
+}
+
+#[cfg(windows)]
+impl std::fmt::Debug for CommandLineHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CommandLineHook")
+    }
+}
+
+/// Receives each message traced during an install, either as its type and redacted text
+/// or parsed into a [`Message`], and may respond to it.
+#[cfg(windows)]
+#[derive(Clone)]
+pub struct MessageHandler(Arc<HandlerFn>);
+
+#[cfg(windows)]
+type HandlerFn = dyn Fn(&Message, u32, &str) -> HandlerResult + Send + Sync;
+
+#[cfg(windows)]
+impl MessageHandler {
+    /// Creates a [`MessageHandler`] that calls `f` with the type and text of each message.
+    pub fn new(f: impl Fn(MessageType, &str) + Send + Sync + 'static) -> Self {
+        MessageHandler(Arc::new(move |message: &Message, _: u32, text: &str| {
+            f(message.message_type(), text);
+            HandlerResult::Default
+        }))
+    }
+
+    /// Creates a [`MessageHandler`] that calls `f` with each parsed [`Message`].
+    pub fn parsed(f: impl Fn(&Message) + Send + Sync + 'static) -> Self {
+        MessageHandler(Arc::new(move |message: &Message, _: u32, _: &str| {
+            f(message);
+            HandlerResult::Default
+        }))
+    }
+
+    /// Creates a [`MessageHandler`] that answers messages, e.g. to retry or ignore an error.
+    ///
+    /// `f` receives each parsed [`Message`] and the message box flags in the low bits of its
+    /// type. Responses a message does not accept, as described by
+    /// [`HandlerResult::is_valid_for`], are replaced with [`HandlerResult::Default`].
+    pub fn responder(f: impl Fn(&Message, u32) -> HandlerResult + Send + Sync + 'static) -> Self {
+        MessageHandler(Arc::new(move |message: &Message, flags: u32, _: &str| {
+            f(message, flags)
+        }))
+    }
+
+    /// Creates a [`MessageHandler`] that sends each message and the end of each action as an
+    /// [`Event`] to the returned [`Receiver`], so another thread can format and write them
+    /// without slowing the install.
+    ///
+    /// The [`Event::elapsed`] time of each event is measured from when the handler is created.
+    /// The channel is disconnected when the handler and all its clones are dropped, e.g. with
+    /// the [`InstallOptions`] that hold it. Set [`InstallOptions::quiet`] so that messages are
+    /// not also printed while installing.
+    pub fn channel() -> (Self, Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel();
+        // The receiver may have been dropped if the consumer is no longer interested.
+        let handler = MessageHandler::events(move |event| {
+            let _ = sender.send(event);
+        });
+
+        (handler, receiver)
+    }
+
+    /// Creates a [`MessageHandler`] that calls `f` with an owned [`Event`] for each message
+    /// and the end of each action.
+    ///
+    /// Each [`Event::Message`] includes the action running when it was received, as tracked
+    /// by an [`ActionTracker`] created with the handler.
+    pub fn events(f: impl Fn(Event) + Send + Sync + 'static) -> Self {
+        let actions = Mutex::new(ActionTracker::new(Instant::now()));
+        MessageHandler(Arc::new(move |message: &Message, _: u32, text: &str| {
+            // Read the clocks once so all events for this message agree.
+            let (time, now) = (SystemTime::now(), Instant::now());
+            let mut actions = actions.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(end) = actions.observe(message, time, now) {
+                f(end);
+            }
+            f(Event::Message {
+                time,
+                elapsed: actions.elapsed(now),
+                message: message.clone(),
+                text: text.to_owned(),
+                action: actions.current(now),
+            });
+            HandlerResult::Default
+        }))
+    }
+
+    /// Creates a [`MessageHandler`] that passes each message to all `handlers` in order,
+    /// e.g. to print a progress bar and write events to a file.
+    ///
+    /// Every handler receives every message. The first response other than
+    /// [`HandlerResult::Default`] is returned, but handlers after it are still called.
+    /// A handler that panics is skipped for the rest of the install with a warning so the
+    /// others keep receiving messages.
+    pub fn chain(handlers: impl IntoIterator<Item = MessageHandler>) -> Self {
+        let handlers: Vec<_> = handlers
+            .into_iter()
+            .map(|handler| (handler, AtomicBool::new(false)))
+            .collect();
+
+        MessageHandler(Arc::new(
+            move |message: &Message, flags: u32, text: &str| {
+                let mut response = HandlerResult::Default;
+                for (i, (handler, panicked)) in handlers.iter().enumerate() {
+                    if panicked.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        handler.call(message, flags, text)
+                    }));
+                    match result {
+                        Ok(result) if response == HandlerResult::Default => response = result,
+                        Ok(_) => {}
+                        Err(_) => {
+                            panicked.store(true, Ordering::Relaxed);
+                            eprintln!(
+                                "warning: message handler {} panicked and was removed",
+                                i + 1
+                            );
+                        }
+                    }
+                }
+
+                response
+            },
+        ))
+    }
+
+    pub(crate) fn call(&self, message: &Message, flags: u32, text: &str) -> HandlerResult {
+        match (self.0)(message, flags, text) {
+            result if result.is_valid_for(message.message_type(), flags) => result,
+            _ => HandlerResult::Default,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl std::fmt::Debug for MessageHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MessageHandler")
+    }
+}
+
+/// What to do when an install requires a reboot.
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Reboot {
+    /// Schedule a restart after [`InstallOptions::reboot_delay`].
+    Schedule,
+
+    /// Suppress reboots except those a `ForceReboot` action requires, setting `REBOOT=Suppress`.
+    Suppress,
+
+    /// Suppress all reboots, setting `REBOOT=ReallySuppress`.
+    ReallySuppress,
+
+    /// Always reboot after the install, setting `REBOOT=Force`.
+    Force,
+
+    /// Prompt before rebooting even if the package suppresses prompts, setting `REBOOTPROMPT=""`.
+    Prompt,
+}
+
+#[cfg(windows)]
+impl Reboot {
+    /// Sets the `REBOOT` or `REBOOTPROMPT` property for this policy.
+    fn apply(self, properties: &mut CommandLine) -> Result<()> {
+        // cspell:ignore REBOOTPROMPT
+        match self {
+            Reboot::Schedule => return Ok(()),
+            Reboot::Suppress => properties.set("REBOOT", "Suppress")?,
+            Reboot::ReallySuppress => properties.set("REBOOT", "ReallySuppress")?,
+            Reboot::Force => properties.set("REBOOT", "Force")?,
+            Reboot::Prompt => properties.set("REBOOTPROMPT", "")?,
+        };
+
+        Ok(())
+    }
+}
+
+/// How to respond when applications hold files an install needs to replace.
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum FilesInUsePolicy {
+    /// Shut down the applications with the Restart Manager and restart them after installing.
+    Close,
+
+    /// Continue installing, which replaces the files when the machine reboots.
+    Ignore,
+
+    /// Ask on the terminal whether to close the applications, ignore them, or cancel.
+    Prompt,
+
+    /// Cancel the install and roll it back.
+    Fail,
+}
+
+/// How to print action data messages.
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ActionDataMode {
+    /// Print every action data message.
+    #[default]
+    All,
+
+    /// Print at most one action data message every 100 milliseconds, and the last of each
+    /// action. Packages with many files send thousands of them.
+    Sample,
+
+    /// Print no action data messages.
+    None,
+}
+
+/// How often to print action data messages with [`ActionDataMode::Sample`].
+#[cfg(windows)]
+const ACTION_DATA_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How Windows Installer uses the Restart Manager, set with `MSIRESTARTMANAGERCONTROL`.
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum RestartManagerControl {
+    /// Do not use the Restart Manager, showing the legacy files-in-use prompt instead.
+    Disable,
+
+    /// Use the Restart Manager to detect applications holding files in use but never
+    /// shut them down.
+    DisableShutdown,
+}
+
+#[cfg(windows)]
+impl RestartManagerControl {
+    fn apply(self, properties: &mut CommandLine) -> Result<()> {
+        let value = match self {
+            RestartManagerControl::Disable => "Disable",
+            RestartManagerControl::DisableShutdown => "DisableShutdown",
+        };
+        properties.set("MSIRESTARTMANAGERCONTROL", value)?;
+
+        Ok(())
+    }
+}
+
+/// Installs a package and traces its actions.
+///
+/// Concurrent calls from multiple threads wait for earlier installs to finish.
+#[cfg(windows)]
+pub fn install(path: &str, options: &InstallOptions) -> Result<()> {
+    let _lock = lock();
+
+    let source = evaluate_source(path)?;
+    if let Some(reason) = source.insecure_reason() {
+        if !options.allow_insecure_source {
+            return Err(Error::verification(format!(
+                "package source is insecure: {}",
+                reason
+            )));
+        }
+    }
+
+    let service = preflight(options)?;
+    if let Some(transaction) = options.transaction {
+        require_installer(4, 5, "joining a transaction")?;
+        ffi::join_transaction(transaction)?;
+    }
+
+    // Download packages so they can be verified like local packages.
+    let downloaded = match source {
+        Source::Url { .. } => Some(download(path, options.sha256.as_deref())?),
+        _ => {
+            if let Some(expected) = &options.sha256 {
+                download::verify_digest(Path::new(path), expected)?;
+            }
+            None
+        }
+    };
+    let downloaded = downloaded.map(|path| path.to_string_lossy().into_owned());
+    let path = downloaded.as_deref().unwrap_or(path);
+
+    verify_package(path)?;
+
+    let selected;
+    let (options, language) = match options.product_language {
+        Some(lcid) => {
+            let language = select_language(path, lcid)?;
+            let mut options = options.clone();
+            options
+                .properties
+                .set("ProductLanguage", lcid.to_string())?;
+            options.transforms.extend(language.transform.clone());
+            selected = options;
+            (&selected, Some(language))
+        }
+        None => (options, None),
+    };
+
+    // Without a user interface Windows Installer cannot prompt for consent to elevate.
+    if matches!(options.ui, UILevel::None) && !is_elevated()? && requires_elevation(path, options)?
+    {
+        return Err(Error::verification(
+            "package installs per-machine and requires elevation; \
+            run elevated or show a user interface to prompt for consent",
+        ));
+    }
+
+    let signer = signature::check_signature(path, options)?;
+
+    let authorization = match &options.manifest {
+        Some(manifest) => Some(manifest.authorize(path)?),
+        None => None,
+    };
+
+    let mut properties = install_properties(options)?;
+    let prepared;
+    let options = match &options.before_install {
+        Some(hook) => {
+            hook.call(path, &mut properties)?;
+            // Trace with the final properties so any the hook hid are redacted.
+            let mut options = options.clone();
+            options.properties = properties.clone();
+            prepared = options;
+            &prepared
+        }
+        None => options,
+    };
+    let command_line = properties.to_install_string();
+
+    let header = Header {
+        path,
+        source: &source,
+        service,
+        version: installer_version().ok(),
+        command_line: properties.redacted(),
+        signer: signer.as_ref(),
+        authorization: authorization.as_ref(),
+        language: options.language,
+    };
+    print!("{}", header);
+    if let Some(language) = &language {
+        println!(
+            "{}: {}",
+            options.language.label(Label::ProductLanguage),
+            language
+        );
+    }
+
+    let install = || {
+        trace(path, options, || {
+            ffi::install_package(path, command_line.as_str())
+        })
+    };
+    let mut result = install();
+    if let Some(wait_busy) = options.wait_busy {
+        let deadline = Instant::now() + wait_busy;
+        let mut attempt = 1;
+        while result.as_ref().err().and_then(Error::code) == Some(ERROR_INSTALL_ALREADY_RUNNING) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                println!("Another install is still in progress after {:?}", wait_busy);
+                break;
+            }
+
+            println!(
+                "Attempt {} failed because another install is in progress; waiting up to {:?}",
+                attempt, remaining
+            );
+            // The mutex is only held while an install runs its execution script, so also wait
+            // briefly for installs that have not started executing yet.
+            if queue::wait_for_installer_for(remaining)? {
+                std::thread::sleep(remaining.min(Duration::from_secs(1)));
+            }
+
+            attempt += 1;
+            println!("Retrying install, attempt {}", attempt);
+            result = install();
+        }
+    }
+
+    let succeeded = match &result {
+        Ok(()) => true,
+        Err(err) => err.code() == Some(ERROR_SUCCESS_REBOOT_REQUIRED),
+    };
+    if succeeded && (options.verify || options.verify_hashes) {
+        verify(path, options)?;
+    }
+
+    result
+}
+
+/// Gets the properties to install with, including those set by other `options`.
+#[cfg(windows)]
+pub(crate) fn install_properties(options: &InstallOptions) -> Result<CommandLine> {
+    let mut properties = options.properties.clone();
+    properties.add_transforms(&options.transforms)?;
+    properties.add_patches(&options.patches)?;
+    if let Some(restart_manager) = options.restart_manager {
+        restart_manager.apply(&mut properties)?;
+    }
+    if let Some(reboot) = options.reboot {
+        reboot.apply(&mut properties)?;
+    }
+
+    Ok(properties)
+}
+
+/// Waits for other operations in this process to finish.
+///
+/// The internal UI level and external handler are process-wide, so operations must not overlap.
+#[cfg(windows)]
+pub(crate) fn lock() -> MutexGuard<'static, ()> {
+    static INSTALL: Mutex<()> = Mutex::new(());
+    match INSTALL.try_lock() {
+        Ok(lock) => lock,
+        Err(TryLockError::Poisoned(err)) => err.into_inner(),
+        Err(TryLockError::WouldBlock) => {
+            println!("Waiting for another msitrace operation to finish");
+            INSTALL.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+}
+
+/// Checks for policies, services, and pending operations that would cause any operation to fail.
+#[cfg(windows)]
+pub(crate) fn preflight(options: &InstallOptions) -> Result<ServiceState> {
+    let policy = always_install_elevated()?;
+    if policy.is_enabled() {
+        if options.strict_policy {
+            return Err(Error::verification(
+                "AlwaysInstallElevated policy is enabled; all packages install elevated",
+            ));
+        }
+
+        eprintln!("****************************************************************");
+        eprintln!("warning: AlwaysInstallElevated policy is enabled; every per-user");
+        eprintln!("install, including this one, runs with elevated privileges");
+        eprintln!("****************************************************************");
+    }
+
+    // Check the properties that will be passed, including PATCH from `patches`.
+    for conflict in installer_policy()?.conflicts(&install_properties(options)?) {
+        eprintln!("warning: {}", conflict);
+    }
+
+    // External UI records require 3.1, and Restart Manager messages require 4.0.
+    require_installer(3, 1, "tracing install messages")?;
+    if options.close_apps {
+        require_installer(4, 0, "closing applications with Restart Manager")?;
+    }
+
+    let service = match options.start_service {
+        true => start_installer_service()?,
+        false => installer_service()?,
+    };
+    if let Some(reason) = service.failure_reason() {
+        return Err(Error::verification(reason));
+    }
+
+    let pending = pending_operations()?;
+    if !pending.is_empty() {
+        let pending: Vec<String> = pending.iter().map(ToString::to_string).collect();
+        if options.require_clean_state {
+            return Err(Error::verification(format!(
+                "operations are pending: {}",
+                pending.join("; ")
+            )));
+        }
+
+        for operation in pending {
+            eprintln!("warning: {}", operation);
+        }
+    }
+
+    Ok(service)
+}
+
+/// Runs an operation with the user interface, log, and handler from `options` and
+/// traces its messages.
+///
+/// The `target` is the package or product passed to hooks.
+#[cfg(windows)]
+pub(crate) fn trace(
+    target: &str,
+    options: &InstallOptions,
+    operation: impl FnOnce() -> Result<()>,
 ) -> Result<()> {
-    let command_line = properties.join(" ");
+    let properties = &options.properties;
+    let is_cancelled = || {
+        options
+            .cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    };
+    if is_cancelled() {
+        return Err(Error::cancelled());
+    }
+
+    // Files in use are only reported in records, so handling them would silently do nothing.
+    if options.external_ui == ExternalUI::String
+        && (options.close_apps || options.files_in_use.is_some() || options.interactive)
+    {
+        return Err(Error::verification(
+            "closing applications, responding to files in use, and prompting require message records",
+        ));
+    }
+
+    // Wine does not implement all logging and external UI functionality, so continue without it.
+    let _ui = ffi::set_internal_ui(options.ui.clone());
+    if let Some(log) = &options.log {
+        match ffi::enable_log(log.as_str()) {
+            Err(err) if wine_version().is_some() => {
+                eprintln!(
+                    "warning: verbose logging is not supported under Wine: {}",
+                    err
+                )
+            }
+            result => result?,
+        }
+    }
+
+    let _logging_policy = match &options.logging_policy {
+        Some(mode) => Some(LoggingPolicyGuard::new(mode, None)?),
+        None => None,
+    };
+
+    let closed: RefCell<Vec<ClosedApplications>> = RefCell::new(Vec::new());
+    let start = Instant::now();
+    let counts = Cell::new((0, 0));
+    let resolving_source = Cell::new(false);
+    let action: RefCell<Option<(String, Instant)>> = RefCell::new(None);
+    let actions: RefCell<Vec<(String, Duration)>> = RefCell::new(Vec::new());
+    let end_action = || {
+        if let Some((name, started)) = action.take() {
+            actions.borrow_mut().push((name, started.elapsed()));
+        }
+    };
+    // Only look up the time zone when printing since it is not free.
+    let print_message = |message: MessageType, time: OffsetDateTime, text: &str| {
+        let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        println!(
+            "{:?} ({}) {}",
+            time.to_offset(offset),
+            options.language.message_type(message),
+            text
+        );
+    };
+    let sampler: RefCell<ActionDataSampler<(OffsetDateTime, String)>> =
+        RefCell::new(ActionDataSampler::new(ACTION_DATA_INTERVAL));
+    let flush_skipped = || {
+        if let Some((time, text)) = sampler.borrow_mut().take_skipped() {
+            print_message(MessageType::ActionData, time, &text);
+        }
+    };
+    // Errors and actions are always needed for the summary and hooks, and files in use for
+    // the options that respond to them, but are only traced if selected.
+    let mut filter = options.messages | SUMMARY_MESSAGES;
+    if options.close_apps || options.files_in_use.is_some() || options.interactive {
+        filter |= MessageFilter::FILES_IN_USE | MessageFilter::RM_FILES_IN_USE;
+    }
+    // The guard is dropped at the end of this function, before anything the handler borrows.
+    let handler = unsafe {
+        ffi::set_external_handler(filter, options.external_ui, |message, flags, data| {
+            let selected = options.messages.contains(MessageFilter::from(message));
+            let (record, text) = match data {
+                // Some messages have no record and are reported only by their type.
+                ffi::MessageData::Record(record) => (
+                    record,
+                    record.map_or_else(String::new, |r| properties.redact(&r.to_string())),
+                ),
+                ffi::MessageData::Text(text) => (None, properties.redact(text)),
+            };
+            // Handlers are called first so those drawing on the console can clear it.
+            let response = match &options.handler {
+                Some(handler) if selected => handler.call(
+                    &Message::parse(message, flags, record, text.clone(), |s| {
+                        properties.redact(s)
+                    }),
+                    flags,
+                    &text,
+                ),
+                _ => HandlerResult::Default,
+            };
 
-    ffi::set_internal_ui(ui);
-    if let Some(log) = log {
-        ffi::enable_log(log.as_str())?;
+            // Progress is sent many times a second and is unreadable as text.
+            let important = matches!(
+                message,
+                MessageType::FatalExit
+                    | MessageType::Error
+                    | MessageType::Warning
+                    | MessageType::FilesInUse
+                    | MessageType::RMFilesInUse
+            );
+            if selected && message != MessageType::Progress && (important || !options.quiet) {
+                match (message, options.action_data) {
+                    (MessageType::ActionData, ActionDataMode::None) => {}
+                    (MessageType::ActionData, ActionDataMode::Sample) => {
+                        let mut sampler = sampler.borrow_mut();
+                        match sampler.should_print(Instant::now()) {
+                            true => print_message(message, OffsetDateTime::now_utc(), &text),
+                            false => sampler.skip((OffsetDateTime::now_utc(), text.clone())),
+                        }
+                    }
+                    (MessageType::ActionData, _) => {
+                        print_message(message, OffsetDateTime::now_utc(), &text);
+                    }
+                    // Common data is only numbers, so describe it instead.
+                    (MessageType::CommonData, _) => {
+                        flush_skipped();
+                        let text = match record {
+                            Some(record) => {
+                                CommonData::parse(record, text.clone(), |s| properties.redact(s))
+                                    .to_string()
+                            }
+                            None => text.clone(),
+                        };
+                        print_message(message, OffsetDateTime::now_utc(), &text);
+                    }
+                    _ => {
+                        flush_skipped();
+                        print_message(message, OffsetDateTime::now_utc(), &text);
+                    }
+                }
+            }
+            if let (true, MessageType::FilesInUse | MessageType::RMFilesInUse, Some(record)) =
+                (selected, message, record)
+            {
+                let applications = files_in_use::applications(record);
+                println!("  Files are in use by {} applications:", applications.len());
+                for application in applications {
+                    println!("    {}", application);
+                }
+            }
+            match message {
+                MessageType::Error | MessageType::FatalExit => {
+                    let (errors, warnings) = counts.get();
+                    counts.set((errors + 1, warnings));
+
+                    #[cfg(feature = "hooks")]
+                    {
+                        let mut event = HookEvent::new("error", target);
+                        event.message = Some(text.clone());
+                        options.hooks.error(&event);
+                    }
+                }
+                MessageType::Warning => {
+                    let (errors, warnings) = counts.get();
+                    counts.set((errors, warnings + 1));
+                }
+                MessageType::ResolveSource => resolving_source.set(true),
+                MessageType::ActionStart => {
+                    // Each action runs until the next one starts.
+                    end_action();
+                    if let Some(name) = record.and_then(|r| r.string_data(1).ok()) {
+                        action.replace(Some((name.clone(), Instant::now())));
+
+                        #[cfg(feature = "hooks")]
+                        {
+                            let mut event = HookEvent::new("action", target);
+                            event.action = Some(name);
+                            event.message = Some(text.clone());
+                            options.hooks.action(&event);
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            // Windows Installer cancels and rolls back when any message is cancelled.
+            if is_cancelled() {
+                return HandlerResult::Cancel;
+            }
+
+            // Responses from the handler take precedence over other options.
+            if response != HandlerResult::Default {
+                return response;
+            }
+
+            match (message, record) {
+                // Any response other than the default fails the install.
+                (MessageType::ResolveSource, _) => HandlerResult::Default,
+                (MessageType::FilesInUse | MessageType::RMFilesInUse, Some(record))
+                    if options.files_in_use.is_some() =>
+                {
+                    let applications = files_in_use::applications(record);
+                    let restart_manager = message == MessageType::RMFilesInUse;
+                    match options.files_in_use {
+                        Some(FilesInUsePolicy::Close) => {
+                            close_apps(applications, restart_manager, &closed)
+                        }
+                        Some(FilesInUsePolicy::Ignore) if restart_manager => {
+                            HandlerResult::KEEP_APPLICATIONS
+                        }
+                        Some(FilesInUsePolicy::Ignore) => HandlerResult::Ignore,
+                        Some(FilesInUsePolicy::Prompt) => {
+                            prompt::files_in_use(&applications, restart_manager)
+                        }
+                        Some(FilesInUsePolicy::Fail) | None => HandlerResult::Cancel,
+                    }
+                }
+                (MessageType::RMFilesInUse, Some(record)) if options.close_apps => {
+                    close_apps(files_in_use::applications(record), true, &closed)
+                }
+                (MessageType::Error, _) if options.interactive => prompt::error(&text, flags),
+                (MessageType::FilesInUse | MessageType::RMFilesInUse, Some(record))
+                    if options.interactive =>
+                {
+                    let applications = files_in_use::applications(record);
+                    prompt::files_in_use(&applications, message == MessageType::RMFilesInUse)
+                }
+                _ => HandlerResult::Default,
+            }
+        })
+    };
+    let handler = match handler {
+        Err(err) if wine_version().is_some() => {
+            eprintln!("warning: tracing is not supported under Wine: {}", err);
+            None
+        }
+        result => Some(result?),
+    };
+
+    let result = operation();
+    if let Some(handler) = &handler {
+        handler.resume_panic();
+    }
+    flush_skipped();
+    let result = match result {
+        Err(err) if err.code() == Some(ERROR_INSTALL_USEREXIT) && is_cancelled() => {
+            Err(Error::cancelled())
+        }
+        result => result,
+    };
+    let code = match &result {
+        Ok(()) => 0,
+        Err(err) => err.code().unwrap_or(u32::MAX),
+    };
+    #[cfg(feature = "hooks")]
+    {
+        let mut event = HookEvent::new("complete", target);
+        event.code = Some(code);
+        options.hooks.complete(&event);
+    }
+
+    end_action();
+    let mut summary = InstallSummary::new(target, code, start.elapsed());
+    (summary.errors, summary.warnings) = counts.get();
+    summary.set_actions(actions.take());
+    summary.log = options.log.clone();
+    println!("{}:", options.language.label(Label::Summary));
+    print!("{}", summary.localized(options.language));
+
+    if matches!(
+        code,
+        ERROR_INSTALL_SOURCE_ABSENT | ERROR_PATCH_PACKAGE_OPEN_FAILED
+    ) && options.source.is_none()
+    {
+        let reason = match resolving_source.get() {
+            true => "the installation source could not be resolved",
+            false => "the installation source is not available",
+        };
+        eprintln!(
+            "warning: {}; pass --source with the directory containing the original package",
+            reason
+        );
     }
 
-    ffi::set_external_handler(|message, record| {
-        let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
-        println!("{:?} ({:?}) {}", now, message, record);
+    if options.reboot == Some(Reboot::Schedule)
+        && result.as_ref().err().and_then(Error::code) == Some(ERROR_SUCCESS_REBOOT_REQUIRED)
+    {
+        schedule_reboot(options)?;
+    }
 
-        ffi::HandlerResult::Default
-    })?;
+    for closed in closed.take() {
+        match closed.restart() {
+            Ok(applications) => {
+                for application in applications {
+                    println!("Restarted {}", application);
+                }
+            }
+            Err(err) => println!("Failed to restart applications: {}", err),
+        }
+    }
 
-    ffi::install_package(path, command_line.as_str())
+    // Write the summary last so it cannot prevent anything else from finishing.
+    #[cfg(feature = "json")]
+    if let Some(path) = &options.summary_out {
+        if let Err(err) = summary.write(path) {
+            eprintln!(
+                "warning: failed to write summary to {}: {}",
+                path.display(),
+                err
+            );
+        }
+    }
+
+    result
+}
+
+/// Prints the verification section of an install and fails if any component is broken.
+#[cfg(windows)]
+fn verify(path: &str, options: &InstallOptions) -> Result<()> {
+    println!("{}:", options.language.label(Label::Verification));
+
+    let checks = verify_install(path, options.verify_hashes)?;
+    for check in &checks {
+        println!("  {}", check);
+    }
+
+    let failed = checks.iter().filter(|c| !c.is_ok()).count();
+    println!(
+        "  {} of {} components verified",
+        checks.len() - failed,
+        checks.len()
+    );
+    match failed {
+        0 => Ok(()),
+        n => Err(Error::verification(format!(
+            "install succeeded but {} components failed verification",
+            n
+        ))),
+    }
+}
+
+/// Schedules a restart to complete an install.
+#[cfg(windows)]
+fn schedule_reboot(options: &InstallOptions) -> Result<()> {
+    let delay = options
+        .reboot_delay
+        .as_secs()
+        .try_into()
+        .unwrap_or(u32::MAX);
+    ffi::shutdown::schedule_reboot(delay, options.reboot_message.as_deref())?;
+
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    println!("Reboot scheduled for {}", now + options.reboot_delay);
+
+    Ok(())
+}
+
+/// Shuts down applications holding files in use so the install can continue without a reboot.
+#[cfg(windows)]
+fn close_apps(
+    applications: Vec<Application>,
+    restart_manager: bool,
+    closed: &RefCell<Vec<ClosedApplications>>,
+) -> HandlerResult {
+    if applications.is_empty() {
+        return HandlerResult::Default;
+    }
+
+    match ClosedApplications::close(applications) {
+        Ok(applications) => {
+            println!(
+                "Closing applications in Restart Manager session {}",
+                applications.session_key()
+            );
+            for application in applications.applications() {
+                println!("Closed {}", application);
+            }
+            closed.borrow_mut().push(applications);
+
+            // Files are no longer in use, so continue without Windows Installer closing them,
+            // or check the files again when the Restart Manager is not used.
+            match restart_manager {
+                true => HandlerResult::KEEP_APPLICATIONS,
+                false => HandlerResult::Retry,
+            }
+        }
+        Err(err) => {
+            println!("Failed to close applications: {}", err);
+            HandlerResult::Default
+        }
+    }
+}
+
+/// Describes an install before it starts.
+#[cfg(windows)]
+struct Header<'a> {
+    path: &'a str,
+    source: &'a Source,
+    service: ServiceState,
+    version: Option<InstallerVersion>,
+    command_line: Redacted<'a>,
+    signer: Option<&'a Signer>,
+    authorization: Option<&'a Authorization>,
+    language: Language,
+}
+
+#[cfg(windows)]
+impl Display for Header<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "msitrace {}", env!("CARGO_PKG_VERSION"))?;
+        let label = |label| self.language.label(label);
+        writeln!(f, "{}: {}", label(Label::Package), self.path)?;
+        writeln!(f, "{}: {}", label(Label::Source), self.source)?;
+        if let Some(signer) = self.signer {
+            writeln!(f, "{}: {}", label(Label::Signer), signer)?;
+        }
+        if let Some(authorization) = self.authorization {
+            writeln!(f, "{}: {}", label(Label::AuthorizedBy), authorization)?;
+        }
+        writeln!(f, "{}: {}", label(Label::InstallerService), self.service)?;
+        if let Some(version) = self.version {
+            writeln!(f, "{}: {}", label(Label::InstallerVersion), version)?;
+        }
+        writeln!(f, "{}: {}", label(Label::CommandLine), self.command_line)?;
+        if let Some(version) = wine_version() {
+            writeln!(f, "Wine: {}", version)?;
+        }
+
+        Ok(())
+    }
 }
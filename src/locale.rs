@@ -0,0 +1,347 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#[cfg(windows)]
+use crate::MessageType;
+
+/// A language msitrace can render trace labels in.
+///
+/// Only labels are translated. Messages from Windows Installer are already localized, and
+/// JSON output always uses invariant names so it can be parsed regardless of language.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Language {
+    /// English.
+    #[default]
+    #[cfg_attr(feature = "cli", value(name = "en"))]
+    English,
+
+    /// German.
+    #[cfg_attr(feature = "cli", value(name = "de"))]
+    German,
+
+    /// French.
+    #[cfg_attr(feature = "cli", value(name = "fr"))]
+    French,
+
+    /// Spanish.
+    #[cfg_attr(feature = "cli", value(name = "es"))]
+    Spanish,
+}
+
+/// A label msitrace writes before a value in its output.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Label {
+    Package,
+    Source,
+    Signer,
+    AuthorizedBy,
+    InstallerService,
+    InstallerVersion,
+    CommandLine,
+    Verification,
+    Product,
+    ReinstallMode,
+    Patch,
+    Patches,
+    Assignment,
+    Feature,
+    FeatureState,
+    Preflight,
+    Summary,
+    Component,
+    File,
+    Path,
+    Category,
+    Qualifier,
+    Script,
+    ProductLanguage,
+    Result,
+    Duration,
+    Errors,
+    Warnings,
+    SlowestActions,
+    Log,
+    RebootRequired,
+    Yes,
+    No,
+}
+
+impl Language {
+    /// Gets the language for a locale name or tag like "de", "de-DE", or "de_DE.UTF-8".
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_', '.']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            "fr" => Some(Language::French),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    /// Gets the language of the current user's locale, or English if not supported.
+    pub fn system() -> Self {
+        system_locale()
+            .and_then(|locale| Language::from_tag(&locale))
+            .unwrap_or_default()
+    }
+
+    /// Gets the translated text of a label.
+    pub fn label(self, label: Label) -> &'static str {
+        use Label::*;
+        use Language::*;
+
+        // cspell:disable
+        match (self, label) {
+            (English, Package) => "Package",
+            (English, Source) => "Source",
+            (English, Signer) => "Signer",
+            (English, AuthorizedBy) => "Authorized by",
+            (English, InstallerService) => "Installer service",
+            (English, InstallerVersion) => "Installer version",
+            (English, CommandLine) => "Command line",
+            (English, Verification) => "Verification",
+            (English, Product) => "Product",
+            (English, ReinstallMode) => "Reinstall mode",
+            (English, Patch) => "Patch",
+            (English, Patches) => "Patches",
+            (English, Assignment) => "Assigned to",
+            (English, Feature) => "Feature",
+            (English, FeatureState) => "Feature state",
+            (English, Preflight) => "Preflight",
+            (English, Summary) => "Summary",
+            (English, Component) => "Component",
+            (English, File) => "File",
+            (English, Path) => "Path",
+            (English, Category) => "Category",
+            (English, Qualifier) => "Qualifier",
+            (English, Script) => "Script",
+            (English, ProductLanguage) => "Product language",
+            (English, Result) => "Result",
+            (English, Duration) => "Duration",
+            (English, Errors) => "Errors",
+            (English, Warnings) => "Warnings",
+            (English, SlowestActions) => "Slowest actions",
+            (English, Log) => "Log",
+            (English, RebootRequired) => "Reboot required",
+            (English, Yes) => "yes",
+            (English, No) => "no",
+
+            (German, Package) => "Paket",
+            (German, Source) => "Quelle",
+            (German, Signer) => "Signaturgeber",
+            (German, AuthorizedBy) => "Autorisiert durch",
+            (German, InstallerService) => "Installer-Dienst",
+            (German, InstallerVersion) => "Installer-Version",
+            (German, CommandLine) => "Befehlszeile",
+            (German, Verification) => "Überprüfung",
+            (German, Product) => "Produkt",
+            (German, ReinstallMode) => "Neuinstallationsmodus",
+            (German, Patch) => "Patch",
+            (German, Patches) => "Patches",
+            (German, Assignment) => "Zugewiesen an",
+            (German, Feature) => "Feature",
+            (German, FeatureState) => "Featurezustand",
+            (German, Preflight) => "Vorabprüfung",
+            (German, Summary) => "Zusammenfassung",
+            (German, Component) => "Komponente",
+            (German, File) => "Datei",
+            (German, Path) => "Pfad",
+            (German, Category) => "Kategorie",
+            (German, Qualifier) => "Qualifizierer",
+            (German, Script) => "Skript",
+            (German, ProductLanguage) => "Produktsprache",
+            (German, Result) => "Ergebnis",
+            (German, Duration) => "Dauer",
+            (German, Errors) => "Fehler",
+            (German, Warnings) => "Warnungen",
+            (German, SlowestActions) => "Langsamste Aktionen",
+            (German, Log) => "Protokoll",
+            (German, RebootRequired) => "Neustart erforderlich",
+            (German, Yes) => "ja",
+            (German, No) => "nein",
+
+            (French, Package) => "Package",
+            (French, Source) => "Source",
+            (French, Signer) => "Signataire",
+            (French, AuthorizedBy) => "Autorisé par",
+            (French, InstallerService) => "Service Installer",
+            (French, InstallerVersion) => "Version d'Installer",
+            (French, CommandLine) => "Ligne de commande",
+            (French, Verification) => "Vérification",
+            (French, Product) => "Produit",
+            (French, ReinstallMode) => "Mode de réinstallation",
+            (French, Patch) => "Correctif",
+            (French, Patches) => "Correctifs",
+            (French, Assignment) => "Attribué à",
+            (French, Feature) => "Fonctionnalité",
+            (French, FeatureState) => "État de la fonctionnalité",
+            (French, Preflight) => "Vérification préalable",
+            (French, Summary) => "Résumé",
+            (French, Component) => "Composant",
+            (French, File) => "Fichier",
+            (French, Path) => "Chemin",
+            (French, Category) => "Catégorie",
+            (French, Qualifier) => "Qualificateur",
+            (French, Script) => "Script",
+            (French, ProductLanguage) => "Langue du produit",
+            (French, Result) => "Résultat",
+            (French, Duration) => "Durée",
+            (French, Errors) => "Erreurs",
+            (French, Warnings) => "Avertissements",
+            (French, SlowestActions) => "Actions les plus lentes",
+            (French, Log) => "Journal",
+            (French, RebootRequired) => "Redémarrage requis",
+            (French, Yes) => "oui",
+            (French, No) => "non",
+
+            (Spanish, Package) => "Paquete",
+            (Spanish, Source) => "Origen",
+            (Spanish, Signer) => "Firmante",
+            (Spanish, AuthorizedBy) => "Autorizado por",
+            (Spanish, InstallerService) => "Servicio de Installer",
+            (Spanish, InstallerVersion) => "Versión de Installer",
+            (Spanish, CommandLine) => "Línea de comandos",
+            (Spanish, Verification) => "Verificación",
+            (Spanish, Product) => "Producto",
+            (Spanish, ReinstallMode) => "Modo de reinstalación",
+            (Spanish, Patch) => "Revisión",
+            (Spanish, Patches) => "Revisiones",
+            (Spanish, Assignment) => "Asignado a",
+            (Spanish, Feature) => "Característica",
+            (Spanish, FeatureState) => "Estado de la característica",
+            (Spanish, Preflight) => "Comprobación previa",
+            (Spanish, Summary) => "Resumen",
+            (Spanish, Component) => "Componente",
+            (Spanish, File) => "Archivo",
+            (Spanish, Path) => "Ruta",
+            (Spanish, Category) => "Categoría",
+            (Spanish, Qualifier) => "Calificador",
+            (Spanish, Script) => "Script",
+            (Spanish, ProductLanguage) => "Idioma del producto",
+            (Spanish, Result) => "Resultado",
+            (Spanish, Duration) => "Duración",
+            (Spanish, Errors) => "Errores",
+            (Spanish, Warnings) => "Advertencias",
+            (Spanish, SlowestActions) => "Acciones más lentas",
+            (Spanish, Log) => "Registro",
+            (Spanish, RebootRequired) => "Reinicio necesario",
+            (Spanish, Yes) => "sí",
+            (Spanish, No) => "no",
+        }
+        // cspell:enable
+    }
+
+    /// Gets the translated name of a message type.
+    ///
+    /// English names match the [`MessageType`] variants, as msitrace has always written them.
+    #[cfg(windows)]
+    pub fn message_type(self, message: MessageType) -> &'static str {
+        use Language::*;
+        use MessageType::*;
+
+        // cspell:disable
+        match (self, message) {
+            (English, FatalExit) => "FatalExit",
+            (English, Error) => "Error",
+            (English, Warning) => "Warning",
+            (English, User) => "User",
+            (English, Info) => "Info",
+            (English, FilesInUse) => "FilesInUse",
+            (English, ResolveSource) => "ResolveSource",
+            (English, OutOfDiskSpace) => "OutOfDiskSpace",
+            (English, ActionStart) => "ActionStart",
+            (English, ActionData) => "ActionData",
+            (English, Progress) => "Progress",
+            (English, CommonData) => "CommonData",
+            (English, Initialize) => "Initialize",
+            (English, Terminate) => "Terminate",
+            (English, ShowDialog) => "ShowDialog",
+            (English, Performance) => "Performance",
+            (English, RMFilesInUse) => "RMFilesInUse",
+            (English, InstallStart) => "InstallStart",
+            (English, InstallEnd) => "InstallEnd",
+            (English, Unknown(_)) => "Unknown",
+
+            (German, FatalExit) => "Schwerwiegender Fehler",
+            (German, Error) => "Fehler",
+            (German, Warning) => "Warnung",
+            (German, User) => "Benutzer",
+            (German, Info) => "Info",
+            (German, FilesInUse) => "Dateien in Verwendung",
+            (German, ResolveSource) => "Quelle auflösen",
+            (German, OutOfDiskSpace) => "Nicht genügend Speicherplatz",
+            (German, ActionStart) => "Aktionsbeginn",
+            (German, ActionData) => "Aktionsdaten",
+            (German, Progress) => "Fortschritt",
+            (German, CommonData) => "Allgemeine Daten",
+            (German, Initialize) => "Initialisierung",
+            (German, Terminate) => "Beendigung",
+            (German, ShowDialog) => "Dialog anzeigen",
+            (German, Performance) => "Leistung",
+            (German, RMFilesInUse) => "Dateien in Verwendung (Restart-Manager)",
+            (German, InstallStart) => "Installationsbeginn",
+            (German, InstallEnd) => "Installationsende",
+            (German, Unknown(_)) => "Unbekannt",
+
+            (French, FatalExit) => "Erreur fatale",
+            (French, Error) => "Erreur",
+            (French, Warning) => "Avertissement",
+            (French, User) => "Utilisateur",
+            (French, Info) => "Info",
+            (French, FilesInUse) => "Fichiers en cours d'utilisation",
+            (French, ResolveSource) => "Résolution de la source",
+            (French, OutOfDiskSpace) => "Espace disque insuffisant",
+            (French, ActionStart) => "Début d'action",
+            (French, ActionData) => "Données d'action",
+            (French, Progress) => "Progression",
+            (French, CommonData) => "Données communes",
+            (French, Initialize) => "Initialisation",
+            (French, Terminate) => "Arrêt",
+            (French, ShowDialog) => "Affichage de boîte de dialogue",
+            (French, Performance) => "Performances",
+            (French, RMFilesInUse) => {
+                "Fichiers en cours d'utilisation (Gestionnaire de redémarrage)"
+            }
+            (French, InstallStart) => "Début d'installation",
+            (French, InstallEnd) => "Fin d'installation",
+            (French, Unknown(_)) => "Inconnu",
+
+            (Spanish, FatalExit) => "Error grave",
+            (Spanish, Error) => "Error",
+            (Spanish, Warning) => "Advertencia",
+            (Spanish, User) => "Usuario",
+            (Spanish, Info) => "Información",
+            (Spanish, FilesInUse) => "Archivos en uso",
+            (Spanish, ResolveSource) => "Resolver origen",
+            (Spanish, OutOfDiskSpace) => "Espacio en disco insuficiente",
+            (Spanish, ActionStart) => "Inicio de acción",
+            (Spanish, ActionData) => "Datos de acción",
+            (Spanish, Progress) => "Progreso",
+            (Spanish, CommonData) => "Datos comunes",
+            (Spanish, Initialize) => "Inicialización",
+            (Spanish, Terminate) => "Finalización",
+            (Spanish, ShowDialog) => "Mostrar cuadro de diálogo",
+            (Spanish, Performance) => "Rendimiento",
+            (Spanish, RMFilesInUse) => "Archivos en uso (Administrador de reinicio)",
+            (Spanish, InstallStart) => "Inicio de instalación",
+            (Spanish, InstallEnd) => "Fin de instalación",
+            (Spanish, Unknown(_)) => "Desconocido",
+        }
+        // cspell:enable
+    }
+}
+
+#[cfg(windows)]
+fn system_locale() -> Option<String> {
+    crate::ffi::locale::user_locale()
+}
+
+#[cfg(not(windows))]
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
@@ -0,0 +1,129 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::download::sha256_digest;
+use crate::{Error, Result};
+use serde::Deserialize;
+use std::fmt::Display;
+#[cfg(feature = "json")]
+use std::fs::File;
+#[cfg(feature = "json")]
+use std::io::BufReader;
+use std::path::Path;
+
+/// A list of packages allowed to be installed, identified by their SHA-256 digests.
+///
+/// Manifests are JSON files like:
+///
+/// ```json
+/// {
+///   "packages": [
+///     { "name": "example.msi", "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08" }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Manifest {
+    /// Where the manifest was read from, if a file.
+    #[serde(skip)]
+    pub source: Option<String>,
+
+    /// Packages allowed to be installed.
+    pub packages: Vec<ManifestEntry>,
+}
+
+/// An entry in a [`Manifest`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct ManifestEntry {
+    /// The file name of the package.
+    pub name: String,
+
+    /// The expected SHA-256 digest of the package as hexadecimal.
+    pub sha256: String,
+}
+
+/// The [`ManifestEntry`] that authorized installing a package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Authorization {
+    /// Where the manifest was read from, if a file.
+    pub source: Option<String>,
+
+    /// The 0-based index of the entry in the manifest.
+    pub index: usize,
+
+    /// The entry matching the package.
+    pub entry: ManifestEntry,
+}
+
+impl Display for Authorization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} entry {} ({}, sha256 {})",
+            self.source.as_deref().unwrap_or("manifest"),
+            self.index,
+            self.entry.name,
+            self.entry.sha256.to_ascii_lowercase(),
+        )
+    }
+}
+
+impl Manifest {
+    /// Reads a manifest from a JSON file.
+    #[cfg(feature = "json")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let file = BufReader::new(File::open(path.as_ref())?);
+        let mut manifest: Manifest = serde_json::from_reader(file).map_err(std::io::Error::from)?;
+        manifest.source = Some(path.as_ref().to_string_lossy().into_owned());
+
+        Ok(manifest)
+    }
+
+    /// Parses a manifest from a JSON string.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json).map_err(std::io::Error::from)?)
+    }
+
+    /// Gets the entry authorizing a package to be installed.
+    ///
+    /// Returns an error if no entry has the same file name as the package,
+    /// or no entry with that name has the same SHA-256 digest.
+    pub fn authorize(&self, path: impl AsRef<Path>) -> Result<Authorization> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let candidates: Vec<(usize, &ManifestEntry)> = self
+            .packages
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.name.eq_ignore_ascii_case(&name))
+            .collect();
+        if candidates.is_empty() {
+            return Err(Error::verification(format!(
+                "{} is not listed in the manifest",
+                name
+            )));
+        }
+
+        let digest = sha256_digest(path)?;
+
+        match candidates
+            .into_iter()
+            .find(|(_, e)| e.sha256.eq_ignore_ascii_case(&digest))
+        {
+            Some((index, entry)) => Ok(Authorization {
+                source: self.source.clone(),
+                index,
+                entry: entry.clone(),
+            }),
+            None => Err(Error::verification(format!(
+                "{} has SHA-256 digest {} which does not match the manifest",
+                name, digest
+            ))),
+        }
+    }
+}
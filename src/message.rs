@@ -0,0 +1,510 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::{Buttons, Icon, MessageType, Record};
+use crate::files_in_use::{self, Application};
+use crate::{ProgressUpdate, Result, Value};
+use std::fmt::Display;
+use std::time::{Duration, Instant, SystemTime};
+use time::OffsetDateTime;
+
+/// A message Windows Installer sent while installing, parsed from its record.
+///
+/// Text is formatted from the record like it is traced, with hidden property values redacted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Message {
+    /// A fatal error ended the install.
+    FatalExit { text: String },
+
+    /// An error, usually with an error code from the `Error` table in field 1.
+    Error {
+        code: Option<i32>,
+        text: String,
+
+        /// The buttons a message box would show, which determine the valid responses.
+        buttons: Buttons,
+        icon: Icon,
+    },
+
+    /// A warning that does not end the install.
+    Warning {
+        code: Option<i32>,
+        text: String,
+        buttons: Buttons,
+        icon: Icon,
+    },
+
+    /// A request for a response from the user.
+    User {
+        text: String,
+        buttons: Buttons,
+        icon: Icon,
+    },
+
+    /// Information written to the log.
+    Info { text: String },
+
+    /// Applications holding files the install needs to replace.
+    FilesInUse { applications: Vec<Application> },
+
+    /// Windows Installer is resolving the source of a product.
+    ResolveSource,
+
+    /// There is not enough disk space to install.
+    OutOfDiskSpace,
+
+    /// An action started.
+    ActionStart {
+        /// The name of the action, e.g. `InstallFiles`.
+        action: String,
+
+        /// The description of the action from the `ActionText` table.
+        description: String,
+
+        /// The template used to format subsequent [`Message::ActionData`].
+        template: String,
+    },
+
+    /// Data about the current action, e.g. the file being copied.
+    ActionData { text: String },
+
+    /// An update to the progress bar.
+    Progress(ProgressUpdate),
+
+    /// Information shared with any user interface, like the language.
+    CommonData(CommonData),
+
+    /// The user interface was initialized.
+    Initialize,
+
+    /// The user interface was terminated.
+    Terminate,
+
+    /// A dialog is to be shown.
+    ShowDialog { dialog: String },
+
+    /// Applications the Restart Manager found holding files the install needs to replace.
+    RMFilesInUse { applications: Vec<Application> },
+
+    /// A product started installing.
+    InstallStart {
+        product: String,
+        product_code: String,
+    },
+
+    /// A product finished installing.
+    InstallEnd {
+        product: String,
+        product_code: String,
+
+        /// The result of the install, e.g. 1 for success.
+        result: Option<i32>,
+    },
+
+    /// Any other message, such as performance messages or types msitrace does not recognize.
+    Other {
+        message_type: MessageType,
+        text: String,
+    },
+}
+
+/// An event during an install, owned so it can be sent to another thread.
+///
+/// See [`MessageHandler::channel`](crate::MessageHandler::channel) and
+/// [`Installer::run`](crate::Installer::run).
+#[derive(Debug)]
+pub enum Event {
+    /// A message was traced.
+    Message {
+        /// When the message was received.
+        time: SystemTime,
+
+        /// How long after tracing started the message was received.
+        elapsed: Duration,
+
+        /// The parsed message.
+        message: Message,
+
+        /// The formatted message with the values of hidden properties redacted.
+        text: String,
+
+        /// The action running when the message was received, if any.
+        action: Option<ActionScope>,
+    },
+
+    /// An action ended because another started or the install ended. Windows Installer does
+    /// not send a message when an action ends.
+    ActionEnd {
+        time: SystemTime,
+        elapsed: Duration,
+
+        /// The action that ended, with how long it ran.
+        action: ActionScope,
+    },
+
+    /// The install finished. This is always the last event from an
+    /// [`Installer`](crate::Installer).
+    Completed {
+        time: SystemTime,
+        elapsed: Duration,
+        result: Result<()>,
+    },
+}
+
+impl Event {
+    /// Gets when the event occurred by the system clock.
+    pub fn time(&self) -> SystemTime {
+        match self {
+            Event::Message { time, .. }
+            | Event::ActionEnd { time, .. }
+            | Event::Completed { time, .. } => *time,
+        }
+    }
+
+    /// Gets how long after tracing started the event occurred.
+    ///
+    /// Unlike [`Event::time`], this is measured with a monotonic clock, so durations are not
+    /// affected by changes to the system clock.
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Event::Message { elapsed, .. }
+            | Event::ActionEnd { elapsed, .. }
+            | Event::Completed { elapsed, .. } => *elapsed,
+        }
+    }
+
+    /// Gets when the event occurred in the local time zone, or UTC if the local offset
+    /// cannot be determined.
+    pub fn local_time(&self) -> OffsetDateTime {
+        let time = OffsetDateTime::from(self.time());
+        match time::UtcOffset::local_offset_at(time) {
+            Ok(offset) => time.to_offset(offset),
+            Err(_) => time,
+        }
+    }
+}
+
+/// The action running when an [`Event`] occurred.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionScope {
+    /// The name of the action, e.g. `InstallFiles`.
+    pub name: String,
+
+    /// The position of the action among those the install ran, starting at 1.
+    pub sequence: u32,
+
+    /// How long the action had run when the event occurred.
+    pub elapsed: Duration,
+}
+
+/// Tracks the running action and times events so they can be correlated with it.
+///
+/// [`MessageHandler::events`](crate::MessageHandler::events) uses this to add the running
+/// action to each [`Event`].
+#[derive(Debug)]
+pub struct ActionTracker {
+    start: Instant,
+    current: Option<(String, Instant)>,
+    count: u32,
+}
+
+impl ActionTracker {
+    /// Creates an [`ActionTracker`] that times events from `start`.
+    pub fn new(start: Instant) -> Self {
+        ActionTracker {
+            start,
+            current: None,
+            count: 0,
+        }
+    }
+
+    /// Updates the running action for a `message` received at `time` and `now`, returning an
+    /// [`Event::ActionEnd`] if the previous action ended.
+    pub fn observe(&mut self, message: &Message, time: SystemTime, now: Instant) -> Option<Event> {
+        match message {
+            // Each action runs until the next one starts.
+            Message::ActionStart { action, .. } => {
+                let end = self.end(time, now);
+                self.count += 1;
+                self.current = Some((action.clone(), now));
+                end
+            }
+            Message::InstallEnd { .. } | Message::Terminate => self.end(time, now),
+            _ => None,
+        }
+    }
+
+    /// Gets how long after `start` an event at `now` occurred.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.start)
+    }
+
+    /// Gets the action running at `now`, if any.
+    pub fn current(&self, now: Instant) -> Option<ActionScope> {
+        let (name, started) = self.current.as_ref()?;
+        Some(ActionScope {
+            name: name.clone(),
+            sequence: self.count,
+            elapsed: now.saturating_duration_since(*started),
+        })
+    }
+
+    fn end(&mut self, time: SystemTime, now: Instant) -> Option<Event> {
+        let action = self.current(now)?;
+        self.current = None;
+        Some(Event::ActionEnd {
+            time,
+            elapsed: self.elapsed(now),
+            action,
+        })
+    }
+}
+
+/// Samples action data so at most one is printed each interval, along with the last of each
+/// action.
+///
+/// Data skipped within an interval is kept until later data replaces it or it is taken with
+/// [`take_skipped`](Self::take_skipped) before printing any other message.
+#[derive(Debug)]
+pub struct ActionDataSampler<T> {
+    interval: Duration,
+    printed: Option<Instant>,
+    skipped: Option<T>,
+}
+
+impl<T> ActionDataSampler<T> {
+    /// Creates an [`ActionDataSampler`] that prints at most one action data every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        ActionDataSampler {
+            interval,
+            printed: None,
+            skipped: None,
+        }
+    }
+
+    /// Gets whether to print action data received at `now`, which drops any skipped data.
+    /// Otherwise, the data should be kept with [`skip`](Self::skip).
+    pub fn should_print(&mut self, now: Instant) -> bool {
+        match self.printed {
+            Some(printed) if now.saturating_duration_since(printed) < self.interval => false,
+            _ => {
+                self.printed = Some(now);
+                self.skipped = None;
+                true
+            }
+        }
+    }
+
+    /// Keeps action data that was not printed, replacing any skipped before it.
+    pub fn skip(&mut self, data: T) {
+        self.skipped = Some(data);
+    }
+
+    /// Takes the last action data skipped since any was printed, which should be printed
+    /// before any other message.
+    pub fn take_skipped(&mut self) -> Option<T> {
+        self.skipped.take()
+    }
+}
+
+/// Information shared with any user interface sent in a [`Message::CommonData`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CommonData {
+    /// The language and code page the user interface should use.
+    Language { lcid: u16, codepage: u16 },
+
+    /// The caption of the user interface, usually the name of the product.
+    Caption { caption: String },
+
+    /// Whether the user interface should show or hide its cancel button, e.g. hiding it
+    /// while rolling back.
+    CancelShow { visible: bool },
+
+    /// Any other common data, with its type in field 1.
+    Other { kind: i32, text: String },
+}
+
+impl Message {
+    /// Parses the record of a message and the message box `flags` in the low bits of its type,
+    /// with `text` already formatted and redacted and `redact` applied to other strings.
+    pub(crate) fn parse(
+        message_type: MessageType,
+        flags: u32,
+        record: Option<&Record>,
+        text: String,
+        redact: impl Fn(&str) -> String,
+    ) -> Self {
+        let (buttons, icon) = (Buttons::from_flags(flags), Icon::from_flags(flags));
+        let Some(record) = record else {
+            // Messages received as text have no fields, but their text is still useful.
+            return match message_type {
+                MessageType::FatalExit => Message::FatalExit { text },
+                MessageType::Error => Message::Error {
+                    code: None,
+                    text,
+                    buttons,
+                    icon,
+                },
+                MessageType::Warning => Message::Warning {
+                    code: None,
+                    text,
+                    buttons,
+                    icon,
+                },
+                MessageType::User => Message::User {
+                    text,
+                    buttons,
+                    icon,
+                },
+                MessageType::Info => Message::Info { text },
+                MessageType::ActionData => Message::ActionData { text },
+                MessageType::ResolveSource => Message::ResolveSource,
+                MessageType::OutOfDiskSpace => Message::OutOfDiskSpace,
+                MessageType::Initialize => Message::Initialize,
+                MessageType::Terminate => Message::Terminate,
+                message_type => Message::Other { message_type, text },
+            };
+        };
+        let string = |i| record.string_data(i).unwrap_or_default();
+        let integer = |i| record.integer_data(i);
+
+        match message_type {
+            MessageType::FatalExit => Message::FatalExit { text },
+            MessageType::Error => Message::Error {
+                code: integer(1),
+                text,
+                buttons,
+                icon,
+            },
+            MessageType::Warning => Message::Warning {
+                code: integer(1),
+                text,
+                buttons,
+                icon,
+            },
+            MessageType::User => Message::User {
+                text,
+                buttons,
+                icon,
+            },
+            MessageType::Info => Message::Info { text },
+            MessageType::FilesInUse => Message::FilesInUse {
+                applications: files_in_use::applications(record),
+            },
+            MessageType::ResolveSource => Message::ResolveSource,
+            MessageType::OutOfDiskSpace => Message::OutOfDiskSpace,
+            MessageType::ActionStart => Message::ActionStart {
+                action: string(1),
+                description: string(2),
+                template: string(3),
+            },
+            MessageType::ActionData => Message::ActionData { text },
+            MessageType::Progress => Message::Progress(progress_update(record)),
+            MessageType::CommonData => Message::CommonData(CommonData::parse(record, text, redact)),
+            MessageType::Initialize => Message::Initialize,
+            MessageType::Terminate => Message::Terminate,
+            MessageType::ShowDialog => Message::ShowDialog { dialog: string(0) },
+            MessageType::RMFilesInUse => Message::RMFilesInUse {
+                applications: files_in_use::applications(record),
+            },
+            MessageType::InstallStart => Message::InstallStart {
+                product: string(1),
+                product_code: string(2),
+            },
+            MessageType::InstallEnd => Message::InstallEnd {
+                product: string(1),
+                product_code: string(2),
+                result: integer(3),
+            },
+            message_type => Message::Other { message_type, text },
+        }
+    }
+
+    /// Gets the type of the message.
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            Message::FatalExit { .. } => MessageType::FatalExit,
+            Message::Error { .. } => MessageType::Error,
+            Message::Warning { .. } => MessageType::Warning,
+            Message::User { .. } => MessageType::User,
+            Message::Info { .. } => MessageType::Info,
+            Message::FilesInUse { .. } => MessageType::FilesInUse,
+            Message::ResolveSource => MessageType::ResolveSource,
+            Message::OutOfDiskSpace => MessageType::OutOfDiskSpace,
+            Message::ActionStart { .. } => MessageType::ActionStart,
+            Message::ActionData { .. } => MessageType::ActionData,
+            Message::Progress(_) => MessageType::Progress,
+            Message::CommonData(_) => MessageType::CommonData,
+            Message::Initialize => MessageType::Initialize,
+            Message::Terminate => MessageType::Terminate,
+            Message::ShowDialog { .. } => MessageType::ShowDialog,
+            Message::RMFilesInUse { .. } => MessageType::RMFilesInUse,
+            Message::InstallStart { .. } => MessageType::InstallStart,
+            Message::InstallEnd { .. } => MessageType::InstallEnd,
+            Message::Other { message_type, .. } => *message_type,
+        }
+    }
+}
+
+/// Parses the fields of a progress message.
+fn progress_update(record: &Record) -> ProgressUpdate {
+    let fields: Vec<i32> = (1..=record.field_count())
+        .map(|i| record.integer_data(i).unwrap_or_default())
+        .collect();
+    ProgressUpdate::from_fields(&fields)
+}
+
+impl CommonData {
+    /// Parses common data from its type in field 1 and the `fields` that follow it, with `text`
+    /// formatted from the whole record.
+    pub fn from_fields(kind: i32, fields: &[Value], text: String) -> Self {
+        let integer = |i: usize| {
+            fields
+                .get(i)
+                .and_then(|v| v.as_integer().or_else(|| v.as_str()?.parse().ok()))
+                .unwrap_or_default()
+        };
+
+        match kind {
+            0 => CommonData::Language {
+                lcid: integer(0) as u16,
+                codepage: integer(1) as u16,
+            },
+            1 => CommonData::Caption {
+                caption: fields.first().map(ToString::to_string).unwrap_or_default(),
+            },
+            2 => CommonData::CancelShow {
+                visible: integer(0) != 0,
+            },
+            kind => CommonData::Other { kind, text },
+        }
+    }
+
+    /// Parses common data from a record, with `redact` applied to its string fields.
+    pub(crate) fn parse(record: &Record, text: String, redact: impl Fn(&str) -> String) -> Self {
+        let fields: Vec<Value> = (2..=record.field_count())
+            .map(|i| match record.integer_data(i) {
+                _ if record.is_null(i) => Value::Null,
+                Some(i) => Value::Integer(i),
+                None => Value::String(redact(&record.string_data(i).unwrap_or_default())),
+            })
+            .collect();
+
+        CommonData::from_fields(record.integer_data(1).unwrap_or_default(), &fields, text)
+    }
+}
+
+impl Display for CommonData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommonData::Language { lcid, codepage } => {
+                write!(f, "Language {}, code page {}", lcid, codepage)
+            }
+            CommonData::Caption { caption } => write!(f, "Caption {:?}", caption),
+            CommonData::CancelShow { visible: true } => f.write_str("Show cancel button"),
+            CommonData::CancelShow { visible: false } => f.write_str("Hide cancel button"),
+            CommonData::Other { text, .. } => f.write_str(text),
+        }
+    }
+}
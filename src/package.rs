@@ -0,0 +1,128 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{ffi, is_bundle, Database, Error, Result, SummaryInfo};
+use std::fmt::Display;
+
+/// Gets whether Windows Installer can open a file as a package.
+///
+/// Use [`verify_package`] to also check its summary information and get a reason it is invalid.
+pub fn is_valid_package(path: &str) -> bool {
+    ffi::verify_package(path).is_ok()
+}
+
+/// Verifies a file is a valid Windows Installer package and gets its [`SummaryInfo`].
+///
+/// This catches corrupt or truncated downloads before installing, which would otherwise
+/// fail part way through with error 1620.
+pub fn verify_package(path: &str) -> Result<SummaryInfo> {
+    if !is_valid_package(path) {
+        if is_bundle(path).unwrap_or_default() {
+            return Err(invalid(
+                "the file is a WiX bundle; extract its packages to install them individually",
+            ));
+        }
+        return Err(invalid("the file could not be opened as a package"));
+    }
+
+    let summary = Database::open(path)?.summary_info()?;
+    if !summary.template.as_deref().is_some_and(|t| t.contains(';')) {
+        return Err(invalid(
+            "the Template summary property is missing or malformed",
+        ));
+    }
+    if !summary.page_count.is_some_and(|v| v > 0) {
+        return Err(invalid("the PageCount summary property is missing"));
+    }
+    if !summary.revision_number.as_deref().is_some_and(is_guid) {
+        return Err(invalid("the package code is missing or malformed"));
+    }
+
+    Ok(summary)
+}
+
+/// How a package installs in a requested language.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LanguageSelection {
+    /// The language identifier (LCID) to install, e.g. 1031 for German.
+    pub lcid: u16,
+
+    /// The embedded language transform to apply, e.g. `:1031`, if the language is not
+    /// the package's own language.
+    pub transform: Option<String>,
+}
+
+impl Display for LanguageSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.transform {
+            Some(transform) => write!(f, "{} (transform {})", self.lcid, transform),
+            None => write!(f, "{}", self.lcid),
+        }
+    }
+}
+
+/// Determines how to install a multi-language package in the language `lcid`.
+///
+/// The package's own `ProductLanguage` needs no transform. Otherwise a transform embedded
+/// in the package named for the language, e.g. `1031` or `1031.mst`, is used if one exists;
+/// failing that, the language must be listed in the Template summary property.
+pub fn select_language(path: &str, lcid: u16) -> Result<LanguageSelection> {
+    let database = Database::open(path)?;
+    let base = database
+        .query("SELECT `Value` FROM `Property` WHERE `Property` = 'ProductLanguage'")?
+        .next()
+        .transpose()?
+        .and_then(|row| row.get(0).and_then(|v| v.as_str().map(str::to_owned)));
+    if base.as_deref() == Some(lcid.to_string().as_str()) {
+        return Ok(LanguageSelection {
+            lcid,
+            transform: None,
+        });
+    }
+
+    let mut storages = Vec::new();
+    for row in database.query("SELECT `Name` FROM `_Storages`")? {
+        storages.push(row?[0].to_string());
+    }
+    let name = lcid.to_string();
+    if let Some(storage) = storages.iter().find(|storage| {
+        storage.as_str() == name || storage.eq_ignore_ascii_case(&format!("{}.mst", name))
+    }) {
+        return Ok(LanguageSelection {
+            lcid,
+            transform: Some(format!(":{}", storage)),
+        });
+    }
+
+    let template = database.summary_info()?.template.unwrap_or_default();
+    let languages: Vec<&str> = template
+        .split_once(';')
+        .map(|(_, languages)| languages.split(',').map(str::trim).collect())
+        .unwrap_or_default();
+    match languages.contains(&name.as_str()) {
+        true => Ok(LanguageSelection {
+            lcid,
+            transform: None,
+        }),
+        false => Err(Error::verification(format!(
+            "package does not support language {}; it supports {}",
+            lcid,
+            languages.join(", ")
+        ))),
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::verification(format!("not a valid installer package: {}", reason))
+}
+
+fn is_guid(value: &str) -> bool {
+    // Package codes may be followed by other GUIDs e.g. for patches, so check only the first.
+    let value = value.get(..38).unwrap_or_default();
+    value.starts_with('{')
+        && value.ends_with('}')
+        && value[1..37].chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
@@ -0,0 +1,330 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::event::describe_error;
+use crate::ffi::{self, cabinet};
+use crate::signature::check_signature;
+use crate::{
+    installer_policy, lock, preflight, trace, Database, Error, InstallOptions, Label, Result,
+    SummaryInfo, TransformInfo,
+};
+use std::fmt::Display;
+use std::path::Path;
+
+/// What a patch targets and updates, read without applying it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PatchInfo {
+    /// The patch code.
+    pub patch_code: String,
+
+    /// Patch codes this patch obsoletes.
+    pub obsoletes: Vec<String>,
+
+    /// Product codes the patch can be applied to.
+    pub target_product_codes: Vec<String>,
+
+    /// Transforms embedded in the patch.
+    pub transforms: Vec<PatchTransform>,
+
+    /// Keys of files in the `File` table updated by the patch.
+    pub files: Vec<String>,
+
+    /// Where the patch is sequenced within its patch families.
+    pub sequences: Vec<PatchSequence>,
+}
+
+/// A row of the `MsiPatchSequence` table in a patch.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PatchSequence {
+    /// The patch family the patch belongs to.
+    pub family: String,
+
+    /// The product code the sequence applies to, or `None` for all targets.
+    pub product_code: Option<String>,
+
+    /// The version of the patch within its family, e.g. 1.2.0.0.
+    pub sequence: String,
+
+    /// Whether the patch supersedes earlier patches in its family.
+    pub supersedes: bool,
+}
+
+/// A transform embedded in a patch.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PatchTransform {
+    /// The name of the transform within the patch.
+    pub name: String,
+
+    /// What the transform applies to, read from its summary information.
+    pub info: TransformInfo,
+}
+
+/// Reads what a patch targets and updates.
+pub fn inspect_patch(path: impl AsRef<Path>) -> Result<PatchInfo> {
+    let database = Database::open_patch(path)?;
+    let summary = database.summary_info()?;
+
+    // Patches list their patch code followed by any obsoleted patch codes.
+    let codes = summary.revision_number.unwrap_or_default();
+    let mut codes = guids(&codes);
+    if codes.is_empty() {
+        return Err(Error::verification(
+            "not a valid patch: the patch code is missing",
+        ));
+    }
+    let patch_code = codes.remove(0);
+
+    let target_product_codes = summary
+        .template
+        .unwrap_or_default()
+        .split(';')
+        .filter(|code| !code.is_empty())
+        .map(str::to_owned)
+        .collect();
+
+    let temp = std::env::temp_dir().join(format!("msitrace-patch-{}", std::process::id()));
+    std::fs::create_dir_all(&temp)?;
+    let result = read_storages(&database, &temp);
+    let _ = std::fs::remove_dir_all(&temp);
+    let (transforms, files) = result?;
+    let sequences = read_sequences(&database)?;
+
+    Ok(PatchInfo {
+        patch_code,
+        obsoletes: codes,
+        target_product_codes,
+        transforms,
+        files,
+        sequences,
+    })
+}
+
+/// Gets the XML describing which products a patch applies to, as used to determine
+/// patch applicability.
+pub fn patch_xml(path: &str) -> Result<String> {
+    ffi::extract_patch_xml(path)
+}
+
+/// Applies patches to installed products in order and traces the install.
+///
+/// If `product_code` is `None`, each patch is applied to every installed product it targets.
+/// Afterward, the state of each patch is printed for each product it was applied to.
+pub fn apply_patches(
+    patches: &[String],
+    product_code: Option<&str>,
+    options: &InstallOptions,
+) -> Result<()> {
+    let _lock = lock();
+    if patches.is_empty() {
+        return Err(Error::verification("no patches to apply"));
+    }
+
+    preflight(options)?;
+    if installer_policy()?.disable_patch {
+        eprintln!("warning: the DisablePatch policy prevents applying patches");
+    }
+
+    let mut infos = Vec::with_capacity(patches.len());
+    for patch in patches {
+        infos.push(inspect_patch(patch)?);
+        check_signature(patch, options)?;
+        if let Some(manifest) = &options.manifest {
+            manifest.authorize(patch)?;
+        }
+    }
+
+    let language = options.language;
+    for (patch, info) in patches.iter().zip(&infos) {
+        println!(
+            "{}: {} ({})",
+            language.label(Label::Patch),
+            patch,
+            info.patch_code
+        );
+    }
+    if let Some(product_code) = product_code {
+        println!("{}: {}", language.label(Label::Product), product_code);
+    }
+    println!(
+        "{}: {}",
+        language.label(Label::CommandLine),
+        options.properties.redacted()
+    );
+
+    let command_line = options.properties.to_install_string();
+    let result = trace(&patches.join(";"), options, || {
+        ffi::apply_multiple_patches(patches, product_code, &command_line)
+    });
+
+    println!("{}:", language.label(Label::Patches));
+    for info in &infos {
+        let targets = match product_code {
+            Some(product_code) => vec![product_code.to_owned()],
+            None => info.target_product_codes.clone(),
+        };
+        for target in targets {
+            match ffi::patch_info(&info.patch_code, &target, "State") {
+                Ok(Some(state)) => {
+                    println!("  {} {}: {}", info.patch_code, target, state_name(&state))
+                }
+                Ok(None) if product_code.is_some() => {
+                    println!("  {} {}: not applied", info.patch_code, target)
+                }
+                Ok(None) => {}
+                Err(err) => println!("  {} {}: unknown: {}", info.patch_code, target, err),
+            }
+        }
+    }
+
+    result
+}
+
+/// Whether and in what order a patch applies to a package or installed product.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchApplicability {
+    /// The path to the patch.
+    pub patch: String,
+
+    /// The zero-based order the patch would be applied in, or `None` if it does not apply.
+    pub order: Option<u32>,
+
+    /// The error code explaining why the patch does not apply, e.g. 1642.
+    pub status: u32,
+}
+
+impl PatchApplicability {
+    /// Gets whether the patch applies to the target.
+    pub fn is_applicable(&self) -> bool {
+        self.order.is_some()
+    }
+}
+
+impl Display for PatchApplicability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.order {
+            Some(order) => write!(f, "{}\t{}", order + 1, self.patch),
+            None => write!(
+                f,
+                "-\t{}: not applicable: {} ({})",
+                self.patch,
+                describe_error(self.status),
+                self.status
+            ),
+        }
+    }
+}
+
+/// Determines which patches apply to a package or installed product and in what sequence,
+/// without applying them.
+///
+/// The `target` is either the path to a package (.msi) or the product code of an installed
+/// product. Patches that apply are sorted in the order they would be applied, followed by
+/// those that do not apply.
+pub fn check_patches(patches: &[String], target: &str) -> Result<Vec<PatchApplicability>> {
+    if patches.is_empty() {
+        return Err(Error::verification("no patches to check"));
+    }
+
+    let mut results: Vec<PatchApplicability> = ffi::patch_sequence(patches, target)?
+        .into_iter()
+        .zip(patches)
+        .map(|((order, status), patch)| PatchApplicability {
+            patch: patch.to_owned(),
+            order,
+            status,
+        })
+        .collect();
+    results.sort_by_key(|result| result.order.unwrap_or(u32::MAX));
+
+    Ok(results)
+}
+
+/// Gets the name of an `MSIPATCHSTATE` value.
+fn state_name(state: &str) -> &str {
+    match state {
+        "1" => "applied",
+        "2" => "superseded",
+        "4" => "obsoleted",
+        "8" => "registered",
+        state => state,
+    }
+}
+
+fn read_storages(database: &Database, temp: &Path) -> Result<(Vec<PatchTransform>, Vec<String>)> {
+    let mut transforms = Vec::new();
+    for row in database.query("SELECT `Name` FROM `_Storages`")? {
+        let name = row?[0].to_string();
+
+        // Patch transforms prefixed with '#' only add patch tables and target nothing themselves.
+        if name.starts_with('#') {
+            continue;
+        }
+
+        // Names are passed as parameters since they may contain quotes.
+        let data = database
+            .read_stream("SELECT `Data` FROM `_Storages` WHERE `Name` = ?", &[&name])?
+            .unwrap_or_default();
+        let path = temp.join(format!("{}.mst", transforms.len()));
+        std::fs::write(&path, data)?;
+
+        transforms.push(PatchTransform {
+            name,
+            info: TransformInfo::from_summary(&SummaryInfo::from_file(&path)?),
+        });
+    }
+
+    let mut files = Vec::new();
+    for row in database.query("SELECT `Name` FROM `_Streams`")? {
+        let name = row?[0].to_string();
+        let data = database
+            .read_stream("SELECT `Data` FROM `_Streams` WHERE `Name` = ?", &[&name])?
+            .unwrap_or_default();
+        if !data.starts_with(b"MSCF") {
+            continue;
+        }
+
+        let path = temp.join(format!("{}.cab", files.len()));
+        std::fs::write(&path, data)?;
+        files.extend(cabinet::list(&path.to_string_lossy())?);
+    }
+
+    Ok((transforms, files))
+}
+
+/// Reads the `MsiPatchSequence` table, which patches without patch families do not have.
+fn read_sequences(database: &Database) -> Result<Vec<PatchSequence>> {
+    if !database.tables()?.iter().any(|t| t == "MsiPatchSequence") {
+        return Ok(Vec::new());
+    }
+
+    // The msidbPatchSequenceSupersedeEarlier attribute.
+    const SUPERSEDE_EARLIER: i32 = 1;
+
+    let mut sequences = Vec::new();
+    for row in database.query(
+        "SELECT `PatchFamily`, `ProductCode`, `Sequence`, `Attributes` FROM `MsiPatchSequence`",
+    )? {
+        let row = row?;
+        let text = |i: usize| row.get(i).and_then(|v| v.as_str()).map(str::to_owned);
+        sequences.push(PatchSequence {
+            family: text(0).unwrap_or_default(),
+            product_code: text(1).filter(|code| !code.is_empty()),
+            sequence: text(2).unwrap_or_default(),
+            supersedes: row.get(3).and_then(|v| v.as_integer()).unwrap_or_default()
+                & SUPERSEDE_EARLIER
+                != 0,
+        });
+    }
+
+    Ok(sequences)
+}
+
+fn guids(value: &str) -> Vec<String> {
+    value
+        .as_bytes()
+        .chunks(38)
+        .filter_map(|code| std::str::from_utf8(code).ok())
+        .filter(|code| code.len() == 38 && code.starts_with('{'))
+        .map(str::to_owned)
+        .collect()
+}
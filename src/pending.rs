@@ -0,0 +1,60 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::registry::{self, Hive};
+use crate::Result;
+use std::fmt::Display;
+
+const INSTALLER_IN_PROGRESS_KEY: &str =
+    r"Software\Microsoft\Windows\CurrentVersion\Installer\InProgress";
+const SESSION_MANAGER_KEY: &str = r"SYSTEM\CurrentControlSet\Control\Session Manager";
+const CBS_REBOOT_PENDING_KEY: &str =
+    r"Software\Microsoft\Windows\CurrentVersion\Component Based Servicing\RebootPending";
+
+/// An operation left pending by a previous install or update that commonly causes installs to fail.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PendingOperation {
+    /// Another Windows Installer install is in progress or was interrupted.
+    InstallInProgress,
+
+    /// Files are scheduled to be replaced or deleted when the computer restarts.
+    FileRenames,
+
+    /// Component Based Servicing requires a restart to finish installing updates.
+    ServicingReboot,
+}
+
+impl Display for PendingOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PendingOperation::InstallInProgress => "an install is in progress or was interrupted",
+            PendingOperation::FileRenames => "file operations are pending a reboot",
+            PendingOperation::ServicingReboot => "Windows updates are pending a reboot",
+        })
+    }
+}
+
+/// Gets operations pending from previous installs or updates.
+pub fn pending_operations() -> Result<Vec<PendingOperation>> {
+    let mut pending = Vec::new();
+
+    if registry::key_exists(Hive::LocalMachine, INSTALLER_IN_PROGRESS_KEY)? {
+        pending.push(PendingOperation::InstallInProgress);
+    }
+
+    // An empty REG_MULTI_SZ is at most two nul characters.
+    let renames = registry::value_len(
+        Hive::LocalMachine,
+        SESSION_MANAGER_KEY,
+        "PendingFileRenameOperations",
+    )?;
+    if renames.is_some_and(|len| len > 4) {
+        pending.push(PendingOperation::FileRenames);
+    }
+
+    if registry::key_exists(Hive::LocalMachine, CBS_REBOOT_PENDING_KEY)? {
+        pending.push(PendingOperation::ServicingReboot);
+    }
+
+    Ok(pending)
+}
@@ -0,0 +1,268 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::registry::{self, Hive};
+use crate::{CommandLine, Error, Result};
+use std::fmt::Display;
+
+const INSTALLER_POLICY_KEY: &str = r"Software\Policies\Microsoft\Windows\Installer";
+
+/// The `AlwaysInstallElevated` policy, which installs every package with elevated privileges
+/// when set for both the machine and the user.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct AlwaysInstallElevated {
+    /// Whether the machine policy is set.
+    pub machine: bool,
+
+    /// Whether the policy is set for the current user.
+    pub user: bool,
+}
+
+impl AlwaysInstallElevated {
+    /// Gets whether both policies are set, so Windows Installer elevates per-user installs.
+    ///
+    /// Any user can then run arbitrary code as LocalSystem through a package.
+    pub fn is_enabled(&self) -> bool {
+        self.machine && self.user
+    }
+}
+
+impl Display for AlwaysInstallElevated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.machine, self.user) {
+            (true, true) => f.write_str("enabled for the machine and user"),
+            (true, false) => f.write_str("set for the machine only"),
+            (false, true) => f.write_str("set for the user only"),
+            (false, false) => f.write_str("not set"),
+        }
+    }
+}
+
+/// Reads the `AlwaysInstallElevated` machine and user policies.
+pub fn always_install_elevated() -> Result<AlwaysInstallElevated> {
+    let enabled = |hive| -> Result<bool> {
+        let value = registry::dword(hive, INSTALLER_POLICY_KEY, "AlwaysInstallElevated")?;
+        Ok(value.unwrap_or_default() != 0)
+    };
+
+    Ok(AlwaysInstallElevated {
+        machine: enabled(Hive::LocalMachine)?,
+        user: enabled(Hive::CurrentUser)?,
+    })
+}
+
+/// Windows Installer policies that commonly cause installs to fail.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InstallerPolicy {
+    /// `DisableMSI`: 0 never disables, 1 allows only managed installs, and 2 always disables.
+    pub disable_msi: u32,
+
+    /// `DisablePatch`: prevents applying patches.
+    pub disable_patch: bool,
+
+    /// `DisableUserInstalls`: ignores per-user installs.
+    pub disable_user_installs: bool,
+
+    /// `Logging`: log modes applied to every install, if set.
+    pub logging: Option<String>,
+
+    /// `Debug`: writes debug messages to the debugger and, if 7, the verbose log.
+    pub debug: u32,
+}
+
+impl InstallerPolicy {
+    /// Gets why an install with these properties will likely fail due to policy.
+    pub fn conflicts(&self, properties: &CommandLine) -> Vec<&'static str> {
+        let mut conflicts = Vec::new();
+        match self.disable_msi {
+            0 => {}
+            1 => conflicts.push("the DisableMSI policy allows only managed installs"),
+            _ => conflicts.push("the DisableMSI policy disables Windows Installer"),
+        }
+
+        if self.disable_patch && properties.get("PATCH").is_some() {
+            conflicts.push("the DisablePatch policy prevents applying patches");
+        }
+
+        let per_user = properties.get("ALLUSERS") == Some("")
+            || properties.get("MSIINSTALLPERUSER") == Some("1");
+        if self.disable_user_installs && per_user {
+            conflicts.push("the DisableUserInstalls policy ignores per-user installs");
+        }
+
+        conflicts
+    }
+}
+
+impl Display for InstallerPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "DisableMSI: {}", self.disable_msi)?;
+        writeln!(f, "DisablePatch: {}", self.disable_patch)?;
+        writeln!(f, "DisableUserInstalls: {}", self.disable_user_installs)?;
+        writeln!(
+            f,
+            "Logging: {}",
+            self.logging.as_deref().unwrap_or("not set")
+        )?;
+        writeln!(f, "Debug: {}", self.debug)
+    }
+}
+
+/// Reads the Windows Installer policies.
+///
+/// Machine policies take precedence, and user policies apply to values the machine does not set.
+pub fn installer_policy() -> Result<InstallerPolicy> {
+    let dword = |name| -> Result<u32> {
+        let value = match registry::dword(Hive::LocalMachine, INSTALLER_POLICY_KEY, name)? {
+            Some(value) => Some(value),
+            None => registry::dword(Hive::CurrentUser, INSTALLER_POLICY_KEY, name)?,
+        };
+        Ok(value.unwrap_or_default())
+    };
+    let logging = match registry::string(Hive::LocalMachine, INSTALLER_POLICY_KEY, "Logging")? {
+        Some(logging) => Some(logging),
+        None => registry::string(Hive::CurrentUser, INSTALLER_POLICY_KEY, "Logging")?,
+    };
+
+    Ok(InstallerPolicy {
+        disable_msi: dword("DisableMSI")?,
+        disable_patch: dword("DisablePatch")? != 0,
+        disable_user_installs: dword("DisableUserInstalls")? != 0,
+        logging,
+        debug: dword("Debug")?,
+    })
+}
+
+/// Where [`enable_logging_policy`] saves the policy values it replaced.
+const SAVED_LOGGING_POLICY_KEY: &str = r"Software\msitrace\LoggingPolicy";
+
+/// Characters allowed in a Windows Installer logging mode, e.g. "voicewarmupx".
+const LOGGING_MODES: &str = "iwearucmopvx+!*";
+
+/// The `Logging` and `Debug` machine policies, which enable logging for every install
+/// on the machine, including those started by other processes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LoggingPolicy {
+    /// `Logging`: log modes like "voicewarmupx", or `None` if not set.
+    pub logging: Option<String>,
+
+    /// `Debug`: 7 also writes debug messages to the verbose log, or `None` if not set.
+    pub debug: Option<u32>,
+}
+
+impl LoggingPolicy {
+    fn read(key: &str) -> Result<Self> {
+        Ok(LoggingPolicy {
+            logging: registry::string(Hive::LocalMachine, key, "Logging")?,
+            debug: registry::dword(Hive::LocalMachine, key, "Debug")?,
+        })
+    }
+
+    /// Writes each value, or deletes it if `None`.
+    fn write(&self, key: &str) -> Result<()> {
+        match &self.logging {
+            Some(logging) => registry::set_string(Hive::LocalMachine, key, "Logging", logging)?,
+            None => registry::delete_value(Hive::LocalMachine, key, "Logging")?,
+        }
+        match self.debug {
+            Some(debug) => registry::set_dword(Hive::LocalMachine, key, "Debug", debug),
+            None => registry::delete_value(Hive::LocalMachine, key, "Debug"),
+        }
+    }
+}
+
+/// Reads the `Logging` and `Debug` machine policies.
+pub fn logging_policy() -> Result<LoggingPolicy> {
+    LoggingPolicy::read(INSTALLER_POLICY_KEY)
+}
+
+/// Sets the `Logging` and `Debug` machine policies until [`disable_logging_policy`] is called.
+///
+/// The values they replace are saved so they can be restored, unless saved already by an
+/// earlier call that was not yet disabled. Requires elevation.
+pub fn enable_logging_policy(mode: &str, debug: Option<u32>) -> Result<()> {
+    let policy = logging_policy_for(mode, debug)?;
+    let saved = registry::dword(Hive::LocalMachine, SAVED_LOGGING_POLICY_KEY, "Saved")?;
+    if saved.unwrap_or_default() == 0 {
+        let previous = logging_policy()?;
+        previous.write(SAVED_LOGGING_POLICY_KEY)?;
+        registry::set_dword(Hive::LocalMachine, SAVED_LOGGING_POLICY_KEY, "Saved", 1)?;
+    }
+
+    policy.write(INSTALLER_POLICY_KEY)
+}
+
+/// Restores the machine policies saved by [`enable_logging_policy`].
+///
+/// Returns `false` if there was nothing to restore. Requires elevation.
+pub fn disable_logging_policy() -> Result<bool> {
+    let saved =
+        registry::dword(Hive::LocalMachine, SAVED_LOGGING_POLICY_KEY, "Saved")?.unwrap_or_default();
+    if saved == 0 {
+        return Ok(false);
+    }
+
+    let previous = LoggingPolicy::read(SAVED_LOGGING_POLICY_KEY)?;
+    previous.write(INSTALLER_POLICY_KEY)?;
+    LoggingPolicy::default().write(SAVED_LOGGING_POLICY_KEY)?;
+    registry::delete_value(Hive::LocalMachine, SAVED_LOGGING_POLICY_KEY, "Saved")?;
+
+    Ok(true)
+}
+
+/// Sets the `Logging` and `Debug` machine policies and restores their previous values when dropped.
+///
+/// Installs started by other processes while the guard is held, such as elevated chained
+/// packages or services, also write verbose logs to `%TEMP%`.
+#[derive(Debug)]
+pub struct LoggingPolicyGuard {
+    /// The policies to restore, or `None` if the guard did not change them.
+    previous: Option<LoggingPolicy>,
+}
+
+impl LoggingPolicyGuard {
+    /// Sets the policies for the lifetime of the guard. Requires elevation.
+    ///
+    /// If `debug` is `None`, any existing `Debug` policy is left as is.
+    pub fn new(mode: &str, debug: Option<u32>) -> Result<Self> {
+        let mut policy = logging_policy_for(mode, debug)?;
+        let previous = logging_policy()?;
+        if policy.debug.is_none() {
+            policy.debug = previous.debug;
+        }
+        if policy == previous {
+            return Ok(LoggingPolicyGuard { previous: None });
+        }
+        policy.write(INSTALLER_POLICY_KEY)?;
+
+        Ok(LoggingPolicyGuard {
+            previous: Some(previous),
+        })
+    }
+}
+
+impl Drop for LoggingPolicyGuard {
+    fn drop(&mut self) {
+        let Some(previous) = &self.previous else {
+            return;
+        };
+        if let Err(err) = previous.write(INSTALLER_POLICY_KEY) {
+            eprintln!("warning: failed to restore the logging policy: {}", err);
+        }
+    }
+}
+
+fn logging_policy_for(mode: &str, debug: Option<u32>) -> Result<LoggingPolicy> {
+    let mode = mode.to_ascii_lowercase();
+    if mode.is_empty() || !mode.chars().all(|c| LOGGING_MODES.contains(c)) {
+        return Err(Error::verification(format!(
+            "invalid logging mode {:?}: use characters from {:?}",
+            mode, LOGGING_MODES
+        )));
+    }
+
+    Ok(LoggingPolicy {
+        logging: Some(mode),
+        debug,
+    })
+}
@@ -0,0 +1,354 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::{self, Buttons, Icon, UILevel};
+use crate::{
+    evaluate_source, install_properties, installer_version, lock, preflight, verify_package,
+    Database, Error, Header, InstallOptions, Label, Message, Result, Source,
+};
+use std::fmt::Display;
+
+/// The result of checking whether a package can be installed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PreflightCheck {
+    /// What was checked, e.g. `platform` or a launch condition.
+    pub check: String,
+
+    /// What was found.
+    pub detail: String,
+
+    /// Why the package cannot be installed, if the check failed.
+    pub failure: Option<String>,
+}
+
+impl PreflightCheck {
+    fn ok(check: impl Into<String>, detail: impl Into<String>) -> Self {
+        PreflightCheck {
+            check: check.into(),
+            detail: detail.into(),
+            failure: None,
+        }
+    }
+
+    fn fail(check: impl Into<String>, detail: impl Into<String>, failure: String) -> Self {
+        PreflightCheck {
+            check: check.into(),
+            detail: detail.into(),
+            failure: Some(failure),
+        }
+    }
+
+    /// Gets whether the check passed.
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl Display for PreflightCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.failure {
+            None => write!(f, "ok\t{}\t{}", self.check, self.detail),
+            Some(failure) => write!(f, "FAIL\t{}\t{}: {}", self.check, self.detail, failure),
+        }
+    }
+}
+
+/// Checks whether a package can be installed without installing it.
+///
+/// Prints the same header as [`install`](crate::install), then checks the Windows Installer
+/// version the package requires, its platform, its launch conditions, and whether it or a
+/// newer related product is already installed. Each check is also passed to
+/// [`InstallOptions::handler`] as an `Info` or `Error` message. Fails if any check failed.
+pub fn preflight_package(path: &str, options: &InstallOptions) -> Result<Vec<PreflightCheck>> {
+    let _lock = lock();
+
+    let source = evaluate_source(path)?;
+    if matches!(source, Source::Url { .. }) {
+        return Err(Error::verification("preflight requires a local package"));
+    }
+
+    let service = preflight(options)?;
+    let summary = verify_package(path)?;
+    let properties = install_properties(options)?;
+
+    let header = Header {
+        path,
+        source: &source,
+        service,
+        version: installer_version().ok(),
+        command_line: properties.redacted(),
+        signer: None,
+        authorization: None,
+        language: options.language,
+    };
+    print!("{}", header);
+
+    let database = Database::open(path)?;
+    let property = |name: &str| -> Result<Option<String>> {
+        Ok(database
+            .query(&format!(
+                "SELECT `Value` FROM `Property` WHERE `Property` = '{}'",
+                name
+            ))?
+            .next()
+            .transpose()?
+            .and_then(|row| row.get(0).and_then(|v| v.as_str().map(str::to_owned))))
+    };
+
+    let mut checks = vec![schema(summary.page_count)?];
+    checks.push(platform(summary.template.as_deref().unwrap_or_default()));
+    checks.extend(launch_conditions(path, &database, &properties)?);
+
+    let product_code = property("ProductCode")?.unwrap_or_default();
+    let version = property("ProductVersion")?.unwrap_or_default();
+    checks.push(match ffi::product_info(&product_code, "VersionString") {
+        Ok(installed) => PreflightCheck::ok(
+            "product",
+            format!(
+                "{} {} is installed; maintenance mode",
+                product_code, installed
+            ),
+        ),
+        Err(_) => PreflightCheck::ok("product", format!("{} is not installed", product_code)),
+    });
+    checks.extend(related_products(&database, &product_code, &version)?);
+
+    println!("{}:", options.language.label(Label::Preflight));
+    for check in &checks {
+        println!("  {}", check);
+        if let Some(handler) = &options.handler {
+            let text = check.to_string();
+            // Pass the same flags Windows Installer would send with an equivalent message.
+            let (message, flags) = match check.is_ok() {
+                true => (Message::Info { text: text.clone() }, 0),
+                false => (
+                    Message::Error {
+                        code: None,
+                        text: text.clone(),
+                        buttons: Buttons::OK,
+                        icon: Icon::Error,
+                    },
+                    ffi::MB_OK | ffi::MB_ICONHAND,
+                ),
+            };
+            handler.call(&message, flags, &text);
+        }
+    }
+
+    let failed = checks.iter().filter(|c| !c.is_ok()).count();
+    println!(
+        "  {} of {} checks passed",
+        checks.len() - failed,
+        checks.len()
+    );
+    match failed {
+        0 => Ok(checks),
+        n => Err(Error::verification(format!(
+            "{} preflight checks failed",
+            n
+        ))),
+    }
+}
+
+/// Checks the installed Windows Installer supports the package schema, e.g. 405 for 4.5.
+fn schema(page_count: Option<i32>) -> Result<PreflightCheck> {
+    let schema = page_count.unwrap_or_default().max(0) as u32;
+    let required = (schema / 100, schema % 100);
+    let (major, minor, _) = ffi::installer_version()?;
+    let detail = format!(
+        "schema {} requires Windows Installer {}.{}",
+        schema, required.0, required.1
+    );
+
+    Ok(match (major, minor) >= required {
+        true => PreflightCheck::ok("schema", detail),
+        false => PreflightCheck::fail(
+            "schema",
+            detail,
+            format!("Windows Installer {}.{} is installed", major, minor),
+        ),
+    })
+}
+
+/// Checks the platform in the package template, e.g. "x64;1033", can run on this machine.
+fn platform(template: &str) -> PreflightCheck {
+    let platform = template.split(';').next().unwrap_or_default();
+    // cspell:ignore ARCHITEW
+    let machine = std::env::var("PROCESSOR_ARCHITEW6432")
+        .or_else(|_| std::env::var("PROCESSOR_ARCHITECTURE"))
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+
+    // x64 packages also install on Arm64 machines that emulate x64.
+    let supported = match platform.to_ascii_lowercase().as_str() {
+        "" | "intel" => true,
+        "x64" | "amd64" => matches!(machine.as_str(), "AMD64" | "ARM64"),
+        "arm64" => machine == "ARM64",
+        "arm" => machine == "ARM",
+        "intel64" => machine == "IA64",
+        _ => false,
+    };
+
+    let detail = format!("{} package on {} machine", platform, machine);
+    match supported {
+        true => PreflightCheck::ok("platform", detail),
+        false => PreflightCheck::fail("platform", detail, "unsupported platform".to_owned()),
+    }
+}
+
+/// Evaluates each row of the `LaunchCondition` table after searching for properties.
+fn launch_conditions(
+    path: &str,
+    database: &Database,
+    properties: &crate::CommandLine,
+) -> Result<Vec<PreflightCheck>> {
+    if !database.tables()?.iter().any(|t| t == "LaunchCondition") {
+        return Ok(Vec::new());
+    }
+
+    let _ui = ffi::set_internal_ui(UILevel::None);
+    let session = ffi::open_package(path)?;
+    for (name, value) in properties.iter() {
+        ffi::set_property(*session, name, value)?;
+    }
+
+    // Launch conditions commonly depend on properties set by searches.
+    ffi::do_action(*session, "AppSearch")?;
+
+    let mut checks = Vec::new();
+    for row in database.query("SELECT `Condition`, `Description` FROM `LaunchCondition`")? {
+        let row = row?;
+        let condition = row.get(0).and_then(|v| v.as_str()).unwrap_or_default();
+        let description = row.get(1).and_then(|v| v.as_str()).unwrap_or_default();
+        checks.push(match ffi::evaluate_condition(*session, condition)? {
+            true => PreflightCheck::ok("launch condition", condition),
+            false => PreflightCheck::fail("launch condition", condition, description.to_owned()),
+        });
+    }
+
+    Ok(checks)
+}
+
+/// Only sets the `ActionProperty` when a related product is found, rather than removing it.
+const UPGRADE_ONLY_DETECT: i32 = 0x002;
+
+/// Related products with exactly `VersionMin` match.
+const UPGRADE_VERSION_MIN_INCLUSIVE: i32 = 0x100;
+
+/// Related products with exactly `VersionMax` match.
+const UPGRADE_VERSION_MAX_INCLUSIVE: i32 = 0x200;
+
+/// A row of the `Upgrade` table.
+struct Upgrade {
+    upgrade_code: String,
+    version_min: Option<String>,
+    version_max: Option<String>,
+    attributes: i32,
+}
+
+impl Upgrade {
+    /// Gets whether an installed product with `version` is in the range of this row.
+    fn matches(&self, version: &str) -> bool {
+        let version = parse_version(version);
+        let above_min = self.version_min.as_deref().is_none_or(|min| {
+            match self.attributes & UPGRADE_VERSION_MIN_INCLUSIVE != 0 {
+                true => version >= parse_version(min),
+                false => version > parse_version(min),
+            }
+        });
+        let below_max = self.version_max.as_deref().is_none_or(|max| {
+            match self.attributes & UPGRADE_VERSION_MAX_INCLUSIVE != 0 {
+                true => version <= parse_version(max),
+                false => version < parse_version(max),
+            }
+        });
+
+        above_min && below_max
+    }
+}
+
+/// Finds products installed with the package's upgrade codes and how the `Upgrade` table
+/// handles them, failing if a newer one is only detected, which packages use to block
+/// downgrades. Languages are not compared.
+fn related_products(
+    database: &Database,
+    product_code: &str,
+    version: &str,
+) -> Result<Vec<PreflightCheck>> {
+    if !database.tables()?.iter().any(|t| t == "Upgrade") {
+        return Ok(Vec::new());
+    }
+
+    let mut upgrades: Vec<Upgrade> = Vec::new();
+    for row in database
+        .query("SELECT `UpgradeCode`, `VersionMin`, `VersionMax`, `Attributes` FROM `Upgrade`")?
+    {
+        let row = row?;
+        let string = |i| row.get(i).and_then(|v| v.as_str()).map(str::to_owned);
+        if let Some(upgrade_code) = string(0) {
+            upgrades.push(Upgrade {
+                upgrade_code,
+                version_min: string(1).filter(|v| !v.is_empty()),
+                version_max: string(2).filter(|v| !v.is_empty()),
+                attributes: row.get(3).and_then(|v| v.as_integer()).unwrap_or_default(),
+            });
+        }
+    }
+
+    let mut upgrade_codes: Vec<&str> = Vec::new();
+    for upgrade in &upgrades {
+        if !upgrade_codes
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(&upgrade.upgrade_code))
+        {
+            upgrade_codes.push(&upgrade.upgrade_code);
+        }
+    }
+
+    let mut checks = Vec::new();
+    for upgrade_code in upgrade_codes {
+        for related in ffi::related_products(upgrade_code)? {
+            if related.eq_ignore_ascii_case(product_code) {
+                continue;
+            }
+
+            let installed = ffi::product_info(&related, "VersionString").unwrap_or_default();
+            let detail = format!("{} {} is installed", related, installed);
+            let upgrade = upgrades.iter().find(|upgrade| {
+                upgrade.upgrade_code.eq_ignore_ascii_case(upgrade_code)
+                    && upgrade.matches(&installed)
+            });
+            checks.push(match upgrade {
+                None => PreflightCheck::ok(
+                    "related product",
+                    format!("{}; not in any upgrade range", detail),
+                ),
+                Some(upgrade)
+                    if upgrade.attributes & UPGRADE_ONLY_DETECT != 0
+                        && parse_version(&installed) > parse_version(version) =>
+                {
+                    PreflightCheck::fail(
+                        "related product",
+                        detail,
+                        format!("newer than {}", version),
+                    )
+                }
+                Some(upgrade) if upgrade.attributes & UPGRADE_ONLY_DETECT != 0 => {
+                    PreflightCheck::ok("related product", format!("{}; detected only", detail))
+                }
+                Some(_) => {
+                    PreflightCheck::ok("related product", format!("{}; will be upgraded", detail))
+                }
+            });
+        }
+    }
+
+    Ok(checks)
+}
+
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.trim().parse().unwrap_or_default())
+        .collect()
+}
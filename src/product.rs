@@ -0,0 +1,213 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::source::supply_source;
+use crate::{
+    ffi, install, lock, preflight, trace, verify_package, Database, Error, InstallOptions, Label,
+    ReinstallMode, Result,
+};
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// The copy of a package Windows Installer caches for repairs, patches, and uninstalls.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CachedPackage {
+    /// The path to the cached package, typically under _C:\Windows\Installer_.
+    pub path: PathBuf,
+
+    /// The package code registered for the product.
+    pub package_code: String,
+
+    /// Whether the cached package exists.
+    pub exists: bool,
+
+    /// The package code of the cached package, if it exists and can be read.
+    pub actual_package_code: Option<String>,
+}
+
+impl CachedPackage {
+    /// Gets whether the cached package exists and matches the registered package code.
+    pub fn is_valid(&self) -> bool {
+        self.actual_package_code
+            .as_deref()
+            .is_some_and(|code| code.eq_ignore_ascii_case(&self.package_code))
+    }
+}
+
+/// A product installed or advertised for the current user or machine.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstalledProduct {
+    /// The product code, e.g. {00000000-0000-0000-0000-000000000000}.
+    pub product_code: String,
+
+    /// The name of the product shown in Programs and Features.
+    pub name: String,
+
+    /// The version of the product, e.g. 1.2.3.
+    pub version: String,
+}
+
+impl InstalledProduct {
+    fn new(product_code: String) -> Self {
+        InstalledProduct {
+            name: ffi::product_info(&product_code, "ProductName").unwrap_or_default(),
+            version: ffi::product_info(&product_code, "VersionString").unwrap_or_default(),
+            product_code,
+        }
+    }
+}
+
+impl Display for InstalledProduct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.product_code, self.name, self.version)
+    }
+}
+
+/// Finds installed products with a name matching `pattern` as [`matches_glob`] does.
+pub fn find_products(pattern: &str) -> Result<Vec<InstalledProduct>> {
+    Ok(ffi::enum_products()?
+        .into_iter()
+        .map(InstalledProduct::new)
+        .filter(|product| matches_glob(pattern, &product.name))
+        .collect())
+}
+
+/// Gets the products installed or advertised with an upgrade code.
+pub fn related_products(upgrade_code: &str) -> Result<Vec<InstalledProduct>> {
+    Ok(ffi::related_products(upgrade_code)?
+        .into_iter()
+        .map(InstalledProduct::new)
+        .collect())
+}
+
+/// Gets whether all of `text` matches `pattern`, ignoring case.
+///
+/// In the pattern, `*` matches any characters and `?` matches any one character.
+pub fn matches_glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Match greedily, and after a mismatch retry from the last `*` with one more character
+    // of text consumed by it. Earlier stars never need to be retried.
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Gets the cached package of an installed product.
+pub fn cached_package(product_code: &str) -> Result<CachedPackage> {
+    let path = PathBuf::from(ffi::product_info(product_code, "LocalPackage")?);
+    let package_code = ffi::product_info(product_code, "PackageCode")?;
+
+    let exists = path.is_file();
+    let actual_package_code = match exists {
+        true => Database::open(&path)
+            .and_then(|database| database.summary_info())
+            .ok()
+            .and_then(|summary| summary.revision_number)
+            .and_then(|code| code.get(..38).map(str::to_owned)),
+        false => None,
+    };
+
+    Ok(CachedPackage {
+        path,
+        package_code,
+        exists,
+        actual_package_code,
+    })
+}
+
+/// Re-caches a missing or corrupt cached package from its original source and traces the install.
+///
+/// The source must have the same product code and package code as the installed product.
+/// It is then reinstalled with `REINSTALLMODE=v`, which re-caches the package without
+/// reinstalling files.
+pub fn repair_cache(product_code: &str, source: &str, options: &InstallOptions) -> Result<()> {
+    let package_code = ffi::product_info(product_code, "PackageCode")?;
+    let summary = verify_package(source)?;
+    let source_package_code = summary
+        .revision_number
+        .as_deref()
+        .and_then(|code| code.get(..38))
+        .unwrap_or_default();
+    if !source_package_code.eq_ignore_ascii_case(&package_code) {
+        return Err(Error::verification(format!(
+            "source package code {} does not match the registered package code {}",
+            source_package_code, package_code
+        )));
+    }
+
+    let database = Database::open(source)?;
+    let source_product_code = database
+        .query("SELECT `Value` FROM `Property` WHERE `Property` = 'ProductCode'")?
+        .next()
+        .transpose()?
+        .and_then(|row| row.get(0).and_then(|v| v.as_str().map(str::to_owned)))
+        .unwrap_or_default();
+    if !source_product_code.eq_ignore_ascii_case(product_code) {
+        return Err(Error::verification(format!(
+            "source product code {} does not match {}",
+            source_product_code, product_code
+        )));
+    }
+
+    let mut options = options.clone();
+    options.properties.set("REINSTALL", "ALL")?;
+    options.properties.set("REINSTALLMODE", "v")?;
+
+    install(source, &options)
+}
+
+/// Uninstalls a product using its cached package and traces the uninstall.
+pub fn uninstall(product_code: &str, options: &InstallOptions) -> Result<()> {
+    let cached = cached_package(product_code)?;
+    if !cached.exists {
+        return Err(Error::verification(format!(
+            "cached package {} is missing; repair it with `msitrace cache repair`",
+            cached.path.display()
+        )));
+    }
+
+    let mut options = options.clone();
+    options.properties.set("REMOVE", "ALL")?;
+
+    install(&cached.path.to_string_lossy(), &options)
+}
+
+/// Repairs an installed product and traces the repair.
+///
+/// Properties cannot be passed to a repair, so [`InstallOptions::properties`] are only redacted.
+pub fn repair(product_code: &str, mode: ReinstallMode, options: &InstallOptions) -> Result<()> {
+    let _lock = lock();
+    preflight(options)?;
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Product), product_code);
+    supply_source(product_code, options)?;
+    println!("{}: {}", language.label(Label::ReinstallMode), mode);
+
+    trace(product_code, options, || {
+        ffi::reinstall_product(product_code, mode)
+    })
+}
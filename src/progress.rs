@@ -0,0 +1,178 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#[cfg(windows)]
+use crate::Message;
+
+/// An update to the progress bar sent in a progress message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProgressUpdate {
+    /// Resets the progress bar to expect `total` ticks.
+    MasterReset {
+        total: i32,
+
+        /// Whether the progress bar moves forward, or backward during rollback.
+        forward: bool,
+
+        /// Whether the install is generating its script rather than running actions.
+        script_in_progress: bool,
+    },
+
+    /// Describes the progress messages the current action sends.
+    ActionInfo {
+        /// How many ticks to move the progress bar for each action data message.
+        ticks_per_message: i32,
+
+        /// Whether to move the progress bar for each action data message.
+        increment: bool,
+    },
+
+    /// Moves the progress bar by `ticks`.
+    Report { ticks: i32 },
+
+    /// Adds `ticks` to the total the progress bar expects.
+    Addition { ticks: i32 },
+
+    /// A progress message of an unknown type.
+    Unknown { kind: i32 },
+}
+
+impl ProgressUpdate {
+    /// Parses a progress message from its integer fields, starting with its type in field 1.
+    ///
+    /// Missing fields are 0, as are null fields in a record.
+    pub fn from_fields(fields: &[i32]) -> Self {
+        let field = |i: usize| fields.get(i - 1).copied().unwrap_or_default();
+
+        match field(1) {
+            0 => ProgressUpdate::MasterReset {
+                total: field(2),
+                forward: field(3) == 0,
+                script_in_progress: field(4) == 1,
+            },
+            1 => ProgressUpdate::ActionInfo {
+                ticks_per_message: field(2),
+                increment: field(3) == 1,
+            },
+            2 => ProgressUpdate::Report { ticks: field(2) },
+            3 => ProgressUpdate::Addition { ticks: field(2) },
+            kind => ProgressUpdate::Unknown { kind },
+        }
+    }
+}
+
+/// Accumulates progress messages into how far along an install is and its current action.
+///
+/// Windows Installer resets progress for each phase of an install, such as generating its
+/// script and then running it, so the percentage may return to 0 more than once.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Progress {
+    total: i64,
+    completed: i64,
+    backward: bool,
+    script_in_progress: bool,
+    ticks_per_message: i64,
+    increment: bool,
+    action: Option<String>,
+}
+
+impl Progress {
+    /// Creates a [`Progress`] with no progress.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a progress message.
+    pub fn update(&mut self, update: ProgressUpdate) {
+        match update {
+            ProgressUpdate::MasterReset {
+                total,
+                forward,
+                script_in_progress,
+            } => {
+                self.total = total.max(0).into();
+                self.backward = !forward;
+                self.completed = match forward {
+                    true => 0,
+                    false => self.total,
+                };
+                self.script_in_progress = script_in_progress;
+                self.increment = false;
+            }
+            ProgressUpdate::ActionInfo {
+                ticks_per_message,
+                increment,
+            } => {
+                self.ticks_per_message = ticks_per_message.into();
+                self.increment = increment;
+            }
+            ProgressUpdate::Report { ticks } => self.advance(ticks.into()),
+            ProgressUpdate::Addition { ticks } => self.total += i64::from(ticks.max(0)),
+            ProgressUpdate::Unknown { .. } => {}
+        }
+    }
+
+    /// Records that an action started, which stops action data from moving the progress bar
+    /// until the action sends its own action info.
+    pub fn action_start(&mut self, action: impl Into<String>) {
+        self.action = Some(action.into());
+        self.increment = false;
+    }
+
+    /// Records an action data message, which moves the progress bar if the current action
+    /// asked it to.
+    pub fn action_data(&mut self) {
+        if self.increment {
+            self.advance(self.ticks_per_message);
+        }
+    }
+
+    /// Applies any message that affects progress and ignores the rest.
+    #[cfg(windows)]
+    pub fn observe(&mut self, message: &Message) {
+        match message {
+            Message::Progress(update) => self.update(*update),
+            Message::ActionStart { action, .. } => self.action_start(action.as_str()),
+            Message::ActionData { .. } => self.action_data(),
+            _ => {}
+        }
+    }
+
+    /// Gets how far along the progress bar is from 0 to 100, or `None` before any total
+    /// is known.
+    ///
+    /// During rollback the progress bar moves backward, so the percentage decreases.
+    pub fn percent(&self) -> Option<f64> {
+        match self.total {
+            0 => None,
+            total => Some((self.completed as f64 * 100.0 / total as f64).clamp(0.0, 100.0)),
+        }
+    }
+
+    /// Gets the name of the current action, if any has started.
+    pub fn action(&self) -> Option<&str> {
+        self.action.as_deref()
+    }
+
+    /// Gets whether the install is generating its script rather than running actions.
+    pub fn is_generating_script(&self) -> bool {
+        self.script_in_progress
+    }
+
+    /// Gets whether the progress bar is moving backward because the install is rolling back.
+    pub fn is_rolling_back(&self) -> bool {
+        self.backward
+    }
+
+    fn advance(&mut self, ticks: i64) {
+        if self.total == 0 {
+            return;
+        }
+
+        self.completed = match self.backward {
+            true => self.completed - ticks,
+            false => self.completed + ticks,
+        }
+        .clamp(0, self.total);
+    }
+}
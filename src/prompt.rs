@@ -0,0 +1,95 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::{Buttons, HandlerResult};
+use crate::Application;
+use std::io::{BufRead, IsTerminal, Write};
+
+type Choice = (char, &'static str, HandlerResult);
+
+/// Asks the user how to respond to an error using the buttons Windows Installer would show.
+pub(crate) fn error(text: &str, flags: u32) -> HandlerResult {
+    use HandlerResult::*;
+
+    let choices: &[Choice] = match Buttons::from_flags(flags) {
+        Buttons::OKCancel => &[('o', "OK", OK), ('c', "Cancel", Cancel)],
+        Buttons::AbortRetryIgnore => &[
+            ('a', "Abort", Abort),
+            ('r', "Retry", Retry),
+            ('i', "Ignore", Ignore),
+        ],
+        Buttons::YesNoCancel => &[('y', "Yes", Yes), ('n', "No", No), ('c', "Cancel", Cancel)],
+        Buttons::YesNo => &[('y', "Yes", Yes), ('n', "No", No)],
+        Buttons::RetryCancel => &[('r', "Retry", Retry), ('c', "Cancel", Cancel)],
+        // Errors with only an OK button have nothing to decide.
+        _ => return Default,
+    };
+
+    ask(text, choices)
+}
+
+/// Asks the user whether to retry or continue when files are in use.
+///
+/// Applications listed by the Restart Manager can also be closed by Windows Installer.
+pub(crate) fn files_in_use(applications: &[Application], restart_manager: bool) -> HandlerResult {
+    use HandlerResult::*;
+
+    let mut question = String::from("Files are in use by these applications:");
+    for application in applications {
+        question.push_str(&format!("\n  {}", application));
+    }
+
+    let choices: &[Choice] = match restart_manager {
+        true => &[
+            ('c', "Close applications", HandlerResult::CLOSE_APPLICATIONS),
+            (
+                'i',
+                "Ignore and reboot later",
+                HandlerResult::KEEP_APPLICATIONS,
+            ),
+            ('x', "Cancel", Cancel),
+        ],
+        false => &[
+            ('r', "Retry after closing them", Retry),
+            ('i', "Ignore and reboot later", Ignore),
+            ('x', "Cancel", Cancel),
+        ],
+    };
+
+    ask(&question, choices)
+}
+
+/// Prompts on the terminal until the user picks a choice, or returns the default if
+/// there is no terminal to prompt on.
+fn ask(question: &str, choices: &[Choice]) -> HandlerResult {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return HandlerResult::Default;
+    }
+
+    let prompt: Vec<String> = choices
+        .iter()
+        .map(|(key, label, _)| format!("[{}] {}", key, label))
+        .collect();
+
+    let mut stderr = std::io::stderr();
+    let _ = writeln!(stderr, "{}", question);
+    loop {
+        let _ = write!(stderr, "{}? ", prompt.join(", "));
+        let _ = stderr.flush();
+
+        let mut answer = String::new();
+        match stdin.lock().read_line(&mut answer) {
+            Ok(0) | Err(_) => return HandlerResult::Default,
+            Ok(_) => {}
+        }
+
+        let answer = answer.trim().to_lowercase();
+        let choice = choices
+            .iter()
+            .find(|(key, label, _)| answer == key.to_string() || answer == label.to_lowercase());
+        if let Some((_, _, result)) = choice {
+            return *result;
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{ffi, lock, preflight, trace, InstallOptions, Label, Result};
+use std::path::PathBuf;
+
+/// How to provide a component with [`provide_component`] or [`provide_qualified_component`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ProvideMode {
+    /// Install the component if it is missing or broken, like an advertised shortcut would.
+    #[default]
+    Default,
+
+    /// Only get the path of a component that is installed, without installing it.
+    Existing,
+
+    /// Install the component if it is not installed, without checking its key path exists.
+    NoDetection,
+
+    /// Like `default`, but fail instead of resolving a source that is not available.
+    NoSourceResolution,
+}
+
+impl ProvideMode {
+    fn install_mode(self) -> u32 {
+        // INSTALLMODE values are negative.
+        let mode: i32 = match self {
+            ProvideMode::Default => 0,
+            ProvideMode::Existing => -1,
+            ProvideMode::NoDetection => -2,
+            ProvideMode::NoSourceResolution => -3,
+        };
+        mode as u32
+    }
+}
+
+/// Installs a component of a product feature if needed and traces the install, as
+/// Windows Installer does when an advertised shortcut is started, and gets its key path.
+pub fn provide_component(
+    product_code: &str,
+    feature: &str,
+    component_code: &str,
+    mode: ProvideMode,
+    options: &InstallOptions,
+) -> Result<PathBuf> {
+    let _lock = lock();
+    preflight(options)?;
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Product), product_code);
+    println!("{}: {}", language.label(Label::Feature), feature);
+    println!("{}: {}", language.label(Label::Component), component_code);
+
+    let mut path = String::new();
+    trace(product_code, options, || {
+        path = ffi::provide_component(product_code, feature, component_code, mode.install_mode())?;
+        Ok(())
+    })?;
+
+    println!("{}: {}", language.label(Label::Path), path);
+    Ok(PathBuf::from(path))
+}
+
+/// Installs a qualified component if needed and traces the install, as Windows Installer
+/// does when an application requests a component by category and qualifier, and gets
+/// its key path.
+pub fn provide_qualified_component(
+    category: &str,
+    qualifier: &str,
+    mode: ProvideMode,
+    options: &InstallOptions,
+) -> Result<PathBuf> {
+    let _lock = lock();
+    preflight(options)?;
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Category), category);
+    println!("{}: {}", language.label(Label::Qualifier), qualifier);
+
+    let mut path = String::new();
+    trace(category, options, || {
+        path = ffi::provide_qualified_component(category, qualifier, mode.install_mode())?;
+        Ok(())
+    })?;
+
+    println!("{}: {}", language.label(Label::Path), path);
+    Ok(PathBuf::from(path))
+}
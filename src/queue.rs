@@ -0,0 +1,211 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::mutex::NamedMutex;
+use crate::version::require_installer;
+use crate::{install, InstallOptions, Result, Transaction};
+use std::time::Duration;
+
+/// Held by msitrace while running a queue so queues from other processes wait their turn.
+const QUEUE_MUTEX: &str = r"Global\msitrace.queue";
+
+/// Held by Windows Installer while an install is running its execution script.
+const INSTALLER_MUTEX: &str = r"Global\_MSIExecute";
+
+/// Installs packages one at a time, waiting for Windows Installer to finish any other install
+/// between items.
+///
+/// Queues in other msitrace processes are serialized with this one, so installs started
+/// repeatedly with `--queue` run in order rather than failing with error 1618.
+#[derive(Clone, Debug, Default)]
+pub struct InstallQueue {
+    items: Vec<QueueItem>,
+    transaction: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+struct QueueItem {
+    path: String,
+    options: InstallOptions,
+    optional: bool,
+}
+
+impl InstallQueue {
+    /// Creates an empty [`InstallQueue`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a package to install after all previously added packages.
+    pub fn push(&mut self, path: impl Into<String>, options: InstallOptions) -> &mut Self {
+        self.items.push(QueueItem {
+            path: path.into(),
+            options,
+            optional: false,
+        });
+        self
+    }
+
+    /// Adds a package to install after all previously added packages, continuing with
+    /// later packages if it fails.
+    pub fn push_optional(&mut self, path: impl Into<String>, options: InstallOptions) -> &mut Self {
+        self.items.push(QueueItem {
+            path: path.into(),
+            options,
+            optional: true,
+        });
+        self
+    }
+
+    /// Installs every package in a [`Transaction`] with this name, so all are rolled back
+    /// if any fails.
+    ///
+    /// Packages that require a reboot did not fail, so the transaction is still committed.
+    pub fn transaction(&mut self, name: impl Into<String>) -> &mut Self {
+        self.transaction = Some(name.into());
+        self
+    }
+
+    /// Gets the number of queued packages.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Gets whether no packages are queued.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Installs each queued package in order, stopping at the first failure of a package
+    /// not added with [`push_optional`](InstallQueue::push_optional).
+    ///
+    /// Packages that require a reboot still succeeded, so later packages are installed and
+    /// the reboot error is returned after the whole queue finishes.
+    pub fn run(self) -> Result<()> {
+        let queue = NamedMutex::create(QUEUE_MUTEX)?;
+        let _queue = match queue.try_lock()? {
+            Some(guard) => guard,
+            None => {
+                println!("Waiting for queued msitrace operations in other processes to finish");
+                queue.lock()?
+            }
+        };
+
+        let transaction = match &self.transaction {
+            Some(name) => {
+                require_installer(4, 5, "transactions")?;
+                wait_for_installer()?;
+                println!("Beginning transaction {}", name);
+                Some(Transaction::begin(name)?)
+            }
+            None => None,
+        };
+
+        let count = self.items.len();
+        let mut failed = 0;
+        let mut reboot = None;
+        for (i, item) in self.items.into_iter().enumerate() {
+            let QueueItem {
+                path,
+                options,
+                optional,
+            } = item;
+            println!("Queue item {} of {}: {}", i + 1, count, path);
+
+            // The transaction holds the installer mutex until it ends.
+            if transaction.is_none() {
+                wait_for_installer()?;
+            }
+
+            match install(&path, &options) {
+                Ok(()) => println!("Queue item {} of {} finished", i + 1, count),
+                // Reboot codes still succeeded, so report them after all are installed.
+                Err(err) if matches!(err.code(), Some(3010 | 1641)) => {
+                    println!(
+                        "Queue item {} of {} finished; a reboot is required",
+                        i + 1,
+                        count
+                    );
+                    reboot = Some(err);
+                }
+                Err(err) if optional && !err.is_cancelled() => {
+                    println!("Queue item {} of {} failed: {}", i + 1, count, err);
+                    println!("Continuing since queue item {} is optional", i + 1);
+                    failed += 1;
+                }
+                Err(err) => {
+                    println!("Queue item {} of {} failed: {}", i + 1, count, err);
+                    if count > i + 1 {
+                        println!("Skipping {} remaining queue items", count - i - 1);
+                    }
+                    if let Some(transaction) = transaction {
+                        println!("Rolling back transaction");
+                        transaction.rollback()?;
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        if let Some(transaction) = transaction {
+            println!("Committing transaction");
+            match transaction.commit() {
+                Ok(()) => {}
+                // Files in use are replaced when the transaction commits, which may require a reboot.
+                Err(err) if matches!(err.code(), Some(3010 | 1641)) => {
+                    println!("Committed transaction; a reboot is required");
+                    reboot = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if failed > 0 {
+            println!("{} optional queue items failed", failed);
+        }
+
+        match reboot {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Gets whether another install is running its execution script, which would cause
+/// an install started now to fail with error 1618.
+pub fn is_installer_busy() -> Result<bool> {
+    let Some(mutex) = NamedMutex::open(INSTALLER_MUTEX)? else {
+        return Ok(false);
+    };
+
+    let busy = mutex.try_lock()?.is_none();
+    Ok(busy)
+}
+
+/// Waits up to `timeout` for another Windows Installer install to finish, returning whether
+/// it finished or was not running.
+///
+/// Unlike [`wait_for_installer`], this returns `false` instead of waiting any longer, so
+/// callers can retry or fail with error 1618 themselves.
+pub(crate) fn wait_for_installer_for(timeout: Duration) -> Result<bool> {
+    let Some(mutex) = NamedMutex::open(INSTALLER_MUTEX)? else {
+        return Ok(true);
+    };
+
+    let finished = mutex.try_lock_for(timeout)?.is_some();
+    Ok(finished)
+}
+
+/// Waits until Windows Installer is not running another install, however long it takes.
+fn wait_for_installer() -> Result<()> {
+    let Some(mutex) = NamedMutex::open(INSTALLER_MUTEX)? else {
+        return Ok(());
+    };
+
+    // Only wait for the mutex; holding it would prevent our own install from running.
+    if mutex.try_lock()?.is_none() {
+        println!("Waiting for another Windows Installer install to finish");
+        drop(mutex.lock()?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::source::supply_source;
+use crate::{ffi, lock, preflight, trace, Error, FeatureState, InstallOptions, Label, Result};
+
+/// Reinstalls a missing or broken component of an installed product and traces the install.
+///
+/// The component is installed `local`, from `source`, or to the `default` state authored for
+/// it. Properties cannot be passed when recovering a component, so
+/// [`InstallOptions::properties`] are only redacted.
+pub fn recover_component(
+    product_code: &str,
+    component: &str,
+    state: FeatureState,
+    options: &InstallOptions,
+) -> Result<()> {
+    if matches!(state, FeatureState::Advertised | FeatureState::Absent) {
+        return Err(Error::verification(format!(
+            "a component cannot be recovered to the {} state",
+            state
+        )));
+    }
+
+    let _lock = lock();
+    preflight(options)?;
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Product), product_code);
+    supply_source(product_code, options)?;
+    println!("{}: {}", language.label(Label::Component), component);
+    println!("{}: {}", language.label(Label::FeatureState), state);
+
+    trace(product_code, options, || {
+        ffi::install_missing_component(product_code, component, state as i32)
+    })
+}
+
+/// Reinstalls a missing or broken file of an installed product and traces the install.
+///
+/// `file` is the name of the file in the `File` table, not its path. Properties cannot be
+/// passed when recovering a file, so [`InstallOptions::properties`] are only redacted.
+pub fn recover_file(product_code: &str, file: &str, options: &InstallOptions) -> Result<()> {
+    let _lock = lock();
+    preflight(options)?;
+
+    let language = options.language;
+    println!("{}: {}", language.label(Label::Product), product_code);
+    supply_source(product_code, options)?;
+    println!("{}: {}", language.label(Label::File), file);
+
+    trace(product_code, options, || {
+        ffi::install_missing_file(product_code, file)
+    })
+}
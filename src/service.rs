@@ -0,0 +1,120 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::service;
+use crate::{installer_version, is_installer_busy, Error, InstallerVersion, Result};
+use std::fmt::Display;
+
+const INSTALLER_SERVICE: &str = "msiserver";
+
+/// The state of the Windows Installer service.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ServiceState {
+    /// The service is not installed.
+    Missing,
+
+    /// The service is disabled and cannot be started.
+    Disabled,
+
+    /// The service is stopped and will be started on demand.
+    Stopped,
+
+    /// The service is running.
+    Running,
+
+    /// The service is starting, stopping, or paused.
+    Pending,
+}
+
+impl ServiceState {
+    /// Gets why installs will fail in this state, if they will.
+    ///
+    /// Installs otherwise fail with the vague error 1601.
+    pub fn failure_reason(&self) -> Option<&'static str> {
+        match self {
+            ServiceState::Missing => Some("the Windows Installer service is not installed"),
+            ServiceState::Disabled => Some("the Windows Installer service is disabled"),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ServiceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ServiceState::Missing => "missing",
+            ServiceState::Disabled => "disabled",
+            ServiceState::Stopped => "stopped",
+            ServiceState::Running => "running",
+            ServiceState::Pending => "pending",
+        })
+    }
+}
+
+/// Whether Windows Installer can start an install now.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InstallerStatus {
+    /// The state of the Windows Installer service.
+    pub service: ServiceState,
+
+    /// Whether another install is in progress.
+    pub busy: bool,
+
+    /// The version of the Windows Installer engine, if it could be determined.
+    pub version: Option<InstallerVersion>,
+}
+
+impl InstallerStatus {
+    /// Gets why an install started now would fail, if it would.
+    ///
+    /// Installs otherwise fail with the vague errors 1601 or 1618.
+    pub fn failure_reason(&self) -> Option<&'static str> {
+        match self.service.failure_reason() {
+            Some(reason) => Some(reason),
+            None if self.busy => Some(
+                "another install is in progress; wait for it to finish or retry with --wait-busy",
+            ),
+            None => None,
+        }
+    }
+}
+
+/// Gets the state of the Windows Installer service, whether another install is in progress,
+/// and the version of the engine.
+pub fn installer_status() -> Result<InstallerStatus> {
+    Ok(InstallerStatus {
+        service: installer_service()?,
+        busy: is_installer_busy()?,
+        version: installer_version().ok(),
+    })
+}
+
+/// Gets the state of the Windows Installer service.
+pub fn installer_service() -> Result<ServiceState> {
+    let status = match service::status(INSTALLER_SERVICE)? {
+        Some(status) => status,
+        None => return Ok(ServiceState::Missing),
+    };
+
+    Ok(match (status.start_type, status.current_state) {
+        (service::SERVICE_DISABLED, _) => ServiceState::Disabled,
+        (_, service::SERVICE_STOPPED) => ServiceState::Stopped,
+        (_, service::SERVICE_RUNNING) => ServiceState::Running,
+        _ => ServiceState::Pending,
+    })
+}
+
+/// Starts the Windows Installer service if it is stopped, and gets its new state.
+pub fn start_installer_service() -> Result<ServiceState> {
+    let state = installer_service()?;
+    if let Some(reason) = state.failure_reason() {
+        return Err(Error::verification(reason));
+    }
+
+    if state == ServiceState::Stopped {
+        service::start(INSTALLER_SERVICE)?;
+        return installer_service();
+    }
+
+    Ok(state)
+}
@@ -0,0 +1,102 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::wintrust;
+use crate::{Error, InstallOptions, Result};
+use std::fmt::Display;
+
+/// The signer of a package with a trusted Authenticode signature.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Signer {
+    /// The display name of the signer certificate subject.
+    pub subject: String,
+
+    /// The display name of the signer certificate issuer.
+    pub issuer: String,
+
+    /// The SHA-1 thumbprint of the signer certificate as uppercase hexadecimal.
+    pub thumbprint: String,
+}
+
+impl Signer {
+    /// Gets whether the signer certificate has the given SHA-1 thumbprint.
+    ///
+    /// Spaces and colons are ignored and hexadecimal digits may be either case.
+    pub fn matches_thumbprint(&self, thumbprint: &str) -> bool {
+        let thumbprint: String = thumbprint
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != ':')
+            .collect();
+
+        self.thumbprint.eq_ignore_ascii_case(&thumbprint)
+    }
+}
+
+impl Display for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (issued by {}, thumbprint {})",
+            self.subject, self.issuer, self.thumbprint
+        )
+    }
+}
+
+/// Verifies the Authenticode signature of a package is trusted and gets its [`Signer`].
+///
+/// If `thumbprint` is specified, the signer certificate must also have that SHA-1 thumbprint.
+pub fn verify_signature(path: &str, thumbprint: Option<&str>) -> Result<Signer> {
+    verify_signature_if_signed(path, thumbprint)?
+        .ok_or_else(|| Error::verification("package is not signed"))
+}
+
+/// Verifies the Authenticode signature of a package like [`verify_signature`], but gets `None`
+/// instead of failing if the package is not signed at all.
+///
+/// Packages that are not signed still fail if `thumbprint` is specified.
+pub fn verify_signature_if_signed(path: &str, thumbprint: Option<&str>) -> Result<Option<Signer>> {
+    let cert = match wintrust::verify_file(path)? {
+        Some(cert) => cert,
+        None if thumbprint.is_some() => return Err(Error::verification("package is not signed")),
+        None => return Ok(None),
+    };
+    let signer = Signer {
+        subject: cert.subject,
+        issuer: cert.issuer,
+        thumbprint: cert
+            .thumbprint
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect(),
+    };
+
+    if let Some(thumbprint) = thumbprint {
+        if !signer.matches_thumbprint(thumbprint) {
+            return Err(Error::verification(format!(
+                "signer thumbprint {} does not match {}",
+                signer.thumbprint, thumbprint
+            )));
+        }
+    }
+
+    Ok(Some(signer))
+}
+
+/// Verifies the signature of a package as required by `options`, warning about packages
+/// that are not signed when [`InstallOptions::allow_unsigned`] is set.
+pub(crate) fn check_signature(path: &str, options: &InstallOptions) -> Result<Option<Signer>> {
+    if !options.require_signed && options.publisher_thumbprint.is_none() {
+        return Ok(None);
+    }
+
+    if !options.allow_unsigned {
+        return verify_signature(path, options.publisher_thumbprint.as_deref()).map(Some);
+    }
+
+    let signer = verify_signature_if_signed(path, options.publisher_thumbprint.as_deref())?;
+    if signer.is_none() {
+        eprintln!("warning: {} is not signed", path);
+    }
+
+    Ok(signer)
+}
@@ -0,0 +1,123 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::ffi::security::{self, WellKnownSid};
+use crate::{Error, InstallOptions, Label, Result};
+use std::fmt::Display;
+use std::path::Path;
+
+/// Where a package is installed from and whether that source can be trusted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Source {
+    /// A local or mapped drive.
+    Local,
+
+    /// A UNC path to a network share.
+    Network {
+        /// Well-known anonymous identities that can write to the package directory.
+        writable_by: Vec<String>,
+    },
+
+    /// A URL Windows Installer will download the package from.
+    Url {
+        /// Whether the URL uses an encrypted transport such as HTTPS.
+        encrypted: bool,
+    },
+}
+
+impl Source {
+    /// Gets why the source is insecure, if it is.
+    ///
+    /// Packages downloaded over plain HTTP or from shares anonymous users can write to
+    /// may have been tampered with.
+    pub fn insecure_reason(&self) -> Option<String> {
+        match self {
+            Source::Url { encrypted: false } => Some("the URL is not encrypted".to_owned()),
+            Source::Network { writable_by } if !writable_by.is_empty() => Some(format!(
+                "the network share is writable by {}",
+                writable_by.join(", ")
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Local => f.write_str("local")?,
+            Source::Network { .. } => f.write_str("network share")?,
+            Source::Url { encrypted: true } => f.write_str("encrypted URL")?,
+            Source::Url { encrypted: false } => f.write_str("unencrypted URL")?,
+        }
+
+        match self.insecure_reason() {
+            Some(reason) => write!(f, " (insecure: {})", reason),
+            None => f.write_str(" (trusted)"),
+        }
+    }
+}
+
+/// Evaluates whether the source of a package can be trusted.
+pub fn evaluate_source(path: &str) -> Result<Source> {
+    if let Some((scheme, _)) = path.split_once("://") {
+        return match scheme.to_ascii_lowercase().as_str() {
+            "https" => Ok(Source::Url { encrypted: true }),
+            "http" | "ftp" => Ok(Source::Url { encrypted: false }),
+            _ => Err(Error::verification(format!(
+                "unsupported package source {}",
+                scheme
+            ))),
+        };
+    }
+
+    let path = path
+        .strip_prefix(r"\\?\UNC\")
+        .map_or_else(|| path.to_owned(), |path| format!(r"\\{}", path));
+    if !path.starts_with(r"\\") || path.starts_with(r"\\?\") {
+        return Ok(Source::Local);
+    }
+
+    let directory = Path::new(&path)
+        .parent()
+        .map_or_else(|| path.clone(), |p| p.to_string_lossy().into_owned());
+    let writable_by = security::writable_by(
+        &directory,
+        &[
+            WellKnownSid::Everyone,
+            WellKnownSid::Anonymous,
+            WellKnownSid::Guests,
+        ],
+    )?;
+
+    Ok(Source::Network {
+        writable_by: writable_by.iter().map(|s| s.name().to_owned()).collect(),
+    })
+}
+
+/// Adds a directory containing the original package to the source list of an installed
+/// product and forces Windows Installer to search the source list again.
+///
+/// Repairs and maintenance installs otherwise fail with error 1612 when the source the
+/// product was last installed from is missing.
+pub fn add_product_source(product_code: &str, path: &str) -> Result<()> {
+    // Network sources are directories and must end with a separator.
+    let mut path = path.to_owned();
+    if !path.ends_with(['\\', '/']) {
+        path.push('\\');
+    }
+
+    ffi::add_network_source(product_code, &path)?;
+    ffi::force_source_resolution(product_code)
+}
+
+/// Adds [`InstallOptions::source`], if any, to the source list of an installed product.
+pub(crate) fn supply_source(product_code: &str, options: &InstallOptions) -> Result<()> {
+    if let Some(source) = &options.source {
+        println!("{}: {}", options.language.label(Label::Source), source);
+        add_product_source(product_code, source)?;
+    }
+
+    Ok(())
+}
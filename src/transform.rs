@@ -0,0 +1,188 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Database, Result, SummaryInfo};
+use std::fmt::Display;
+use std::path::Path;
+
+/// Describes the changes in a transform in the `_TransformView` table instead of making them.
+const MSITRANSFORM_ERROR_VIEWTRANSFORM: u32 = 0x0100;
+
+/// Error conditions a transform can suppress, from the low word of `PID_CHARCOUNT`.
+const ERROR_CONDITIONS: &[(u32, &str)] = &[
+    (0x0001, "adding a row that exists"),
+    (0x0002, "deleting a row that does not exist"),
+    (0x0004, "adding a table that exists"),
+    (0x0008, "deleting a table that does not exist"),
+    (0x0010, "updating a row that does not exist"),
+    (0x0020, "transform and database code pages differ"),
+];
+
+/// Conditions a transform validates, from the high word of `PID_CHARCOUNT`.
+const VALIDATIONS: &[(u32, &str)] = &[
+    (0x0001, "language"),
+    (0x0002, "product code"),
+    (0x0004, "platform"),
+    (0x0008, "major version"),
+    (0x0010, "minor version"),
+    (0x0020, "update version"),
+    (0x0040, "new version < base version"),
+    (0x0080, "new version <= base version"),
+    (0x0100, "new version = base version"),
+    (0x0200, "new version >= base version"),
+    (0x0400, "new version > base version"),
+    (0x0800, "upgrade code"),
+];
+
+/// What a transform applies to, read from its summary information.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TransformInfo {
+    /// The product code of the product the transform applies to.
+    pub target_product_code: Option<String>,
+
+    /// The version of the product the transform applies to.
+    pub target_version: Option<String>,
+
+    /// The product code after the transform is applied.
+    pub upgraded_product_code: Option<String>,
+
+    /// The version of the product after the transform is applied.
+    pub upgraded_version: Option<String>,
+
+    /// The upgrade code of the product.
+    pub upgrade_code: Option<String>,
+
+    /// What Windows Installer checks before applying the transform.
+    pub validations: Vec<&'static str>,
+
+    /// Errors Windows Installer ignores when applying the transform.
+    pub suppressed_errors: Vec<&'static str>,
+}
+
+impl TransformInfo {
+    pub(crate) fn from_summary(summary: &SummaryInfo) -> Self {
+        // Transforms list "{TargetProductCode}TargetVersion;{UpgradedProductCode}UpgradedVersion;{UpgradeCode}".
+        let revision = summary.revision_number.as_deref().unwrap_or_default();
+        let mut products = revision.split(';');
+        let target = products.next().unwrap_or_default();
+        let upgraded = products.next().unwrap_or_default();
+        let upgrade_code = products.next().unwrap_or_default();
+
+        let code = |value: &str| value.get(..38).map(str::to_owned);
+        let version = |value: &str| value.get(38..).filter(|v| !v.is_empty()).map(str::to_owned);
+
+        let flags = summary.character_count.unwrap_or_default() as u32;
+        let names = |flags: u32, names: &[(u32, &'static str)]| {
+            names
+                .iter()
+                .filter(|(flag, _)| flags & flag != 0)
+                .map(|(_, name)| *name)
+                .collect()
+        };
+
+        TransformInfo {
+            target_product_code: code(target),
+            target_version: version(target),
+            upgraded_product_code: code(upgraded),
+            upgraded_version: version(upgraded),
+            upgrade_code: code(upgrade_code),
+            validations: names(flags >> 16, VALIDATIONS),
+            suppressed_errors: names(flags & 0xffff, ERROR_CONDITIONS),
+        }
+    }
+}
+
+/// A change a transform makes to a database.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransformChange {
+    /// Adds a table.
+    AddTable { table: String },
+
+    /// Removes a table.
+    DropTable { table: String },
+
+    /// Adds a row, identified by its tab-separated primary keys.
+    AddRow { table: String, row: String },
+
+    /// Removes a row, identified by its tab-separated primary keys.
+    DeleteRow { table: String, row: String },
+
+    /// Changes a cell, or sets it in a row the transform adds.
+    ChangeCell {
+        table: String,
+        row: String,
+        column: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl Display for TransformChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransformChange::AddTable { table } => write!(f, "+ {}", table),
+            TransformChange::DropTable { table } => write!(f, "- {}", table),
+            TransformChange::AddRow { table, row } => write!(f, "+ {} [{}]", table, row),
+            TransformChange::DeleteRow { table, row } => write!(f, "- {} [{}]", table, row),
+            TransformChange::ChangeCell {
+                table,
+                row,
+                column,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ {} [{}] {}: {} -> {}",
+                table,
+                row,
+                column,
+                old.as_deref().unwrap_or("(null)"),
+                new.as_deref().unwrap_or("(null)")
+            ),
+        }
+    }
+}
+
+/// Reads what a transform applies to.
+pub fn inspect_transform(path: impl AsRef<Path>) -> Result<TransformInfo> {
+    let summary = SummaryInfo::from_file(path)?;
+    Ok(TransformInfo::from_summary(&summary))
+}
+
+/// Gets the changes a transform makes to a base package without modifying the package.
+pub fn transform_changes(
+    base: impl AsRef<Path>,
+    transform: impl AsRef<Path>,
+) -> Result<Vec<TransformChange>> {
+    let database = Database::open(base)?;
+    database.apply_transform(transform, MSITRANSFORM_ERROR_VIEWTRANSFORM)?;
+
+    let view = database.query(
+        "SELECT `Table`, `Column`, `Row`, `Data`, `Current` FROM `_TransformView` ORDER BY `Table`",
+    )?;
+
+    let mut changes = Vec::new();
+    for row in view {
+        let row = row?;
+        let text = |i: usize| row[i].as_str().map(str::to_owned);
+        let table = text(0).unwrap_or_default();
+        let column = text(1).unwrap_or_default();
+        let keys = text(2).unwrap_or_default();
+
+        changes.push(match column.as_str() {
+            "CREATE" => TransformChange::AddTable { table },
+            "DROP" => TransformChange::DropTable { table },
+            "INSERT" => TransformChange::AddRow { table, row: keys },
+            "DELETE" => TransformChange::DeleteRow { table, row: keys },
+            _ => TransformChange::ChangeCell {
+                table,
+                row: keys,
+                column,
+                old: text(4),
+                new: text(3),
+            },
+        });
+    }
+
+    Ok(changes)
+}
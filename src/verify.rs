@@ -0,0 +1,118 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi::{self, INSTALLSTATE_LOCAL, INSTALLSTATE_SOURCE};
+use crate::{Database, Error, Result};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// The key path is a registry value.
+const MSIDBCOMPONENTATTRIBUTES_REGISTRYKEYPATH: i32 = 0x4;
+
+/// The key path is an ODBC data source.
+const MSIDBCOMPONENTATTRIBUTES_ODBCDATASOURCE: i32 = 0x20;
+
+/// The result of verifying an installed component's key path.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComponentCheck {
+    /// The `Component` table key.
+    pub component: String,
+
+    /// The resolved key path, if the component is installed.
+    pub path: Option<String>,
+
+    /// Why the component failed verification, if it did.
+    pub failure: Option<String>,
+}
+
+impl ComponentCheck {
+    /// Gets whether the component passed verification.
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl Display for ComponentCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.as_deref().unwrap_or("(not installed)");
+        match &self.failure {
+            None => write!(f, "ok\t{}\t{}", self.component, path),
+            Some(failure) => write!(f, "FAIL\t{}\t{}: {}", self.component, path, failure),
+        }
+    }
+}
+
+/// Verifies the key path of every component a package installed exists.
+///
+/// Components that are not installed, have no component code, or have registry or ODBC key
+/// paths are skipped. When `check_hashes` is set, unversioned key files listed in the
+/// `MsiFileHash` table must also match their authored hash.
+pub fn verify_install(package: &str, check_hashes: bool) -> Result<Vec<ComponentCheck>> {
+    let database = Database::open(package)?;
+    let product_code = database
+        .query("SELECT `Value` FROM `Property` WHERE `Property` = 'ProductCode'")?
+        .next()
+        .transpose()?
+        .and_then(|row| row.get(0).and_then(|v| v.as_str().map(str::to_owned)))
+        .ok_or_else(|| Error::verification("package has no ProductCode"))?;
+
+    let hashes = match check_hashes && database.tables()?.iter().any(|t| t == "MsiFileHash") {
+        true => file_hashes(&database)?,
+        false => HashMap::new(),
+    };
+
+    let mut checks = Vec::new();
+    let view = database
+        .query("SELECT `Component`, `ComponentId`, `KeyPath`, `Attributes` FROM `Component`")?;
+    for row in view {
+        let row = row?;
+        let component = row[0].to_string();
+        let Some(component_code) = row[1].as_str() else {
+            continue;
+        };
+        let attributes = row[3].as_integer().unwrap_or_default();
+        if attributes
+            & (MSIDBCOMPONENTATTRIBUTES_REGISTRYKEYPATH | MSIDBCOMPONENTATTRIBUTES_ODBCDATASOURCE)
+            != 0
+        {
+            continue;
+        }
+
+        let (state, path) = ffi::component_path(&product_code, component_code)?;
+        if state != INSTALLSTATE_LOCAL && state != INSTALLSTATE_SOURCE {
+            continue;
+        }
+
+        let failure = match Path::new(&path).exists() {
+            false => Some("key path does not exist".to_owned()),
+            true => match row[2].as_str().and_then(|file| hashes.get(file)) {
+                Some(expected) if ffi::file_hash(&path)? != *expected => {
+                    Some("key file does not match MsiFileHash".to_owned())
+                }
+                _ => None,
+            },
+        };
+
+        checks.push(ComponentCheck {
+            component,
+            path: Some(path),
+            failure,
+        });
+    }
+
+    Ok(checks)
+}
+
+fn file_hashes(database: &Database) -> Result<HashMap<String, [i32; 4]>> {
+    let view = database.query(
+        "SELECT `File_`, `HashPart1`, `HashPart2`, `HashPart3`, `HashPart4` FROM `MsiFileHash`",
+    )?;
+
+    view.map(|row| {
+        let row = row?;
+        let part = |i: usize| row[i].as_integer().unwrap_or_default();
+        Ok((row[0].to_string(), [part(1), part(2), part(3), part(4)]))
+    })
+    .collect()
+}
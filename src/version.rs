@@ -0,0 +1,45 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{ffi, Error, Result};
+use std::fmt::Display;
+
+/// The version of the Windows Installer engine, msi.dll.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct InstallerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub build: u32,
+}
+
+impl Display for InstallerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.build)
+    }
+}
+
+/// Gets the version of the Windows Installer engine.
+pub fn installer_version() -> Result<InstallerVersion> {
+    let (major, minor, build) = ffi::installer_version()?;
+    Ok(InstallerVersion {
+        major,
+        minor,
+        build,
+    })
+}
+
+/// Returns an error if the Windows Installer engine is older than `major.minor`,
+/// rather than failing to find an export or getting an unexpected result at run time.
+///
+/// Nothing is gated if the version cannot be determined.
+pub(crate) fn require_installer(major: u32, minor: u32, feature: &str) -> Result<()> {
+    match installer_version() {
+        Ok(version) if (version.major, version.minor) < (major, minor) => {
+            Err(Error::verification(format!(
+                "{} requires Windows Installer {}.{}, but {} is installed",
+                feature, major, minor, version
+            )))
+        }
+        _ => Ok(()),
+    }
+}
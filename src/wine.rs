@@ -0,0 +1,14 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use std::sync::OnceLock;
+
+/// Gets the version of Wine if running under Wine.
+///
+/// Wine does not implement every Windows Installer API or deliver every message
+/// the same as Windows, so callers may want to relax their expectations.
+pub fn wine_version() -> Option<&'static str> {
+    static VERSION: OnceLock<Option<String>> = OnceLock::new();
+    VERSION.get_or_init(ffi::wine_version).as_deref()
+}
@@ -0,0 +1,120 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+use msitrace::{ActionDataSampler, ActionTracker, Event, Message};
+use std::time::{Duration, Instant, SystemTime};
+
+fn action_start(action: &str) -> Message {
+    Message::ActionStart {
+        action: action.to_owned(),
+        description: String::new(),
+        template: String::new(),
+    }
+}
+
+#[test]
+fn no_action_before_first_start() {
+    let start = Instant::now();
+    let mut tracker = ActionTracker::new(start);
+
+    let info = Message::Info {
+        text: "Starting".to_owned(),
+    };
+    assert!(tracker.observe(&info, SystemTime::now(), start).is_none());
+    assert!(tracker.current(start).is_none());
+}
+
+#[test]
+fn actions_end_when_the_next_starts() {
+    let start = Instant::now();
+    let mut tracker = ActionTracker::new(start);
+
+    let first = start + Duration::from_secs(1);
+    assert!(tracker
+        .observe(&action_start("CostInitialize"), SystemTime::now(), first)
+        .is_none());
+
+    let current = tracker.current(first + Duration::from_secs(2)).unwrap();
+    assert_eq!(current.name, "CostInitialize");
+    assert_eq!(current.sequence, 1);
+    assert_eq!(current.elapsed, Duration::from_secs(2));
+
+    let second = start + Duration::from_secs(4);
+    let end = tracker.observe(&action_start("InstallFiles"), SystemTime::now(), second);
+    match end {
+        Some(Event::ActionEnd {
+            elapsed, action, ..
+        }) => {
+            assert_eq!(elapsed, Duration::from_secs(4));
+            assert_eq!(action.name, "CostInitialize");
+            assert_eq!(action.sequence, 1);
+            assert_eq!(action.elapsed, Duration::from_secs(3));
+        }
+        other => panic!("expected ActionEnd, got {:?}", other),
+    }
+
+    let current = tracker.current(second).unwrap();
+    assert_eq!(current.name, "InstallFiles");
+    assert_eq!(current.sequence, 2);
+    assert_eq!(tracker.elapsed(second), Duration::from_secs(4));
+}
+
+#[test]
+fn actions_end_with_the_install() {
+    let start = Instant::now();
+    let mut tracker = ActionTracker::new(start);
+    tracker.observe(&action_start("InstallFinalize"), SystemTime::now(), start);
+
+    let end = tracker.observe(&Message::Terminate, SystemTime::now(), start);
+    assert!(matches!(end, Some(Event::ActionEnd { .. })));
+    assert!(tracker.current(start).is_none());
+
+    // An action that already ended does not end again.
+    assert!(tracker
+        .observe(&Message::Terminate, SystemTime::now(), start)
+        .is_none());
+}
+
+#[test]
+fn sampler_prints_once_each_interval() {
+    let start = Instant::now();
+    let mut sampler = ActionDataSampler::new(Duration::from_millis(100));
+
+    assert!(sampler.should_print(start));
+    assert!(!sampler.should_print(start + Duration::from_millis(50)));
+    assert!(!sampler.should_print(start + Duration::from_millis(99)));
+    assert!(sampler.should_print(start + Duration::from_millis(100)));
+    assert!(!sampler.should_print(start + Duration::from_millis(150)));
+    sampler.skip("skipped");
+    assert_eq!(sampler.take_skipped(), Some("skipped"));
+}
+
+#[test]
+fn sampler_keeps_last_skipped() {
+    let start = Instant::now();
+    let mut sampler = ActionDataSampler::new(Duration::from_millis(100));
+
+    assert!(sampler.should_print(start));
+    for (ms, data) in [(10, "first"), (20, "second"), (30, "last")] {
+        assert!(!sampler.should_print(start + Duration::from_millis(ms)));
+        sampler.skip(data);
+    }
+
+    // The last of each action is printed before the next message.
+    assert_eq!(sampler.take_skipped(), Some("last"));
+    assert_eq!(sampler.take_skipped(), None);
+}
+
+#[test]
+fn sampler_drops_skipped_when_printing() {
+    let start = Instant::now();
+    let mut sampler = ActionDataSampler::new(Duration::from_millis(100));
+
+    assert!(sampler.should_print(start));
+    assert!(!sampler.should_print(start + Duration::from_millis(10)));
+    sampler.skip("superseded");
+    assert!(sampler.should_print(start + Duration::from_millis(200)));
+    assert_eq!(sampler.take_skipped(), None);
+}
@@ -0,0 +1,131 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+mod common;
+
+use common::fixture_dir;
+use msitrace::{extract_bundle, is_bundle};
+use std::path::{Path, PathBuf};
+
+const HEADER_OFFSET: u32 = 0x80;
+
+/// Builds an uncompressed cabinet containing a single file.
+fn cabinet(name: &str, data: &[u8]) -> Vec<u8> {
+    const HEADER_SIZE: u32 = 36;
+    const FOLDER_SIZE: u32 = 8;
+    let file_size = 16 + name.len() as u32 + 1;
+    let data_offset = HEADER_SIZE + FOLDER_SIZE + file_size;
+    let total = data_offset + 8 + data.len() as u32;
+
+    let mut cab = Vec::new();
+    cab.extend_from_slice(b"MSCF");
+    cab.extend_from_slice(&0u32.to_le_bytes());
+    cab.extend_from_slice(&total.to_le_bytes());
+    cab.extend_from_slice(&0u32.to_le_bytes());
+    cab.extend_from_slice(&(HEADER_SIZE + FOLDER_SIZE).to_le_bytes());
+    cab.extend_from_slice(&0u32.to_le_bytes());
+    cab.extend_from_slice(&[3, 1]); // version 1.3
+    cab.extend_from_slice(&1u16.to_le_bytes()); // folders
+    cab.extend_from_slice(&1u16.to_le_bytes()); // files
+    cab.extend_from_slice(&[0; 6]); // flags, set ID, and cabinet index
+
+    cab.extend_from_slice(&data_offset.to_le_bytes());
+    cab.extend_from_slice(&1u16.to_le_bytes()); // data blocks
+    cab.extend_from_slice(&0u16.to_le_bytes()); // uncompressed
+
+    cab.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    cab.extend_from_slice(&0u32.to_le_bytes()); // offset in folder
+    cab.extend_from_slice(&0u16.to_le_bytes()); // folder index
+    cab.extend_from_slice(&0x5021u16.to_le_bytes()); // 2020-01-01
+    cab.extend_from_slice(&0u16.to_le_bytes());
+    cab.extend_from_slice(&0x20u16.to_le_bytes()); // archive
+    cab.extend_from_slice(name.as_bytes());
+    cab.push(0);
+
+    cab.extend_from_slice(&0u32.to_le_bytes()); // no checksum
+    cab.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    cab.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    cab.extend_from_slice(data);
+
+    assert_eq!(cab.len() as u32, total);
+    cab
+}
+
+/// Builds a minimal executable with a `.wixburn` section and the given containers attached.
+fn bundle(containers: &[Vec<u8>]) -> Vec<u8> {
+    let mut exe = vec![0u8; HEADER_OFFSET as usize];
+    exe[..2].copy_from_slice(b"MZ");
+    exe[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    exe[0x40..0x44].copy_from_slice(b"PE\0\0");
+    exe[0x46..0x48].copy_from_slice(&1u16.to_le_bytes()); // sections
+
+    // The section table immediately follows the headers without an optional header.
+    exe[0x58..0x60].copy_from_slice(b".wixburn");
+    exe[0x6c..0x70].copy_from_slice(&HEADER_OFFSET.to_le_bytes());
+
+    let stub_size = HEADER_OFFSET + 48 + 4 * containers.len() as u32;
+    let mut header = [0u8; 48];
+    header[..4].copy_from_slice(&0x00f1_4300u32.to_le_bytes());
+    header[24..28].copy_from_slice(&stub_size.to_le_bytes());
+    header[40..44].copy_from_slice(&1u32.to_le_bytes()); // cabinets
+    header[44..48].copy_from_slice(&(containers.len() as u32).to_le_bytes());
+    exe.extend_from_slice(&header);
+    for container in containers {
+        exe.extend_from_slice(&(container.len() as u32).to_le_bytes());
+    }
+
+    assert_eq!(exe.len() as u32, stub_size);
+    for container in containers {
+        exe.extend_from_slice(container);
+    }
+
+    exe
+}
+
+fn write_bundle(dir: &Path, file_path: &str) -> PathBuf {
+    let manifest = format!(
+        r#"<BurnManifest><Payload Id="a" FilePath="{}" SourcePath="a" /></BurnManifest>"#,
+        file_path
+    );
+    let path = dir.join("bundle.exe");
+    let containers = [
+        cabinet("0", manifest.as_bytes()),
+        cabinet("a", b"not a package"),
+    ];
+    std::fs::write(&path, bundle(&containers)).expect("write bundle");
+    path
+}
+
+#[test]
+fn extract_bundle_payload() {
+    let dir = fixture_dir("bundle-payload");
+    let path = write_bundle(&dir, r"packages\example.msi");
+    assert!(is_bundle(&path).expect("read bundle"));
+
+    let output = dir.join("output");
+    let packages = extract_bundle(&path, &output).expect("extract bundle");
+    assert_eq!(packages, vec![output.join(r"packages\example.msi")]);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn extract_bundle_rejects_escaping_payload() {
+    let dir = fixture_dir("bundle-escape");
+    let output = dir.join("output");
+    for file_path in [
+        r"..\evil.msi",
+        r"packages\..\..\evil.msi",
+        r"\evil.msi",
+        r"C:evil.msi",
+    ] {
+        let path = write_bundle(&dir, file_path);
+        let err = extract_bundle(&path, &output).expect_err(file_path);
+        assert!(err.to_string().contains("not relative"), "{}", err);
+        assert!(!dir.join("evil.msi").exists());
+    }
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,49 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(feature = "toml")]
+
+use msitrace::{ChainManifest, CommandLine};
+use std::path::Path;
+
+const MANIFEST: &str = r#"
+name = "Example suite"
+transaction = true
+
+[[package]]
+path = "runtime.msi"
+
+[[package]]
+path = "example.msi"
+properties = { INSTALLDIR = 'C:\Example', LICENSEKEY = "secret" }
+hide = ["LICENSEKEY"]
+continue_on_error = true
+"#;
+
+#[test]
+fn chain_manifest_parses_packages() {
+    let manifest = ChainManifest::from_toml(MANIFEST).unwrap();
+
+    assert_eq!(manifest.name.as_deref(), Some("Example suite"));
+    assert!(manifest.transaction);
+    assert_eq!(manifest.packages.len(), 2);
+    assert_eq!(manifest.packages[0].path, Path::new("runtime.msi"));
+    assert!(!manifest.continue_on_error(0));
+    assert!(manifest.continue_on_error(1));
+
+    let mut properties = CommandLine::new();
+    manifest.packages[1].apply(&mut properties).unwrap();
+    assert_eq!(properties.get("INSTALLDIR"), Some(r"C:\Example"));
+    assert!(properties.is_hidden("LICENSEKEY"));
+}
+
+#[test]
+fn chain_manifest_requires_packages() {
+    assert!(ChainManifest::from_toml("name = \"empty\"").is_err());
+}
+
+#[test]
+fn chain_manifest_rejects_unknown_fields() {
+    let toml = "[[package]]\npath = \"example.msi\"\nproperty = { A = \"1\" }\n";
+    assert!(ChainManifest::from_toml(toml).is_err());
+}
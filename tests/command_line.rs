@@ -0,0 +1,234 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{CommandLine, InstallContext};
+use proptest::prelude::*;
+
+/// Parses a command line as Windows Installer does: properties separated by whitespace,
+/// with values optionally enclosed in double quotes and embedded quotes doubled.
+fn parse(command_line: &str) -> Vec<(String, String)> {
+    let mut properties = Vec::new();
+    let mut chars = command_line.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut name = String::new();
+        for c in chars.by_ref() {
+            if c == '=' {
+                break;
+            }
+            name.push(c);
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                if c == '"' && chars.next_if_eq(&'"').is_none() {
+                    break;
+                }
+                value.push(c);
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+
+        properties.push((name, value));
+    }
+
+    properties
+}
+
+fn name() -> impl Strategy<Value = String> {
+    "[A-Za-z_][A-Za-z0-9_.]{0,16}"
+}
+
+fn value() -> impl Strategy<Value = String> {
+    prop_oneof![any::<String>(), "[ \t\"';=a-zA-Z0-9\\\\:.é漢🦀]{0,32}",]
+}
+
+proptest! {
+    #[test]
+    fn round_trips(properties in prop::collection::btree_map(name(), value(), 0..8)) {
+        let mut command_line = CommandLine::new();
+        for (name, value) in &properties {
+            command_line.set(name.as_str(), value.as_str()).unwrap();
+        }
+
+        let expected: Vec<(String, String)> = properties.into_iter().collect();
+        prop_assert_eq!(parse(&command_line.to_string()), expected);
+    }
+
+    #[test]
+    fn round_trips_cli_arguments(name in name(), value in value()) {
+        let mut command_line = CommandLine::new();
+        command_line.set_property(&format!("{}={}", name, value)).unwrap();
+
+        prop_assert_eq!(parse(&command_line.to_string()), vec![(name, value)]);
+    }
+
+    #[test]
+    fn replaces_values(name in name(), first in value(), second in value()) {
+        let mut command_line = CommandLine::new();
+        command_line.set(name.as_str(), first).unwrap();
+        command_line.set(name.as_str(), second.as_str()).unwrap();
+
+        prop_assert_eq!(parse(&command_line.to_string()), vec![(name, second)]);
+    }
+
+    #[test]
+    fn rejects_invalid_names(name in "[0-9.=\" ;][^=]*|[A-Za-z_]*[ \t\";][^=]*", value in value()) {
+        let mut command_line = CommandLine::new();
+
+        prop_assert!(command_line.set(name.as_str(), value.as_str()).is_err());
+        prop_assert!(command_line.is_empty());
+    }
+}
+
+#[test]
+fn rejects_missing_value() {
+    let mut command_line = CommandLine::new();
+    assert!(command_line.set_property("PROP").is_err());
+}
+
+#[test]
+fn quotes_empty_values() {
+    let mut command_line = CommandLine::new();
+    command_line.set_property("PROP=").unwrap();
+
+    assert_eq!(command_line.to_string(), r#"PROP="""#);
+}
+
+#[test]
+fn redacts_hidden_properties() {
+    let mut command_line = CommandLine::new();
+    command_line.set_property("USER=admin").unwrap();
+    command_line.set_property("PASSWORD=p@ss word").unwrap();
+    command_line.hide("PASSWORD").unwrap();
+
+    assert_eq!(
+        command_line.redacted().to_string(),
+        "USER=admin PASSWORD=**********"
+    );
+    assert_eq!(
+        command_line.redact("Property(S): PASSWORD = p@ss word"),
+        "Property(S): PASSWORD = **********"
+    );
+}
+
+#[test]
+fn sets_context() {
+    let mut command_line = CommandLine::new();
+    command_line.set_property("ALLUSERS=1").unwrap();
+    command_line.set_context(InstallContext::User);
+    assert_eq!(command_line.to_string(), "ALLUSERS=2 MSIINSTALLPERUSER=1");
+
+    command_line.set_context(InstallContext::Machine);
+    assert_eq!(command_line.to_string(), "ALLUSERS=1");
+}
+
+#[test]
+fn adds_transforms() {
+    let mut command_line = CommandLine::new();
+    command_line.set_property("TRANSFORMS=a.mst").unwrap();
+    command_line
+        .add_transforms([r"C:\Program Files\b.mst", ":fr-FR.mst"])
+        .unwrap();
+    assert_eq!(
+        command_line.to_string(),
+        r#"TRANSFORMS="a.mst;C:\Program Files\b.mst;:fr-FR.mst""#
+    );
+
+    assert!(command_line.add_transforms(["a.mst;b.mst"]).is_err());
+    assert!(command_line.add_transforms([":"]).is_err());
+}
+
+#[test]
+fn adds_patches() {
+    let mut command_line = CommandLine::new();
+    command_line
+        .add_patches([r"C:\patches\a.msp", r"C:\patches\b.msp"])
+        .unwrap();
+    assert_eq!(
+        command_line.to_string(),
+        r"PATCH=C:\patches\a.msp;C:\patches\b.msp"
+    );
+}
+
+#[test]
+fn sets_properties_from_text() {
+    let mut command_line = CommandLine::new();
+    command_line
+        .set_properties(
+            r#"
+# Install options
+INSTALLDIR = C:\Program Files\Example
+COMMENT=" padded ""quoted"" "
+EMPTY=
+"#,
+        )
+        .unwrap();
+
+    assert_eq!(
+        command_line.get("INSTALLDIR"),
+        Some(r"C:\Program Files\Example")
+    );
+    assert_eq!(command_line.get("COMMENT"), Some(r#" padded "quoted" "#));
+    assert_eq!(command_line.get("EMPTY"), Some(""));
+    assert!(command_line.set_properties("MISSING").is_err());
+}
+
+#[test]
+fn expands_variables() {
+    let lookup = |name: &str| match name {
+        "ProgramFiles" => Some(r"C:\Program Files".to_owned()),
+        "APP" => Some("Example".to_owned()),
+        _ => None,
+    };
+
+    let mut command_line = CommandLine::new();
+    command_line
+        .set_property(r"INSTALLDIR=%ProgramFiles%\$APP\${APP}_1")
+        .unwrap();
+    command_line
+        .set_property("LITERAL=100%% $$APP 50% $")
+        .unwrap();
+    command_line.expand_vars(lookup).unwrap();
+
+    assert_eq!(
+        command_line.get("INSTALLDIR"),
+        Some(r"C:\Program Files\Example\Example_1")
+    );
+    assert_eq!(command_line.get("LITERAL"), Some("100% $APP 50% $"));
+
+    command_line.set_property("MISSING=%MISSING%").unwrap();
+    assert!(command_line.expand_vars(lookup).is_err());
+}
+
+#[test]
+fn expands_only_variable_names() {
+    let lookup = |name: &str| match name {
+        "ProgramFiles(x86)" => Some(r"C:\Program Files (x86)".to_owned()),
+        _ => None,
+    };
+
+    let mut command_line = CommandLine::new();
+    command_line
+        .set_property("MESSAGE=50% now or 20% later")
+        .unwrap();
+    command_line
+        .set_property(r"INSTALLDIR=%ProgramFiles(x86)%\Example")
+        .unwrap();
+    command_line.expand_vars(lookup).unwrap();
+
+    assert_eq!(command_line.get("MESSAGE"), Some("50% now or 20% later"));
+    assert_eq!(
+        command_line.get("INSTALLDIR"),
+        Some(r"C:\Program Files (x86)\Example")
+    );
+}
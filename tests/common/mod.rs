@@ -0,0 +1,37 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Fixtures shared by integration tests, each of which uses only some of them.
+
+#![allow(dead_code)]
+
+use msitrace::Database;
+use std::path::PathBuf;
+
+/// Creates an empty directory named `msitrace-{name}` in the temporary directory.
+pub fn fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("msitrace-{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create fixture directory");
+    dir
+}
+
+/// Removes a directory created by [`fixture_dir`] and anything in it.
+pub fn remove_fixture_dir(name: &str) {
+    std::fs::remove_dir_all(std::env::temp_dir().join(format!("msitrace-{}", name))).ok();
+}
+
+/// Creates a database named `msitrace-{name}.msi` in the temporary directory and executes
+/// each of `statements`, e.g. to create and populate tables.
+pub fn database<S: AsRef<str>>(name: &str, statements: impl IntoIterator<Item = S>) -> Database {
+    let path = std::env::temp_dir().join(format!("msitrace-{}.msi", name));
+    let database = Database::create(path).expect("create database");
+    for statement in statements {
+        let statement = statement.as_ref();
+        database
+            .execute(statement)
+            .unwrap_or_else(|err| panic!("execute {:?}: {}", statement, err));
+    }
+
+    database
+}
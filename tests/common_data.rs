@@ -0,0 +1,91 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+use msitrace::{CommonData, Value};
+
+#[test]
+fn common_data_language() {
+    let data = CommonData::from_fields(
+        0,
+        &[Value::Integer(1033), Value::Integer(1252)],
+        "1: 0 2: 1033 3: 1252".to_string(),
+    );
+    assert_eq!(
+        data,
+        CommonData::Language {
+            lcid: 1033,
+            codepage: 1252
+        }
+    );
+    assert_eq!(data.to_string(), "Language 1033, code page 1252");
+}
+
+#[test]
+fn common_data_caption() {
+    let data = CommonData::from_fields(
+        1,
+        &[Value::String("Example".to_string())],
+        "1: 1 2: Example".to_string(),
+    );
+    assert_eq!(
+        data,
+        CommonData::Caption {
+            caption: "Example".to_string()
+        }
+    );
+    assert_eq!(data.to_string(), r#"Caption "Example""#);
+
+    // Numeric captions are still captions.
+    let data = CommonData::from_fields(1, &[Value::Integer(2022)], "1: 1 2: 2022".to_string());
+    assert_eq!(
+        data,
+        CommonData::Caption {
+            caption: "2022".to_string()
+        }
+    );
+}
+
+#[test]
+fn common_data_cancel_show() {
+    let data = CommonData::from_fields(2, &[Value::Integer(0)], "1: 2 2: 0".to_string());
+    assert_eq!(data, CommonData::CancelShow { visible: false });
+    assert_eq!(data.to_string(), "Hide cancel button");
+
+    let data = CommonData::from_fields(2, &[Value::String("1".to_string())], String::new());
+    assert_eq!(data, CommonData::CancelShow { visible: true });
+}
+
+#[test]
+fn common_data_missing_fields() {
+    let data = CommonData::from_fields(0, &[], "1: 0".to_string());
+    assert_eq!(
+        data,
+        CommonData::Language {
+            lcid: 0,
+            codepage: 0
+        }
+    );
+
+    let data = CommonData::from_fields(1, &[Value::Null], "1: 1".to_string());
+    assert_eq!(
+        data,
+        CommonData::Caption {
+            caption: String::new()
+        }
+    );
+}
+
+#[test]
+fn common_data_other_keeps_text() {
+    let data = CommonData::from_fields(3, &[Value::Integer(1)], "1: 3 2: 1".to_string());
+    assert_eq!(
+        data,
+        CommonData::Other {
+            kind: 3,
+            text: "1: 3 2: 1".to_string()
+        }
+    );
+    assert_eq!(data.to_string(), "1: 3 2: 1");
+}
@@ -0,0 +1,78 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+mod common;
+
+use msitrace::{diff, CellDiff, Database, DiffStatus};
+
+/// Creates a database with a `Property` table and, optionally, an empty `Extra` table.
+fn fixture(name: &str, properties: &[(&str, &str)], extra: bool) -> Database {
+    let mut statements = vec!["CREATE TABLE `Property` (`Property` CHAR(72) NOT NULL, `Value` LONGCHAR NOT NULL PRIMARY KEY `Property`)".to_owned()];
+    if extra {
+        statements.push("CREATE TABLE `Extra` (`Id` INT NOT NULL PRIMARY KEY `Id`)".to_owned());
+    }
+    statements.extend(properties.iter().map(|(name, value)| {
+        format!(
+            "INSERT INTO `Property` (`Property`, `Value`) VALUES ('{}', '{}')",
+            name, value
+        )
+    }));
+
+    common::database(&format!("diff-{}", name), statements)
+}
+
+#[test]
+fn identical() {
+    let old = fixture("identical-old", &[("A", "1")], false);
+    let new = fixture("identical-new", &[("A", "1")], false);
+
+    assert!(diff(&old, &new).expect("diff").tables.is_empty());
+}
+
+#[test]
+fn rows_by_primary_key() {
+    let old = fixture("rows-old", &[("A", "1"), ("B", "2"), ("C", "3")], false);
+    let new = fixture("rows-new", &[("A", "1"), ("B", "4"), ("D", "5")], false);
+
+    let diff = diff(&old, &new).expect("diff");
+    assert_eq!(diff.tables.len(), 1);
+
+    let table = &diff.tables[0];
+    assert_eq!(table.name, "Property");
+    assert_eq!(table.status, DiffStatus::Changed);
+
+    let rows: Vec<(&str, DiffStatus)> = table
+        .rows
+        .iter()
+        .map(|r| (r.keys[0].as_str(), r.status))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("B", DiffStatus::Changed),
+            ("C", DiffStatus::Removed),
+            ("D", DiffStatus::Added)
+        ]
+    );
+    assert_eq!(
+        table.rows[0].cells,
+        [CellDiff {
+            column: "Value".to_owned(),
+            old: Some("2".to_owned()),
+            new: Some("4".to_owned()),
+        }]
+    );
+}
+
+#[test]
+fn tables() {
+    let old = fixture("tables-old", &[], true);
+    let new = fixture("tables-new", &[], false);
+
+    let diff = diff(&old, &new).expect("diff");
+    assert_eq!(diff.tables.len(), 1);
+    assert_eq!(diff.tables[0].name, "Extra");
+    assert_eq!(diff.tables[0].status, DiffStatus::Removed);
+}
@@ -0,0 +1,35 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+use msitrace::quote_argument;
+
+#[test]
+fn quote_only_when_needed() {
+    assert_eq!(quote_argument("install"), "install");
+    assert_eq!(
+        quote_argument(r"C:\path\example.msi"),
+        r"C:\path\example.msi"
+    );
+    assert_eq!(quote_argument(""), r#""""#);
+    assert_eq!(quote_argument("two words"), r#""two words""#);
+    assert_eq!(quote_argument("tab\there"), "\"tab\there\"");
+}
+
+#[test]
+fn quote_escapes_quotes() {
+    assert_eq!(quote_argument(r#"PROP="value""#), r#""PROP=\"value\"""#);
+    assert_eq!(quote_argument(r#"a\"b"#), r#""a\\\"b""#);
+}
+
+#[test]
+fn quote_backslashes() {
+    // Backslashes are literal unless they precede a quote.
+    assert_eq!(
+        quote_argument(r"C:\Program Files\"),
+        r#""C:\Program Files\\""#
+    );
+    assert_eq!(quote_argument(r"a b\\"), r#""a b\\\\""#);
+    assert_eq!(quote_argument(r"a\b c"), r#""a\b c""#);
+}
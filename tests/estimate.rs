@@ -0,0 +1,76 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+mod common;
+
+use msitrace::{estimate, Database, InstallStats};
+use std::time::Duration;
+
+const MB: i64 = 1024 * 1024;
+
+/// Creates a database with a `File` table containing one file of `size` bytes.
+fn fixture(name: &str, size: i64) -> Database {
+    common::database(
+        &format!("estimate-{}", name),
+        [
+            "CREATE TABLE `File` (`File` CHAR(72) NOT NULL, `Component_` CHAR(72) NOT NULL, `FileSize` LONG NOT NULL PRIMARY KEY `File`)".to_owned(),
+            format!(
+                "INSERT INTO `File` (`File`, `Component_`, `FileSize`) VALUES ('File', 'Component', {})",
+                size
+            ),
+        ],
+    )
+}
+
+fn stats(size: u64, duration_secs: f64) -> InstallStats {
+    InstallStats {
+        size,
+        duration_secs,
+    }
+}
+
+#[test]
+fn calibrated() {
+    let database = fixture("calibrated", 10 * MB);
+    let calibration = [stats(MB as u64, 1.0), stats(MB as u64, 3.0)];
+
+    let estimate = estimate(&database, &calibration).expect("estimate");
+    assert_eq!(estimate.files, 1);
+    assert_eq!(estimate.size, 10 * MB as u64);
+    assert!(estimate.calibrated);
+    assert_eq!(estimate.duration, Duration::from_secs(20));
+}
+
+#[test]
+fn uncalibrated() {
+    let database = fixture("uncalibrated", 10 * MB);
+
+    let estimate = estimate(&database, &[]).expect("estimate");
+    assert!(!estimate.calibrated);
+    assert_eq!(estimate.duration, Duration::from_secs(16));
+}
+
+#[test]
+fn zero_calibration_uses_default_rate() {
+    let database = fixture("zero", 10 * MB);
+
+    for calibration in [stats(0, 0.0), stats(0, 10.0), stats(MB as u64, 0.0)] {
+        let estimate = estimate(&database, &[calibration]).expect("estimate");
+        assert!(!estimate.calibrated);
+        assert_eq!(estimate.duration, Duration::from_secs(16));
+    }
+}
+
+#[test]
+fn negative_calibration_uses_default_rate() {
+    let database = fixture("negative", 10 * MB);
+    let calibration = [stats(MB as u64, -1.0), stats(MB as u64, f64::NAN)];
+
+    for calibration in calibration {
+        let estimate = estimate(&database, &[calibration]).expect("estimate");
+        assert!(!estimate.calibrated);
+        assert_eq!(estimate.duration, Duration::from_secs(16));
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{InstallSummary, Language, TraceEvent, SCHEMA_VERSION};
+use std::time::Duration;
+
+#[test]
+fn trace_event_has_schema_version() {
+    let event = TraceEvent::new("ActionStart", "Action start: InstallFiles.");
+    let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+
+    assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["message_type"], "ActionStart");
+    assert_eq!(json["text"], "Action start: InstallFiles.");
+}
+
+#[test]
+fn trace_event_round_trips() {
+    let event = TraceEvent::new("Error", "Error 1603.");
+    let line = serde_json::to_string(&event).unwrap();
+
+    assert!(!line.contains('\n'));
+    assert_eq!(serde_json::from_str::<TraceEvent>(&line).unwrap(), event);
+}
+
+#[test]
+fn trace_event_elapsed_is_optional() {
+    let mut event = TraceEvent::new("Info", "");
+    assert!(!serde_json::to_string(&event)
+        .unwrap()
+        .contains("elapsed_ms"));
+
+    event.elapsed_ms = Some(1500);
+    let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["elapsed_ms"], 1500);
+}
+
+#[test]
+fn trace_event_action_is_optional() {
+    let mut event = TraceEvent::new("ActionData", "File: example.dll");
+    let line = serde_json::to_string(&event).unwrap();
+    assert!(!line.contains("action"));
+    assert_eq!(serde_json::from_str::<TraceEvent>(&line).unwrap(), event);
+
+    event.action = Some("InstallFiles".to_owned());
+    event.action_sequence = Some(12);
+    let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["action"], "InstallFiles");
+    assert_eq!(json["action_sequence"], 12);
+    assert_eq!(serde_json::from_value::<TraceEvent>(json).unwrap(), event);
+}
+
+#[test]
+fn trace_event_ignores_unknown_fields() {
+    let line = r#"{"schema_version":1,"time":"2022-11-01T00:00:00Z","message_type":"Info","text":"","extra":true}"#;
+    let event: TraceEvent = serde_json::from_str(line).unwrap();
+
+    assert_eq!(event.message_type, "Info");
+}
+
+#[test]
+fn install_summary_keeps_slowest_actions() {
+    let mut summary = InstallSummary::new("example.msi", 3010, Duration::from_secs(60));
+    summary.set_actions((1..=7).map(|i| (format!("Action{}", i), Duration::from_secs(i))));
+
+    let slowest: Vec<&str> = summary
+        .slowest_actions
+        .iter()
+        .map(|a| a.action.as_str())
+        .collect();
+    assert_eq!(
+        slowest,
+        ["Action7", "Action6", "Action5", "Action4", "Action3"]
+    );
+    assert!(summary.reboot_required);
+
+    let json: serde_json::Value = serde_json::to_value(&summary).unwrap();
+    assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["code"], 3010);
+}
+
+#[test]
+fn install_summary_localized() {
+    let summary = InstallSummary::new("example.msi", 3010, Duration::from_secs(60));
+
+    let english = summary.to_string();
+    assert!(english.contains("  Duration: 60.0s\n"));
+    assert!(english.ends_with("  Reboot required: yes\n"));
+    assert_eq!(summary.localized(Language::English).to_string(), english);
+
+    let german = summary.localized(Language::German).to_string();
+    assert!(german.starts_with("  Ergebnis: 3010 "));
+    assert!(german.contains("  Dauer: 60.0s\n"));
+    assert!(german.ends_with("  Neustart erforderlich: ja\n"));
+}
@@ -0,0 +1,64 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+use msitrace::matches_glob;
+use proptest::prelude::*;
+
+/// Matches by trying every split at each `*`, which is slow but obviously correct.
+fn reference(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|i| reference(rest, &text[i..])),
+        Some(('?', rest)) => !text.is_empty() && reference(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && reference(rest, &text[1..]),
+    }
+}
+
+#[test]
+fn literal() {
+    assert!(matches_glob("Example", "Example"));
+    assert!(matches_glob("example", "EXAMPLE"));
+    assert!(!matches_glob("Example", "Example 2"));
+    assert!(!matches_glob("Example 2", "Example"));
+    assert!(matches_glob("", ""));
+    assert!(!matches_glob("", "Example"));
+}
+
+#[test]
+fn wildcards() {
+    assert!(matches_glob("*", ""));
+    assert!(matches_glob("*", "Example"));
+    assert!(matches_glob("Microsoft*", "Microsoft Visual C++ 2022"));
+    assert!(matches_glob(
+        "*C++*x64*",
+        "Microsoft Visual C++ 2022 x64 Runtime"
+    ));
+    assert!(!matches_glob(
+        "*C++*x86*",
+        "Microsoft Visual C++ 2022 x64 Runtime"
+    ));
+    assert!(matches_glob("Example ?.0", "Example 2.0"));
+    assert!(!matches_glob("Example ?.0", "Example 10.0"));
+    assert!(matches_glob("*a*b", "aab"));
+    assert!(!matches_glob("?", ""));
+}
+
+#[test]
+fn many_stars_finish_quickly() {
+    // Backtracking into every star took exponential time.
+    let text = "a".repeat(100);
+    assert!(!matches_glob(&format!("{}b", "*a".repeat(20)), &text));
+}
+
+proptest! {
+    #[test]
+    fn matches_reference(pattern in "[ab*?]{0,8}", text in "[ab]{0,10}") {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        prop_assert_eq!(
+            matches_glob(&pattern, &text),
+            reference(&chars(&pattern), &chars(&text))
+        );
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{Label, Language};
+
+#[test]
+fn from_tag() {
+    assert_eq!(Language::from_tag("de"), Some(Language::German));
+    assert_eq!(Language::from_tag("fr-CA"), Some(Language::French));
+    assert_eq!(Language::from_tag("es_MX.UTF-8"), Some(Language::Spanish));
+    assert_eq!(Language::from_tag("EN-us"), Some(Language::English));
+    assert_eq!(Language::from_tag("ja-JP"), None);
+    assert_eq!(Language::from_tag(""), None);
+}
+
+#[test]
+fn label() {
+    assert_eq!(Language::English.label(Label::CommandLine), "Command line");
+    assert_eq!(Language::German.label(Label::CommandLine), "Befehlszeile");
+    assert_eq!(Language::default(), Language::English);
+}
@@ -0,0 +1,53 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+mod common;
+
+use common::remove_fixture_dir;
+use msitrace::{evaluate_source, Database, Source};
+use std::path::PathBuf;
+
+/// Creates a directory whose path is longer than MAX_PATH.
+fn fixture_dir(name: &str) -> PathBuf {
+    let mut dir = common::fixture_dir(name);
+    while dir.as_os_str().len() <= 300 {
+        dir.push("a-directory-name-long-enough-to-exceed-max-path");
+    }
+
+    std::fs::create_dir_all(&dir).expect("create fixture directory");
+    dir
+}
+
+#[test]
+fn open_database_long_path() {
+    let dir = fixture_dir("long-open");
+    let missing = dir.join("missing.msi");
+    let invalid = dir.join("invalid.msi");
+    std::fs::write(&invalid, "not a package").expect("write fixture");
+    assert!(invalid.as_os_str().len() > 260);
+
+    // Without the extended-length prefix neither path could be found.
+    let missing = Database::open(&missing).err().map(|err| err.to_string());
+    let invalid = Database::open(&invalid).err().map(|err| err.to_string());
+    assert!(missing.is_some() && invalid.is_some());
+    assert_ne!(missing, invalid);
+
+    remove_fixture_dir("long-open");
+}
+
+#[test]
+fn evaluate_source_long_path() {
+    let dir = fixture_dir("long-source");
+    let path = dir
+        .canonicalize()
+        .expect("canonicalize")
+        .join("example.msi");
+    assert!(path.as_os_str().len() > 260);
+
+    let source = evaluate_source(&path.to_string_lossy()).expect("evaluate source");
+    assert_eq!(source, Source::Local);
+
+    remove_fixture_dir("long-source");
+}
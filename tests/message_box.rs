@@ -0,0 +1,25 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+use msitrace::{Buttons, HandlerResult, Icon, MessageType};
+
+#[test]
+fn buttons_and_icon_from_flags() {
+    // MB_ABORTRETRYIGNORE | MB_ICONEXCLAMATION | MB_DEFBUTTON2
+    let flags = 0x0000_0132;
+
+    assert_eq!(Buttons::from_flags(flags), Buttons::AbortRetryIgnore);
+    assert_eq!(Icon::from_flags(flags), Icon::Warning);
+}
+
+#[test]
+fn unknown_buttons_accept_only_default() {
+    let buttons = Buttons::from_flags(0x0000_0006);
+
+    assert_eq!(buttons, Buttons::Unknown(6));
+    assert!(buttons.responses().is_empty());
+    assert!(!HandlerResult::Retry.is_valid_for(MessageType::Error, 0x0000_0006));
+    assert!(HandlerResult::Default.is_valid_for(MessageType::Error, 0x0000_0006));
+}
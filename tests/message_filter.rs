@@ -0,0 +1,36 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+use msitrace::{MessageFilter, MessageType};
+
+#[test]
+fn parse_names() {
+    let filter: MessageFilter = "Error, action-start,RM_FILES_IN_USE".parse().unwrap();
+
+    assert_eq!(
+        filter,
+        MessageFilter::ERROR | MessageFilter::ACTION_START | MessageFilter::RM_FILES_IN_USE
+    );
+    assert_eq!(
+        "default".parse::<MessageFilter>().unwrap(),
+        MessageFilter::default()
+    );
+}
+
+#[test]
+fn parse_invalid_name_as_typed() {
+    let err = "Error,Bogus-Name".parse::<MessageFilter>().unwrap_err();
+
+    assert!(err.to_string().contains("\"Bogus-Name\""), "{}", err);
+}
+
+#[test]
+fn combine_message_types() {
+    assert_eq!(
+        MessageType::Error | MessageType::Warning | MessageType::ActionStart,
+        MessageFilter::ERROR | MessageFilter::WARNING | MessageFilter::ACTION_START
+    );
+    assert_eq!(0x0000_0010 | MessageType::Error, 0x0100_0010);
+}
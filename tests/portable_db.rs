@@ -0,0 +1,171 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(all(not(windows), feature = "portable-db"))]
+
+use msitrace::{Database, Value};
+use std::fs::OpenOptions;
+
+/// Creates a package with a `Numbers` table of integers and their nullable names.
+fn fixture(name: &str) -> Database {
+    let path = std::env::temp_dir().join(format!("msitrace-portable-{}.msi", name));
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .expect("create package");
+
+    let mut package =
+        msi::Package::create(msi::PackageType::Installer, file).expect("create package");
+    package
+        .create_table(
+            "Numbers",
+            vec![
+                msi::Column::build("Number").primary_key().int16(),
+                msi::Column::build("Name").nullable().string(50),
+            ],
+        )
+        .expect("create table");
+    package
+        .insert_rows(
+            msi::Insert::into("Numbers")
+                .row(vec![msi::Value::Int(1), msi::Value::Str("One".to_owned())])
+                .row(vec![
+                    msi::Value::Int(2),
+                    msi::Value::Str("Two words".to_owned()),
+                ])
+                .row(vec![msi::Value::Int(3), msi::Value::Null]),
+        )
+        .expect("insert rows");
+    package.into_inner().expect("write package");
+
+    Database::open(&path).expect("open package")
+}
+
+fn query(database: &Database, sql: &str) -> Vec<Vec<Value>> {
+    database
+        .query(sql)
+        .expect(sql)
+        .map(|row| row.expect("fetch row").iter().cloned().collect())
+        .collect()
+}
+
+fn query_err(database: &Database, sql: &str) -> String {
+    match database.query(sql) {
+        Ok(_) => panic!("{} succeeded", sql),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn select_all_columns() {
+    let database = fixture("select-all");
+
+    let view = database.query("SELECT * FROM `Numbers`").expect("query");
+    let columns: Vec<&str> = view.columns().iter().map(|c| c.name()).collect();
+    assert_eq!(columns, ["Number", "Name"]);
+    assert_eq!(view.count(), 3);
+}
+
+#[test]
+fn select_columns_in_order() {
+    let database = fixture("select-columns");
+
+    let rows = query(&database, "SELECT `Name`, `Number` FROM `Numbers`");
+    assert_eq!(
+        rows[0],
+        [Value::String("One".to_owned()), Value::Integer(1)]
+    );
+}
+
+#[test]
+fn where_conditions() {
+    let database = fixture("where");
+
+    let rows = query(&database, "SELECT `Name` FROM `Numbers` WHERE `Number` = 2");
+    assert_eq!(rows, [[Value::String("Two words".to_owned())]]);
+
+    let rows = query(
+        &database,
+        "SELECT `Number` FROM `Numbers` WHERE `Name` = 'One' AND `Number` = 1",
+    );
+    assert_eq!(rows, [[Value::Integer(1)]]);
+
+    let rows = query(
+        &database,
+        "SELECT `Number` FROM `Numbers` WHERE `Name` = NULL",
+    );
+    assert_eq!(rows, [[Value::Integer(3)]]);
+
+    let rows = query(
+        &database,
+        "SELECT `Number` FROM `Numbers` WHERE `Number` = -1",
+    );
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn unquoted_names_and_keywords() {
+    let database = fixture("unquoted");
+
+    let rows = query(
+        &database,
+        "select Number from Numbers where Name = 'Two words'",
+    );
+    assert_eq!(rows, [[Value::Integer(2)]]);
+}
+
+#[test]
+fn invalid_queries() {
+    let database = fixture("invalid");
+
+    for (sql, message) in [
+        ("DELETE FROM `Numbers`", "expected SELECT"),
+        ("SELECT *", "expected FROM"),
+        ("SELECT * FROM", "expected a table or column name"),
+        ("SELECT `Number`, FROM `Numbers`", "expected FROM"),
+        (
+            "SELECT `Number`, * FROM `Numbers`",
+            "expected a table or column name",
+        ),
+        (
+            "SELECT * FROM `Numbers` ORDER BY `Number`",
+            "expected WHERE",
+        ),
+        (
+            "SELECT * FROM `Numbers` WHERE `Number` < 2",
+            "unsupported character",
+        ),
+        (
+            "SELECT * FROM `Numbers` WHERE `Number` 2",
+            "only `=` comparisons",
+        ),
+        (
+            "SELECT * FROM `Numbers` WHERE `Number` =",
+            "expected a value",
+        ),
+        (
+            "SELECT * FROM `Numbers` WHERE `Number` = 1 OR `Number` = 2",
+            "expected AND",
+        ),
+        (
+            "SELECT * FROM `Numbers` WHERE `Name` = 'One",
+            "unterminated '",
+        ),
+        ("SELECT * FROM `Numbers", "unterminated `"),
+        (
+            "SELECT * FROM `Numbers` WHERE `Number` = 99999999999",
+            "invalid integer",
+        ),
+        ("SELECT * FROM `Missing`", "table `Missing` not found"),
+        (
+            "SELECT `Missing` FROM `Numbers`",
+            "column `Missing` not found",
+        ),
+    ] {
+        let err = query_err(&database, sql);
+        assert!(err.contains(message), "{}: {}", sql, err);
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use msitrace::{Progress, ProgressUpdate};
+
+fn reset(total: i32, forward: bool) -> ProgressUpdate {
+    ProgressUpdate::MasterReset {
+        total,
+        forward,
+        script_in_progress: false,
+    }
+}
+
+#[test]
+fn progress_unknown_before_reset() {
+    let mut progress = Progress::new();
+    progress.update(ProgressUpdate::Report { ticks: 10 });
+
+    assert_eq!(progress.percent(), None);
+}
+
+#[test]
+fn progress_reports_and_additions() {
+    let mut progress = Progress::new();
+    progress.update(reset(100, true));
+    progress.update(ProgressUpdate::Report { ticks: 25 });
+    assert_eq!(progress.percent(), Some(25.0));
+
+    progress.update(ProgressUpdate::Addition { ticks: 100 });
+    assert_eq!(progress.percent(), Some(12.5));
+
+    // Progress never exceeds the total.
+    progress.update(ProgressUpdate::Report { ticks: 1000 });
+    assert_eq!(progress.percent(), Some(100.0));
+}
+
+#[test]
+fn progress_action_data_increments_only_when_asked() {
+    let mut progress = Progress::new();
+    progress.update(reset(100, true));
+    progress.action_start("InstallFiles");
+    progress.action_data();
+    assert_eq!(progress.percent(), Some(0.0));
+
+    progress.update(ProgressUpdate::ActionInfo {
+        ticks_per_message: 10,
+        increment: true,
+    });
+    progress.action_data();
+    progress.action_data();
+    assert_eq!(progress.percent(), Some(20.0));
+    assert_eq!(progress.action(), Some("InstallFiles"));
+
+    // Each action must ask again.
+    progress.action_start("WriteRegistryValues");
+    progress.action_data();
+    assert_eq!(progress.percent(), Some(20.0));
+}
+
+#[test]
+fn progress_moves_backward_during_rollback() {
+    let mut progress = Progress::new();
+    progress.update(reset(200, false));
+    assert!(progress.is_rolling_back());
+    assert_eq!(progress.percent(), Some(100.0));
+
+    progress.update(ProgressUpdate::Report { ticks: 50 });
+    assert_eq!(progress.percent(), Some(75.0));
+}
+
+#[test]
+fn progress_update_from_fields() {
+    assert_eq!(
+        ProgressUpdate::from_fields(&[0, 100, 1, 1]),
+        ProgressUpdate::MasterReset {
+            total: 100,
+            forward: false,
+            script_in_progress: true,
+        }
+    );
+    assert_eq!(
+        ProgressUpdate::from_fields(&[1, 5, 1]),
+        ProgressUpdate::ActionInfo {
+            ticks_per_message: 5,
+            increment: true,
+        }
+    );
+    assert_eq!(
+        ProgressUpdate::from_fields(&[2, 10]),
+        ProgressUpdate::Report { ticks: 10 }
+    );
+
+    // Missing fields are 0.
+    assert_eq!(
+        ProgressUpdate::from_fields(&[3]),
+        ProgressUpdate::Addition { ticks: 0 }
+    );
+    assert_eq!(ProgressUpdate::from_fields(&[]), reset(0, true));
+    assert_eq!(
+        ProgressUpdate::from_fields(&[7, 1]),
+        ProgressUpdate::Unknown { kind: 7 }
+    );
+}
@@ -0,0 +1,64 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+mod common;
+
+use msitrace::{Database, Value};
+
+/// Creates a database with a table of string values, keyed by their length.
+fn fixture(name: &str, values: &[String]) -> Database {
+    let create =
+        "CREATE TABLE `Strings` (`Length` INT NOT NULL, `Value` LONGCHAR PRIMARY KEY `Length`)";
+    let inserts = values.iter().map(|value| {
+        format!(
+            "INSERT INTO `Strings` (`Length`, `Value`) VALUES ({}, '{}')",
+            value.len(),
+            value
+        )
+    });
+
+    common::database(
+        &format!("record-{}", name),
+        std::iter::once(create.to_owned()).chain(inserts),
+    )
+}
+
+fn query(database: &Database, len: usize) -> Value {
+    let sql = format!("SELECT `Value` FROM `Strings` WHERE `Length` = {}", len);
+    let row = database
+        .query(&sql)
+        .expect("query")
+        .next()
+        .expect("row")
+        .expect("fetch row");
+
+    row[0].clone()
+}
+
+#[test]
+fn empty_string() {
+    let database = fixture("empty", &[String::new()]);
+
+    // Windows Installer stores empty strings as null.
+    assert_eq!(query(&database, 0), Value::Null);
+}
+
+#[test]
+fn exact_fit_strings() {
+    let values: Vec<String> = [255, 256, 257].iter().map(|len| "x".repeat(*len)).collect();
+    let database = fixture("exact", &values);
+
+    for value in values {
+        assert_eq!(query(&database, value.len()), Value::String(value));
+    }
+}
+
+#[test]
+fn long_string() {
+    let value = "0123456789".repeat(10_000);
+    let database = fixture("long", std::slice::from_ref(&value));
+
+    assert_eq!(query(&database, value.len()), Value::String(value));
+}
@@ -0,0 +1,42 @@
+// Copyright 2022 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(windows)]
+
+mod common;
+
+use msitrace::{verify_package, Database};
+use std::path::PathBuf;
+
+/// Creates a directory whose name cannot be represented in most ANSI code pages.
+fn fixture_dir(name: &str) -> PathBuf {
+    common::fixture_dir(&format!("{}-ünïcødé-日本語-Ελληνικά", name))
+}
+
+#[test]
+fn open_database_non_ascii_path() {
+    let dir = fixture_dir("open");
+    let missing = dir.join("missing-пакет.msi");
+    let invalid = dir.join("invalid-пакет.msi");
+    std::fs::write(&invalid, "not a package").expect("write fixture");
+
+    // Through the ANSI APIs both paths were mangled and neither could be found.
+    let missing = Database::open(&missing).err().map(|err| err.to_string());
+    let invalid = Database::open(&invalid).err().map(|err| err.to_string());
+    assert!(missing.is_some() && invalid.is_some());
+    assert_ne!(missing, invalid);
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn verify_package_non_ascii_path() {
+    let dir = fixture_dir("verify");
+    let invalid = dir.join("invalid-пакет.msi");
+    std::fs::write(&invalid, "not a package").expect("write fixture");
+
+    let err = verify_package(&invalid.to_string_lossy()).err();
+    assert!(err.is_some_and(|err| err.to_string().starts_with("not a valid installer package")));
+
+    std::fs::remove_dir_all(dir).ok();
+}